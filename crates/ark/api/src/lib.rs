@@ -8,12 +8,16 @@ use serde::{Deserialize, Serialize};
 pub mod consts {
     pub const NAMESPACE: &str = "ark";
 
+    pub const LABEL_BIND_ACTIVITY_TIMESTAMP: &str = "ark.ulagbulag.io/bind.activity.timestamp";
     pub const LABEL_BIND_BY_USER: &str = "ark.ulagbulag.io/bind.user";
+    pub const LABEL_BIND_EXPIRED_TIMESTAMP: &str = "ark.ulagbulag.io/bind.expired.timestamp";
+    pub const LABEL_BIND_IDLE_TIMEOUT: &str = "ark.ulagbulag.io/bind.idle.timeout";
     pub const LABEL_BIND_NAMESPACE: &str = "ark.ulagbulag.io/bind.namespace";
     pub const LABEL_BIND_NODE: &str = "ark.ulagbulag.io/bind.node";
     pub const LABEL_BIND_PERSISTENT: &str = "ark.ulagbulag.io/bind.persistent";
     pub const LABEL_BIND_STATUS: &str = "ark.ulagbulag.io/bind";
     pub const LABEL_BIND_TIMESTAMP: &str = "ark.ulagbulag.io/bind.timestamp";
+    pub const LABEL_BIND_WARNED_TIMESTAMP: &str = "ark.ulagbulag.io/bind.warned.timestamp";
 
     pub const HEADER_NAMESPACE: &str = "X-ARK-NAMESPACE";
 }
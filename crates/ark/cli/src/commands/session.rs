@@ -95,7 +95,7 @@ impl BatchArgs {
             },
             wait: !detach,
         }
-        .exec(&kube)
+        .exec(&kube, &::vine_session::audit::kube_caller(&kube).await)
         .await?;
 
         info!("Executed in {num_boxes} boxes.");
@@ -111,6 +111,14 @@ pub(crate) struct LoginArgs {
     #[arg(long, env = "VINE_SESSION_USER", value_name = "NAME")]
     user: String,
 
+    #[arg(
+        long = "group",
+        env = "VINE_SESSION_GROUPS",
+        value_name = "NAME",
+        value_delimiter = ','
+    )]
+    groups: Vec<String>,
+
     #[arg(long, env = "VINE_SESSION_LOGOUT_ON_FAILED")]
     logout_on_failed: bool,
 }
@@ -121,10 +129,11 @@ impl LoginArgs {
         let Self {
             r#box: box_name,
             user: user_name,
+            groups,
             logout_on_failed,
         } = self;
 
-        ::vine_rbac::login::execute(&kube, &box_name, &user_name, logout_on_failed).await
+        ::vine_rbac::login::execute(&kube, &box_name, &user_name, &groups, logout_on_failed).await
     }
 }
 
@@ -135,6 +144,14 @@ pub(crate) struct LogoutArgs {
 
     #[arg(long, env = "VINE_SESSION_USER", value_name = "NAME")]
     user: String,
+
+    #[arg(
+        long = "group",
+        env = "VINE_SESSION_GROUPS",
+        value_name = "NAME",
+        value_delimiter = ','
+    )]
+    groups: Vec<String>,
 }
 
 impl LogoutArgs {
@@ -143,14 +160,23 @@ impl LogoutArgs {
         let Self {
             r#box: box_name,
             user: user_name,
+            groups,
         } = &self;
 
-        ::vine_rbac::logout::execute(&kube, box_name, user_name).await
+        ::vine_rbac::logout::execute(&kube, box_name, user_name, groups).await
     }
 }
 
 #[derive(Clone, Debug, Parser)]
 pub(crate) struct ShellArgs {
+    /// Record the shells, to be downloaded by the admins
+    #[arg(long, env = "VINE_AUDIT_RECORD")]
+    record: bool,
+
+    /// Record the key inputs too, which may contain the passwords
+    #[arg(long, env = "VINE_AUDIT_RECORD_INPUT", requires = "record")]
+    record_input: bool,
+
     #[arg(long, env = "VINE_SESSION_SHELL", value_name = "COMMAND", default_value = ShellArgs::default_shell())]
     shell: String,
 
@@ -166,12 +192,16 @@ impl ShellArgs {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub(crate) async fn run(self, kube: Client) -> Result<()> {
         let Self {
+            record,
+            record_input,
             shell,
             user_pattern,
         } = self;
 
         ::vine_session::shell::BatchShellArgs {
             command: shell,
+            record,
+            record_input,
             users: match user_pattern.as_ref() {
                 Some(re) => ::vine_session::batch::BatchCommandUsers::Pattern(re),
                 None => ::vine_session::batch::BatchCommandUsers::All,
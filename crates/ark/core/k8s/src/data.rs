@@ -1,6 +1,6 @@
 use std::{borrow::Borrow, cmp::Ordering, fmt, ops, str::FromStr};
 
-use anyhow::{bail, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -194,6 +194,392 @@ impl Name {
     }
 }
 
+/// A kubernetes resource quantity, exactly represented in nano units.
+///
+/// The values are compared by their amounts, and formatted in their original format.
+#[derive(Copy, Clone, Default)]
+pub struct Quantity {
+    format: QuantityFormat,
+    nanos: i128,
+}
+
+impl FromStr for Quantity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let value = s.trim();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+
+        // split the number and the suffix
+        let (number, suffix) = value.split_at(
+            value
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(value.len()),
+        );
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if (integer.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+            bail!("invalid quantity: {s:?}")
+        }
+        let fraction = fraction.trim_end_matches('0');
+
+        let (format, base2, base10) = match suffix {
+            "n" => (QuantityFormat::DecimalSI, 0, -9),
+            "u" => (QuantityFormat::DecimalSI, 0, -6),
+            "m" => (QuantityFormat::DecimalSI, 0, -3),
+            "" => (QuantityFormat::DecimalSI, 0, 0),
+            "k" => (QuantityFormat::DecimalSI, 0, 3),
+            "M" => (QuantityFormat::DecimalSI, 0, 6),
+            "G" => (QuantityFormat::DecimalSI, 0, 9),
+            "T" => (QuantityFormat::DecimalSI, 0, 12),
+            "P" => (QuantityFormat::DecimalSI, 0, 15),
+            "E" => (QuantityFormat::DecimalSI, 0, 18),
+            "Ki" => (QuantityFormat::BinarySI, 10, 0),
+            "Mi" => (QuantityFormat::BinarySI, 20, 0),
+            "Gi" => (QuantityFormat::BinarySI, 30, 0),
+            "Ti" => (QuantityFormat::BinarySI, 40, 0),
+            "Pi" => (QuantityFormat::BinarySI, 50, 0),
+            "Ei" => (QuantityFormat::BinarySI, 60, 0),
+            suffix => match suffix
+                .strip_prefix(['e', 'E'])
+                .and_then(|exponent| exponent.parse::<i32>().ok())
+            {
+                Some(exponent) => (QuantityFormat::DecimalExponent, 0, exponent),
+                None => bail!("invalid quantity suffix: {s:?}"),
+            },
+        };
+
+        let out_of_range = || anyhow!("quantity is out of range: {s:?}");
+        let mantissa = format!("{integer}{fraction}");
+        let mantissa: i128 = match mantissa.trim_start_matches('0') {
+            "" => 0,
+            mantissa => mantissa.parse().map_err(|_| out_of_range())?,
+        };
+        let mantissa = mantissa.checked_mul(1 << base2).ok_or_else(out_of_range)?;
+
+        // NOTE: the precision is limited to nano units, rounding up as kubernetes does
+        let exponent = base10 + Self::NANOS_EXPONENT as i32 - fraction.len() as i32;
+        let nanos = if exponent >= 0 {
+            10i128
+                .checked_pow(exponent as u32)
+                .and_then(|scale| mantissa.checked_mul(scale))
+                .ok_or_else(out_of_range)?
+        } else {
+            match 10i128.checked_pow(exponent.unsigned_abs()) {
+                Some(scale) => div_ceil(mantissa, scale),
+                None => mantissa.signum(),
+            }
+        };
+
+        Ok(Self {
+            format,
+            nanos: if negative { -nanos } else { nanos },
+        })
+    }
+}
+
+impl TryFrom<&::k8s_openapi::apimachinery::pkg::api::resource::Quantity> for Quantity {
+    type Error = Error;
+
+    fn try_from(
+        quantity: &::k8s_openapi::apimachinery::pkg::api::resource::Quantity,
+    ) -> Result<Self, Self::Error> {
+        quantity.0.parse()
+    }
+}
+
+impl From<Quantity> for ::k8s_openapi::apimachinery::pkg::api::resource::Quantity {
+    fn from(quantity: Quantity) -> Self {
+        Self(quantity.to_string())
+    }
+}
+
+impl fmt::Debug for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Quantity").field(&self.to_string()).finish()
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.nanos == 0 {
+            return "0".fmt(f);
+        }
+        let sign = if self.nanos < 0 { "-" } else { "" };
+        let nanos = self.nanos.unsigned_abs();
+
+        // use the largest binary suffix which divides the value exactly
+        if self.format == QuantityFormat::BinarySI && nanos % Self::NANOS_PER_UNIT as u128 == 0 {
+            const SUFFIXES: &[(&str, u32)] = &[
+                ("Ei", 60),
+                ("Pi", 50),
+                ("Ti", 40),
+                ("Gi", 30),
+                ("Mi", 20),
+                ("Ki", 10),
+            ];
+
+            let units = nanos / Self::NANOS_PER_UNIT as u128;
+            if let Some((suffix, shift)) = SUFFIXES
+                .iter()
+                .find(|&&(_, shift)| units % (1 << shift) == 0)
+            {
+                return write!(f, "{sign}{mantissa}{suffix}", mantissa = units >> shift);
+            }
+        }
+
+        // otherwise, use the largest decimal exponent which divides the value exactly
+        let mut mantissa = nanos;
+        let mut exponent = -(Self::NANOS_EXPONENT as i32);
+        while mantissa % 1_000 == 0 && exponent < 18 {
+            mantissa /= 1_000;
+            exponent += 3;
+        }
+
+        match self.format {
+            QuantityFormat::DecimalExponent if exponent != 0 => {
+                write!(f, "{sign}{mantissa}e{exponent}")
+            }
+            _ => {
+                let suffix = match exponent {
+                    -9 => "n",
+                    -6 => "u",
+                    -3 => "m",
+                    3 => "k",
+                    6 => "M",
+                    9 => "G",
+                    12 => "T",
+                    15 => "P",
+                    18 => "E",
+                    _ => "",
+                };
+                write!(f, "{sign}{mantissa}{suffix}")
+            }
+        }
+    }
+}
+
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos == other.nanos
+    }
+}
+
+impl Eq for Quantity {}
+
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(<Self as Ord>::cmp(self, other))
+    }
+}
+
+impl Ord for Quantity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos.cmp(&other.nanos)
+    }
+}
+
+impl ::core::hash::Hash for Quantity {
+    fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+        self.nanos.hash(state)
+    }
+}
+
+impl ops::Add for Quantity {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            format: self.format,
+            nanos: self.nanos + rhs.nanos,
+        }
+    }
+}
+
+impl ops::AddAssign for Quantity {
+    fn add_assign(&mut self, rhs: Self) {
+        self.nanos += rhs.nanos;
+    }
+}
+
+impl ops::Sub for Quantity {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            format: self.format,
+            nanos: self.nanos - rhs.nanos,
+        }
+    }
+}
+
+impl ops::SubAssign for Quantity {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.nanos -= rhs.nanos;
+    }
+}
+
+impl ops::Mul<i64> for Quantity {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self {
+            format: self.format,
+            nanos: self.nanos * rhs as i128,
+        }
+    }
+}
+
+impl ops::Neg for Quantity {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            format: self.format,
+            nanos: -self.nanos,
+        }
+    }
+}
+
+impl ::core::iter::Sum for Quantity {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, ops::Add::add)
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <String as Deserialize<'de>>::deserialize(deserializer)
+            .and_then(|quantity| Self::from_str(&quantity).map_err(::serde::de::Error::custom))
+    }
+}
+
+impl JsonSchema for Quantity {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "Quantity".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl Quantity {
+    const NANOS_EXPONENT: u32 = 9;
+    const NANOS_PER_UNIT: i128 = 10i128.pow(Self::NANOS_EXPONENT);
+
+    pub const ZERO: Self = Self {
+        format: QuantityFormat::DecimalSI,
+        nanos: 0,
+    };
+
+    pub const fn from_nanos(nanos: i128, format: QuantityFormat) -> Self {
+        Self { format, nanos }
+    }
+
+    pub const fn from_units(units: i64, format: QuantityFormat) -> Self {
+        Self {
+            format,
+            nanos: units as i128 * Self::NANOS_PER_UNIT,
+        }
+    }
+
+    pub const fn format(&self) -> QuantityFormat {
+        self.format
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.nanos == 0
+    }
+
+    pub const fn as_nanos(&self) -> i128 {
+        self.nanos
+    }
+
+    /// Returns the amount in milli units, rounding up.
+    pub const fn as_millis(&self) -> i128 {
+        div_ceil(self.nanos, Self::NANOS_PER_UNIT / 1_000)
+    }
+
+    /// Returns the amount in units, rounding up.
+    pub const fn as_units(&self) -> i128 {
+        div_ceil(self.nanos, Self::NANOS_PER_UNIT)
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.nanos as f64 / Self::NANOS_PER_UNIT as f64
+    }
+
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.nanos.checked_add(rhs.nanos) {
+            Some(nanos) => Some(Self {
+                format: self.format,
+                nanos,
+            }),
+            None => None,
+        }
+    }
+
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.nanos.checked_sub(rhs.nanos) {
+            Some(nanos) => Some(Self {
+                format: self.format,
+                nanos,
+            }),
+            None => None,
+        }
+    }
+
+    pub const fn checked_mul(self, rhs: i64) -> Option<Self> {
+        match self.nanos.checked_mul(rhs as i128) {
+            Some(nanos) => Some(Self {
+                format: self.format,
+                nanos,
+            }),
+            None => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum QuantityFormat {
+    /// e.g. `1Ki`, `1Mi`, `1Gi`
+    BinarySI,
+    /// e.g. `1m`, `1`, `1k`, `1M`
+    #[default]
+    DecimalSI,
+    /// e.g. `1e-3`, `1e3`
+    DecimalExponent,
+}
+
+/// Divides rounding away from zero, as the quantities are rounded up.
+const fn div_ceil(lhs: i128, rhs: i128) -> i128 {
+    let quotient = lhs / rhs;
+    if lhs % rhs == 0 {
+        quotient
+    } else {
+        quotient + lhs.signum()
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Url(pub ::url::Url);
@@ -251,3 +637,199 @@ impl JsonSchema for Url {
         String::json_schema(gen)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantity(s: &str) -> Quantity {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_quantity_decimal_suffixes() {
+        for (s, nanos) in [
+            ("1n", 1),
+            ("1u", 1_000),
+            ("1m", 1_000_000),
+            ("1", 1_000_000_000),
+            ("1k", 10i128.pow(12)),
+            ("1M", 10i128.pow(15)),
+            ("1G", 10i128.pow(18)),
+            ("1T", 10i128.pow(21)),
+            ("1P", 10i128.pow(24)),
+            ("1E", 10i128.pow(27)),
+        ] {
+            let quantity = quantity(s);
+            assert_eq!(quantity.as_nanos(), nanos, "{s}");
+            assert_eq!(quantity.format(), QuantityFormat::DecimalSI, "{s}");
+        }
+    }
+
+    #[test]
+    fn parse_quantity_binary_suffixes() {
+        for (s, shift) in [
+            ("1Ki", 10),
+            ("1Mi", 20),
+            ("1Gi", 30),
+            ("1Ti", 40),
+            ("1Pi", 50),
+            ("1Ei", 60),
+        ] {
+            let quantity = quantity(s);
+            assert_eq!(quantity.as_units(), 1 << shift, "{s}");
+            assert_eq!(quantity.format(), QuantityFormat::BinarySI, "{s}");
+        }
+    }
+
+    #[test]
+    fn parse_quantity_decimal_exponents() {
+        for (s, expected) in [("1e3", "1k"), ("1E3", "1k"), ("1e-3", "1m"), ("2e0", "2")] {
+            let quantity = quantity(s);
+            assert_eq!(quantity, self::quantity(expected), "{s}");
+            assert_eq!(quantity.format(), QuantityFormat::DecimalExponent, "{s}");
+        }
+
+        // `E` alone is the exa suffix, not an exponent
+        assert_eq!(quantity("1E").as_units(), 10i128.pow(18));
+    }
+
+    #[test]
+    fn parse_quantity_fractions() {
+        assert_eq!(quantity("1.5Ki").as_units(), 1_536);
+        assert_eq!(quantity("0.5"), quantity("500m"));
+        assert_eq!(quantity(".5"), quantity("500m"));
+        assert_eq!(quantity("1.50"), quantity("1500m"));
+        assert_eq!(quantity("0.001"), quantity("1m"));
+    }
+
+    #[test]
+    fn parse_quantity_rounding_up_below_nano() {
+        assert_eq!(quantity("1.5n").as_nanos(), 2);
+        assert_eq!(quantity("0.1n").as_nanos(), 1);
+        assert_eq!(quantity("1e-12").as_nanos(), 1);
+        assert_eq!(quantity("1e-100").as_nanos(), 1);
+        assert_eq!(quantity("0.0n").as_nanos(), 0);
+
+        // away from zero, as kubernetes does
+        assert_eq!(quantity("-1.5n").as_nanos(), -2);
+        assert_eq!(quantity("-1e-12").as_nanos(), -1);
+    }
+
+    #[test]
+    fn parse_quantity_signs() {
+        assert_eq!(quantity("-1Ki").as_units(), -1_024);
+        assert_eq!(quantity("+1Ki").as_units(), 1_024);
+        assert_eq!(quantity(" 1 "), quantity("1"));
+        assert_eq!(quantity("-0"), Quantity::ZERO);
+    }
+
+    #[test]
+    fn parse_quantity_invalid() {
+        for s in [
+            "", "-", "Ki", "1.2.3", "1KiB", "1K", "1 Ki", "abc", "1e", "1e1.5",
+        ] {
+            assert!(s.parse::<Quantity>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn parse_quantity_overflow() {
+        for s in [
+            "1e40",
+            "1000000000000000000000000000000000000000",
+            "1000000000000000000Ei",
+            "170141183460469231731687303715884105727",
+        ] {
+            assert!(s.parse::<Quantity>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn display_quantity() {
+        for (s, expected) in [
+            ("0", "0"),
+            ("0Ki", "0"),
+            ("1", "1"),
+            ("1000", "1k"),
+            ("1.5", "1500m"),
+            ("100n", "100n"),
+            ("1Ki", "1Ki"),
+            ("2048Mi", "2Gi"),
+            ("1.5Ki", "1536"),
+            ("1e3", "1e3"),
+            ("1e0", "1"),
+            ("-1Ki", "-1Ki"),
+            ("-500m", "-500m"),
+        ] {
+            assert_eq!(quantity(s).to_string(), expected, "{s}");
+        }
+    }
+
+    #[test]
+    fn display_quantity_round_trip() {
+        for s in [
+            "1n", "999u", "250m", "1", "16", "1k", "1E", "1Ki", "3Mi", "16Gi", "1Ei", "1.5Ki",
+            "1e3", "1e-6", "-2Gi", "0.1n",
+        ] {
+            let quantity = quantity(s);
+            let parsed = self::quantity(&quantity.to_string());
+            assert_eq!(parsed, quantity, "{s}");
+            assert_eq!(parsed.to_string(), quantity.to_string(), "{s}");
+        }
+    }
+
+    #[test]
+    fn quantity_arithmetic() {
+        assert_eq!(quantity("1Gi") + quantity("1Gi"), quantity("2Gi"));
+        assert_eq!(quantity("1") - quantity("250m"), quantity("750m"));
+        assert_eq!(quantity("250m") * 4, quantity("1"));
+        assert_eq!(-quantity("1Ki"), quantity("-1Ki"));
+        assert_eq!(
+            ["100m", "200m", "700m"]
+                .into_iter()
+                .map(quantity)
+                .sum::<Quantity>(),
+            quantity("1"),
+        );
+
+        // the format of the left operand is kept
+        assert_eq!(
+            (quantity("1Ki") + quantity("1k")).format(),
+            QuantityFormat::BinarySI
+        );
+
+        let max = Quantity::from_nanos(i128::MAX, QuantityFormat::DecimalSI);
+        assert_eq!(max.checked_add(quantity("1n")), None);
+        assert_eq!(max.checked_mul(2), None);
+        assert_eq!((-max).checked_sub(quantity("2n")), None);
+        assert_eq!(
+            quantity("1").checked_sub(quantity("1m")),
+            Some(quantity("999m")),
+        );
+    }
+
+    #[test]
+    fn quantity_rounding_units() {
+        assert_eq!(quantity("1n").as_millis(), 1);
+        assert_eq!(quantity("-1n").as_millis(), -1);
+        assert_eq!(quantity("1500m").as_units(), 2);
+        assert_eq!(quantity("1000m").as_units(), 1);
+        assert_eq!(quantity("1.5").as_f64(), 1.5);
+    }
+
+    #[test]
+    fn compare_quantities() {
+        // the cases which the former `vine_rbac` parser got wrong
+        assert!(quantity("500m") < quantity("1"));
+        assert!(quantity("1500m") > quantity("1"));
+        assert!(quantity("1Gi") > quantity("1G"));
+        assert!(quantity("1Ki") > quantity("1k"));
+        assert!(quantity("4") > quantity("3900m"));
+        assert_eq!(quantity("1Mi"), quantity("1048576"));
+        assert_eq!(quantity("1k"), quantity("1e3"));
+        assert_eq!(quantity("1000m"), quantity("1"));
+        assert_eq!(quantity("0"), Quantity::ZERO);
+        assert!(Quantity::ZERO.is_zero());
+    }
+}
@@ -168,6 +168,9 @@ impl StorageResourceRequirements for ResourceRequirements {
 
 impl StorageResourceRequirements for BTreeMap<String, Quantity> {
     fn quota(&self) -> Option<Byte> {
-        self.get("storage").and_then(|quota| quota.0.parse().ok())
+        self.get("storage")
+            .and_then(|quota| ::ark_core_k8s::data::Quantity::try_from(quota).ok())
+            .and_then(|quota| u128::try_from(quota.as_units()).ok())
+            .and_then(Byte::from_u128)
     }
 }
@@ -0,0 +1,30 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Derive a graph from the live cluster topology.
+///
+/// Nodes are collected from the `Node`s, `Pod`s, `Service`s, `Deployment`s
+/// and Gateway API `HTTPRoute`s, with their labels as `label.<key>` columns.
+/// Edges are collected from the pod placements, the `Service` endpoints and
+/// selectors, the `HTTPRoute` backends and the `NetworkPolicy` rules.
+///
+/// The given resource is mapped so that each `Node` may accept its
+/// allocatable (`capacity`) and each `Pod` provides its requests (`supply`).
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConnectorKubernetesSpec {
+    /// Namespaces to collect pods and services from; all namespaces if empty
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+
+    #[serde(default = "NetworkConnectorKubernetesSpec::default_resource")]
+    pub resource: String,
+}
+
+impl NetworkConnectorKubernetesSpec {
+    fn default_resource() -> String {
+        "cpu".into()
+    }
+}
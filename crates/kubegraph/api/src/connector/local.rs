@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,18 +10,90 @@ use serde::{Deserialize, Serialize};
 pub struct NetworkConnectorLocalSpec {
     pub path: PathBuf,
 
-    #[serde(default = "NetworkConnectorLocalSpec::default_key_edges")]
-    pub key_edges: String,
-    #[serde(default = "NetworkConnectorLocalSpec::default_key_nodes")]
-    pub key_nodes: String,
+    #[serde(default)]
+    pub format: NetworkConnectorLocalFormat,
+
+    #[serde(default)]
+    pub key_edges: Option<String>,
+    #[serde(default)]
+    pub key_nodes: Option<String>,
+
+    /// Column types to be enforced after loading
+    #[serde(default)]
+    pub schema: BTreeMap<String, NetworkConnectorLocalDataType>,
+
+    /// Reload the graph as soon as the files are changed
+    #[serde(default)]
+    pub watch: bool,
 }
 
 impl NetworkConnectorLocalSpec {
-    fn default_key_edges() -> String {
-        "edges.csv".into()
+    pub fn key_edges(&self) -> String {
+        match self.key_edges.as_ref() {
+            Some(key) => key.clone(),
+            None => self.format.default_key("edges"),
+        }
+    }
+
+    pub fn key_nodes(&self) -> String {
+        match self.key_nodes.as_ref() {
+            Some(key) => key.clone(),
+            None => self.format.default_key("nodes"),
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkConnectorLocalFormat {
+    ArrowIpc,
+    #[default]
+    Csv,
+    /// A single file containing both nodes and edges
+    GraphMl,
+    Ndjson,
+    Parquet,
+}
+
+impl NetworkConnectorLocalFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::ArrowIpc => "arrow",
+            Self::Csv => "csv",
+            Self::GraphMl => "graphml",
+            Self::Ndjson => "ndjson",
+            Self::Parquet => "parquet",
+        }
     }
 
-    fn default_key_nodes() -> String {
-        "nodes.csv".into()
+    fn default_key(&self, kind: &str) -> String {
+        match self {
+            Self::GraphMl => format!("graph.{ext}", ext = self.extension()),
+            _ => format!("{kind}.{ext}", ext = self.extension()),
+        }
     }
 }
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkConnectorLocalDataType {
+    Bool,
+    Float,
+    Int,
+    String,
+}
@@ -2,16 +2,26 @@
 pub mod fake;
 #[cfg(feature = "connector-http")]
 pub mod http;
+#[cfg(feature = "connector-kubernetes")]
+pub mod kubernetes;
 #[cfg(feature = "connector-local")]
 pub mod local;
 #[cfg(feature = "connector-prometheus")]
 pub mod prometheus;
+pub mod resample;
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    pin::pin,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use futures::{stream::FuturesUnordered, TryStreamExt};
+use futures::{
+    future::{pending, select, Either},
+    stream::FuturesUnordered,
+    TryStreamExt,
+};
 use kube::{CustomResource, CustomResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -41,6 +51,7 @@ where
 
         let mut inited = false;
         let mut scopes = BTreeMap::default();
+        let mut dirty_scopes = BTreeSet::default();
         loop {
             let instant = Instant::now();
 
@@ -57,11 +68,12 @@ where
                     let scope = GraphScope::from_resource::<NetworkConnectorCrd>(&cr);
                     let version = cr.metadata.resource_version.clone();
 
-                    if scopes
-                        .get(&scope) // updated
-                        .map(|last_version| last_version != &version)
-                        // new
-                        .unwrap_or(true)
+                    if dirty_scopes.contains(&scope) // changed sources
+                        || scopes
+                            .get(&scope) // updated
+                            .map(|last_version| last_version != &version)
+                            // new
+                            .unwrap_or(true)
                     {
                         new_connectors.push(cr);
                     }
@@ -75,6 +87,11 @@ where
                     .cloned()
                     .map(NetworkConnectorEvent::Deleted)
                     .collect();
+                for event in &events {
+                    if let NetworkConnectorEvent::Deleted(scope) = event {
+                        self.forget(scope);
+                    }
+                }
 
                 match self.pull(new_connectors).await {
                     Ok(data) => {
@@ -97,6 +114,7 @@ where
                             Ok(()) => {
                                 // Update the scopes database
                                 scopes = new_scopes;
+                                dirty_scopes.clear();
                             }
                             Err(error) => {
                                 let name = self.name();
@@ -145,7 +163,13 @@ where
             };
             let elapsed = instant.elapsed();
             if elapsed < interval {
-                sleep(interval - elapsed).await;
+                // wake up early if the sources have been changed
+                if let Either::Right((changed, _)) =
+                    select(pin!(sleep(interval - elapsed)), self.wait_for_changes()).await
+                {
+                    // force reloading the changed scopes until they are stored
+                    dirty_scopes.extend(changed);
+                }
             }
         }
     }
@@ -164,6 +188,19 @@ pub trait NetworkConnector {
         &mut self,
         connectors: Vec<NetworkConnectorCrd>,
     ) -> Result<Vec<Graph<GraphData<LazyFrame>>>>;
+
+    /// Forget the sources of the removed graph.
+    fn forget(&mut self, scope: &GraphScope) {
+        let _ = scope;
+    }
+
+    /// Wait until the sources of the pulled graphs are changed,
+    /// returning the scopes to be reloaded.
+    ///
+    /// By default, the connector is only reloaded by the restart policy.
+    async fn wait_for_changes(&mut self) -> Vec<GraphScope> {
+        pending().await
+    }
 }
 
 enum NetworkConnectorEvent {
@@ -238,6 +275,8 @@ pub enum NetworkConnectorKind {
     Fake(self::fake::NetworkConnectorFakeSpec),
     #[cfg(feature = "connector-local")]
     Http(self::http::NetworkConnectorHttpSpec),
+    #[cfg(feature = "connector-kubernetes")]
+    Kubernetes(self::kubernetes::NetworkConnectorKubernetesSpec),
     #[cfg(feature = "connector-local")]
     Local(self::local::NetworkConnectorLocalSpec),
     #[cfg(feature = "connector-prometheus")]
@@ -252,6 +291,8 @@ impl NetworkConnectorKind {
             Self::Fake(_) => NetworkConnectorType::Fake.name().into(),
             #[cfg(feature = "connector-http")]
             Self::Http(_) => NetworkConnectorType::Http.name().into(),
+            #[cfg(feature = "connector-kubernetes")]
+            Self::Kubernetes(_) => NetworkConnectorType::Kubernetes.name().into(),
             #[cfg(feature = "connector-local")]
             Self::Local(_) => NetworkConnectorType::Local.name().into(),
             #[cfg(feature = "connector-prometheus")]
@@ -270,6 +311,8 @@ impl NetworkConnectorKind {
             Self::Fake(_) => NetworkConnectorType::Fake,
             #[cfg(feature = "connector-http")]
             Self::Http(_) => NetworkConnectorType::Http,
+            #[cfg(feature = "connector-kubernetes")]
+            Self::Kubernetes(_) => NetworkConnectorType::Kubernetes,
             #[cfg(feature = "connector-local")]
             Self::Local(_) => NetworkConnectorType::Local,
             #[cfg(feature = "connector-prometheus")]
@@ -294,6 +337,8 @@ pub enum NetworkConnectorType {
     Fake,
     #[cfg(feature = "connector-http")]
     Http,
+    #[cfg(feature = "connector-kubernetes")]
+    Kubernetes,
     #[cfg(feature = "connector-local")]
     Local,
     #[cfg(feature = "connector-prometheus")]
@@ -308,6 +353,8 @@ impl NetworkConnectorType {
             Self::Fake => "fake",
             #[cfg(feature = "connector-http")]
             Self::Http => "http",
+            #[cfg(feature = "connector-kubernetes")]
+            Self::Kubernetes => "kubernetes",
             #[cfg(feature = "connector-local")]
             Self::Local => "local",
             #[cfg(feature = "connector-prometheus")]
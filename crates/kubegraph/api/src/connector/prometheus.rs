@@ -1,12 +1,20 @@
+use std::{str::FromStr, time::Duration};
+
 use ark_core_k8s::data::Url;
+use duration_string::DurationString;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::query::{NetworkQuery, NetworkQueryMetadata};
 
+use super::resample::NetworkConnectorAggregation;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkConnectorPrometheusSpec<M = NetworkQueryMetadata> {
+    /// Run the template as a range query and aggregate the samples of each series
+    #[serde(default)]
+    pub range: Option<NetworkConnectorPrometheusRange>,
     pub template: NetworkQuery<M>,
     pub url: Url,
 }
@@ -16,3 +24,31 @@ impl NetworkConnectorPrometheusSpec {
         self.template.name()
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConnectorPrometheusRange {
+    #[serde(default)]
+    pub aggregation: NetworkConnectorAggregation,
+
+    /// Query resolution step, e.g. `30s`
+    #[serde(default = "NetworkConnectorPrometheusRange::default_step")]
+    pub step: String,
+
+    /// How far to look back from now, e.g. `15m`
+    pub window: String,
+}
+
+impl NetworkConnectorPrometheusRange {
+    fn default_step() -> String {
+        "1m".into()
+    }
+
+    pub fn step(&self) -> Result<Duration, ::duration_string::Error> {
+        DurationString::from_str(&self.step).map(Into::into)
+    }
+
+    pub fn window(&self) -> Result<Duration, ::duration_string::Error> {
+        DurationString::from_str(&self.window).map(Into::into)
+    }
+}
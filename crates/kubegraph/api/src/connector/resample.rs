@@ -0,0 +1,276 @@
+use anyhow::Result;
+#[cfg(feature = "df-polars")]
+use pl::lazy::dsl;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::frame::LazyFrame;
+
+/// Collapse a time-series frame into a single sample per series.
+///
+/// All columns except `timestamp` and `value` are treated as the series keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConnectorResampleSpec {
+    #[serde(default)]
+    pub aggregation: NetworkConnectorAggregation,
+
+    #[serde(default = "NetworkConnectorResampleSpec::default_timestamp")]
+    pub timestamp: String,
+
+    #[serde(default = "NetworkConnectorResampleSpec::default_value")]
+    pub value: String,
+}
+
+impl Default for NetworkConnectorResampleSpec {
+    fn default() -> Self {
+        Self {
+            aggregation: NetworkConnectorAggregation::default(),
+            timestamp: Self::default_timestamp(),
+            value: Self::default_value(),
+        }
+    }
+}
+
+impl NetworkConnectorResampleSpec {
+    fn default_timestamp() -> String {
+        "timestamp".into()
+    }
+
+    fn default_value() -> String {
+        "value".into()
+    }
+
+    pub fn resample(&self, df: LazyFrame) -> Result<LazyFrame> {
+        match df {
+            LazyFrame::Empty => Ok(LazyFrame::Empty),
+            #[cfg(feature = "df-polars")]
+            LazyFrame::Polars(df) => self.resample_polars(df).map(LazyFrame::Polars),
+        }
+    }
+
+    #[cfg(feature = "df-polars")]
+    fn resample_polars(
+        &self,
+        mut df: ::pl::lazy::frame::LazyFrame,
+    ) -> Result<::pl::lazy::frame::LazyFrame> {
+        let Self {
+            aggregation,
+            timestamp,
+            value,
+        } = self;
+
+        let schema = df.collect_schema().map_err(|error| {
+            ::anyhow::anyhow!("failed to get the schema of polars dataframe: {error}")
+        })?;
+        let keys: Vec<_> = schema
+            .iter_names()
+            .filter(|name| name.as_str() != timestamp && name.as_str() != value)
+            .map(|name| dsl::col(name.as_str()))
+            .collect();
+
+        let aggs = [
+            dsl::col(timestamp.as_str()).max(),
+            aggregation.to_polars_expr(dsl::col(value.as_str())),
+        ];
+        if keys.is_empty() {
+            Ok(df.select(aggs))
+        } else {
+            Ok(df.group_by_stable(keys).agg(aggs))
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkConnectorAggregation {
+    Last,
+    Max,
+    #[default]
+    Mean,
+    Min,
+    P50,
+    P90,
+    P95,
+    P99,
+    Sum,
+}
+
+impl NetworkConnectorAggregation {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Last => "last",
+            Self::Max => "max",
+            Self::Mean => "mean",
+            Self::Min => "min",
+            Self::P50 => "p50",
+            Self::P90 => "p90",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+            Self::Sum => "sum",
+        }
+    }
+
+    #[cfg(feature = "df-polars")]
+    fn to_polars_expr(self, expr: dsl::Expr) -> dsl::Expr {
+        use pl::prelude::QuantileInterpolOptions;
+
+        let quantile =
+            |expr: dsl::Expr, q: f64| expr.quantile(dsl::lit(q), QuantileInterpolOptions::Linear);
+
+        match self {
+            Self::Last => expr.last(),
+            Self::Max => expr.max(),
+            Self::Mean => expr.mean(),
+            Self::Min => expr.min(),
+            Self::P50 => expr.median(),
+            Self::P90 => quantile(expr, 0.90),
+            Self::P95 => quantile(expr, 0.95),
+            Self::P99 => quantile(expr, 0.99),
+            Self::Sum => expr.sum(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "df-polars"))]
+mod tests {
+    use super::*;
+
+    fn sample() -> LazyFrame {
+        let df = ::pl::df!(
+            "name"      => [ "a",  "a",  "a",  "a",  "a",  "b",  "b"],
+            "timestamp" => [  1i64, 2i64, 3i64, 4i64, 5i64, 1i64, 2i64],
+            "value"     => [  1.0,  2.0,  3.0,  4.0,  10.0, 7.0,  5.0],
+        )
+        .expect("failed to create dataframe");
+        LazyFrame::Polars(::pl::lazy::frame::IntoLazy::lazy(df))
+    }
+
+    fn resample(aggregation: NetworkConnectorAggregation, df: LazyFrame) -> ::pl::frame::DataFrame {
+        let spec = NetworkConnectorResampleSpec {
+            aggregation,
+            ..Default::default()
+        };
+        match spec.resample(df).expect("failed to resample") {
+            LazyFrame::Empty => panic!("resampled frame should not be empty"),
+            LazyFrame::Polars(df) => df.collect().expect("failed to collect"),
+        }
+    }
+
+    fn column_i64(df: &::pl::frame::DataFrame, name: &str) -> Vec<i64> {
+        df.column(name)
+            .expect("no such column")
+            .as_materialized_series()
+            .i64()
+            .expect("not an i64 column")
+            .into_no_null_iter()
+            .collect()
+    }
+
+    fn column_f64(df: &::pl::frame::DataFrame, name: &str) -> Vec<f64> {
+        df.column(name)
+            .expect("no such column")
+            .as_materialized_series()
+            .f64()
+            .expect("not a f64 column")
+            .into_no_null_iter()
+            .collect()
+    }
+
+    fn assert_approx_eq(given: &[f64], expected: &[f64]) {
+        assert_eq!(given.len(), expected.len(), "{given:?} != {expected:?}");
+        for (given_value, expected_value) in given.iter().zip(expected) {
+            assert!(
+                (given_value - expected_value).abs() < 1e-9,
+                "{given:?} != {expected:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn resample_empty() {
+        let spec = NetworkConnectorResampleSpec::default();
+        assert!(matches!(
+            spec.resample(LazyFrame::Empty),
+            Ok(LazyFrame::Empty),
+        ));
+    }
+
+    #[test]
+    fn resample_group_by_series() {
+        let df = resample(NetworkConnectorAggregation::Mean, sample());
+
+        // one sample per series, in the order of the first appearance
+        assert_eq!(df.height(), 2);
+        let names: Vec<_> = df
+            .column("name")
+            .expect("no such column")
+            .as_materialized_series()
+            .str()
+            .expect("not a string column")
+            .into_no_null_iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+
+        // the latest timestamp of each series is kept
+        assert_eq!(column_i64(&df, "timestamp"), [5, 2]);
+        assert_approx_eq(&column_f64(&df, "value"), &[4.0, 6.0]);
+    }
+
+    #[test]
+    fn resample_without_keys() {
+        let df = ::pl::df!(
+            "timestamp" => [ 3i64, 1i64, 2i64],
+            "value"     => [  1.0,  2.0,  6.0],
+        )
+        .expect("failed to create dataframe");
+        let df = resample(
+            NetworkConnectorAggregation::Sum,
+            LazyFrame::Polars(::pl::lazy::frame::IntoLazy::lazy(df)),
+        );
+
+        assert_eq!(df.height(), 1);
+        assert_eq!(column_i64(&df, "timestamp"), [3]);
+        assert_approx_eq(&column_f64(&df, "value"), &[9.0]);
+    }
+
+    #[test]
+    fn resample_aggregations() {
+        for (aggregation, expected) in [
+            (NetworkConnectorAggregation::Last, [10.0, 5.0]),
+            (NetworkConnectorAggregation::Max, [10.0, 7.0]),
+            (NetworkConnectorAggregation::Mean, [4.0, 6.0]),
+            (NetworkConnectorAggregation::Min, [1.0, 5.0]),
+            (NetworkConnectorAggregation::P50, [3.0, 6.0]),
+            (NetworkConnectorAggregation::P90, [7.6, 6.8]),
+            (NetworkConnectorAggregation::P95, [8.8, 6.9]),
+            (NetworkConnectorAggregation::P99, [9.76, 6.98]),
+            (NetworkConnectorAggregation::Sum, [20.0, 12.0]),
+        ] {
+            let df = resample(aggregation, sample());
+            let given = column_f64(&df, "value");
+            assert!(
+                given
+                    .iter()
+                    .zip(&expected)
+                    .all(|(given, expected)| (given - expected).abs() < 1e-9),
+                "{}: {given:?} != {expected:?}",
+                aggregation.name(),
+            );
+        }
+    }
+}
@@ -1,14 +1,22 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use kube::{
+    api::{Patch, PatchParams},
+    Api, Client,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, instrument, Level};
 
 use crate::{
     connector::NetworkConnectorCrd,
     frame::LazyFrame,
     function::NetworkFunctionCrd,
     graph::{Graph, GraphData, GraphEdges, GraphScope},
-    problem::VirtualProblem,
+    problem::{NetworkProblemCrd, VirtualProblem},
 };
 
 #[async_trait]
@@ -32,6 +40,150 @@ pub struct NetworkDependencyPipeline<G> {
 }
 
 pub struct NetworkDependencyPipelineTemplate<G> {
+    pub explanation: NetworkDependencyExplanation,
     pub graph: G,
     pub static_edges: Option<GraphEdges<LazyFrame>>,
 }
+
+/// Explains why some graphs are not covered by any function chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDependencyExplanation {
+    /// The graphs without any feasible function chains
+    #[serde(default)]
+    pub graphs: Vec<NetworkDependencyGraphExplanation>,
+}
+
+impl fmt::Display for NetworkDependencyExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { graphs } = self;
+        for (index, graph) in graphs.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            graph.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl NetworkDependencyExplanation {
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDependencyGraphExplanation {
+    pub scope: GraphScope,
+
+    /// The columns required by the problem, but provided by neither the graph nor any function
+    #[serde(default)]
+    pub missing_columns: Vec<String>,
+
+    /// All the functions considered for the graph
+    #[serde(default)]
+    pub functions: Vec<NetworkDependencyFunctionExplanation>,
+}
+
+impl fmt::Display for NetworkDependencyGraphExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            scope,
+            missing_columns,
+            functions,
+        } = self;
+
+        write!(f, "graph {scope}: ")?;
+        if missing_columns.is_empty() {
+            write!(f, "no function chain provides the required columns")?;
+        } else {
+            write!(f, "missing columns {missing_columns:?}")?;
+        }
+        for function in functions {
+            write!(f, "\n  - {function}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDependencyFunctionExplanation {
+    pub scope: GraphScope,
+
+    /// The filter of the function, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+
+    /// The columns required by the filter, but unreachable from the graph
+    #[serde(default)]
+    pub missing_filter_columns: Vec<String>,
+
+    /// The columns required by the script, but unreachable from the graph
+    #[serde(default)]
+    pub missing_script_columns: Vec<String>,
+}
+
+impl fmt::Display for NetworkDependencyFunctionExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            scope,
+            filter,
+            missing_filter_columns,
+            missing_script_columns,
+        } = self;
+
+        write!(f, "function {scope}: ")?;
+        match (filter, self.is_rejected_by_filter()) {
+            (Some(filter), true) => write!(
+                f,
+                "rejected by filter {filter:?} (missing columns {missing_filter_columns:?})"
+            )?,
+            _ if !missing_script_columns.is_empty() => {
+                write!(f, "missing columns {missing_script_columns:?}")?
+            }
+            _ => write!(f, "applicable, but does not provide the required columns")?,
+        }
+        Ok(())
+    }
+}
+
+impl NetworkDependencyFunctionExplanation {
+    pub fn is_rejected_by_filter(&self) -> bool {
+        !self.missing_filter_columns.is_empty()
+    }
+}
+
+/// Publishes the explanation to the problem status, if changed.
+#[instrument(level = Level::INFO, skip(kube, cr, explanation))]
+pub async fn publish(
+    kube: &Client,
+    cr: &NetworkProblemCrd,
+    explanation: NetworkDependencyExplanation,
+) -> Result<()> {
+    let explanation = Some(explanation).filter(|explanation| !explanation.is_empty());
+    let last = cr
+        .status
+        .as_ref()
+        .and_then(|status| status.explanation.as_ref());
+    if last == explanation.as_ref() {
+        return Ok(());
+    }
+
+    let scope = GraphScope::from_resource(cr);
+    info!("Publishing the dependency explanation: {scope}");
+
+    let api = Api::<NetworkProblemCrd>::namespaced(kube.clone(), &scope.namespace);
+    let patch = Patch::Merge(json!({
+        "status": {
+            "explanation": explanation,
+        },
+    }));
+    let pp = PatchParams::default();
+    api.patch_status(&scope.name, &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to update the status of {scope}: {error}"))
+}
@@ -144,6 +144,7 @@ impl LazyFrame {
     {
         let ProblemSpec {
             metadata,
+            plan: _,
             verbose: _,
         } = problem;
 
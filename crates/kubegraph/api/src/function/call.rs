@@ -14,3 +14,22 @@ pub struct FunctionCallRequest<T = DataFrame, M = GraphMetadataPinned> {
     pub static_edges: Option<GraphEdges<T>>,
     pub template: super::NetworkFunctionTemplate,
 }
+
+/// The updated graph data returned by a function; missing tables are left as-is.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallResponse<T = DataFrame> {
+    #[serde(default)]
+    pub edges: Option<T>,
+    #[serde(default)]
+    pub nodes: Option<T>,
+}
+
+impl<T> Default for FunctionCallResponse<T> {
+    fn default() -> Self {
+        Self {
+            edges: None,
+            nodes: None,
+        }
+    }
+}
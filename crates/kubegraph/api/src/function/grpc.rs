@@ -0,0 +1,11 @@
+use ark_core_k8s::data::Url;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFunctionGrpcSpec {
+    pub endpoint: Url,
+}
@@ -2,6 +2,7 @@ pub mod annotation;
 pub mod call;
 #[cfg(feature = "function-fake")]
 pub mod fake;
+pub mod grpc;
 #[cfg(feature = "function-entrypoint")]
 pub mod service;
 pub mod spawn;
@@ -63,6 +64,8 @@ pub enum NetworkFunctionKind {
     Annotation(self::annotation::NetworkFunctionAnnotationSpec),
     #[cfg(feature = "function-fake")]
     Fake(self::fake::NetworkFunctionFakeSpec),
+    #[cfg(feature = "function-grpc")]
+    Grpc(self::grpc::NetworkFunctionGrpcSpec),
     #[cfg(feature = "function-webhook")]
     Webhook(self::webhook::NetworkFunctionWebhookSpec),
 }
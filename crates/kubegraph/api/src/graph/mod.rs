@@ -3,8 +3,9 @@ pub mod polars;
 
 use std::{collections::BTreeMap, fmt, mem::swap, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::try_join;
 use kube::ResourceExt;
 use schemars::JsonSchema;
@@ -103,6 +104,17 @@ where
 {
     async fn get(&self, scope: &GraphScope) -> Result<Option<Graph<GraphData<LazyFrame>>>>;
 
+    /// Get the graph as it was at the given moment (time-travel).
+    ///
+    /// Only versioned graph databases support this query.
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        bail!("time-travel query is not supported by this graph db: {scope} at {at}")
+    }
+
     async fn insert(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()>;
 
     async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>>;
@@ -9,6 +9,7 @@ pub mod function;
 pub mod graph;
 pub mod market;
 pub mod ops;
+pub mod plan;
 pub mod problem;
 pub mod query;
 pub mod resource;
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{product::ProductSpec, sub::SubSpec, transaction::TransactionSpec};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSpec {
+    /// The cost that can be spent
+    pub balance: <Self as super::BaseModel>::Cost,
+    /// The cost held by the open subs and the unsettled transactions
+    pub escrow: <Self as super::BaseModel>::Cost,
+}
+
+impl super::BaseModel for AccountSpec {
+    type Id = <ProductSpec as super::BaseModel>::Id;
+    type Cost = <ProductSpec as super::BaseModel>::Cost;
+    type Count = <ProductSpec as super::BaseModel>::Count;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerItem {
+    pub id: <AccountSpec as super::BaseModel>::Id,
+    pub timestamp: DateTime<Utc>,
+    pub account: <AccountSpec as super::BaseModel>::Id,
+    pub kind: LedgerKind,
+    pub amount: <AccountSpec as super::BaseModel>::Cost,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<<SubSpec as super::BaseModel>::Id>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txn: Option<<TransactionSpec as super::BaseModel>::Id>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LedgerKind {
+    /// The amount is added to the balance
+    Deposit,
+    /// The amount is moved from the balance to the escrow
+    Escrow,
+    /// The amount is moved from the escrow back to the balance
+    Refund,
+    /// The amount is paid from the escrow to the pub
+    Payment,
+    /// The amount is received from a sub
+    Income,
+}
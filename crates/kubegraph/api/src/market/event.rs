@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    price::{Direction, PriceItem},
+    product::ProductSpec,
+    transaction::TransactionSpec,
+    BaseModel,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketEvent {
+    pub prod: <ProductSpec as BaseModel>::Id,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: MarketEventKind,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "spec")]
+pub enum MarketEventKind {
+    /// A new pub or sub is registered
+    OrderCreated(PriceItem),
+    /// The remaining count of a pub or sub is changed by a trade
+    OrderUpdated(PriceItem),
+    /// A pub or sub is removed
+    OrderRemoved {
+        id: <ProductSpec as BaseModel>::Id,
+        direction: Direction,
+    },
+    /// A transaction is created, or the state of its tasks is changed
+    Transaction {
+        id: <TransactionSpec as BaseModel>::Id,
+        spec: TransactionSpec,
+    },
+    /// The product is removed; no more events are followed
+    ProductRemoved,
+}
@@ -1,3 +1,5 @@
+pub mod account;
+pub mod event;
 pub mod price;
 pub mod product;
 pub mod r#pub;
@@ -7,6 +7,8 @@ use crate::problem::ProblemSpec;
 #[serde(rename_all = "camelCase")]
 pub struct ProductSpec {
     pub problem: ProblemSpec,
+    #[serde(default)]
+    pub market: ProductMarketSpec,
 }
 
 impl super::BaseModel for ProductSpec {
@@ -14,3 +16,31 @@ impl super::BaseModel for ProductSpec {
     type Cost = i64;
     type Count = i64;
 }
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductMarketSpec {
+    /// The minimum cost to be traded, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserve_price: Option<<ProductSpec as super::BaseModel>::Cost>,
+    /// The matching algorithm of the product; follows the market solver if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solver: Option<ProductMarketSolver>,
+}
+
+impl ProductMarketSpec {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProductMarketSolver {
+    /// Match the cheapest pub with the highest sub, charging the sub's price
+    Greedy,
+    /// Match all crossing orders at a single clearing price per round
+    UniformPrice,
+    /// Split the matched count among the orders of the same price level
+    ProRata,
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::function::webhook::NetworkFunctionWebhookSpec;
 
-use super::product::ProductSpec;
+use super::{account::AccountSpec, product::ProductSpec};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +10,8 @@ pub struct PubSpec {
     pub cost: <Self as super::BaseModel>::Cost,
     pub count: <Self as super::BaseModel>::Count,
     pub function: NetworkFunctionWebhookSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<<AccountSpec as super::BaseModel>::Id>,
 }
 
 impl super::BaseModel for PubSpec {
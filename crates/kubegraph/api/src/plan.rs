@@ -0,0 +1,341 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use kube::{
+    api::{Patch, PatchParams},
+    Api, Client, ResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::{info, instrument, Level};
+
+use crate::{
+    frame::{DataFrame, LazyFrame},
+    graph::{GraphMetadataPinnedExt, GraphScope},
+    problem::{NetworkProblemCrd, ProblemStatus},
+};
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum NetworkPlanPolicy {
+    /// Apply the solved flows as soon as possible
+    #[default]
+    Apply,
+    /// Publish the solved flows as a plan and wait for an explicit approval
+    Review,
+}
+
+impl FromStr for NetworkPlanPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Apply" | "apply" | "Always" | "always" => Ok(Self::Apply),
+            "Review" | "review" | "Plan" | "plan" | "DryRun" | "dry-run" => Ok(Self::Review),
+            s => Err(format!("unknown plan policy: {s:?}")),
+        }
+    }
+}
+
+impl fmt::Display for NetworkPlanPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkPlanPolicy::Apply => "Apply".fmt(f),
+            NetworkPlanPolicy::Review => "Review".fmt(f),
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPlan {
+    /// The flows to be applied, except the empty ones
+    pub flows: Vec<NetworkPlanFlow>,
+    pub created_at: DateTime<Utc>,
+    /// A digest of the flows and the creation time, used to approve exactly this plan
+    pub id: String,
+    /// A summary of the flows to be applied
+    pub summary: NetworkPlanSummary,
+}
+
+impl NetworkPlan {
+    pub fn new(flows: &[NetworkPlanFlow]) -> Self {
+        let flows = collect_flows(flows);
+        let summary = NetworkPlanSummary::from_flows(&flows);
+
+        // NOTE: the creation time is hashed too, so that an approval is never reused
+        // by any later plan with the same flows
+        let created_at = Utc::now();
+        let id = {
+            let mut hasher = Sha256::new();
+            hasher.update(summary.digest.as_bytes());
+            hasher.update(created_at.to_rfc3339().as_bytes());
+            let hash = hasher.finalize();
+            // encode to hex format
+            format!("{hash:x}")
+        };
+
+        Self {
+            flows,
+            created_at,
+            id,
+            summary,
+        }
+    }
+}
+
+/// A digest of the flows, so that the applied flows can be compared
+/// without storing all of them.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPlanSummary {
+    /// The number of the non-empty flows
+    pub count: usize,
+    /// The SHA-256 digest of the sorted non-empty flows, in hex format
+    pub digest: String,
+}
+
+impl NetworkPlanSummary {
+    pub fn new(flows: &[NetworkPlanFlow]) -> Self {
+        Self::from_flows(&collect_flows(flows))
+    }
+
+    fn from_flows(flows: &[NetworkPlanFlow]) -> Self {
+        let digest = {
+            let mut hasher = Sha256::new();
+            hasher.update(serde_json::to_vec(flows).unwrap_or_default());
+            let hash = hasher.finalize();
+            // encode to hex format
+            format!("{hash:x}")
+        };
+
+        Self {
+            count: flows.len(),
+            digest,
+        }
+    }
+}
+
+/// Sorts the flows and drops the empty ones, so that the same flows are
+/// always summarized into the same digest.
+fn collect_flows(flows: &[NetworkPlanFlow]) -> Vec<NetworkPlanFlow> {
+    let flows: BTreeMap<_, _> = flows
+        .iter()
+        .filter(|flow| flow.flow != 0)
+        .map(|NetworkPlanFlow { src, sink, flow }| ((src.as_str(), sink.as_str()), *flow))
+        .collect();
+
+    flows
+        .into_iter()
+        .map(|((src, sink), flow)| NetworkPlanFlow {
+            flow,
+            sink: sink.into(),
+            src: src.into(),
+        })
+        .collect()
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPlanFlow {
+    pub flow: i64,
+    pub sink: String,
+    pub src: String,
+}
+
+impl NetworkPlanFlow {
+    pub async fn collect_edges<M>(edges: LazyFrame, metadata: &M) -> Result<Vec<Self>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match edges.collect().await? {
+            DataFrame::Empty => Ok(Vec::default()),
+            #[cfg(feature = "df-polars")]
+            DataFrame::Polars(df) => collect_polars_flows(&df, metadata),
+        }
+    }
+}
+
+#[cfg(feature = "df-polars")]
+fn collect_polars_flows<M>(
+    df: &::pl::frame::DataFrame,
+    metadata: &M,
+) -> Result<Vec<NetworkPlanFlow>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    use pl::datatypes::DataType;
+
+    use crate::frame::polars::get_column;
+
+    let src = get_column(df, "edge", "src", metadata.src(), Some(&DataType::String))?;
+    let sink = get_column(df, "edge", "sink", metadata.sink(), Some(&DataType::String))?;
+    let flow = get_column(df, "edge", "flow", metadata.flow(), Some(&DataType::Int64))?;
+
+    let src = src.str()?;
+    let sink = sink.str()?;
+    let flow = flow.i64()?;
+
+    Ok(src
+        .into_iter()
+        .zip(sink)
+        .zip(flow)
+        .filter_map(|((src, sink), flow)| {
+            Some(NetworkPlanFlow {
+                flow: flow.unwrap_or_default(),
+                sink: sink?.into(),
+                src: src?.into(),
+            })
+        })
+        .collect())
+}
+
+/// Reviews the planned flows of the given problem.
+///
+/// Returns `true` if the flows are approved (or nothing is changed),
+/// otherwise publishes the plan to the problem status and returns `false`.
+#[instrument(level = Level::INFO, skip(kube, flows))]
+pub async fn review(kube: &Client, scope: &GraphScope, flows: &[NetworkPlanFlow]) -> Result<bool> {
+    let GraphScope { namespace, name } = scope;
+    let api = Api::<NetworkProblemCrd>::namespaced(kube.clone(), namespace);
+
+    let cr = api
+        .get_opt(name)
+        .await
+        .map_err(|error| anyhow!("failed to get network problem {scope}: {error}"))?
+        .ok_or_else(|| anyhow!("cannot review a plan without network problem: {scope}"))?;
+    let status = cr.status.clone().unwrap_or_default();
+
+    // Apply the flows if nothing is changed since the last applied ones
+    let plan = NetworkPlan::new(flows);
+    let applied = status
+        .applied
+        .clone()
+        .unwrap_or_else(|| NetworkPlanSummary::new(&[]));
+    if applied == plan.summary {
+        return Ok(true);
+    }
+
+    // Keep the published plan if nothing is changed since then
+    let plan = match status.plan {
+        Some(last) if last.summary == plan.summary => last,
+        Some(_) | None => {
+            info!("Publishing the plan: {scope} ({id})", id = plan.id);
+            let status = ProblemStatus {
+                applied: status.applied,
+                plan: Some(plan),
+                explanation: None,
+            };
+            patch_status(&api, scope, status).await?;
+            return Ok(false);
+        }
+    };
+
+    let approved = cr
+        .annotations()
+        .get(NetworkProblemCrd::ANNOTATION_APPROVED_PLAN)
+        .map(|id| id == &plan.id)
+        .unwrap_or_default();
+    if approved {
+        info!("The plan is approved: {scope} ({id})", id = plan.id);
+    }
+    Ok(approved)
+}
+
+/// Marks the flows as applied, clearing the pending plan and its approval.
+#[instrument(level = Level::INFO, skip(kube, flows))]
+pub async fn commit(kube: &Client, scope: &GraphScope, flows: &[NetworkPlanFlow]) -> Result<()> {
+    let api = Api::<NetworkProblemCrd>::namespaced(kube.clone(), &scope.namespace);
+    let status = ProblemStatus {
+        applied: Some(NetworkPlanSummary::new(flows)),
+        plan: None,
+        explanation: None,
+    };
+    patch_status(&api, scope, status).await?;
+
+    let patch = Patch::Merge(json!({
+        "metadata": {
+            "annotations": {
+                (NetworkProblemCrd::ANNOTATION_APPROVED_PLAN): null,
+            },
+        },
+    }));
+    let pp = PatchParams::default();
+    api.patch(&scope.name, &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to clear the approved plan of {scope}: {error}"))
+}
+
+/// Approves the pending plan of the given problem.
+#[instrument(level = Level::INFO, skip(kube))]
+pub async fn approve(kube: &Client, scope: &GraphScope, id: &str) -> Result<()> {
+    let GraphScope { namespace, name } = scope;
+    let api = Api::<NetworkProblemCrd>::namespaced(kube.clone(), namespace);
+
+    let cr = api
+        .get_opt(name)
+        .await
+        .map_err(|error| anyhow!("failed to get network problem {scope}: {error}"))?
+        .ok_or_else(|| anyhow!("cannot approve a plan without network problem: {scope}"))?;
+    let resource_version = cr.metadata.resource_version.clone();
+    match cr.status.and_then(|status| status.plan) {
+        Some(plan) if plan.id == id => (),
+        Some(plan) => bail!(
+            "cannot approve a stale plan of {scope}: expected {expected}",
+            expected = plan.id,
+        ),
+        None => bail!("no plans are pending: {scope}"),
+    }
+
+    // NOTE: the resource version guards against the plan being replaced meanwhile
+    let patch = Patch::Merge(json!({
+        "metadata": {
+            "annotations": {
+                (NetworkProblemCrd::ANNOTATION_APPROVED_PLAN): id,
+            },
+            "resourceVersion": resource_version,
+        },
+    }));
+    let pp = PatchParams::default();
+    api.patch(name, &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to approve the plan of {scope}: {error}"))
+}
+
+async fn patch_status(
+    api: &Api<NetworkProblemCrd>,
+    scope: &GraphScope,
+    status: ProblemStatus,
+) -> Result<()> {
+    let patch = Patch::Merge(json!({
+        "status": status,
+    }));
+    let pp = PatchParams::default();
+    api.patch_status(&scope.name, &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to update the status of {scope}: {error}"))
+}
@@ -3,7 +3,9 @@ use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
+    dependency::NetworkDependencyExplanation,
     graph::{GraphFilter, GraphMetadataPinned, GraphScope},
+    plan::{NetworkPlan, NetworkPlanPolicy, NetworkPlanSummary},
     resource::NetworkResource,
 };
 
@@ -43,6 +45,7 @@ pub struct VirtualProblem<M = GraphMetadataPinned> {
     version = "v1alpha1",
     kind = "NetworkProblem",
     root = "NetworkProblemCrd",
+    status = "ProblemStatus",
     shortname = "np",
     namespaced,
     printcolumn = r#"{
@@ -56,6 +59,12 @@ pub struct VirtualProblem<M = GraphMetadataPinned> {
         "type": "integer",
         "description": "problem version",
         "jsonPath": ".metadata.generation"
+    }"#,
+    printcolumn = r#"{
+        "name": "plan",
+        "type": "string",
+        "description": "pending plan to be approved",
+        "jsonPath": ".status.plan.id"
     }"#
 )]
 #[schemars(bound = "M: Default + JsonSchema")]
@@ -67,6 +76,10 @@ pub struct ProblemSpec<M = GraphMetadataPinned> {
     #[serde(default)]
     pub metadata: M,
 
+    /// Overrides the plan policy of the virtual machine
+    #[serde(default)]
+    pub plan: Option<NetworkPlanPolicy>,
+
     #[serde(default = "ProblemSpec::<M>::default_verbose")]
     pub verbose: bool,
 }
@@ -78,11 +91,16 @@ where
     fn default() -> Self {
         Self {
             metadata: M::default(),
+            plan: None,
             verbose: Self::default_verbose(),
         }
     }
 }
 
+impl NetworkProblemCrd {
+    pub const ANNOTATION_APPROVED_PLAN: &'static str = "kubegraph.ulagbulag.io/approved-plan";
+}
+
 impl NetworkResource for NetworkProblemCrd {
     type Filter = ();
 
@@ -98,6 +116,22 @@ impl NetworkResource for NetworkProblemCrd {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemStatus {
+    /// A summary of the flows applied at last, used to skip the unchanged plans
+    #[serde(default)]
+    pub applied: Option<NetworkPlanSummary>,
+
+    /// The plan waiting for an approval
+    #[serde(default)]
+    pub plan: Option<NetworkPlan>,
+
+    /// Explains why some graphs are not covered by any function chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<NetworkDependencyExplanation>,
+}
+
 impl<M> ProblemSpec<M> {
     pub const MAX_CAPACITY: u64 = u64::MAX >> 32;
 
@@ -21,7 +21,15 @@ pub trait NetworkTrader<T> {
     where
         T: 'async_trait;
 
-    async fn register(&self, ctx: NetworkTraderContext<T>) -> Result<()>
+    /// Registers the problem to the market.
+    ///
+    /// Returns `false` if the trader refuses to bid, e.g. the cost exceeds the budget.
+    async fn register(&self, ctx: NetworkTraderContext<T>) -> Result<bool>
+    where
+        T: 'async_trait;
+
+    /// Completes the trade of the problem, once the problem is satisfied.
+    async fn complete(&self, scope: &GraphScope) -> Result<()>
     where
         T: 'async_trait;
 }
@@ -42,7 +50,14 @@ where
         Ok(false)
     }
 
-    async fn register(&self, _: NetworkTraderContext<T>) -> Result<()>
+    async fn register(&self, _: NetworkTraderContext<T>) -> Result<bool>
+    where
+        T: 'async_trait,
+    {
+        Ok(false)
+    }
+
+    async fn complete(&self, _: &GraphScope) -> Result<()>
     where
         T: 'async_trait,
     {
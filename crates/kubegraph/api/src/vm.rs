@@ -12,7 +12,10 @@ use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
 use clap::Parser;
 use duration_string::DurationString;
-use futures::{stream::FuturesUnordered, TryStreamExt};
+use futures::{
+    stream::{iter, FuturesUnordered},
+    StreamExt, TryStreamExt,
+};
 use num_traits::FromPrimitive;
 use ordered_float::OrderedFloat;
 use schemars::JsonSchema;
@@ -32,6 +35,7 @@ use crate::{
         NetworkGraphDBExt, ScopedNetworkGraphDBContainer,
     },
     ops::{And, Eq, Ge, Gt, Le, Lt, Max, Min, Ne, Or},
+    plan::{NetworkPlanFlow, NetworkPlanPolicy},
     problem::{NetworkProblemCrd, ProblemSpec, VirtualProblem},
     resource::{NetworkResourceClient, NetworkResourceCollectionDB, NetworkResourceDB},
     runner::{NetworkRunner, NetworkRunnerContext},
@@ -135,7 +139,8 @@ where
                 }
                 self::sealed::NetworkVirtualMachineState::Ready
                 | self::sealed::NetworkVirtualMachineState::Empty
-                | self::sealed::NetworkVirtualMachineState::Trading => {
+                | self::sealed::NetworkVirtualMachineState::Trading
+                | self::sealed::NetworkVirtualMachineState::Planning => {
                     match self.restart_policy() {
                         NetworkVirtualMachineRestartPolicy::Always => {
                             NetworkVirtualMachineRestartPolicy::DEFAULT_INTERVAL
@@ -211,6 +216,7 @@ where
             functions,
             template:
                 NetworkDependencyPipelineTemplate {
+                    explanation,
                     graph:
                         Graph {
                             connector,
@@ -233,12 +239,31 @@ where
             None => return Ok(self::sealed::NetworkVirtualMachineState::Empty),
         };
 
-        // Step 3. Solve edge flows
+        // Step 3. Publish the reason why some graphs are unfeasible
+        let cr = self
+            .resource_db()
+            .list(())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|cr: &NetworkProblemCrd| GraphScope::from_resource(cr) == problem.scope);
+        if let Some(cr) = cr {
+            let kube = self.resource_db().kube();
+            if let Err(error) = crate::dependency::publish(kube, &cr, explanation.clone()).await {
+                warn!("{error}");
+            }
+        }
+
+        // Step 4. Solve edge flows
         let data = self.solver().solve(data, &problem.spec).await?;
 
-        // Step 4. Register to the market if no feasible functions are found
+        // Step 5. Register to the market if no feasible functions are found
         if matches!(&data.edges, LazyFrame::Empty) {
-            info!("No feasible functions are found: {scope}");
+            if explanation.is_empty() {
+                info!("No feasible functions are found: {scope}");
+            } else {
+                info!("No feasible functions are found: {scope}\n{explanation}");
+            }
             if self.trader().is_enabled() {
                 info!("Registering the problem to the market: {scope}");
                 let ctx = NetworkTraderContext {
@@ -247,15 +272,40 @@ where
                     problem,
                     static_edges,
                 };
-                self.trader().register(ctx).await?;
-                info!("Registered the problem to the market: {scope}");
-                return Ok(self::sealed::NetworkVirtualMachineState::Trading);
+                if self.trader().register(ctx).await? {
+                    info!("Registered the problem to the market: {scope}");
+                    return Ok(self::sealed::NetworkVirtualMachineState::Trading);
+                } else {
+                    warn!("Skipped registering the problem to the market: {scope}");
+                    return Ok(self::sealed::NetworkVirtualMachineState::Completed);
+                }
             } else {
                 return Ok(self::sealed::NetworkVirtualMachineState::Completed);
             }
         }
 
-        // Step 5. Apply edges to real-world (or simulator)
+        // Step 6. Wait for an approval if the plan should be reviewed
+        let plan_policy = problem.spec.plan.unwrap_or_else(|| self.plan_policy());
+        let planned_flows = match plan_policy {
+            NetworkPlanPolicy::Apply => None,
+            NetworkPlanPolicy::Review => {
+                let kube = self.resource_db().kube();
+                let flows =
+                    NetworkPlanFlow::collect_edges(data.edges.clone(), &problem.spec.metadata)
+                        .await?;
+                if !crate::plan::review(kube, &problem.scope, &flows).await? {
+                    info!(
+                        "Waiting for the plan to be approved: {scope}",
+                        scope = &problem.scope,
+                    );
+                    return Ok(self::sealed::NetworkVirtualMachineState::Planning);
+                }
+                Some(flows)
+            }
+        };
+        let problem_scope = problem.scope.clone();
+
+        // Step 7. Apply edges to real-world (or simulator)
         let runner_ctx = NetworkRunnerContext {
             connectors,
             functions,
@@ -269,8 +319,14 @@ where
             static_edges,
         };
         self.runner().execute(runner_ctx).await?;
+        if let Some(flows) = planned_flows {
+            crate::plan::commit(self.resource_db().kube(), &problem_scope, &flows).await?;
+        }
+        if self.trader().is_enabled() {
+            self.trader().complete(&problem_scope).await?;
+        }
 
-        // Step 6. Visualize the outputs
+        // Step 8. Visualize the outputs
         let graph = Graph {
             connector,
             data,
@@ -283,7 +339,7 @@ where
 
     #[instrument(level = Level::INFO, skip(self))]
     async fn pull_problems(&self) -> Result<Vec<VirtualProblem>> {
-        Ok(self
+        let problems = self
             .resource_db()
             .list(())
             .await
@@ -296,8 +352,16 @@ where
                     scope,
                     spec: cr.spec,
                 }
+            });
+
+        // Skip the problems owned by other replicas
+        Ok(iter(problems)
+            .filter(|problem| {
+                let scope = problem.scope.clone();
+                async move { self.is_responsible_for(&scope).await }
             })
-            .collect())
+            .collect()
+            .await)
     }
 
     #[instrument(level = Level::INFO, skip(self, problem))]
@@ -308,10 +372,12 @@ where
         let VirtualProblem {
             filter,
             scope,
-            spec: ProblemSpec {
-                metadata,
-                verbose: _,
-            },
+            spec:
+                ProblemSpec {
+                    metadata,
+                    plan: _,
+                    verbose: _,
+                },
         } = problem;
 
         // Step 1. Collect all graphs
@@ -353,6 +419,7 @@ where
             graphs,
         };
         let NetworkDependencyPipelineTemplate {
+            explanation,
             graph: data,
             static_edges,
         } = self
@@ -364,6 +431,7 @@ where
             connectors,
             functions,
             template: NetworkDependencyPipelineTemplate {
+                explanation,
                 graph: Graph {
                     connector: None,
                     data,
@@ -399,6 +467,7 @@ mod sealed {
         Ready,
         Empty,
         Trading,
+        Planning,
         #[default]
         Completed,
     }
@@ -451,6 +520,15 @@ where
         NetworkVirtualMachineRestartPolicy::default()
     }
 
+    fn plan_policy(&self) -> NetworkPlanPolicy {
+        NetworkPlanPolicy::default()
+    }
+
+    /// Returns whether this replica should handle the given problem.
+    async fn is_responsible_for(&self, _scope: &GraphScope) -> bool {
+        true
+    }
+
     async fn close_workers(&self) -> Result<()>;
 }
 
@@ -503,6 +581,15 @@ where
         <T as NetworkVirtualMachine>::restart_policy(&**self)
     }
 
+    fn plan_policy(&self) -> NetworkPlanPolicy {
+        <T as NetworkVirtualMachine>::plan_policy(&**self)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn is_responsible_for(&self, scope: &GraphScope) -> bool {
+        <T as NetworkVirtualMachine>::is_responsible_for(&**self, scope).await
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn close_workers(&self) -> Result<()> {
         <T as NetworkVirtualMachine>::close_workers(&**self).await
@@ -0,0 +1,931 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::{stream::iter, StreamExt};
+use k8s_openapi::{
+    api::{
+        apps::v1::Deployment,
+        core::v1::{Endpoints, Namespace, Node, Pod, Service},
+        networking::v1::{NetworkPolicy, NetworkPolicyPeer},
+    },
+    apimachinery::pkg::{api::resource::Quantity, apis::meta::v1::LabelSelector},
+    NamespaceResourceScope,
+};
+use kube::{
+    api::{ApiResource, DynamicObject, GroupVersionKind, ListParams},
+    Api, Client, Resource, ResourceExt,
+};
+use kubegraph_api::{
+    connector::{
+        kubernetes::NetworkConnectorKubernetesSpec, NetworkConnectorCrd, NetworkConnectorKind,
+        NetworkConnectorSpec, NetworkConnectorType,
+    },
+    frame::LazyFrame,
+    graph::{Graph, GraphData, GraphMetadata, GraphMetadataStandard, GraphScope},
+    problem::ProblemSpec,
+};
+use polars::{
+    error::PolarsError,
+    frame::DataFrame,
+    lazy::{dsl, frame::LazyFrame as PolarsLazyFrame},
+    prelude::{IntoSeries, StringChunked},
+    series::Series,
+};
+use serde::de::DeserializeOwned;
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct NetworkConnector {
+    client: Option<Client>,
+}
+
+#[async_trait]
+impl ::kubegraph_api::connector::NetworkConnector for NetworkConnector {
+    #[inline]
+    fn connector_type(&self) -> NetworkConnectorType {
+        NetworkConnectorType::Kubernetes
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        "kubernetes"
+    }
+
+    #[instrument(level = Level::INFO, skip(self, connectors))]
+    async fn pull(
+        &mut self,
+        connectors: Vec<NetworkConnectorCrd>,
+    ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        let items: Vec<_> = connectors
+            .into_iter()
+            .filter_map(|object| {
+                let cr = Arc::new(object.clone());
+                let scope = GraphScope::from_resource(&object);
+                let NetworkConnectorSpec { kind } = object.spec;
+
+                match kind {
+                    NetworkConnectorKind::Kubernetes(spec) => {
+                        Some(NetworkConnectorItem { cr, scope, spec })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        if items.is_empty() {
+            return Ok(Vec::default());
+        }
+
+        let client = match self.client.as_ref() {
+            Some(client) => client.clone(),
+            None => {
+                let client = Client::try_default()
+                    .await
+                    .map_err(|error| anyhow!("failed to init kubernetes client: {error}"))?;
+                self.client.replace(client.clone());
+                client
+            }
+        };
+
+        let data = iter(items).filter_map(|item| {
+            let client = client.clone();
+            async move {
+                let GraphScope { namespace, name } = item.scope.clone();
+                match item.load_graph_data(client).await {
+                    Ok(data) => Some(data),
+                    Err(error) => {
+                        warn!("failed to load kubernetes connector ({namespace}/{name}): {error}");
+                        None
+                    }
+                }
+            }
+        });
+
+        Ok(data.collect().await)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct NetworkConnectorItem {
+    cr: Arc<NetworkConnectorCrd>,
+    scope: GraphScope,
+    spec: NetworkConnectorKubernetesSpec,
+}
+
+impl NetworkConnectorItem {
+    #[instrument(level = Level::INFO, skip(self, client))]
+    async fn load_graph_data(self, client: Client) -> Result<Graph<GraphData<LazyFrame>>> {
+        let Self {
+            cr,
+            scope,
+            spec:
+                NetworkConnectorKubernetesSpec {
+                    namespaces,
+                    resource,
+                },
+        } = self;
+
+        let GraphScope { namespace, name } = &scope;
+        info!("Loading kubernetes connector: {namespace}/{name}");
+
+        let cluster = ClusterObjects::load(&client, &namespaces).await?;
+        let (nodes, edges) = cluster.collect_rows(&resource);
+
+        let nodes = collect_polars_nodes(nodes).map_err(|error| {
+            anyhow!("failed to collect nodes into dataframe ({namespace}/{name}): {error}")
+        })?;
+        let edges = collect_polars_edges(edges).map_err(|error| {
+            anyhow!("failed to collect edges into dataframe ({namespace}/{name}): {error}")
+        })?;
+
+        Ok(Graph {
+            connector: Some(cr.clone()),
+            data: GraphData {
+                edges: edges.into(),
+                nodes: nodes.into(),
+            },
+            metadata: GraphMetadata::Standard(GraphMetadataStandard::default()),
+            scope,
+        })
+    }
+}
+
+struct ClusterObjects {
+    deployments: Vec<Deployment>,
+    endpoints: Vec<Endpoints>,
+    namespaces: Vec<Namespace>,
+    nodes: Vec<Node>,
+    pods: Vec<Pod>,
+    policies: Vec<NetworkPolicy>,
+    routes: Vec<DynamicObject>,
+    services: Vec<Service>,
+}
+
+impl ClusterObjects {
+    async fn load(client: &Client, namespaces: &[String]) -> Result<Self> {
+        let params = ListParams::default();
+
+        Ok(Self {
+            deployments: list_namespaced(client, namespaces).await?,
+            endpoints: list_namespaced(client, namespaces).await?,
+            namespaces: Api::<Namespace>::all(client.clone())
+                .list(&params)
+                .await?
+                .items,
+            nodes: Api::<Node>::all(client.clone()).list(&params).await?.items,
+            pods: list_namespaced::<Pod>(client, namespaces)
+                .await?
+                .into_iter()
+                .filter(|pod| {
+                    // skip the terminated pods
+                    !matches!(
+                        pod.status
+                            .as_ref()
+                            .and_then(|status| status.phase.as_deref()),
+                        Some("Failed" | "Succeeded"),
+                    )
+                })
+                .collect(),
+            policies: list_namespaced(client, namespaces).await?,
+            routes: list_http_routes(client, namespaces).await,
+            services: list_namespaced(client, namespaces).await?,
+        })
+    }
+
+    fn collect_rows(&self, resource: &str) -> (Vec<NodeRow>, Vec<EdgeRow>) {
+        let mut nodes = Vec::default();
+        let mut edges = Vec::default();
+
+        // Step 1. Collect the cluster nodes
+        for node in &self.nodes {
+            let capacity = node
+                .status
+                .as_ref()
+                .and_then(|status| status.allocatable.as_ref())
+                .and_then(|allocatable| allocatable.get(resource))
+                .map(|quantity| parse_quantity(resource, quantity))
+                .unwrap_or_default();
+
+            nodes.push(NodeRow {
+                name: NodeName::Node(&node.name_any()).to_string(),
+                kind: "node",
+                namespace: None,
+                capacity,
+                supply: 0,
+                labels: node.labels().clone(),
+            });
+        }
+
+        // Step 2. Collect the pods and their placements
+        for pod in &self.pods {
+            let namespace = pod.namespace().unwrap_or_default();
+            let name = NodeName::Pod(&namespace, &pod.name_any()).to_string();
+            let requests = pod_requests(pod, resource);
+
+            if let Some(node_name) = pod.spec.as_ref().and_then(|spec| spec.node_name.as_ref()) {
+                edges.push(EdgeRow {
+                    src: name.clone(),
+                    sink: NodeName::Node(node_name).to_string(),
+                    kind: "placement",
+                    capacity: requests,
+                });
+            }
+
+            nodes.push(NodeRow {
+                name,
+                kind: "pod",
+                namespace: Some(namespace),
+                capacity: 0,
+                supply: requests,
+                labels: pod.labels().clone(),
+            });
+        }
+
+        // Step 3. Collect the services
+        for service in &self.services {
+            let namespace = service.namespace().unwrap_or_default();
+            nodes.push(NodeRow {
+                name: NodeName::Service(&namespace, &service.name_any()).to_string(),
+                kind: "service",
+                namespace: Some(namespace),
+                capacity: 0,
+                supply: 0,
+                labels: service.labels().clone(),
+            });
+        }
+
+        // Step 4. Connect the services to their endpoints
+        for endpoints in &self.endpoints {
+            let namespace = endpoints.namespace().unwrap_or_default();
+            let src = NodeName::Service(&namespace, &endpoints.name_any()).to_string();
+
+            let targets = endpoints
+                .subsets
+                .iter()
+                .flatten()
+                .flat_map(|subset| subset.addresses.iter().flatten())
+                .filter_map(|address| address.target_ref.as_ref())
+                .filter(|target| target.kind.as_deref() == Some("Pod"));
+
+            for target in targets {
+                let Some(name) = target.name.as_deref() else {
+                    continue;
+                };
+                let namespace = target.namespace.as_deref().unwrap_or(&namespace);
+
+                edges.push(EdgeRow {
+                    src: src.clone(),
+                    sink: NodeName::Pod(namespace, name).to_string(),
+                    kind: "endpoint",
+                    capacity: MAX_CAPACITY,
+                });
+            }
+        }
+
+        // Step 5. Connect the pods allowed by the network policies
+        for policy in &self.policies {
+            let namespace = policy.namespace().unwrap_or_default();
+            let Some(spec) = policy.spec.as_ref() else {
+                continue;
+            };
+
+            let selected: Vec<_> = self
+                .pods
+                .iter()
+                .filter(|pod| {
+                    pod.namespace().as_deref() == Some(namespace.as_str())
+                        && matches_selector(&spec.pod_selector, pod.labels())
+                })
+                .collect();
+
+            let mut push = |src: &Pod, sink: &Pod| {
+                edges.push(EdgeRow {
+                    src: NodeName::from_pod(src).to_string(),
+                    sink: NodeName::from_pod(sink).to_string(),
+                    kind: "network-policy",
+                    capacity: MAX_CAPACITY,
+                })
+            };
+
+            for rule in spec.ingress.iter().flatten() {
+                for peer in self.select_peers(&namespace, rule.from.as_deref()) {
+                    for &pod in &selected {
+                        push(peer, pod);
+                    }
+                }
+            }
+            for rule in spec.egress.iter().flatten() {
+                for peer in self.select_peers(&namespace, rule.to.as_deref()) {
+                    for &pod in &selected {
+                        push(pod, peer);
+                    }
+                }
+            }
+        }
+
+        // Step 6. Connect the services to the deployments of their pods
+        for deployment in &self.deployments {
+            let namespace = deployment.namespace().unwrap_or_default();
+            let name = NodeName::Deployment(&namespace, &deployment.name_any()).to_string();
+
+            let labels = deployment
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.template.metadata.as_ref())
+                .and_then(|metadata| metadata.labels.as_ref());
+            let services = self.services.iter().filter(|service| {
+                let selector = service
+                    .spec
+                    .as_ref()
+                    .and_then(|spec| spec.selector.as_ref())
+                    .filter(|selector| !selector.is_empty());
+
+                service.namespace().as_deref() == Some(namespace.as_str())
+                    && match (selector, labels) {
+                        (Some(selector), Some(labels)) => selector
+                            .iter()
+                            .all(|(key, value)| labels.get(key) == Some(value)),
+                        (Some(_), None) | (None, _) => false,
+                    }
+            });
+            for service in services {
+                edges.push(EdgeRow {
+                    src: NodeName::Service(&namespace, &service.name_any()).to_string(),
+                    sink: name.clone(),
+                    kind: "backend",
+                    capacity: MAX_CAPACITY,
+                });
+            }
+
+            nodes.push(NodeRow {
+                name,
+                kind: "deployment",
+                namespace: Some(namespace),
+                capacity: MAX_CAPACITY,
+                supply: 0,
+                labels: deployment.labels().clone(),
+            });
+        }
+
+        // Step 7. Connect the HTTP routes to their backend services
+        for route in &self.routes {
+            let namespace = route.namespace().unwrap_or_default();
+            let name = NodeName::HttpRoute(&namespace, &route.name_any()).to_string();
+
+            let backends = route
+                .data
+                .get("spec")
+                .and_then(|spec| spec.get("rules"))
+                .and_then(|rules| rules.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|rule| rule.get("backendRefs"))
+                .filter_map(|backends| backends.as_array())
+                .flatten();
+            for backend in backends {
+                let get = |key: &str| backend.get(key).and_then(|value| value.as_str());
+                let is_service = get("group").unwrap_or_default().is_empty()
+                    && get("kind").unwrap_or("Service") == "Service";
+                let Some(backend_name) = get("name").filter(|_| is_service) else {
+                    continue;
+                };
+                let backend_namespace = get("namespace").unwrap_or(&namespace);
+
+                edges.push(EdgeRow {
+                    src: name.clone(),
+                    sink: NodeName::Service(backend_namespace, backend_name).to_string(),
+                    kind: "route",
+                    capacity: MAX_CAPACITY,
+                });
+            }
+
+            nodes.push(NodeRow {
+                name,
+                kind: "httproute",
+                namespace: Some(namespace),
+                capacity: 0,
+                supply: 0,
+                labels: route.labels().clone(),
+            });
+        }
+
+        (nodes, edges)
+    }
+
+    /// Select the pods matched by the peers.
+    ///
+    /// A missing or empty peer list matches all pods, as Kubernetes does.
+    /// Note that `ipBlock` peers are ignored.
+    fn select_peers<'a>(
+        &'a self,
+        namespace: &'a str,
+        peers: Option<&'a [NetworkPolicyPeer]>,
+    ) -> impl 'a + Iterator<Item = &'a Pod> {
+        self.pods.iter().filter(move |pod| {
+            let peers = match peers {
+                Some(peers) if !peers.is_empty() => peers,
+                _ => return true,
+            };
+            let pod_namespace = pod.namespace().unwrap_or_default();
+
+            peers.iter().any(|peer| {
+                let namespace_matched = match peer.namespace_selector.as_ref() {
+                    Some(selector) => self.namespaces.iter().any(|object| {
+                        object.name_any() == pod_namespace
+                            && matches_selector(selector, object.labels())
+                    }),
+                    None => pod_namespace == namespace,
+                };
+
+                namespace_matched
+                    && match (peer.namespace_selector.as_ref(), peer.pod_selector.as_ref()) {
+                        (_, Some(selector)) => matches_selector(selector, pod.labels()),
+                        (Some(_), None) => true,
+                        (None, None) => false,
+                    }
+            })
+        })
+    }
+}
+
+enum NodeName<'a> {
+    Deployment(&'a str, &'a str),
+    HttpRoute(&'a str, &'a str),
+    Node(&'a str),
+    Pod(&'a str, &'a str),
+    Service(&'a str, &'a str),
+}
+
+impl<'a> NodeName<'a> {
+    fn from_pod(pod: &'a Pod) -> Self {
+        Self::Pod(
+            pod.metadata.namespace.as_deref().unwrap_or_default(),
+            pod.metadata.name.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+impl fmt::Display for NodeName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deployment(namespace, name) => write!(f, "deployment/{namespace}/{name}"),
+            Self::HttpRoute(namespace, name) => write!(f, "httproute/{namespace}/{name}"),
+            Self::Node(name) => write!(f, "node/{name}"),
+            Self::Pod(namespace, name) => write!(f, "pod/{namespace}/{name}"),
+            Self::Service(namespace, name) => write!(f, "service/{namespace}/{name}"),
+        }
+    }
+}
+
+struct NodeRow {
+    name: String,
+    kind: &'static str,
+    namespace: Option<String>,
+    capacity: i64,
+    supply: i64,
+    labels: BTreeMap<String, String>,
+}
+
+struct EdgeRow {
+    src: String,
+    sink: String,
+    kind: &'static str,
+    capacity: i64,
+}
+
+const MAX_CAPACITY: i64 = ProblemSpec::<GraphMetadata>::MAX_CAPACITY as i64;
+
+async fn list_namespaced<K>(client: &Client, namespaces: &[String]) -> Result<Vec<K>>
+where
+    K: Clone + fmt::Debug + DeserializeOwned + Resource<Scope = NamespaceResourceScope>,
+    <K as Resource>::DynamicType: Default,
+{
+    let params = ListParams::default();
+
+    if namespaces.is_empty() {
+        Ok(Api::<K>::all(client.clone()).list(&params).await?.items)
+    } else {
+        let mut items = Vec::default();
+        for namespace in namespaces {
+            let api = Api::<K>::namespaced(client.clone(), namespace);
+            items.extend(api.list(&params).await?.items);
+        }
+        Ok(items)
+    }
+}
+
+/// List the Gateway API `HTTPRoute`s.
+///
+/// NOTE: the routes are skipped if the Gateway API is not installed.
+async fn list_http_routes(client: &Client, namespaces: &[String]) -> Vec<DynamicObject> {
+    let gvk = GroupVersionKind::gvk("gateway.networking.k8s.io", "v1", "HTTPRoute");
+    let resource = ApiResource::from_gvk(&gvk);
+    let params = ListParams::default();
+
+    let apis = if namespaces.is_empty() {
+        vec![Api::<DynamicObject>::all_with(client.clone(), &resource)]
+    } else {
+        namespaces
+            .iter()
+            .map(|namespace| {
+                Api::<DynamicObject>::namespaced_with(client.clone(), namespace, &resource)
+            })
+            .collect()
+    };
+
+    let mut items = Vec::default();
+    for api in apis {
+        match api.list(&params).await {
+            Ok(list) => items.extend(list.items),
+            Err(error) => {
+                warn!("skipping HTTP routes: {error}");
+                break;
+            }
+        }
+    }
+    items
+}
+
+fn matches_selector(selector: &LabelSelector, labels: &BTreeMap<String, String>) -> bool {
+    let LabelSelector {
+        match_expressions,
+        match_labels,
+    } = selector;
+
+    match_labels
+        .iter()
+        .flatten()
+        .all(|(key, value)| labels.get(key) == Some(value))
+        && match_expressions.iter().flatten().all(|requirement| {
+            let value = labels.get(&requirement.key);
+            let values = requirement.values.as_deref().unwrap_or_default();
+
+            match requirement.operator.as_str() {
+                "In" => value.is_some_and(|value| values.contains(value)),
+                "NotIn" => value.map_or(true, |value| !values.contains(value)),
+                "Exists" => value.is_some(),
+                "DoesNotExist" => value.is_none(),
+                _ => false,
+            }
+        })
+}
+
+fn pod_requests(pod: &Pod, resource: &str) -> i64 {
+    pod.spec
+        .iter()
+        .flat_map(|spec| &spec.containers)
+        .filter_map(|container| {
+            container
+                .resources
+                .as_ref()?
+                .requests
+                .as_ref()?
+                .get(resource)
+        })
+        .map(|quantity| parse_quantity(resource, quantity))
+        .sum()
+}
+
+/// Parse a quantity as an integer; `cpu` is measured in millicores.
+fn parse_quantity(resource: &str, quantity: &Quantity) -> i64 {
+    match ::ark_core_k8s::data::Quantity::try_from(quantity) {
+        Ok(quantity) => {
+            let value = if resource == "cpu" {
+                quantity.as_millis()
+            } else {
+                quantity.as_units()
+            };
+            i64::try_from(value).unwrap_or(if value < 0 { i64::MIN } else { i64::MAX })
+        }
+        Err(error) => {
+            warn!("failed to parse quantity {quantity:?}: {error}");
+            0
+        }
+    }
+}
+
+fn collect_polars_nodes(rows: Vec<NodeRow>) -> Result<DataFrame, PolarsError> {
+    if rows.is_empty() {
+        return Ok(DataFrame::default());
+    }
+
+    // collect all known columns
+    let columns = vec![
+        rows.iter()
+            .map(|row| row.name.as_str())
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_NAME.into()),
+        rows.iter()
+            .map(|row| row.kind)
+            .collect::<Series>()
+            .with_name("kind".into()),
+        rows.iter()
+            .map(|row| row.namespace.as_deref())
+            .collect::<StringChunked>()
+            .into_series()
+            .with_name("namespace".into()),
+        rows.iter()
+            .map(|row| row.capacity)
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_CAPACITY.into()),
+        rows.iter()
+            .map(|row| row.supply)
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_SUPPLY.into()),
+        rows.iter()
+            .map(|_| 0i64)
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_UNIT_COST.into()),
+    ];
+
+    // collect all labels
+    let label_keys: BTreeSet<_> = rows
+        .iter()
+        .flat_map(|row| row.labels.keys().map(|key| key.as_str()))
+        .collect();
+    let labels = label_keys.into_iter().map(|key| {
+        rows.iter()
+            .map(|row| row.labels.get(key).map(|value| value.as_str()))
+            .collect::<StringChunked>()
+            .into_series()
+            .with_name(format!("label.{key}").into())
+    });
+
+    // finalize
+    PolarsLazyFrame::default()
+        .with_columns(
+            &columns
+                .into_iter()
+                .chain(labels)
+                .map(dsl::lit)
+                .collect::<Vec<_>>(),
+        )
+        .collect()
+}
+
+fn collect_polars_edges(rows: Vec<EdgeRow>) -> Result<DataFrame, PolarsError> {
+    if rows.is_empty() {
+        return Ok(DataFrame::default());
+    }
+
+    let columns = vec![
+        rows.iter()
+            .map(|row| row.src.as_str())
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_SRC.into()),
+        rows.iter()
+            .map(|row| row.sink.as_str())
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_SINK.into()),
+        rows.iter()
+            .map(|row| row.kind)
+            .collect::<Series>()
+            .with_name("kind".into()),
+        rows.iter()
+            .map(|row| row.capacity)
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_CAPACITY.into()),
+        rows.iter()
+            .map(|_| 1i64)
+            .collect::<Series>()
+            .with_name(GraphMetadataStandard::DEFAULT_UNIT_COST.into()),
+    ];
+
+    PolarsLazyFrame::default()
+        .with_columns(&columns.into_iter().map(dsl::lit).collect::<Vec<_>>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn from_json<T>(value: Value) -> T
+    where
+        T: DeserializeOwned,
+    {
+        ::serde_json::from_value(value).expect("failed to parse an object")
+    }
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(key, value)| (key.into(), value.into()))
+            .collect()
+    }
+
+    fn new_pod(namespace: &str, name: &str, app: &str, node: &str, cpu: &str) -> Pod {
+        from_json(json!({
+            "metadata": {
+                "namespace": namespace,
+                "name": name,
+                "labels": { "app": app },
+            },
+            "spec": {
+                "nodeName": node,
+                "containers": [{
+                    "name": "main",
+                    "resources": { "requests": { "cpu": cpu } },
+                }],
+            },
+        }))
+    }
+
+    fn new_objects() -> ClusterObjects {
+        ClusterObjects {
+            deployments: vec![from_json(json!({
+                "metadata": { "namespace": "default", "name": "web" },
+                "spec": {
+                    "selector": { "matchLabels": { "app": "web" } },
+                    "template": { "metadata": { "labels": { "app": "web" } } },
+                },
+            }))],
+            endpoints: vec![from_json(json!({
+                "metadata": { "namespace": "default", "name": "web" },
+                "subsets": [{
+                    "addresses": [{
+                        "ip": "10.0.0.1",
+                        "targetRef": { "kind": "Pod", "name": "web-0" },
+                    }],
+                }],
+            }))],
+            namespaces: vec![
+                from_json(json!({
+                    "metadata": { "name": "default", "labels": { "team": "web" } },
+                })),
+                from_json(json!({
+                    "metadata": { "name": "monitoring", "labels": { "team": "ops" } },
+                })),
+            ],
+            nodes: vec![from_json(json!({
+                "metadata": { "name": "worker", "labels": { "zone": "a" } },
+                "status": { "allocatable": { "cpu": "4" } },
+            }))],
+            pods: vec![
+                new_pod("default", "web-0", "web", "worker", "250m"),
+                new_pod("default", "db-0", "db", "worker", "1"),
+                new_pod("monitoring", "agent-0", "agent", "worker", "100m"),
+            ],
+            policies: vec![from_json(json!({
+                "metadata": { "namespace": "default", "name": "db" },
+                "spec": {
+                    "podSelector": { "matchLabels": { "app": "db" } },
+                    "ingress": [{
+                        "from": [{ "podSelector": { "matchLabels": { "app": "web" } } }],
+                    }],
+                },
+            }))],
+            routes: vec![from_json(json!({
+                "apiVersion": "gateway.networking.k8s.io/v1",
+                "kind": "HTTPRoute",
+                "metadata": { "namespace": "default", "name": "web" },
+                "spec": {
+                    "rules": [{
+                        "backendRefs": [
+                            { "name": "web", "port": 80 },
+                            { "group": "example.com", "kind": "Bucket", "name": "assets" },
+                        ],
+                    }],
+                },
+            }))],
+            services: vec![from_json(json!({
+                "metadata": { "namespace": "default", "name": "web" },
+                "spec": { "selector": { "app": "web" } },
+            }))],
+        }
+    }
+
+    fn peer_names<'a>(pods: impl Iterator<Item = &'a Pod>) -> Vec<String> {
+        pods.map(|pod| NodeName::from_pod(pod).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn matches_selector_labels_and_expressions() {
+        let labels = labels(&[("app", "web"), ("tier", "frontend")]);
+
+        // an empty selector matches everything
+        assert!(matches_selector(&LabelSelector::default(), &labels));
+
+        let selector: LabelSelector = from_json(json!({ "matchLabels": { "app": "web" } }));
+        assert!(matches_selector(&selector, &labels));
+        let selector: LabelSelector = from_json(json!({ "matchLabels": { "app": "db" } }));
+        assert!(!matches_selector(&selector, &labels));
+
+        let matches = |operator: &str, key: &str, values: &[&str]| {
+            let selector: LabelSelector = from_json(json!({
+                "matchExpressions": [{ "key": key, "operator": operator, "values": values }],
+            }));
+            matches_selector(&selector, &labels)
+        };
+        assert!(matches("In", "tier", &["frontend", "backend"]));
+        assert!(!matches("In", "tier", &["backend"]));
+        assert!(!matches("In", "zone", &["a"]));
+        assert!(matches("NotIn", "tier", &["backend"]));
+        assert!(!matches("NotIn", "tier", &["frontend"]));
+        assert!(matches("NotIn", "zone", &["a"]));
+        assert!(matches("Exists", "app", &[]));
+        assert!(!matches("Exists", "zone", &[]));
+        assert!(matches("DoesNotExist", "zone", &[]));
+        assert!(!matches("DoesNotExist", "app", &[]));
+        assert!(!matches("Unknown", "app", &[]));
+
+        // both labels and expressions should be matched
+        let selector: LabelSelector = from_json(json!({
+            "matchLabels": { "app": "web" },
+            "matchExpressions": [{ "key": "tier", "operator": "In", "values": ["backend"] }],
+        }));
+        assert!(!matches_selector(&selector, &labels));
+    }
+
+    #[test]
+    fn select_peers_by_namespace_and_pod() {
+        let objects = new_objects();
+        let select = |peers: Option<Value>| {
+            let peers: Option<Vec<NetworkPolicyPeer>> = peers.map(from_json);
+            peer_names(objects.select_peers("default", peers.as_deref()))
+        };
+
+        // a missing or empty peer list matches all pods
+        assert_eq!(select(None).len(), 3);
+        assert_eq!(select(Some(json!([]))).len(), 3);
+
+        // a pod selector is restricted to the policy namespace
+        assert_eq!(
+            select(Some(json!([{ "podSelector": {} }]))),
+            ["pod/default/web-0", "pod/default/db-0"],
+        );
+        assert_eq!(
+            select(Some(
+                json!([{ "podSelector": { "matchLabels": { "app": "agent" } } }])
+            )),
+            Vec::<String>::default(),
+        );
+
+        // a namespace selector matches all pods in the selected namespaces
+        assert_eq!(
+            select(Some(json!([{
+                "namespaceSelector": { "matchLabels": { "team": "ops" } },
+            }]))),
+            ["pod/monitoring/agent-0"],
+        );
+        assert_eq!(
+            select(Some(json!([{
+                "namespaceSelector": {},
+                "podSelector": { "matchLabels": { "app": "db" } },
+            }]))),
+            ["pod/default/db-0"],
+        );
+
+        // ipBlock peers are ignored
+        assert_eq!(
+            select(Some(json!([{ "ipBlock": { "cidr": "10.0.0.0/8" } }]))),
+            Vec::<String>::default(),
+        );
+    }
+
+    #[test]
+    fn collect_rows_from_cluster_objects() {
+        let objects = new_objects();
+        let (nodes, edges) = objects.collect_rows("cpu");
+
+        let node = |name: &str| {
+            nodes
+                .iter()
+                .find(|row| row.name == name)
+                .unwrap_or_else(|| panic!("node should exist: {name}"))
+        };
+        assert_eq!(nodes.len(), 7);
+        assert_eq!(node("node/worker").capacity, 4_000);
+        assert_eq!(node("node/worker").labels, labels(&[("zone", "a")]));
+        assert_eq!(node("pod/default/web-0").supply, 250);
+        assert_eq!(node("pod/default/db-0").supply, 1_000);
+        assert_eq!(node("service/default/web").kind, "service");
+        assert_eq!(node("deployment/default/web").capacity, MAX_CAPACITY);
+        assert_eq!(node("httproute/default/web").kind, "httproute");
+
+        let mut edges: Vec<_> = edges
+            .iter()
+            .map(|row| (row.kind, row.src.as_str(), row.sink.as_str()))
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            [
+                ("backend", "service/default/web", "deployment/default/web"),
+                ("endpoint", "service/default/web", "pod/default/web-0"),
+                ("network-policy", "pod/default/web-0", "pod/default/db-0"),
+                ("placement", "pod/default/db-0", "node/worker"),
+                ("placement", "pod/default/web-0", "node/worker"),
+                ("placement", "pod/monitoring/agent-0", "node/worker"),
+                ("route", "httproute/default/web", "service/default/web"),
+            ],
+        );
+    }
+}
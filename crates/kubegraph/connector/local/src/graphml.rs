@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+use kubegraph_api::graph::GraphMetadataStandard;
+use polars::{
+    datatypes::DataType,
+    frame::DataFrame,
+    lazy::{dsl, frame::LazyFrame as PolarsLazyFrame},
+    prelude::{IntoSeries, StringChunked},
+};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+#[derive(Default)]
+pub(crate) struct GraphMl {
+    edges: Vec<BTreeMap<String, String>>,
+    keys: BTreeMap<String, GraphMlKey>,
+    nodes: Vec<BTreeMap<String, String>>,
+}
+
+struct GraphMlKey {
+    name: String,
+    ty: Option<DataType>,
+}
+
+impl GraphMl {
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let mut reader = Reader::from_str(text);
+        reader.config_mut().trim_text(true);
+
+        let mut graph = Self::default();
+        let mut current_data = None;
+        let mut current_row: Option<(bool, BTreeMap<String, String>)> = None;
+
+        loop {
+            let (element, is_empty) = match reader
+                .read_event()
+                .map_err(|error| anyhow!("failed to parse graphml: {error}"))?
+            {
+                Event::Start(element) => (element, false),
+                Event::Empty(element) => (element, true),
+                Event::Text(text) => {
+                    if let (Some(key), Some((_, row))) = (current_data.as_ref(), &mut current_row) {
+                        let name = match graph.keys.get(key) {
+                            Some(GraphMlKey { name, .. }) => name.clone(),
+                            None => key.clone(),
+                        };
+                        row.insert(name, text.unescape()?.into_owned());
+                    }
+                    continue;
+                }
+                Event::End(element) => {
+                    match element.name().as_ref() {
+                        b"data" => current_data = None,
+                        b"edge" | b"node" => graph.push(current_row.take()),
+                        _ => (),
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            let mut attributes = parse_attributes(&element)?;
+            match element.name().as_ref() {
+                b"key" => {
+                    let Some(id) = attributes.remove("id") else {
+                        continue;
+                    };
+                    let name = attributes.remove("attr.name").unwrap_or_else(|| id.clone());
+                    let ty = attributes
+                        .get("attr.type")
+                        .and_then(|ty| match ty.as_str() {
+                            "boolean" => Some(DataType::Boolean),
+                            "int" | "long" => Some(DataType::Int64),
+                            "float" | "double" => Some(DataType::Float64),
+                            _ => None,
+                        });
+                    graph.keys.insert(id, GraphMlKey { name, ty });
+                }
+                b"node" => {
+                    let mut row = BTreeMap::default();
+                    if let Some(id) = attributes.remove("id") {
+                        row.insert(GraphMetadataStandard::DEFAULT_NAME.into(), id);
+                    }
+                    if is_empty {
+                        graph.push(Some((false, row)));
+                    } else {
+                        current_row = Some((false, row));
+                    }
+                }
+                b"edge" => {
+                    let mut row = BTreeMap::default();
+                    if let Some(src) = attributes.remove("source") {
+                        row.insert(GraphMetadataStandard::DEFAULT_SRC.into(), src);
+                    }
+                    if let Some(sink) = attributes.remove("target") {
+                        row.insert(GraphMetadataStandard::DEFAULT_SINK.into(), sink);
+                    }
+                    if is_empty {
+                        graph.push(Some((true, row)));
+                    } else {
+                        current_row = Some((true, row));
+                    }
+                }
+                b"data" if !is_empty => current_data = attributes.remove("key"),
+                _ => (),
+            }
+        }
+        Ok(graph)
+    }
+
+    fn push(&mut self, row: Option<(bool, BTreeMap<String, String>)>) {
+        match row {
+            Some((true, row)) => self.edges.push(row),
+            Some((false, row)) => self.nodes.push(row),
+            None => (),
+        }
+    }
+
+    pub(crate) fn edges(&self) -> Result<DataFrame> {
+        self.collect_polars_rows(&self.edges)
+    }
+
+    pub(crate) fn nodes(&self) -> Result<DataFrame> {
+        self.collect_polars_rows(&self.nodes)
+    }
+
+    fn collect_polars_rows(&self, rows: &[BTreeMap<String, String>]) -> Result<DataFrame> {
+        if rows.is_empty() {
+            return Ok(DataFrame::default());
+        }
+
+        // collect all known columns
+        let column_names: BTreeSet<_> = rows
+            .iter()
+            .flat_map(|row| row.keys().map(|key| key.as_str()))
+            .collect();
+        let columns = column_names.iter().map(|&name| {
+            let column = rows
+                .iter()
+                .map(|row| row.get(name).map(|value| value.as_str()))
+                .collect::<StringChunked>()
+                .into_series()
+                .with_name(name.into());
+            dsl::lit(column)
+        });
+
+        // apply the declared types
+        let casts = column_names.iter().filter_map(|&name| {
+            self.keys
+                .values()
+                .find(|key| key.name == name)
+                .and_then(|key| key.ty.clone())
+                .map(|ty| dsl::col(name).cast(ty))
+        });
+
+        PolarsLazyFrame::default()
+            .with_columns(&columns.collect::<Vec<_>>())
+            .with_columns(&casts.collect::<Vec<_>>())
+            .collect()
+            .map_err(|error| anyhow!("failed to collect graphml into dataframe: {error}"))
+    }
+}
+
+fn parse_attributes(element: &BytesStart) -> Result<BTreeMap<String, String>> {
+    element
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute?;
+            let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+            let value = attribute.unescape_value()?.into_owned();
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="capacity" attr.type="int"/>
+  <key id="d1" for="edge" attr.name="unit_cost" attr.type="double"/>
+  <key id="d2" for="node" attr.name="label" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="a">
+      <data key="d0">300</data>
+      <data key="d2">A &amp; B</data>
+    </node>
+    <node id="b">
+      <data key="d0">100</data>
+    </node>
+    <node id="c"/>
+    <edge source="a" target="b">
+      <data key="d1">1.5</data>
+    </edge>
+    <edge source="b" target="c"/>
+  </graph>
+</graphml>
+"#;
+
+    #[test]
+    fn parse_nodes() {
+        let graph = GraphMl::parse(SAMPLE).expect("failed to parse graphml");
+        let nodes = graph.nodes().expect("failed to collect nodes");
+
+        let expected = df!(
+            "capacity"  => [Some(300i64), Some(100i64), None],
+            "label"     => [Some("A & B"), None, None],
+            "name"      => ["a", "b", "c"],
+        )
+        .expect("failed to create nodes dataframe");
+        assert_eq!(nodes, expected);
+    }
+
+    #[test]
+    fn parse_edges() {
+        let graph = GraphMl::parse(SAMPLE).expect("failed to parse graphml");
+        let edges = graph.edges().expect("failed to collect edges");
+
+        let expected = df!(
+            "sink"      => ["b", "c"],
+            "src"       => ["a", "b"],
+            "unit_cost" => [Some(1.5f64), None],
+        )
+        .expect("failed to create edges dataframe");
+        assert_eq!(edges, expected);
+    }
+
+    #[test]
+    fn parse_undeclared_keys() {
+        let text = r#"<graphml><graph>
+            <node id="a"><data key="extra">hello</data></node>
+        </graph></graphml>"#;
+        let graph = GraphMl::parse(text).expect("failed to parse graphml");
+        let nodes = graph.nodes().expect("failed to collect nodes");
+
+        // undeclared keys are kept as string columns named by the key id
+        let expected = df!(
+            "extra" => ["hello"],
+            "name"  => ["a"],
+        )
+        .expect("failed to create nodes dataframe");
+        assert_eq!(nodes, expected);
+        assert_eq!(graph.edges().expect("failed to collect edges").height(), 0);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(GraphMl::parse("<graphml><graph></node></graphml>").is_err());
+    }
+}
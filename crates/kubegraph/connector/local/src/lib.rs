@@ -1,26 +1,42 @@
-use std::{path::Path, sync::Arc};
+mod graphml;
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use futures::{stream::iter, StreamExt};
+use futures::{future::pending, stream::iter, StreamExt};
 use kubegraph_api::{
     connector::{
-        local::NetworkConnectorLocalSpec, NetworkConnectorCrd, NetworkConnectorKind,
-        NetworkConnectorSpec, NetworkConnectorType,
+        local::{
+            NetworkConnectorLocalDataType, NetworkConnectorLocalFormat, NetworkConnectorLocalSpec,
+        },
+        NetworkConnectorCrd, NetworkConnectorKind, NetworkConnectorSpec, NetworkConnectorType,
     },
     frame::LazyFrame,
     graph::{Graph, GraphData, GraphMetadataRaw, GraphScope},
 };
 use polars::{
+    datatypes::DataType,
     frame::DataFrame,
     io::{csv::read::CsvReadOptions, SerReader},
-    lazy::frame::IntoLazy,
+    lazy::{dsl, frame::IntoLazy},
+    prelude::{IpcReader, JsonLineReader, ParquetReader},
 };
-use tokio::fs;
+use tokio::{fs, time::sleep};
 use tracing::{info, instrument, warn, Level};
 
+use crate::graphml::GraphMl;
+
 #[derive(Default)]
-pub struct NetworkConnector {}
+pub struct NetworkConnector {
+    watched: BTreeMap<GraphScope, Vec<(PathBuf, Option<SystemTime>)>>,
+}
 
 #[async_trait]
 impl ::kubegraph_api::connector::NetworkConnector for NetworkConnector {
@@ -39,29 +55,98 @@ impl ::kubegraph_api::connector::NetworkConnector for NetworkConnector {
         &mut self,
         connectors: Vec<NetworkConnectorCrd>,
     ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
-        let items = connectors.into_iter().filter_map(|object| {
-            let cr = Arc::new(object.clone());
-            let scope = GraphScope::from_resource(&object);
-            let NetworkConnectorSpec { kind } = object.spec;
-
-            match kind {
-                NetworkConnectorKind::Local(spec) => Some(NetworkConnectorItem { cr, scope, spec }),
-                _ => None,
-            }
-        });
+        let items: Vec<_> = connectors
+            .into_iter()
+            .filter_map(|object| {
+                let cr = Arc::new(object.clone());
+                let scope = GraphScope::from_resource(&object);
+                let NetworkConnectorSpec { kind } = object.spec;
 
-        let data = iter(items).filter_map(|item| async move {
-            let GraphScope { namespace, name } = item.scope.clone();
-            match item.load_graph_data().await {
-                Ok(data) => Some(data),
+                match kind {
+                    NetworkConnectorKind::Local(spec) => {
+                        Some(NetworkConnectorItem { cr, scope, spec })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let data: Vec<_> = iter(items)
+            .then(|item| async move {
+                // stat the files first, so that any changes while loading are detected later
+                let files = if item.spec.watch {
+                    let mut files = Vec::default();
+                    for path in item.paths() {
+                        let modified = load_modified(&path).await;
+                        files.push((path, modified));
+                    }
+                    Some(files)
+                } else {
+                    None
+                };
+
+                let scope = item.scope.clone();
+                (scope, files, item.load_graph_data().await)
+            })
+            .collect()
+            .await;
+
+        let mut graphs = Vec::with_capacity(data.len());
+        for (scope, files, result) in data {
+            match result {
+                Ok(graph) => {
+                    // Register the files to be watched, only after loaded
+                    match files {
+                        Some(files) => {
+                            self.watched.insert(scope, files);
+                        }
+                        None => {
+                            self.watched.remove(&scope);
+                        }
+                    }
+                    graphs.push(graph);
+                }
                 Err(error) => {
+                    let GraphScope { namespace, name } = scope;
                     warn!("failed to load local connector ({namespace}/{name}): {error}");
-                    None
                 }
             }
-        });
+        }
+        Ok(graphs)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    fn forget(&mut self, scope: &GraphScope) {
+        self.watched.remove(scope);
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn wait_for_changes(&mut self) -> Vec<GraphScope> {
+        const INTERVAL: Duration = Duration::from_secs(1);
+
+        if self.watched.is_empty() {
+            return pending().await;
+        }
+
+        loop {
+            sleep(INTERVAL).await;
+
+            // NOTE: the watched files are updated only when they are reloaded,
+            // so that no changes are lost even if this future is cancelled.
+            let mut changed = Vec::default();
+            for (scope, files) in &self.watched {
+                for (path, last_modified) in files {
+                    if *last_modified != load_modified(path).await {
+                        changed.push(scope.clone());
+                        break;
+                    }
+                }
+            }
 
-        Ok(data.collect().await)
+            if !changed.is_empty() {
+                return changed;
+            }
+        }
     }
 }
 
@@ -73,24 +158,55 @@ struct NetworkConnectorItem {
 }
 
 impl NetworkConnectorItem {
+    fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![
+            self.spec.path.join(self.spec.key_edges()),
+            self.spec.path.join(self.spec.key_nodes()),
+        ];
+        paths.dedup();
+        paths
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn load_graph_data(self) -> Result<Graph<GraphData<LazyFrame>>> {
+        let key_edges = self.spec.key_edges();
+        let key_nodes = self.spec.key_nodes();
+
         let Self {
             cr,
             scope,
             spec:
                 NetworkConnectorLocalSpec {
                     path: base_dir,
-                    key_edges,
-                    key_nodes,
+                    format,
+                    key_edges: _,
+                    key_nodes: _,
+                    schema,
+                    watch: _,
                 },
         } = self;
 
         let GraphScope { namespace, name } = &scope;
         info!("Loading local connector: {namespace}/{name}");
 
-        let edges = load_csv(&base_dir, &key_edges).await?;
-        let nodes = load_csv(&base_dir, &key_nodes).await?;
+        let (edges, nodes) = match format {
+            NetworkConnectorLocalFormat::GraphMl => {
+                let graph_nodes = load_graphml(&base_dir, &key_nodes).await?;
+                let edges = if key_edges == key_nodes {
+                    graph_nodes.edges()?
+                } else {
+                    load_graphml(&base_dir, &key_edges).await?.edges()?
+                };
+                (edges, graph_nodes.nodes()?)
+            }
+            format => (
+                load_file(&base_dir, &key_edges, format).await?,
+                load_file(&base_dir, &key_nodes, format).await?,
+            ),
+        };
+
+        let edges = apply_schema(edges, &schema)?;
+        let nodes = apply_schema(nodes, &schema)?;
 
         let metadata = GraphMetadataRaw::from_polars(&nodes).into();
 
@@ -107,25 +223,92 @@ impl NetworkConnectorItem {
 }
 
 #[instrument(level = Level::INFO)]
-async fn load_csv(base_dir: &Path, filename: &str) -> Result<DataFrame> {
-    let mut path = base_dir.to_path_buf();
-    path.push(filename);
+async fn load_file(
+    base_dir: &Path,
+    filename: &str,
+    format: NetworkConnectorLocalFormat,
+) -> Result<DataFrame> {
+    let path = base_dir.join(filename);
 
-    if fs::try_exists(&path).await? {
-        CsvReadOptions::default()
+    if !fs::try_exists(&path).await? {
+        return Ok(DataFrame::default());
+    }
+
+    let open = || {
+        File::open(&path)
+            .map_err(|error| anyhow!("failed to load file {path}: {error}", path = path.display()))
+    };
+    let df = match format {
+        NetworkConnectorLocalFormat::ArrowIpc => IpcReader::new(open()?).finish(),
+        NetworkConnectorLocalFormat::Csv => CsvReadOptions::default()
             .with_has_header(true)
             .try_into_reader_with_file_path(Some(path.to_path_buf()))
             .map_err(
                 |error| anyhow!("failed to load file {path}: {error}", path = path.display(),),
             )?
-            .finish()
-            .map_err(|error| {
-                anyhow!(
-                    "failed to parse file {path}: {error}",
-                    path = path.display(),
-                )
-            })
+            .finish(),
+        NetworkConnectorLocalFormat::GraphMl => {
+            return load_graphml(base_dir, filename).await?.nodes();
+        }
+        NetworkConnectorLocalFormat::Ndjson => JsonLineReader::new(open()?).finish(),
+        NetworkConnectorLocalFormat::Parquet => ParquetReader::new(open()?).finish(),
+    };
+
+    df.map_err(|error| {
+        anyhow!(
+            "failed to parse file {path}: {error}",
+            path = path.display(),
+        )
+    })
+}
+
+#[instrument(level = Level::INFO)]
+async fn load_graphml(base_dir: &Path, filename: &str) -> Result<GraphMl> {
+    let path = base_dir.join(filename);
+
+    if fs::try_exists(&path).await? {
+        let text = fs::read_to_string(&path).await.map_err(|error| {
+            anyhow!("failed to load file {path}: {error}", path = path.display())
+        })?;
+        GraphMl::parse(&text).map_err(|error| {
+            anyhow!(
+                "failed to parse file {path}: {error}",
+                path = path.display()
+            )
+        })
     } else {
-        Ok(DataFrame::default())
+        Ok(GraphMl::default())
+    }
+}
+
+fn apply_schema(
+    df: DataFrame,
+    schema: &BTreeMap<String, NetworkConnectorLocalDataType>,
+) -> Result<DataFrame> {
+    let casts: Vec<_> = schema
+        .iter()
+        .filter(|(name, _)| df.get_column_index(name).is_some())
+        .map(|(name, ty)| {
+            let ty = match ty {
+                NetworkConnectorLocalDataType::Bool => DataType::Boolean,
+                NetworkConnectorLocalDataType::Float => DataType::Float64,
+                NetworkConnectorLocalDataType::Int => DataType::Int64,
+                NetworkConnectorLocalDataType::String => DataType::String,
+            };
+            dsl::col(name.as_str()).cast(ty)
+        })
+        .collect();
+
+    if casts.is_empty() {
+        return Ok(df);
     }
+
+    df.lazy()
+        .with_columns(casts)
+        .collect()
+        .map_err(|error| anyhow!("failed to apply schema hints: {error}"))
+}
+
+async fn load_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
 }
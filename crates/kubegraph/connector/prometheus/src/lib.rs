@@ -1,12 +1,18 @@
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::Arc,
+    time::SystemTime,
+};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use futures::{stream::iter, StreamExt};
 use kubegraph_api::{
     connector::{
-        prometheus::NetworkConnectorPrometheusSpec, NetworkConnectorCrd, NetworkConnectorKind,
-        NetworkConnectorSpec, NetworkConnectorType,
+        prometheus::{NetworkConnectorPrometheusRange, NetworkConnectorPrometheusSpec},
+        resample::NetworkConnectorResampleSpec,
+        NetworkConnectorCrd, NetworkConnectorKind, NetworkConnectorSpec, NetworkConnectorType,
     },
     frame::LazyFrame,
     graph::{Graph, GraphData, GraphMetadata, GraphMetadataRaw, GraphScope},
@@ -18,7 +24,7 @@ use polars::{
     lazy::{dsl, frame::LazyFrame as PolarsLazyFrame},
     series::Series,
 };
-use prometheus_http_query::{response::InstantVector, Client};
+use prometheus_http_query::{response::Sample, Client};
 use tracing::{info, instrument, warn, Level};
 
 #[derive(Default)]
@@ -82,6 +88,7 @@ struct NetworkConnectorItem<T> {
     client: Client,
     cr: Arc<NetworkConnectorCrd>,
     query: NetworkQuery<T>,
+    range: Option<NetworkConnectorPrometheusRange>,
     scope: GraphScope,
 }
 
@@ -93,7 +100,11 @@ impl NetworkConnectorItem<NetworkQueryMetadata> {
     ) -> Result<Self> {
         #[instrument(level = Level::INFO, skip(spec))]
         fn load_client(spec: &NetworkConnectorPrometheusSpec) -> Result<Client> {
-            let NetworkConnectorPrometheusSpec { template: _, url } = spec;
+            let NetworkConnectorPrometheusSpec {
+                range: _,
+                template: _,
+                url,
+            } = spec;
 
             Client::from_str(url.as_str())
                 .map_err(|error| anyhow!("failed to init prometheus client {url:?}: {error}"))
@@ -103,6 +114,7 @@ impl NetworkConnectorItem<NetworkQueryMetadata> {
             cr,
             client: load_client(&spec)?,
             query: spec.template,
+            range: spec.range,
             scope,
         })
     }
@@ -112,6 +124,7 @@ impl NetworkConnectorItem<NetworkQueryMetadata> {
         let Self {
             cr,
             client,
+            range,
             scope,
             query:
                 NetworkQuery {
@@ -124,14 +137,63 @@ impl NetworkConnectorItem<NetworkQueryMetadata> {
         info!("Loading prometheus {type} connector: {namespace}/{name}");
 
         // Evaluate a PromQL query.
-        let response = client.query(query).get().await?;
-        let (data, _) = response.into_inner();
-        let vectors = data.into_vector().ok().unwrap();
-
-        // Collect columns
-        let df = collect_polars_columns(vectors, consts).map_err(|error| {
-            anyhow!("failed to collect {type} into dataframe ({namespace}/{name}): {error}")
-        })?;
+        let df = match range {
+            Some(range) => {
+                let step = range.step()?;
+                let window = range.window()?;
+
+                let end = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)?
+                    .as_secs();
+                let start = end.saturating_sub(window.as_secs());
+
+                let response = client
+                    .query_range(query, start as i64, end as i64, step.as_secs_f64())
+                    .get()
+                    .await?;
+                let (data, _) = response.into_inner();
+                let matrix = data
+                    .into_matrix()
+                    .map_err(|_| anyhow!("expected a range vector ({namespace}/{name})"))?;
+
+                // Collect columns
+                let rows = matrix
+                    .iter()
+                    .flat_map(|row| row.samples().iter().map(|sample| (row.metric(), sample)))
+                    .collect();
+                let df = collect_polars_columns(rows, consts).map_err(|error| {
+                    anyhow!("failed to collect {type} into dataframe ({namespace}/{name}): {error}")
+                })?;
+
+                // Aggregate the samples of each series
+                let resample = NetworkConnectorResampleSpec {
+                    aggregation: range.aggregation,
+                    ..Default::default()
+                };
+                match resample.resample(df.into())? {
+                    LazyFrame::Polars(df) => df.collect().map_err(|error| {
+                        anyhow!("failed to resample {type} ({namespace}/{name}): {error}")
+                    })?,
+                    LazyFrame::Empty => DataFrame::default(),
+                }
+            }
+            None => {
+                let response = client.query(query).get().await?;
+                let (data, _) = response.into_inner();
+                let vectors = data
+                    .into_vector()
+                    .map_err(|_| anyhow!("expected an instant vector ({namespace}/{name})"))?;
+
+                // Collect columns
+                let rows = vectors
+                    .iter()
+                    .map(|row| (row.metric(), row.sample()))
+                    .collect();
+                collect_polars_columns(rows, consts).map_err(|error| {
+                    anyhow!("failed to collect {type} into dataframe ({namespace}/{name}): {error}")
+                })?
+            }
+        };
         let metadata = GraphMetadataRaw::from_polars(&df).into();
 
         let graph = Graph {
@@ -154,12 +216,12 @@ impl NetworkConnectorItem<NetworkQueryMetadata> {
 }
 
 fn collect_polars_columns(
-    vectors: Vec<InstantVector>,
+    vectors: Vec<(&HashMap<String, String>, &Sample)>,
     consts: BTreeMap<String, String>,
 ) -> Result<DataFrame, PolarsError> {
     // trust the first row's column names
     let column_names: Vec<_> = match vectors.first() {
-        Some(row) => row.metric().keys().map(|key| key.as_str()).collect(),
+        Some((metric, _)) => metric.keys().map(|key| key.as_str()).collect(),
         None => return Ok(DataFrame::default()),
     };
 
@@ -167,7 +229,7 @@ fn collect_polars_columns(
     let columns = column_names.into_iter().map(|name| {
         vectors
             .iter()
-            .filter_map(|row| row.metric().get(name))
+            .filter_map(|(metric, _)| metric.get(name))
             .map(|value| value.as_str())
             .collect::<Series>()
             .with_name(name.into())
@@ -178,14 +240,14 @@ fn collect_polars_columns(
         .chain(Some(
             vectors
                 .iter()
-                .map(|row| row.sample().timestamp())
+                .map(|(_, sample)| sample.timestamp())
                 .collect::<Series>()
                 .with_name("timestamp".into()),
         ))
         .chain(Some(
             vectors
                 .iter()
-                .map(|row| row.sample().value())
+                .map(|(_, sample)| sample.value())
                 .collect::<Series>()
                 .with_name("value".into()),
         ));
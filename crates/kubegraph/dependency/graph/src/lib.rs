@@ -181,6 +181,67 @@ where
     }
 }
 
+impl<N> Graph<N>
+where
+    N: Node,
+{
+    /// Explains which features block the given claim.
+    pub fn explain<'a>(
+        &'a self,
+        claim: &GraphPipelineClaim<'a, <N as Node>::Feature>,
+    ) -> GraphExplanation<'a, N> {
+        let GraphPipelineClaim {
+            option: _,
+            src: claim_src,
+            sink: claim_sink,
+        } = claim;
+
+        // Collect all features reachable from the source
+        // NOTE: the features of the final nodes cannot be passed to other nodes
+        let mut features: BTreeSet<_> = claim_src.iter().collect();
+        let mut final_features = BTreeSet::default();
+        let mut visited = vec![false; self.nodes.len()];
+        loop {
+            let mut is_updated = false;
+            for (index, node) in self.nodes.iter().enumerate() {
+                if visited[index] || !features.contains_all(node.requirements()) {
+                    continue;
+                }
+
+                visited[index] = true;
+                if node.is_final() {
+                    final_features.extend(node.provided());
+                } else {
+                    is_updated = true;
+                    features.extend(node.provided());
+                }
+            }
+            if !is_updated {
+                break;
+            }
+        }
+
+        GraphExplanation {
+            missing: claim_sink
+                .iter()
+                .filter(|&feature| !features.contains(feature) && !final_features.contains(feature))
+                .collect(),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| GraphNodeExplanation {
+                    node,
+                    missing: node
+                        .requirements()
+                        .iter()
+                        .filter(|&feature| !features.contains(feature))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct GraphPipelineClaim<'a, T> {
     pub option: GraphPipelineClaimOptions,
@@ -229,6 +290,33 @@ where
     }
 }
 
+pub struct GraphExplanation<'a, N>
+where
+    N: Node,
+{
+    /// The claimed features which are reachable from none of the nodes
+    pub missing: Vec<&'a <N as Node>::Feature>,
+    pub nodes: Vec<GraphNodeExplanation<'a, N>>,
+}
+
+pub struct GraphNodeExplanation<'a, N>
+where
+    N: Node,
+{
+    pub node: &'a N,
+    /// The requirements of the node, which are unreachable from the source
+    pub missing: Vec<&'a <N as Node>::Feature>,
+}
+
+impl<'a, N> GraphNodeExplanation<'a, N>
+where
+    N: Node,
+{
+    pub fn is_reachable(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
 struct GraphVisitState<'a, T> {
     features: BTreeSet<&'a T>,
     travelled: Vec<usize>,
@@ -1,7 +1,7 @@
 use std::fmt;
 
 use kubegraph_dependency_graph::{
-    Graph, GraphPipeline, GraphPipelineClaim, GraphPipelineClaimOptions, Node,
+    Graph, GraphNodeExplanation, GraphPipeline, GraphPipelineClaim, GraphPipelineClaimOptions, Node,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -89,3 +89,46 @@ fn solve() {
     }];
     assert_eq!(pipelines, expected_pipelines);
 }
+
+#[test]
+fn explain() {
+    let mut graph = Graph::default();
+
+    let node_a = Package {
+        name: "A",
+        provides: &["b"],
+        requirements: &["a"],
+    };
+    let node_b = Package {
+        name: "B",
+        provides: &["d"],
+        requirements: &["b", "c"],
+    };
+    let node_c = Package {
+        name: "C",
+        provides: &["e"],
+        requirements: &["b"],
+    };
+
+    graph.add_node(node_a);
+    graph.add_node(node_b);
+    graph.add_node(node_c);
+
+    let claim = GraphPipelineClaim {
+        option: GraphPipelineClaimOptions::default(),
+        src: &["a"],
+        sink: &["d", "e"],
+    };
+    assert!(graph.build_pipeline(&claim).is_none());
+
+    let explanation = graph.explain(&claim);
+    assert_eq!(explanation.missing, vec![&"d"]);
+
+    let nodes: Vec<_> = explanation
+        .nodes
+        .iter()
+        .map(|GraphNodeExplanation { node, missing }| (node.name, missing.clone()))
+        .collect();
+    let expected_nodes = vec![("A", vec![]), ("B", vec![&"c"]), ("C", vec![])];
+    assert_eq!(nodes, expected_nodes);
+}
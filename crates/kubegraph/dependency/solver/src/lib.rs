@@ -7,7 +7,11 @@ use std::{
 use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use kubegraph_api::{
-    dependency::{NetworkDependencyPipelineTemplate, NetworkDependencySolverSpec},
+    dependency::{
+        NetworkDependencyExplanation, NetworkDependencyFunctionExplanation,
+        NetworkDependencyGraphExplanation, NetworkDependencyPipelineTemplate,
+        NetworkDependencySolverSpec,
+    },
     frame::LazyFrame,
     function::{
         FunctionMetadata, NetworkFunctionCrd, NetworkFunctionKind, NetworkFunctionTemplate,
@@ -18,7 +22,8 @@ use kubegraph_api::{
 };
 use kubegraph_dependency_graph::{
     merge::{GraphPipelineMerge, GraphPipelineMergedNode, NodeIndex},
-    Graph, GraphPipelineClaim, GraphPipelineClaimOptions, Node,
+    Graph, GraphExplanation, GraphNodeExplanation, GraphPipelineClaim, GraphPipelineClaimOptions,
+    Node,
 };
 use kubegraph_vm_lazy::{
     function::{NetworkFunction, NetworkFunctionInferType},
@@ -59,7 +64,7 @@ impl ::kubegraph_api::dependency::NetworkDependencySolver for NetworkDependencyG
             nodes.alias_nodes(&problem.spec.metadata, &scope)?;
 
             static_edges.push(edges);
-            static_nodes.push((metadata, nodes));
+            static_nodes.push((scope, metadata, nodes));
         }
 
         // Step 3. Collect all static edges
@@ -69,7 +74,10 @@ impl ::kubegraph_api::dependency::NetworkDependencySolver for NetworkDependencyG
 
         // Step 4. Collect all pipelines per graph
         // NOTE: static edges can be used instead of pipelines
-        let (pipelines, static_nodes) = graph.build_pipelines(problem, static_nodes);
+        let (pipelines, static_nodes, explanation) = graph.build_pipelines(problem, static_nodes);
+        if !explanation.is_empty() {
+            info!("Some graphs are not covered by any function chain:\n{explanation}");
+        }
 
         // Step 5. Merge duplicated pipelines
         let merged_pipelines = pipelines
@@ -168,6 +176,7 @@ impl ::kubegraph_api::dependency::NetworkDependencySolver for NetworkDependencyG
         }
 
         Ok(NetworkDependencyPipelineTemplate {
+            explanation,
             graph,
             static_edges: Some(static_edges),
         })
@@ -178,25 +187,40 @@ trait GraphPipelineBuilder {
     fn build_pipelines<M>(
         &self,
         problem: &VirtualProblem,
-        nodes: Vec<(M, LazyFrame)>,
-    ) -> (Vec<GraphPipeline<'_>>, Vec<LazyFrame>)
+        nodes: Vec<(GraphScope, M, LazyFrame)>,
+    ) -> (
+        Vec<GraphPipeline<'_>>,
+        Vec<LazyFrame>,
+        NetworkDependencyExplanation,
+    )
     where
         M: GraphMetadataExt;
+
+    fn explain_pipeline(
+        &self,
+        scope: GraphScope,
+        claim: &GraphPipelineClaim<String>,
+    ) -> NetworkDependencyGraphExplanation;
 }
 
 impl GraphPipelineBuilder for Graph<Function> {
     fn build_pipelines<M>(
         &self,
         problem: &VirtualProblem,
-        nodes: Vec<(M, LazyFrame)>,
-    ) -> (Vec<GraphPipeline<'_>>, Vec<LazyFrame>)
+        nodes: Vec<(GraphScope, M, LazyFrame)>,
+    ) -> (
+        Vec<GraphPipeline<'_>>,
+        Vec<LazyFrame>,
+        NetworkDependencyExplanation,
+    )
     where
         M: GraphMetadataExt,
     {
         let mut dropped_nodes = Vec::default();
+        let mut explanation = NetworkDependencyExplanation::default();
         let mut pipelines = Vec::default();
 
-        for (metadata, nodes) in nodes {
+        for (scope, metadata, nodes) in nodes {
             let src = metadata.all_node_inputs_raw();
             let sink: Vec<_> = problem
                 .spec
@@ -215,16 +239,56 @@ impl GraphPipelineBuilder for Graph<Function> {
                 sink: &sink,
             };
 
-            match self
-                .build_pipeline(&claim)
-                .and_then(|mut pipelines| pipelines.pop())
-            {
-                Some(inner) => pipelines.push(GraphPipeline { inner, nodes }),
-                None => dropped_nodes.push(nodes),
+            match self.build_pipeline(&claim) {
+                Some(mut found) => match found.pop() {
+                    Some(inner) => pipelines.push(GraphPipeline { inner, nodes }),
+                    // NOTE: the graph already provides all the required columns
+                    None => dropped_nodes.push(nodes),
+                },
+                None => {
+                    explanation
+                        .graphs
+                        .push(self.explain_pipeline(scope, &claim));
+                    dropped_nodes.push(nodes)
+                }
             }
         }
 
-        (pipelines, dropped_nodes)
+        (pipelines, dropped_nodes, explanation)
+    }
+
+    fn explain_pipeline(
+        &self,
+        scope: GraphScope,
+        claim: &GraphPipelineClaim<String>,
+    ) -> NetworkDependencyGraphExplanation {
+        let GraphExplanation { missing, nodes } = self.explain(claim);
+
+        NetworkDependencyGraphExplanation {
+            scope,
+            missing_columns: missing.into_iter().cloned().collect(),
+            functions: nodes
+                .into_iter()
+                .map(
+                    |GraphNodeExplanation {
+                         node: function,
+                         missing,
+                     }| {
+                        let (missing_filter_columns, missing_script_columns) = missing
+                            .into_iter()
+                            .cloned()
+                            .partition(|column| function.filter_requirements.contains(column));
+
+                        NetworkDependencyFunctionExplanation {
+                            scope: function.scope(),
+                            filter: function.cr.spec.template.filter.clone(),
+                            missing_filter_columns,
+                            missing_script_columns,
+                        }
+                    },
+                )
+                .collect(),
+        }
     }
 }
 
@@ -277,6 +341,7 @@ impl<'a> NodeIndex for GraphPipelineNode<'a> {
 #[derive(Debug)]
 struct Function {
     cr: NetworkFunctionCrd,
+    filter_requirements: BTreeSet<String>,
     is_final: bool,
     provided: Vec<String>,
     requirements: Vec<String>,
@@ -297,14 +362,23 @@ impl Function {
             .transpose()?;
         let script = LazyVirtualMachine::with_lazy_script(&cr.spec.template.script)?;
 
+        let mut filter_requirements = BTreeSet::default();
         let mut provided = BTreeSet::default();
         let mut requirements = BTreeSet::default();
-        for Instruction { name, stmt } in script.dump_script().code.into_iter().chain(
-            filter
-                .as_ref()
-                .map(|vm| vm.dump_script().code)
-                .unwrap_or_default(),
-        ) {
+        let instructions = script
+            .dump_script()
+            .code
+            .into_iter()
+            .map(|instruction| (false, instruction))
+            .chain(
+                filter
+                    .as_ref()
+                    .map(|vm| vm.dump_script().code)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|instruction| (true, instruction)),
+            );
+        for (is_filter, Instruction { name, stmt }) in instructions {
             let name = match name {
                 Some(ref name) => {
                     let re = Regex::new(r"^s(rc|ink)\.").unwrap();
@@ -315,6 +389,9 @@ impl Function {
 
             let buf = match &stmt {
                 Stmt::DefineLocalFeature { .. } | Stmt::DefineLocalValue { .. } => {
+                    if is_filter {
+                        filter_requirements.insert(name.clone());
+                    }
                     &mut requirements
                 }
                 _ => &mut provided,
@@ -329,6 +406,7 @@ impl Function {
 
         Ok(Self {
             cr,
+            filter_requirements,
             is_final,
             provided: provided.into_iter().collect(),
             requirements: requirements.into_iter().collect(),
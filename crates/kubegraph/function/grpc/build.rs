@@ -0,0 +1,8 @@
+fn main() {
+    println!("cargo:rerun-if-changed=./proto/function.proto");
+
+    ::tonic_build::configure()
+        .build_server(cfg!(feature = "server"))
+        .compile_protos(&["./proto/function.proto"], &["./proto"])
+        .unwrap();
+}
@@ -0,0 +1,290 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, bail, Result};
+use kubegraph_api::{frame::DataFrame, function::call::FunctionCallResponse};
+use pl::prelude::{IpcStreamReader, IpcStreamWriter, SerReader, SerWriter};
+
+use crate::proto::{GraphTable, RecordBatch};
+
+/// The maximum number of rows in a record batch.
+pub(crate) const MAX_BATCH_ROWS: usize = 16 * 1024;
+
+/// The maximum size of a gRPC message, which is large enough for a record batch.
+pub(crate) const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Splits the given table into self-contained Arrow IPC streams.
+///
+/// The record batches are encoded on demand, so that the whole table is never
+/// buffered twice while being sent.
+pub(crate) fn encode(
+    table: GraphTable,
+    df: DataFrame,
+) -> impl Send + Iterator<Item = Result<RecordBatch>> {
+    let df = match df {
+        DataFrame::Empty => None,
+        DataFrame::Polars(df) => Some(df),
+    };
+
+    df.into_iter().flat_map(move |df| {
+        // NOTE: an empty table is sent once to keep its schema
+        let height = df.height().max(1);
+        (0..height)
+            .step_by(MAX_BATCH_ROWS)
+            .map(move |offset| -> Result<_> {
+                let mut chunk = df.slice(offset as i64, MAX_BATCH_ROWS);
+                let mut ipc = Vec::default();
+                IpcStreamWriter::new(&mut ipc)
+                    .finish(&mut chunk)
+                    .map_err(|error| anyhow!("failed to encode record batch: {error}"))?;
+                Ok(RecordBatch {
+                    table: table.into(),
+                    ipc,
+                })
+            })
+    })
+}
+
+/// Concatenates the received record batches by table.
+#[derive(Default)]
+pub(crate) struct FrameBuilder {
+    edges: Option<::pl::frame::DataFrame>,
+    nodes: Option<::pl::frame::DataFrame>,
+    static_edges: Option<::pl::frame::DataFrame>,
+}
+
+impl FrameBuilder {
+    pub(crate) fn push(&mut self, batch: RecordBatch) -> Result<()> {
+        let table = match batch.table() {
+            GraphTable::Unspecified => bail!("unspecified graph table"),
+            GraphTable::Edges => &mut self.edges,
+            GraphTable::Nodes => &mut self.nodes,
+            GraphTable::StaticEdges => &mut self.static_edges,
+        };
+
+        let df = IpcStreamReader::new(Cursor::new(batch.ipc))
+            .finish()
+            .map_err(|error| anyhow!("failed to decode record batch: {error}"))?;
+        match table {
+            Some(table) => table
+                .vstack_mut(&df)
+                .map(|_| ())
+                .map_err(|error| anyhow!("failed to concat record batches: {error}")),
+            None => {
+                table.replace(df);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the edges, the nodes and the static edges, respectively.
+    #[cfg(feature = "server")]
+    pub(crate) fn finish(self) -> (Option<DataFrame>, Option<DataFrame>, Option<DataFrame>) {
+        let Self {
+            edges,
+            nodes,
+            static_edges,
+        } = self;
+        (
+            edges.map(DataFrame::Polars),
+            nodes.map(DataFrame::Polars),
+            static_edges.map(DataFrame::Polars),
+        )
+    }
+
+    pub(crate) fn finish_response(self) -> FunctionCallResponse {
+        let Self { edges, nodes, .. } = self;
+        FunctionCallResponse {
+            edges: edges.map(DataFrame::Polars),
+            nodes: nodes.map(DataFrame::Polars),
+        }
+    }
+}
+
+/// Encodes the response of a function on demand.
+#[cfg(feature = "server")]
+pub(crate) fn encode_response(
+    response: FunctionCallResponse,
+) -> impl Send + Iterator<Item = Result<RecordBatch>> {
+    let FunctionCallResponse { edges, nodes } = response;
+
+    let edges = edges
+        .into_iter()
+        .flat_map(|edges| encode(GraphTable::Edges, edges));
+    let nodes = nodes
+        .into_iter()
+        .flat_map(|nodes| encode(GraphTable::Nodes, nodes));
+    edges.chain(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use pl::{df, frame::DataFrame as PolarsDataFrame};
+
+    use super::*;
+
+    fn new_edges(height: usize) -> PolarsDataFrame {
+        let src: Vec<_> = (0..height as i64).collect();
+        let sink: Vec<_> = (0..height as i64).map(|index| index + 1).collect();
+        let flow: Vec<_> = (0..height as i64).map(|index| index % 7).collect();
+        df!(
+            "src" => src,
+            "sink" => sink,
+            "flow" => flow,
+        )
+        .unwrap()
+    }
+
+    fn new_nodes() -> PolarsDataFrame {
+        df!(
+            "name" => ["a", "b", "c"],
+            "supply" => [3i64, 0, -3],
+        )
+        .unwrap()
+    }
+
+    fn encode_all(table: GraphTable, df: PolarsDataFrame) -> Vec<RecordBatch> {
+        encode(table, DataFrame::Polars(df))
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    fn unwrap_polars(df: Option<DataFrame>) -> PolarsDataFrame {
+        match df {
+            Some(DataFrame::Polars(df)) => df,
+            Some(DataFrame::Empty) => panic!("unexpected empty table"),
+            None => panic!("missing table"),
+        }
+    }
+
+    #[test]
+    fn round_trip_record_batches() {
+        let edges = new_edges(2 * MAX_BATCH_ROWS + 1);
+
+        let batches = encode_all(GraphTable::Edges, edges.clone());
+        assert_eq!(batches.len(), 3);
+        assert!(batches
+            .iter()
+            .all(|batch| batch.table() == GraphTable::Edges));
+
+        let mut builder = FrameBuilder::default();
+        for batch in batches {
+            builder.push(batch).unwrap();
+        }
+
+        let FunctionCallResponse {
+            edges: output,
+            nodes,
+        } = builder.finish_response();
+        assert!(unwrap_polars(output).equals(&edges));
+        assert!(nodes.is_none());
+    }
+
+    #[test]
+    fn round_trip_empty_tables() {
+        assert_eq!(encode(GraphTable::Edges, DataFrame::Empty).count(), 0);
+
+        // the schema should be kept
+        let edges = new_edges(0);
+        let batches = encode_all(GraphTable::Edges, edges.clone());
+        assert_eq!(batches.len(), 1);
+
+        let mut builder = FrameBuilder::default();
+        builder.push(batches.into_iter().next().unwrap()).unwrap();
+
+        let output = unwrap_polars(builder.finish_response().edges);
+        assert_eq!(output.height(), 0);
+        assert_eq!(output.schema(), edges.schema());
+    }
+
+    #[test]
+    fn merge_batches_by_table() {
+        let edges = new_edges(MAX_BATCH_ROWS + 1);
+        let nodes = new_nodes();
+        let static_edges = new_edges(3);
+
+        // NOTE: the batches of the different tables may be interleaved
+        let mut edge_batches = encode_all(GraphTable::Edges, edges.clone()).into_iter();
+        let mut builder = FrameBuilder::default();
+        builder.push(edge_batches.next().unwrap()).unwrap();
+        for batch in encode_all(GraphTable::Nodes, nodes.clone()) {
+            builder.push(batch).unwrap();
+        }
+        for batch in encode_all(GraphTable::StaticEdges, static_edges) {
+            builder.push(batch).unwrap();
+        }
+        for batch in edge_batches {
+            builder.push(batch).unwrap();
+        }
+
+        // the static edges are never returned by the functions
+        let FunctionCallResponse {
+            edges: output_edges,
+            nodes: output_nodes,
+        } = builder.finish_response();
+        assert!(unwrap_polars(output_edges).equals(&edges));
+        assert!(unwrap_polars(output_nodes).equals(&nodes));
+    }
+
+    #[test]
+    fn reject_invalid_batches() {
+        let mut builder = FrameBuilder::default();
+        assert!(builder
+            .push(RecordBatch {
+                table: GraphTable::Unspecified.into(),
+                ipc: Vec::default(),
+            })
+            .is_err());
+        assert!(builder
+            .push(RecordBatch {
+                table: GraphTable::Edges.into(),
+                ipc: b"not an arrow ipc stream".to_vec(),
+            })
+            .is_err());
+
+        // the schema of the batches should be matched
+        for batch in encode_all(GraphTable::Nodes, new_nodes()) {
+            builder.push(batch).unwrap();
+        }
+        for batch in encode_all(GraphTable::Nodes, new_edges(1)) {
+            assert!(builder.push(batch).is_err());
+        }
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn round_trip_response() {
+        let edges = new_edges(MAX_BATCH_ROWS + 1);
+        let nodes = new_nodes();
+
+        let response = FunctionCallResponse {
+            edges: Some(DataFrame::Polars(edges.clone())),
+            nodes: Some(DataFrame::Polars(nodes.clone())),
+        };
+        let batches: Vec<_> = encode_response(response).collect::<Result<_>>().unwrap();
+        assert_eq!(
+            batches
+                .iter()
+                .map(|batch| batch.table())
+                .collect::<Vec<_>>(),
+            [GraphTable::Edges, GraphTable::Edges, GraphTable::Nodes],
+        );
+
+        let mut builder = FrameBuilder::default();
+        for batch in batches {
+            builder.push(batch).unwrap();
+        }
+        let FunctionCallResponse {
+            edges: output_edges,
+            nodes: output_nodes,
+        } = builder.finish_response();
+        assert!(unwrap_polars(output_edges).equals(&edges));
+        assert!(unwrap_polars(output_nodes).equals(&nodes));
+
+        // the functions may apply the flows by themselves
+        let response = FunctionCallResponse {
+            edges: None,
+            nodes: None,
+        };
+        assert_eq!(encode_response(response).count(), 0);
+    }
+}
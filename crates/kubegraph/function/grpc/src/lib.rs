@@ -0,0 +1,176 @@
+extern crate polars as pl;
+
+mod frame;
+mod polars;
+pub mod proto;
+#[cfg(feature = "server")]
+pub mod service;
+
+use std::{
+    iter,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use kubegraph_api::{
+    frame::{DataFrame, LazyFrame},
+    function::{
+        call::FunctionCallResponse, grpc::NetworkFunctionGrpcSpec, spawn::FunctionSpawnContext,
+    },
+    graph::{Graph, GraphData, GraphEdges, GraphMetadataExt, ScopedNetworkGraphDB},
+};
+use tonic::codec::CompressionEncoding;
+use tracing::{instrument, Level};
+
+use crate::proto::{
+    function_call_request::Payload, network_function_client::NetworkFunctionClient,
+    FunctionCallHeader, FunctionCallRequest, GraphTable,
+};
+
+#[async_trait]
+pub trait NetworkFunctionGrpc<DB, T, M>
+where
+    DB: ScopedNetworkGraphDB<LazyFrame, M>,
+{
+    async fn spawn(&self, ctx: FunctionSpawnContext<'async_trait, DB, T, M>) -> Result<()>
+    where
+        DB: 'async_trait + Send,
+        M: 'async_trait + Send;
+}
+
+#[async_trait]
+impl<DB, M> NetworkFunctionGrpc<DB, LazyFrame, M> for NetworkFunctionGrpcSpec
+where
+    DB: ScopedNetworkGraphDB<LazyFrame, M>,
+    M: GraphMetadataExt,
+{
+    #[instrument(level = Level::INFO, skip(self, ctx))]
+    async fn spawn(&self, ctx: FunctionSpawnContext<'async_trait, DB, LazyFrame, M>) -> Result<()>
+    where
+        DB: 'async_trait + Send,
+        M: 'async_trait + Send,
+    {
+        let Self { endpoint } = self;
+        let FunctionSpawnContext {
+            graph:
+                Graph {
+                    connector,
+                    data: GraphData { edges, nodes },
+                    metadata: graph_metadata,
+                    scope: graph_scope,
+                },
+            graph_db,
+            kube: _,
+            metadata,
+            static_edges,
+            template,
+        } = ctx;
+        let static_edges = static_edges.map(GraphEdges::into_inner);
+
+        // Step 1. Encode the graph
+        let header = FunctionCallHeader {
+            connector: connector
+                .as_deref()
+                .map(::serde_json::to_string)
+                .transpose()
+                .map_err(|error| anyhow!("failed to encode connector: {error}"))?,
+            graph_metadata: Some(graph_metadata.to_pinned().into()),
+            graph_scope: Some(graph_scope.clone().into()),
+            function_scope: Some(metadata.scope.into()),
+            template: Some(template.into()),
+        };
+
+        let mut tables = Vec::default();
+        for (table, df) in [
+            (GraphTable::Edges, Some(edges)),
+            (GraphTable::Nodes, Some(nodes.clone())),
+            (GraphTable::StaticEdges, static_edges.clone()),
+        ] {
+            let df = match df {
+                Some(df) => df.collect().await?,
+                None => DataFrame::Empty,
+            };
+            tables.push((table, df));
+        }
+
+        // NOTE: the record batches are encoded while being sent,
+        // and the first error stops the request to be reported later
+        let last_error = Arc::<Mutex<Option<Error>>>::default();
+        let batches = tables
+            .into_iter()
+            .flat_map(|(table, df)| self::frame::encode(table, df))
+            .map_while({
+                let last_error = last_error.clone();
+                move |batch| match batch {
+                    Ok(batch) => Some(FunctionCallRequest {
+                        payload: Some(Payload::Batch(batch)),
+                    }),
+                    Err(error) => {
+                        if let Ok(mut last_error) = last_error.lock() {
+                            last_error.replace(error);
+                        }
+                        None
+                    }
+                }
+            });
+        let requests = iter::once(FunctionCallRequest {
+            payload: Some(Payload::Header(header)),
+        })
+        .chain(batches);
+
+        // Step 2. Call the function
+        let mut client = NetworkFunctionClient::connect(endpoint.0.to_string())
+            .await
+            .map_err(|error| anyhow!("failed to connect to grpc function: {error}"))?
+            .accept_compressed(CompressionEncoding::Gzip)
+            .send_compressed(CompressionEncoding::Gzip)
+            .max_decoding_message_size(self::frame::MAX_MESSAGE_SIZE)
+            .max_encoding_message_size(self::frame::MAX_MESSAGE_SIZE);
+
+        let mut responses = client
+            .call(::tokio_stream::iter(requests))
+            .await
+            .map_err(|error| anyhow!("failed to call grpc function: {error}"))?
+            .into_inner();
+
+        // Step 3. Collect the updated tables
+        let mut builder = self::frame::FrameBuilder::default();
+        while let Some(response) = responses
+            .message()
+            .await
+            .map_err(|error| anyhow!("failed to get a response from grpc function: {error}"))?
+        {
+            if let Some(batch) = response.batch {
+                builder.push(batch)?;
+            }
+        }
+
+        let FunctionCallResponse {
+            edges: updated_edges,
+            nodes: updated_nodes,
+        } = builder.finish_response();
+        if let Some(error) = last_error.lock().ok().and_then(|mut error| error.take()) {
+            return Err(error);
+        }
+        if updated_edges.is_none() && updated_nodes.is_none() {
+            // The function has applied the flows by itself
+            return Ok(());
+        }
+
+        // Step 4. Upload to the DB
+        let graph = Graph {
+            connector,
+            data: GraphData {
+                edges: updated_edges
+                    .map(DataFrame::lazy)
+                    .or(static_edges)
+                    .unwrap_or_default(),
+                nodes: updated_nodes.map(DataFrame::lazy).unwrap_or(nodes),
+            },
+            metadata: graph_metadata,
+            scope: graph_scope,
+        };
+        graph_db.insert(graph).await
+    }
+}
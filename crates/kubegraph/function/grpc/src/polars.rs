@@ -0,0 +1,55 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use kubegraph_api::{
+    function::{grpc::NetworkFunctionGrpcSpec, spawn::FunctionSpawnContext},
+    graph::{Graph, GraphData, GraphEdges, GraphMetadataExt, ScopedNetworkGraphDB},
+};
+use pl::lazy::frame::LazyFrame;
+use tracing::{instrument, Level};
+
+#[async_trait]
+impl<DB, M> super::NetworkFunctionGrpc<DB, LazyFrame, M> for NetworkFunctionGrpcSpec
+where
+    DB: ScopedNetworkGraphDB<::kubegraph_api::frame::LazyFrame, M>,
+    M: GraphMetadataExt,
+{
+    #[instrument(level = Level::INFO, skip(self, ctx))]
+    async fn spawn(&self, ctx: FunctionSpawnContext<'async_trait, DB, LazyFrame, M>) -> Result<()>
+    where
+        DB: 'async_trait + Send,
+        M: 'async_trait + Send,
+    {
+        let FunctionSpawnContext {
+            graph:
+                Graph {
+                    connector,
+                    data,
+                    metadata: graph_metadata,
+                    scope: graph_scope,
+                },
+            graph_db,
+            kube,
+            metadata,
+            static_edges,
+            template,
+        } = ctx;
+
+        let ctx = FunctionSpawnContext {
+            graph: Graph {
+                connector,
+                data: GraphData::<::kubegraph_api::frame::LazyFrame>::from(data),
+                metadata: graph_metadata,
+                scope: graph_scope,
+            },
+            graph_db,
+            kube,
+            metadata,
+            static_edges: static_edges
+                .map(GraphEdges::into_inner)
+                .map(Into::into)
+                .map(GraphEdges::new),
+            template,
+        };
+        self.spawn(ctx).await
+    }
+}
@@ -0,0 +1,89 @@
+#![allow(clippy::all)]
+
+::tonic::include_proto!("kubegraph.function.v1alpha1");
+
+impl From<::kubegraph_api::graph::GraphMetadataPinned> for GraphMetadata {
+    fn from(value: ::kubegraph_api::graph::GraphMetadataPinned) -> Self {
+        let ::kubegraph_api::graph::GraphMetadataPinned {
+            capacity,
+            connector,
+            flow,
+            function,
+            interval_ms,
+            name,
+            sink,
+            src,
+            supply,
+            unit_cost,
+        } = value;
+        Self {
+            capacity,
+            connector,
+            flow,
+            function,
+            interval_ms,
+            name,
+            sink,
+            src,
+            supply,
+            unit_cost,
+        }
+    }
+}
+
+impl From<GraphMetadata> for ::kubegraph_api::graph::GraphMetadataPinned {
+    fn from(value: GraphMetadata) -> Self {
+        let GraphMetadata {
+            capacity,
+            connector,
+            flow,
+            function,
+            interval_ms,
+            name,
+            sink,
+            src,
+            supply,
+            unit_cost,
+        } = value;
+        Self {
+            capacity,
+            connector,
+            flow,
+            function,
+            interval_ms,
+            name,
+            sink,
+            src,
+            supply,
+            unit_cost,
+        }
+    }
+}
+
+impl From<::kubegraph_api::graph::GraphScope> for GraphScope {
+    fn from(value: ::kubegraph_api::graph::GraphScope) -> Self {
+        let ::kubegraph_api::graph::GraphScope { namespace, name } = value;
+        Self { namespace, name }
+    }
+}
+
+impl From<GraphScope> for ::kubegraph_api::graph::GraphScope {
+    fn from(value: GraphScope) -> Self {
+        let GraphScope { namespace, name } = value;
+        Self { namespace, name }
+    }
+}
+
+impl From<::kubegraph_api::function::NetworkFunctionTemplate> for FunctionTemplate {
+    fn from(value: ::kubegraph_api::function::NetworkFunctionTemplate) -> Self {
+        let ::kubegraph_api::function::NetworkFunctionTemplate { filter, script } = value;
+        Self { filter, script }
+    }
+}
+
+impl From<FunctionTemplate> for ::kubegraph_api::function::NetworkFunctionTemplate {
+    fn from(value: FunctionTemplate) -> Self {
+        let FunctionTemplate { filter, script } = value;
+        Self { filter, script }
+    }
+}
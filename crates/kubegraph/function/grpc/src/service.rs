@@ -0,0 +1,291 @@
+use std::{net::SocketAddr, pin::Pin, sync::Arc};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core::{env::infer, signal::FunctionSignal};
+use async_trait::async_trait;
+use clap::{Args, Parser};
+use futures::Stream;
+use kubegraph_api::{
+    component::{NetworkComponent, NetworkComponentExt},
+    frame::DataFrame,
+    function::{
+        call::{FunctionCallRequest, FunctionCallResponse},
+        FunctionMetadata,
+    },
+    graph::{Graph, GraphData, GraphEdges},
+    vm::NetworkFallbackPolicy,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::{spawn, time::sleep};
+use tonic::{codec::CompressionEncoding, transport::Server, Request, Response, Status, Streaming};
+use tracing::{error, info, instrument, warn, Level};
+
+use crate::{
+    frame::{encode_response, FrameBuilder, MAX_MESSAGE_SIZE},
+    proto::{
+        self,
+        function_call_request::Payload,
+        network_function_server::{NetworkFunction, NetworkFunctionServer},
+    },
+};
+
+#[async_trait]
+pub trait NetworkFunctionGrpcServiceExt
+where
+    Self: NetworkComponentExt + NetworkFunctionGrpcService,
+    <Self as NetworkComponent>::Args: Send + Args + Parser,
+{
+    async fn main()
+    where
+        Self: 'static + Sized,
+    {
+        <Self as NetworkFunctionGrpcServiceExt>::main_with_handlers(|_, _| vec![]).await
+    }
+
+    async fn main_with_handlers<F>(handlers: F)
+    where
+        Self: 'static + Sized,
+        F: Send + FnOnce(&FunctionSignal, &Arc<Self>) -> Vec<::tokio::task::JoinHandle<()>>,
+    {
+        ::ark_core::tracer::init_once();
+        info!("Welcome to kubegraph grpc function service!");
+
+        let signal = FunctionSignal::default().trap_on_panic();
+        if let Err(error) = signal.trap_on_sigint() {
+            error!("{error}");
+            return;
+        }
+
+        info!("Booting...");
+        let NetworkFunctionGrpcServiceAgentArgs {
+            fallback_policy,
+            service: args,
+        } = match NetworkFunctionGrpcServiceAgentArgs::try_parse() {
+            Ok(args) => args,
+            Err(error) => signal.panic(error).await,
+        };
+        let function = match <Self as NetworkComponent>::try_new(args, &signal).await {
+            Ok(function) => Arc::new(function),
+            Err(error) => {
+                signal
+                    .panic(anyhow!("failed to init grpc function service: {error}"))
+                    .await
+            }
+        };
+
+        info!("Creating grpc server...");
+        let handler_grpc_server = spawn(loop_forever(
+            signal.clone(),
+            function.clone(),
+            fallback_policy,
+        ));
+
+        info!("Registering side workers...");
+        let mut handlers = handlers(&signal, &function);
+        handlers.push(handler_grpc_server);
+
+        info!("Ready");
+        signal.wait_to_terminate().await;
+
+        info!("Terminating...");
+        for handler in handlers {
+            handler.abort();
+        }
+
+        if let Err(error) = function.close() {
+            error!("{error}");
+        };
+
+        signal.exit().await
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, Parser)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+struct NetworkFunctionGrpcServiceAgentArgs<S>
+where
+    S: Args,
+{
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FUNCTION_FALLBACK_POLICY",
+        value_name = "POLICY",
+        default_value_t = NetworkFallbackPolicy::default(),
+    )]
+    #[serde(default)]
+    fallback_policy: NetworkFallbackPolicy,
+
+    #[command(flatten)]
+    service: S,
+}
+
+#[async_trait]
+impl<T> NetworkFunctionGrpcServiceExt for T
+where
+    Self: NetworkComponentExt + NetworkFunctionGrpcService,
+    <Self as NetworkComponent>::Args: Send + Args + Parser,
+{
+}
+
+#[async_trait]
+pub trait NetworkFunctionGrpcService
+where
+    Self: Send + Sync,
+{
+    /// Handles a function call, and returns the updated tables, if any.
+    async fn handle(&self, request: FunctionCallRequest) -> Result<FunctionCallResponse>;
+
+    #[instrument(level = Level::INFO, skip(self))]
+    fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+async fn loop_forever<F>(
+    signal: FunctionSignal,
+    function: Arc<F>,
+    fallback_policy: NetworkFallbackPolicy,
+) where
+    F: 'static + NetworkFunctionGrpcService,
+{
+    loop {
+        if let Err(error) = try_loop_forever(&function).await {
+            error!("failed to operate grpc server: {error}");
+
+            match fallback_policy {
+                NetworkFallbackPolicy::Interval { interval } => {
+                    warn!("restarting grpc server in {interval:?}...");
+                    sleep(interval).await;
+                    info!("Restarted grpc server");
+                }
+                NetworkFallbackPolicy::Never => {
+                    signal.terminate_on_panic();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn try_loop_forever<F>(function: &Arc<F>) -> Result<()>
+where
+    F: 'static + NetworkFunctionGrpcService,
+{
+    info!("Starting grpc server...");
+
+    // Initialize pipe
+    let addr =
+        infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
+
+    // Create a grpc server
+    let service = NetworkFunctionServer::new(Service {
+        function: function.clone(),
+    })
+    .accept_compressed(CompressionEncoding::Gzip)
+    .send_compressed(CompressionEncoding::Gzip)
+    .max_decoding_message_size(MAX_MESSAGE_SIZE)
+    .max_encoding_message_size(MAX_MESSAGE_SIZE);
+
+    // Start grpc server
+    Server::builder()
+        .add_service(service)
+        .serve(addr)
+        .await
+        .map_err(|error| anyhow!("failed to serve on {addr}: {error}"))
+}
+
+struct Service<F> {
+    function: Arc<F>,
+}
+
+#[async_trait]
+impl<F> NetworkFunction for Service<F>
+where
+    F: 'static + NetworkFunctionGrpcService,
+{
+    type CallStream =
+        Pin<Box<dyn Send + Stream<Item = Result<proto::FunctionCallResponse, Status>>>>;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn call(
+        &self,
+        request: Request<Streaming<proto::FunctionCallRequest>>,
+    ) -> Result<Response<Self::CallStream>, Status> {
+        let request = decode_request(request.into_inner())
+            .await
+            .map_err(|error| Status::invalid_argument(error.to_string()))?;
+
+        let response = self
+            .function
+            .handle(request)
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?;
+
+        let responses = encode_response(response).map(|batch| {
+            batch
+                .map(|batch| proto::FunctionCallResponse { batch: Some(batch) })
+                .map_err(|error| Status::internal(error.to_string()))
+        });
+        Ok(Response::new(Box::pin(::tokio_stream::iter(responses))))
+    }
+}
+
+async fn decode_request(
+    mut messages: Streaming<proto::FunctionCallRequest>,
+) -> Result<FunctionCallRequest> {
+    let header = match messages.message().await? {
+        Some(proto::FunctionCallRequest {
+            payload: Some(Payload::Header(header)),
+        }) => header,
+        Some(_) => bail!("the first message should be a header"),
+        None => bail!("empty request"),
+    };
+
+    let mut builder = FrameBuilder::default();
+    while let Some(message) = messages.message().await? {
+        match message.payload {
+            Some(Payload::Batch(batch)) => builder.push(batch)?,
+            Some(Payload::Header(_)) => bail!("duplicated header"),
+            None => continue,
+        }
+    }
+    let (edges, nodes, static_edges) = builder.finish();
+
+    let proto::FunctionCallHeader {
+        connector,
+        graph_metadata,
+        graph_scope,
+        function_scope,
+        template,
+    } = header;
+
+    Ok(FunctionCallRequest {
+        graph: Graph {
+            connector: connector
+                .as_deref()
+                .map(::serde_json::from_str)
+                .transpose()
+                .map_err(|error| anyhow!("failed to decode connector: {error}"))?
+                .map(Arc::new),
+            data: GraphData {
+                edges: edges.unwrap_or(DataFrame::Empty),
+                nodes: nodes.unwrap_or(DataFrame::Empty),
+            },
+            metadata: graph_metadata.map(Into::into).unwrap_or_default(),
+            scope: graph_scope
+                .map(Into::into)
+                .ok_or_else(|| anyhow!("missing graph scope"))?,
+        },
+        metadata: FunctionMetadata {
+            scope: function_scope
+                .map(Into::into)
+                .ok_or_else(|| anyhow!("missing function scope"))?,
+        },
+        static_edges: static_edges.map(GraphEdges::new),
+        template: template
+            .map(Into::into)
+            .ok_or_else(|| anyhow!("missing function template"))?,
+    })
+}
@@ -7,6 +7,7 @@ use ark_core::{env::infer, signal::FunctionSignal};
 use futures::TryFutureExt;
 use kubegraph_api::{
     graph::NetworkGraphDB,
+    resource::NetworkResourceClient,
     vm::{NetworkFallbackPolicy, NetworkVirtualMachine},
 };
 use tokio::time::sleep;
@@ -47,14 +48,20 @@ async fn try_loop_forever(vm: &impl NetworkVirtualMachine) -> Result<()> {
 
     let graph_db: Box<dyn Send + NetworkGraphDB> = Box::new(vm.graph_db().clone());
     let graph_db = Data::new(graph_db);
+    let kube = Data::new(vm.resource_db().kube().clone());
 
     // Create a http server
     let server = HttpServer::new(move || {
-        let app = App::new().app_data(Data::clone(&graph_db));
+        let app = App::new()
+            .app_data(Data::clone(&graph_db))
+            .app_data(Data::clone(&kube));
         let app = app
             .service(health)
+            .service(crate::routes::explain::get)
             .service(crate::routes::graph::get)
-            .service(crate::routes::graph::post);
+            .service(crate::routes::graph::post)
+            .service(crate::routes::plan::get)
+            .service(crate::routes::plan::post);
         app.wrap(middleware::NormalizePath::new(
             middleware::TrailingSlash::Trim,
         ))
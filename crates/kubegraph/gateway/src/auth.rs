@@ -0,0 +1,140 @@
+use actix_web::HttpRequest;
+use anyhow::{anyhow, bail, Result};
+use k8s_openapi::api::{
+    authentication::v1::{TokenReview, TokenReviewSpec, TokenReviewStatus, UserInfo},
+    authorization::v1::{
+        ResourceAttributes, SubjectAccessReview, SubjectAccessReviewSpec, SubjectAccessReviewStatus,
+    },
+};
+use kube::{api::PostParams, Api, Client, Resource};
+use tracing::{instrument, Level};
+
+/// Asserts that the caller is allowed to read the given resource.
+#[instrument(level = Level::INFO, skip(kube, request))]
+pub(crate) async fn assert_get<K>(
+    kube: &Client,
+    request: &HttpRequest,
+    namespace: &str,
+    name: &str,
+) -> Result<()>
+where
+    K: Resource<DynamicType = ()>,
+{
+    assert_verb::<K>(kube, request, namespace, name, "get").await
+}
+
+/// Asserts that the caller is allowed to update the given resource.
+#[instrument(level = Level::INFO, skip(kube, request))]
+pub(crate) async fn assert_update<K>(
+    kube: &Client,
+    request: &HttpRequest,
+    namespace: &str,
+    name: &str,
+) -> Result<()>
+where
+    K: Resource<DynamicType = ()>,
+{
+    assert_verb::<K>(kube, request, namespace, name, "patch").await
+}
+
+/// Asserts that the caller is allowed to access the given resource with the verb.
+///
+/// The caller is authenticated with its kubernetes bearer token,
+/// and authorized by the kubernetes RBAC.
+async fn assert_verb<K>(
+    kube: &Client,
+    request: &HttpRequest,
+    namespace: &str,
+    name: &str,
+    verb: &str,
+) -> Result<()>
+where
+    K: Resource<DynamicType = ()>,
+{
+    let token = get_bearer_token(request)?;
+    let UserInfo {
+        extra,
+        groups,
+        uid,
+        username,
+    } = review_token(kube, token).await?;
+
+    let api = Api::<SubjectAccessReview>::all(kube.clone());
+    let pp = PostParams::default();
+    let review = SubjectAccessReview {
+        spec: SubjectAccessReviewSpec {
+            extra,
+            groups,
+            resource_attributes: Some(ResourceAttributes {
+                group: Some(K::group(&()).into_owned()),
+                name: Some(name.into()),
+                namespace: Some(namespace.into()),
+                resource: Some(K::plural(&()).into_owned()),
+                verb: Some(verb.into()),
+                version: Some(K::version(&()).into_owned()),
+                ..Default::default()
+            }),
+            uid,
+            user: username.clone(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    match api.create(&pp, &review).await {
+        Ok(SubjectAccessReview {
+            status: Some(SubjectAccessReviewStatus { allowed: true, .. }),
+            ..
+        }) => Ok(()),
+        Ok(_) => bail!(
+            "permission denied: {user:?} cannot {verb} {namespace}/{name}",
+            user = username.unwrap_or_default(),
+        ),
+        Err(error) => bail!("failed to review the permission: {error}"),
+    }
+}
+
+async fn review_token(kube: &Client, token: &str) -> Result<UserInfo> {
+    let api = Api::<TokenReview>::all(kube.clone());
+    let pp = PostParams::default();
+    let review = TokenReview {
+        spec: TokenReviewSpec {
+            token: Some(token.into()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    match api.create(&pp, &review).await {
+        Ok(TokenReview {
+            status:
+                Some(TokenReviewStatus {
+                    authenticated: Some(true),
+                    user: Some(user),
+                    ..
+                }),
+            ..
+        }) => Ok(user),
+        Ok(TokenReview { status, .. }) => {
+            let error = status.and_then(|status| status.error);
+            bail!(
+                "the token is not authenticated: {error}",
+                error = error.as_deref().unwrap_or("unknown"),
+            )
+        }
+        Err(error) => bail!("failed to review the token: {error}"),
+    }
+}
+
+fn get_bearer_token(request: &HttpRequest) -> Result<&str> {
+    const HEADER_AUTHORIZATION: &str = "Authorization";
+
+    request
+        .headers()
+        .get(HEADER_AUTHORIZATION)
+        .ok_or_else(|| anyhow!("the Authorization token is not found"))?
+        .to_str()
+        .map_err(|_| anyhow!("the Authorization token is malformed"))?
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| anyhow!("the Authorization token is not a Bearer token"))
+}
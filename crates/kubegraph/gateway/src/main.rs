@@ -1,4 +1,5 @@
 mod actix;
+mod auth;
 mod routes;
 mod vm;
 
@@ -0,0 +1,40 @@
+use actix_web::{
+    get,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use anyhow::anyhow;
+use ark_core::result::Result;
+use kube::{Api, Client};
+use kubegraph_api::problem::NetworkProblemCrd;
+use tracing::{instrument, warn, Level};
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/_explain/{namespace}/{name}")]
+pub async fn get(
+    request: HttpRequest,
+    path: Path<(String, String)>,
+    kube: Data<Client>,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+
+    // Only the users who can read the problem are allowed to read its explanations
+    if let Err(error) =
+        crate::auth::assert_get::<NetworkProblemCrd>(&kube, &request, &namespace, &name).await
+    {
+        warn!("{error}");
+        return HttpResponse::Forbidden().json(Result::<()>::Err(error.to_string()));
+    }
+
+    let api = Api::<NetworkProblemCrd>::namespaced((**kube).clone(), &namespace);
+
+    HttpResponse::Ok().json(Result::from(
+        api.get_opt(&name)
+            .await
+            .map(|cr| {
+                cr.and_then(|cr| cr.status)
+                    .and_then(|status| status.explanation)
+            })
+            .map_err(|error| anyhow!("failed to get network problem {namespace}/{name}: {error}")),
+    ))
+}
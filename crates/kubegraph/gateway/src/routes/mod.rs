@@ -1 +1,3 @@
+pub mod explain;
 pub mod graph;
+pub mod plan;
@@ -0,0 +1,59 @@
+use actix_web::{
+    get, post,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use anyhow::anyhow;
+use ark_core::result::Result;
+use kube::{Api, Client};
+use kubegraph_api::{graph::GraphScope, plan::approve, problem::NetworkProblemCrd};
+use tracing::{instrument, warn, Level};
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/_plan/{namespace}/{name}")]
+pub async fn get(
+    request: HttpRequest,
+    path: Path<(String, String)>,
+    kube: Data<Client>,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+
+    // Only the users who can read the problem are allowed to read its plans
+    if let Err(error) =
+        crate::auth::assert_get::<NetworkProblemCrd>(&kube, &request, &namespace, &name).await
+    {
+        warn!("{error}");
+        return HttpResponse::Forbidden().json(Result::<()>::Err(error.to_string()));
+    }
+
+    let api = Api::<NetworkProblemCrd>::namespaced((**kube).clone(), &namespace);
+
+    HttpResponse::Ok().json(Result::from(
+        api.get_opt(&name)
+            .await
+            .map(|cr| cr.and_then(|cr| cr.status).and_then(|status| status.plan))
+            .map_err(|error| anyhow!("failed to get network problem {namespace}/{name}: {error}")),
+    ))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/_plan/{namespace}/{name}/{id}")]
+pub async fn post(
+    request: HttpRequest,
+    path: Path<(String, String, String)>,
+    kube: Data<Client>,
+) -> impl Responder {
+    let (namespace, name, id) = path.into_inner();
+
+    // Only the users who can update the problem are allowed to approve its plans
+    if let Err(error) =
+        crate::auth::assert_update::<NetworkProblemCrd>(&kube, &request, &namespace, &name).await
+    {
+        warn!("{error}");
+        return HttpResponse::Forbidden().json(Result::<()>::Err(error.to_string()));
+    }
+
+    let scope = GraphScope { namespace, name };
+
+    HttpResponse::Ok().json(Result::from(approve(&kube, &scope, &id).await))
+}
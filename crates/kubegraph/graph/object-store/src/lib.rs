@@ -0,0 +1,699 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Cursor,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use futures::{future::try_join_all, try_join, TryStreamExt};
+use kubegraph_api::{
+    component::NetworkComponent,
+    connector::NetworkConnectorCrd,
+    frame::{DataFrame, LazyFrame},
+    graph::{Graph, GraphData, GraphFilter, GraphMetadata, GraphScope},
+};
+use object_store::{aws::AmazonS3Builder, path::Path, ObjectStore, PutPayload};
+use polars::prelude::{ParquetReader, ParquetWriter, SerReader};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, Level};
+use uuid::Uuid;
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkGraphDBArgs {
+    #[arg(
+        long,
+        env = "KUBEGRAPH_GRAPH_DB_BUCKET",
+        value_name = "NAME",
+        default_value_t = NetworkGraphDBArgs::default_graph_db_bucket(),
+    )]
+    #[serde(default = "NetworkGraphDBArgs::default_graph_db_bucket")]
+    graph_db_bucket: String,
+
+    /// S3-compatible endpoint (e.g. MinIO); AWS S3 is used if not given
+    #[arg(long, env = "KUBEGRAPH_GRAPH_DB_ENDPOINT", value_name = "URL")]
+    #[serde(default)]
+    graph_db_endpoint: Option<String>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_GRAPH_DB_PREFIX",
+        value_name = "PATH",
+        default_value_t = NetworkGraphDBArgs::default_graph_db_prefix(),
+    )]
+    #[serde(default = "NetworkGraphDBArgs::default_graph_db_prefix")]
+    graph_db_prefix: String,
+}
+
+impl Default for NetworkGraphDBArgs {
+    fn default() -> Self {
+        Self {
+            graph_db_bucket: Self::default_graph_db_bucket(),
+            graph_db_endpoint: None,
+            graph_db_prefix: Self::default_graph_db_prefix(),
+        }
+    }
+}
+
+impl NetworkGraphDBArgs {
+    fn default_graph_db_bucket() -> String {
+        "kubegraph".into()
+    }
+
+    fn default_graph_db_prefix() -> String {
+        "graphs".into()
+    }
+}
+
+/// A versioned graph database on an S3-compatible object store.
+///
+/// Every `insert` and `remove` writes a new immutable version under
+/// `<prefix>/<namespace>/<name>/<version>/`, so that the history of each
+/// graph scope is kept and several VMs can share the same graphs.
+/// The frames are stored as parquet files and the `manifest.json` is written
+/// last, committing the version. The `latest` file points to the newest
+/// version, so that reads can skip the former history.
+#[derive(Clone)]
+pub struct NetworkGraphDB {
+    prefix: Path,
+    store: Arc<dyn ObjectStore>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkGraphDB {
+    type Args = NetworkGraphDBArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(args: <Self as NetworkComponent>::Args, _: &FunctionSignal) -> Result<Self> {
+        info!("Loading object store db...");
+
+        let NetworkGraphDBArgs {
+            graph_db_bucket,
+            graph_db_endpoint,
+            graph_db_prefix,
+        } = args;
+
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(graph_db_bucket);
+        if let Some(endpoint) = graph_db_endpoint {
+            builder = builder
+                .with_allow_http(endpoint.starts_with("http://"))
+                .with_endpoint(endpoint);
+        }
+
+        Ok(Self {
+            prefix: Path::from(graph_db_prefix.as_str()),
+            store: Arc::new(
+                builder
+                    .build()
+                    .map_err(|error| anyhow!("failed to open object store db: {error}"))?,
+            ),
+        })
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get(&self, scope: &GraphScope) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        // NOTE: the pointer may be outdated by the concurrent writers, so seek the newer ones
+        let path = self.scope_path(scope);
+        let offset = self
+            .read_latest(&path)
+            .await?
+            .map(|version| path.child(version));
+        let versions = self.list_versions(&path, offset.as_ref()).await?;
+        match versions.get(scope).and_then(|versions| versions.last()) {
+            Some(version) => self.load(scope, version).await,
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        let at = at.timestamp_millis();
+        let versions = self.list_versions(&self.scope_path(scope), None).await?;
+        match versions.get(scope).and_then(|versions| {
+            versions
+                .iter()
+                .rev()
+                .find(|version| parse_version_timestamp(version).is_some_and(|ts| ts <= at))
+        }) {
+            Some(version) => self.load(scope, version).await,
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    async fn insert(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()> {
+        let Graph {
+            connector,
+            data: GraphData { edges, nodes },
+            metadata,
+            scope,
+        } = graph.collect().await?;
+
+        let timestamp = Utc::now();
+        let scope_path = self.scope_path(&scope);
+        let version = new_version(timestamp);
+        let path = scope_path.child(version.as_str());
+
+        let (edges, nodes) = try_join!(
+            self.write_frame(&path, Self::FILENAME_EDGES, edges),
+            self.write_frame(&path, Self::FILENAME_NODES, nodes),
+        )?;
+
+        let manifest = GraphManifest {
+            connector,
+            data: Some(GraphData { edges, nodes }),
+            metadata,
+            scope,
+            timestamp,
+        };
+        self.write_manifest(&path, &manifest).await?;
+        self.write_latest(&scope_path, &version).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        let GraphFilter { namespace, name } = filter;
+
+        let path = match (namespace.as_str(), name.as_deref()) {
+            ("", _) => self.prefix.clone(),
+            (namespace, None | Some("")) => self.prefix.child(namespace),
+            (namespace, Some(name)) => self.prefix.child(namespace).child(name),
+        };
+
+        let versions = self.list_versions(&path, None).await?;
+        let graphs = try_join_all(
+            versions
+                .iter()
+                .filter(|(scope, _)| filter.contains(scope))
+                .filter_map(|(scope, versions)| Some((scope, versions.last()?)))
+                .map(|(scope, version)| self.load(scope, version)),
+        )
+        .await?;
+        Ok(graphs.into_iter().flatten().collect())
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn remove(&self, scope: GraphScope) -> Result<()> {
+        // NOTE: write a tombstone so that the former versions can be still queried
+        let timestamp = Utc::now();
+        let scope_path = self.scope_path(&scope);
+        let version = new_version(timestamp);
+        let path = scope_path.child(version.as_str());
+
+        let manifest = GraphManifest {
+            connector: None,
+            data: None,
+            metadata: GraphMetadata::default(),
+            scope,
+            timestamp,
+        };
+        self.write_manifest(&path, &manifest).await?;
+        self.write_latest(&scope_path, &version).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn close(&self) -> Result<()> {
+        info!("Closing object store db...");
+        Ok(())
+    }
+}
+
+impl NetworkGraphDB {
+    const FILENAME_EDGES: &'static str = "edges.parquet";
+    const FILENAME_LATEST: &'static str = "latest";
+    const FILENAME_MANIFEST: &'static str = "manifest.json";
+    const FILENAME_NODES: &'static str = "nodes.parquet";
+
+    fn scope_path(&self, scope: &GraphScope) -> Path {
+        let GraphScope { namespace, name } = scope;
+        self.prefix.child(namespace.as_str()).child(name.as_str())
+    }
+
+    /// Lists the committed versions under the path, after the offset if given.
+    async fn list_versions(
+        &self,
+        path: &Path,
+        offset: Option<&Path>,
+    ) -> Result<BTreeMap<GraphScope, BTreeSet<String>>> {
+        let objects = match offset {
+            Some(offset) => self.store.list_with_offset(Some(path), offset),
+            None => self.store.list(Some(path)),
+        };
+        let objects: Vec<_> = objects
+            .try_collect()
+            .await
+            .map_err(|error| anyhow!("failed to list graphs from object store db: {error}"))?;
+
+        let mut versions: BTreeMap<_, BTreeSet<_>> = BTreeMap::default();
+        for object in objects {
+            let parts: Vec<String> = match object.location.prefix_match(&self.prefix) {
+                Some(parts) => parts.map(|part| part.as_ref().into()).collect(),
+                None => continue,
+            };
+            if let [namespace, name, version, filename] = parts.as_slice() {
+                if filename == Self::FILENAME_MANIFEST {
+                    let scope = GraphScope {
+                        namespace: namespace.clone(),
+                        name: name.clone(),
+                    };
+                    versions.entry(scope).or_default().insert(version.clone());
+                }
+            }
+        }
+        Ok(versions)
+    }
+
+    async fn load(
+        &self,
+        scope: &GraphScope,
+        version: &str,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        let path = self.scope_path(scope).child(version);
+
+        let manifest = self.read(&path.child(Self::FILENAME_MANIFEST)).await?;
+        let GraphManifest {
+            connector,
+            data,
+            metadata,
+            scope,
+            timestamp: _,
+        } = ::serde_json::from_slice(&manifest)?;
+
+        // the graph has been removed at this version
+        let Some(GraphData { edges, nodes }) = data else {
+            return Ok(None);
+        };
+
+        let (edges, nodes) = try_join!(
+            self.read_frame(&path, Self::FILENAME_EDGES, edges),
+            self.read_frame(&path, Self::FILENAME_NODES, nodes),
+        )?;
+
+        Ok(Some(Graph {
+            connector,
+            data: GraphData { edges, nodes }.lazy(),
+            metadata,
+            scope,
+        }))
+    }
+
+    async fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let result = self
+            .store
+            .get(path)
+            .await
+            .map_err(|error| anyhow!("failed to get a graph from object store db: {error}"))?;
+
+        result
+            .bytes()
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|error| anyhow!("failed to read a graph from object store db: {error}"))
+    }
+
+    async fn read_latest(&self, scope_path: &Path) -> Result<Option<String>> {
+        match self
+            .store
+            .get(&scope_path.child(Self::FILENAME_LATEST))
+            .await
+        {
+            Ok(result) => result
+                .bytes()
+                .await
+                .map(|bytes| Some(String::from_utf8_lossy(&bytes).into_owned()))
+                .map_err(|error| anyhow!("failed to read a graph from object store db: {error}")),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(error) => Err(anyhow!(
+                "failed to get a graph from object store db: {error}"
+            )),
+        }
+    }
+
+    async fn read_frame(&self, path: &Path, filename: &str, exists: bool) -> Result<DataFrame> {
+        if !exists {
+            return Ok(DataFrame::Empty);
+        }
+
+        let bytes = self.read(&path.child(filename)).await?;
+        ParquetReader::new(Cursor::new(bytes))
+            .finish()
+            .map(DataFrame::Polars)
+            .map_err(|error| anyhow!("failed to decode a graph frame: {error}"))
+    }
+
+    async fn write_frame(&self, path: &Path, filename: &str, df: DataFrame) -> Result<bool> {
+        match df {
+            DataFrame::Empty => Ok(false),
+            DataFrame::Polars(mut df) => {
+                let mut buf = Vec::default();
+                ParquetWriter::new(&mut buf)
+                    .finish(&mut df)
+                    .map_err(|error| anyhow!("failed to encode a graph frame: {error}"))?;
+
+                self.store
+                    .put(&path.child(filename), buf.into())
+                    .await
+                    .map(|_| true)
+                    .map_err(|error| {
+                        anyhow!("failed to insert graph frame into object store db: {error}")
+                    })
+            }
+        }
+    }
+
+    async fn write_latest(&self, scope_path: &Path, version: &str) -> Result<()> {
+        let payload = PutPayload::from(version.to_string());
+
+        self.store
+            .put(&scope_path.child(Self::FILENAME_LATEST), payload)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to insert graph into object store db: {error}"))
+    }
+
+    async fn write_manifest(&self, path: &Path, manifest: &GraphManifest) -> Result<()> {
+        let buf = ::serde_json::to_vec(manifest)?;
+
+        self.store
+            .put(&path.child(Self::FILENAME_MANIFEST), buf.into())
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to insert graph into object store db: {error}"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphManifest {
+    #[serde(default)]
+    connector: Option<Arc<NetworkConnectorCrd>>,
+    /// Whether each frame is stored; `None` marks the graph as removed
+    #[serde(default)]
+    data: Option<GraphData<bool>>,
+    #[serde(default)]
+    metadata: GraphMetadata,
+    scope: GraphScope,
+    timestamp: DateTime<Utc>,
+}
+
+/// Versions are sorted lexicographically in the order of creation.
+///
+/// The versions created in the same millisecond are ordered by a monotonic counter.
+fn new_version(timestamp: DateTime<Utc>) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "{:020}-{count:020}-{}",
+        timestamp.timestamp_millis(),
+        Uuid::new_v4(),
+    )
+}
+
+fn parse_version_timestamp(version: &str) -> Option<i64> {
+    version
+        .split_once('-')
+        .and_then(|(timestamp, _)| timestamp.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use kubegraph_api::graph::NetworkGraphDB as _;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn new_db() -> NetworkGraphDB {
+        NetworkGraphDB {
+            prefix: Path::from("kubegraph"),
+            store: Arc::new(InMemory::new()),
+        }
+    }
+
+    fn new_scope() -> GraphScope {
+        GraphScope {
+            namespace: "default".into(),
+            name: "warehouse".into(),
+        }
+    }
+
+    async fn write_version(db: &NetworkGraphDB, scope: &GraphScope, millis: i64, removed: bool) {
+        let timestamp = Utc.timestamp_millis_opt(millis).unwrap();
+        let path = db.scope_path(scope).child(new_version(timestamp));
+
+        let manifest = GraphManifest {
+            connector: None,
+            data: if removed {
+                None
+            } else {
+                Some(GraphData {
+                    edges: false,
+                    nodes: false,
+                })
+            },
+            metadata: GraphMetadata::default(),
+            scope: scope.clone(),
+            timestamp,
+        };
+        db.write_manifest(&path, &manifest)
+            .await
+            .expect("failed to write manifest")
+    }
+
+    #[test]
+    fn version_layout() {
+        let early = Utc.timestamp_millis_opt(999).unwrap();
+        let late = Utc.timestamp_millis_opt(1_000).unwrap();
+
+        let version_early = new_version(early);
+        let version_late = new_version(late);
+
+        // versions should be sorted by the creation time, regardless of the digits
+        assert!(version_early < version_late);
+        assert_eq!(parse_version_timestamp(&version_early), Some(999));
+        assert_eq!(parse_version_timestamp(&version_late), Some(1_000));
+        assert_eq!(parse_version_timestamp("manifest.json"), None);
+
+        // versions in the same millisecond should be sorted by the creation order
+        let versions: Vec<_> = (0..100).map(|_| new_version(late)).collect();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(versions
+            .iter()
+            .all(|version| parse_version_timestamp(version) == Some(1_000)));
+    }
+
+    #[::tokio::test]
+    async fn list_versions_by_scope() {
+        let db = new_db();
+        let scope_a = new_scope();
+        let scope_b = GraphScope {
+            namespace: "default".into(),
+            name: "factory".into(),
+        };
+
+        write_version(&db, &scope_a, 1_000, false).await;
+        write_version(&db, &scope_a, 2_000, false).await;
+        write_version(&db, &scope_b, 1_500, false).await;
+
+        // orphaned frames without a manifest are not committed versions
+        db.store
+            .put(
+                &db.scope_path(&scope_a)
+                    .child(new_version(Utc.timestamp_millis_opt(3_000).unwrap()))
+                    .child(NetworkGraphDB::FILENAME_NODES),
+                Vec::default().into(),
+            )
+            .await
+            .expect("failed to write an orphaned frame");
+
+        let versions = db
+            .list_versions(&db.prefix, None)
+            .await
+            .expect("failed to list versions");
+        assert_eq!(versions.len(), 2);
+
+        let timestamps: Vec<_> = versions[&scope_a]
+            .iter()
+            .filter_map(|version| parse_version_timestamp(version))
+            .collect();
+        assert_eq!(timestamps, [1_000, 2_000]);
+
+        let timestamps: Vec<_> = versions[&scope_b]
+            .iter()
+            .filter_map(|version| parse_version_timestamp(version))
+            .collect();
+        assert_eq!(timestamps, [1_500]);
+    }
+
+    #[::tokio::test]
+    async fn get_at_latest_version_before() {
+        let db = new_db();
+        let scope = new_scope();
+
+        write_version(&db, &scope, 1_000, false).await;
+        write_version(&db, &scope, 2_000, true).await;
+        write_version(&db, &scope, 3_000, false).await;
+
+        let get_at = |millis| {
+            let db = db.clone();
+            let scope = scope.clone();
+            async move {
+                db.get_at(&scope, Utc.timestamp_millis_opt(millis).unwrap())
+                    .await
+                    .expect("failed to get a graph")
+                    .is_some()
+            }
+        };
+
+        // no versions yet
+        assert!(!get_at(999).await);
+        // the exact version timestamp is inclusive
+        assert!(get_at(1_000).await);
+        assert!(get_at(1_999).await);
+        // removed
+        assert!(!get_at(2_000).await);
+        assert!(!get_at(2_999).await);
+        // recreated
+        assert!(get_at(3_000).await);
+        assert!(get_at(10_000).await);
+
+        // the latest version is used by default
+        assert!(db
+            .get(&scope)
+            .await
+            .expect("failed to get a graph")
+            .is_some());
+    }
+
+    #[::tokio::test]
+    async fn get_seeks_from_latest_pointer() {
+        let db = new_db();
+        let scope = new_scope();
+        let path = db.scope_path(&scope);
+
+        write_version(&db, &scope, 1_000, false).await;
+        write_version(&db, &scope, 2_000, false).await;
+        write_version(&db, &scope, 3_000, true).await;
+
+        let versions: Vec<_> = db
+            .list_versions(&path, None)
+            .await
+            .expect("failed to list versions")
+            .remove(&scope)
+            .expect("versions should exist")
+            .into_iter()
+            .collect();
+
+        // the pointed version and the newer ones are listed
+        db.write_latest(&path, &versions[1])
+            .await
+            .expect("failed to write the latest pointer");
+        let offset = path.child(versions[1].as_str());
+        let seeked: Vec<_> = db
+            .list_versions(&path, Some(&offset))
+            .await
+            .expect("failed to list versions")
+            .remove(&scope)
+            .expect("versions should exist")
+            .into_iter()
+            .collect();
+        assert_eq!(seeked, &versions[1..]);
+
+        // an outdated pointer should not hide the newer versions
+        assert!(db
+            .get(&scope)
+            .await
+            .expect("failed to get a graph")
+            .is_none());
+
+        db.write_latest(&path, &versions[0])
+            .await
+            .expect("failed to write the latest pointer");
+        assert!(db
+            .get(&scope)
+            .await
+            .expect("failed to get a graph")
+            .is_none());
+    }
+
+    #[::tokio::test]
+    async fn insert_and_remove() {
+        let db = new_db();
+        let scope = new_scope();
+
+        let nodes = ::polars::df!(
+            "name"      => [    "a",     "b"],
+            "capacity"  => [ 300i64,  300i64],
+        )
+        .expect("failed to create nodes dataframe");
+
+        let graph = Graph {
+            connector: None,
+            data: GraphData {
+                edges: LazyFrame::Empty,
+                nodes: DataFrame::Polars(nodes.clone()).lazy(),
+            },
+            metadata: GraphMetadata::default(),
+            scope: scope.clone(),
+        };
+        db.insert(graph).await.expect("failed to insert a graph");
+
+        let graph = db
+            .get(&scope)
+            .await
+            .expect("failed to get a graph")
+            .expect("graph should exist")
+            .collect()
+            .await
+            .expect("failed to collect a graph");
+        assert_eq!(graph.scope, scope);
+        assert_eq!(graph.data.edges, DataFrame::Empty);
+        assert_eq!(graph.data.nodes, DataFrame::Polars(nodes));
+
+        let filter = GraphFilter::all("default".into());
+        assert_eq!(db.list(&filter).await.expect("failed to list").len(), 1);
+
+        // wait for the next version timestamp
+        ::tokio::time::sleep(::std::time::Duration::from_millis(2)).await;
+        db.remove(scope.clone())
+            .await
+            .expect("failed to remove a graph");
+
+        assert!(db.get(&scope).await.expect("failed to get").is_none());
+        assert!(db.list(&filter).await.expect("failed to list").is_empty());
+
+        // the former version can be still queried
+        let versions = db
+            .list_versions(&db.scope_path(&scope), None)
+            .await
+            .expect("failed to list versions");
+        let first = versions[&scope]
+            .first()
+            .and_then(|version| parse_version_timestamp(version))
+            .expect("version should exist");
+        let graph = db
+            .get_at(&scope, Utc.timestamp_millis_opt(first).unwrap())
+            .await
+            .expect("failed to get a graph");
+        assert!(graph.is_some());
+    }
+}
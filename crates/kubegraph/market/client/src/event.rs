@@ -0,0 +1,43 @@
+use anyhow::{anyhow, Result};
+use async_stream::try_stream;
+use futures::{Stream, StreamExt};
+use kubegraph_api::market::event::MarketEvent;
+use reqwest::Response;
+
+/// Decodes the server-sent events of the market gateway.
+pub(crate) fn create_stream(response: Response) -> impl Stream<Item = Result<MarketEvent>> {
+    try_stream! {
+        let mut chunks = response.bytes_stream();
+        let mut buf = Vec::default();
+
+        while let Some(chunk) = chunks.next().await {
+            buf.extend_from_slice(&chunk?);
+
+            // NOTE: the messages are separated by the blank lines
+            while let Some(end) = buf.windows(2).position(|window| window == b"\n\n") {
+                let message: Vec<_> = buf.drain(..end + 2).collect();
+                let message = ::std::str::from_utf8(&message)
+                    .map_err(|error| anyhow!("malformed market event: {error}"))?;
+                if let Some(event) = parse_message(message)? {
+                    yield event;
+                }
+            }
+        }
+    }
+}
+
+fn parse_message(message: &str) -> Result<Option<MarketEvent>> {
+    // NOTE: the lines without data (e.g. keep-alive comments) are ignored
+    let data: Vec<_> = message
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    if data.is_empty() {
+        return Ok(None);
+    }
+
+    ::serde_json::from_str(&data.join("\n"))
+        .map(Some)
+        .map_err(|error| anyhow!("malformed market event: {error}"))
+}
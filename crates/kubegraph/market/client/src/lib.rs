@@ -1,6 +1,9 @@
+mod event;
 mod page;
 
-use anyhow::{bail, Error, Result};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Error, Result};
 use ark_core::signal::FunctionSignal;
 use ark_core_k8s::data::Url;
 use async_trait::async_trait;
@@ -9,17 +12,19 @@ use futures::Stream;
 use kubegraph_api::{
     component::NetworkComponent,
     market::{
+        event::MarketEvent,
         price::{PriceHistogram, PriceItem},
         product::ProductSpec,
         r#pub::PubSpec,
         sub::SubSpec,
-        transaction::{TransactionReceipt, TransactionTemplate},
+        transaction::{TaskState, TransactionReceipt, TransactionSpec, TransactionTemplate},
         BaseModel, Page,
     },
 };
 use reqwest::Method;
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::fs::read_to_string;
 use tracing::{instrument, Level};
 
 #[derive(Clone)]
@@ -139,6 +144,25 @@ impl MarketClient {
         self.execute(request).await
     }
 
+    /// Subscribes the events of the product, such as price changes,
+    /// new orders and transaction state transitions.
+    ///
+    /// NOTE: the stream is closed when the subscriber lags behind or the product is removed.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn subscribe(
+        &self,
+        prod_id: <ProductSpec as BaseModel>::Id,
+    ) -> Result<impl Stream<Item = Result<MarketEvent>>> {
+        let url = self.args.endpoint.join(&format!("prod/{prod_id}/event"))?;
+        let mut request = self.session.get(url);
+        if let Some(token) = self.load_token().await? {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        Ok(self::event::create_stream(response))
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     pub async fn trade(
         &self,
@@ -155,6 +179,58 @@ impl MarketClient {
     }
 }
 
+impl MarketClient {
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn get_transaction(
+        &self,
+        txn_id: <TransactionSpec as BaseModel>::Id,
+    ) -> Result<Option<TransactionSpec>> {
+        let request = RequestWithoutPayload {
+            method: Method::GET,
+            rel_url: &format!("txn/{txn_id}"),
+            page: None,
+            payload: None,
+        };
+        self.execute(request).await
+    }
+
+    /// Reports the state of the pub task of the transaction.
+    ///
+    /// The transaction is settled when both the pub and sub tasks are completed.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn update_pub_state(
+        &self,
+        txn_id: <TransactionSpec as BaseModel>::Id,
+        state: TaskState,
+    ) -> Result<TransactionSpec> {
+        let request = Request {
+            method: Method::PUT,
+            rel_url: &format!("txn/{txn_id}/pub"),
+            page: None,
+            payload: Some(&state),
+        };
+        self.execute(request).await
+    }
+
+    /// Reports the state of the sub task of the transaction.
+    ///
+    /// The transaction is settled when both the pub and sub tasks are completed.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn update_sub_state(
+        &self,
+        txn_id: <TransactionSpec as BaseModel>::Id,
+        state: TaskState,
+    ) -> Result<TransactionSpec> {
+        let request = Request {
+            method: Method::PUT,
+            rel_url: &format!("txn/{txn_id}/sub"),
+            page: None,
+            payload: Some(&state),
+        };
+        self.execute(request).await
+    }
+}
+
 impl MarketClient {
     #[instrument(level = Level::INFO, skip(self))]
     pub async fn get_pub(
@@ -325,6 +401,9 @@ impl MarketClient {
         if let Some(payload) = payload {
             request = request.json(&payload);
         }
+        if let Some(token) = self.load_token().await? {
+            request = request.bearer_auth(token);
+        }
 
         request
             .send()
@@ -339,6 +418,19 @@ impl MarketClient {
     }
 }
 
+impl MarketClient {
+    async fn load_token(&self) -> Result<Option<String>> {
+        match &self.args.token_file {
+            // NOTE: the projected service account tokens are rotated periodically
+            Some(path) => read_to_string(path)
+                .await
+                .map(|token| Some(token.trim().into()))
+                .map_err(|error| anyhow!("failed to read the market token file {path:?}: {error}")),
+            None => Ok(self.args.token.clone()),
+        }
+    }
+}
+
 type RequestWithoutPayload<'a> = Request<'a, ()>;
 
 struct Request<'a, T> {
@@ -361,12 +453,29 @@ pub struct MarketClientArgs {
     )]
     #[serde(default = "MarketClientArgs::default_endpoint")]
     pub endpoint: Url,
+
+    /// The bearer token to authenticate to the market gateway
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_CLIENT_TOKEN",
+        value_name = "TOKEN",
+        hide_env_values = true
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// The file of the bearer token, which is preferred to the token
+    #[arg(long, env = "KUBEGRAPH_MARKET_CLIENT_TOKEN_FILE", value_name = "PATH")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
 }
 
 impl Default for MarketClientArgs {
     fn default() -> Self {
         Self {
             endpoint: Self::default_endpoint(),
+            token: None,
+            token_file: None,
         }
     }
 }
@@ -0,0 +1,52 @@
+use chrono::NaiveDateTime;
+use kubegraph_api::market::{account::AccountSpec, BaseModel};
+use sea_orm::{
+    ActiveModelBehavior, ActiveValue, DeriveEntityModel, DerivePrimaryKey, DeriveRelation,
+    EnumIter, PrimaryKeyTrait,
+};
+
+type Id = <AccountSpec as BaseModel>::Id;
+type Cost = <AccountSpec as BaseModel>::Cost;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "accounts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    #[sea_orm(column_type = "Timestamp")]
+    pub created_at: NaiveDateTime,
+    pub balance: Cost,
+    pub escrow: Cost,
+    pub owner: Option<String>,
+}
+
+impl From<Model> for AccountSpec {
+    fn from(value: Model) -> Self {
+        let Model {
+            id: _,
+            created_at: _,
+            balance,
+            escrow,
+            owner: _,
+        } = value;
+
+        Self { balance, escrow }
+    }
+}
+
+impl ActiveModel {
+    pub const fn from_id(id: Id) -> Self {
+        Self {
+            id: ActiveValue::Set(id),
+            created_at: ActiveValue::NotSet,
+            balance: ActiveValue::NotSet,
+            escrow: ActiveValue::NotSet,
+            owner: ActiveValue::NotSet,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+use kubegraph_api::market::{
+    account::{AccountSpec, LedgerItem},
+    BaseModel,
+};
+use sea_orm::{
+    ActiveModelBehavior, ActiveValue, DeriveActiveEnum, DeriveEntityModel, DerivePrimaryKey,
+    DeriveRelation, EntityTrait, EnumIter, PrimaryKeyTrait,
+};
+
+type Id = <AccountSpec as BaseModel>::Id;
+type Cost = <AccountSpec as BaseModel>::Cost;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "ledger")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Id,
+    pub account_id: Id,
+    #[sea_orm(column_type = "Timestamp")]
+    pub created_at: NaiveDateTime,
+    pub kind: LedgerKind,
+    pub amount: Cost,
+    pub price_id: Option<Id>,
+    pub txn_id: Option<Id>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "i16", db_type = "SmallInteger")]
+#[repr(i16)]
+pub enum LedgerKind {
+    Deposit = 0,
+    Escrow = 1,
+    Refund = 2,
+    Payment = 3,
+    Income = 4,
+}
+
+impl From<LedgerKind> for ::kubegraph_api::market::account::LedgerKind {
+    fn from(value: LedgerKind) -> Self {
+        match value {
+            LedgerKind::Deposit => Self::Deposit,
+            LedgerKind::Escrow => Self::Escrow,
+            LedgerKind::Refund => Self::Refund,
+            LedgerKind::Payment => Self::Payment,
+            LedgerKind::Income => Self::Income,
+        }
+    }
+}
+
+impl From<::kubegraph_api::market::account::LedgerKind> for LedgerKind {
+    fn from(value: ::kubegraph_api::market::account::LedgerKind) -> Self {
+        match value {
+            ::kubegraph_api::market::account::LedgerKind::Deposit => Self::Deposit,
+            ::kubegraph_api::market::account::LedgerKind::Escrow => Self::Escrow,
+            ::kubegraph_api::market::account::LedgerKind::Refund => Self::Refund,
+            ::kubegraph_api::market::account::LedgerKind::Payment => Self::Payment,
+            ::kubegraph_api::market::account::LedgerKind::Income => Self::Income,
+        }
+    }
+}
+
+impl From<Model> for LedgerItem {
+    fn from(value: Model) -> Self {
+        let Model {
+            id,
+            account_id,
+            created_at,
+            kind,
+            amount,
+            price_id,
+            txn_id,
+        } = value;
+
+        Self {
+            id,
+            timestamp: created_at.and_utc(),
+            account: account_id,
+            kind: kind.into(),
+            amount,
+            price: price_id,
+            txn: txn_id,
+        }
+    }
+}
+
+impl ActiveModel {
+    pub const fn from_item(
+        id: Id,
+        account_id: Id,
+        kind: LedgerKind,
+        amount: Cost,
+        price_id: Option<Id>,
+        txn_id: Option<Id>,
+    ) -> Self {
+        Self {
+            id: ActiveValue::Set(id),
+            account_id: ActiveValue::Set(account_id),
+            created_at: ActiveValue::NotSet,
+            kind: ActiveValue::Set(kind),
+            amount: ActiveValue::Set(amount),
+            price_id: ActiveValue::Set(price_id),
+            txn_id: ActiveValue::Set(txn_id),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::account::Entity",
+        from = "self::Column::AccountId",
+        to = "super::account::Column::Id"
+    )]
+    Accounts,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
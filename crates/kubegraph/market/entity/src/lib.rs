@@ -1,3 +1,5 @@
+pub mod account;
+pub mod ledger;
 pub mod price;
 pub mod product;
 pub mod transaction;
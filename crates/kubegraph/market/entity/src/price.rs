@@ -2,7 +2,7 @@ use anyhow::{Error, Result};
 use chrono::NaiveDateTime;
 use kubegraph_api::{
     function::webhook::NetworkFunctionWebhookSpec,
-    market::{product::ProductSpec, r#pub::PubSpec, sub::SubSpec, BaseModel},
+    market::{price::PriceItem, product::ProductSpec, r#pub::PubSpec, sub::SubSpec, BaseModel},
 };
 use sea_orm::{
     ActiveModelBehavior, ActiveValue, DeriveActiveEnum, DeriveEntityModel, DerivePrimaryKey,
@@ -26,6 +26,8 @@ pub struct Model {
     pub cost: Cost,
     pub count: Count,
     pub spec: Value,
+    pub account_id: Option<Id>,
+    pub owner: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, DeriveActiveEnum)]
@@ -54,6 +56,18 @@ impl From<::kubegraph_api::market::price::Direction> for Direction {
     }
 }
 
+impl From<&Model> for PriceItem {
+    fn from(value: &Model) -> Self {
+        Self {
+            id: value.id,
+            timestamp: value.created_at.and_utc(),
+            direction: value.direction.into(),
+            cost: value.cost,
+            count: value.count,
+        }
+    }
+}
+
 impl TryFrom<Model> for PubSpec {
     type Error = Error;
 
@@ -66,6 +80,8 @@ impl TryFrom<Model> for PubSpec {
             cost,
             count,
             spec,
+            account_id: account,
+            owner: _,
         } = value;
 
         let function = ::serde_json::from_value(spec)?;
@@ -74,6 +90,7 @@ impl TryFrom<Model> for PubSpec {
             cost,
             count,
             function,
+            account,
         })
     }
 }
@@ -90,6 +107,8 @@ impl TryFrom<Model> for SubSpec {
             cost,
             count,
             spec,
+            account_id: account,
+            owner: _,
         } = value;
 
         let function = ::serde_json::from_value(spec)?;
@@ -98,6 +117,7 @@ impl TryFrom<Model> for SubSpec {
             cost,
             count,
             function,
+            account,
         })
     }
 }
@@ -112,6 +132,8 @@ impl ActiveModel {
             cost: ActiveValue::NotSet,
             count: ActiveValue::NotSet,
             spec: ActiveValue::NotSet,
+            account_id: ActiveValue::NotSet,
+            owner: ActiveValue::NotSet,
         }
     }
 
@@ -120,6 +142,7 @@ impl ActiveModel {
             count,
             cost,
             function,
+            account,
         } = spec;
 
         let spec = to_spec(function)?;
@@ -135,6 +158,8 @@ impl ActiveModel {
             cost: ActiveValue::Set(cost),
             count: ActiveValue::Set(count),
             spec: ActiveValue::Set(spec),
+            account_id: ActiveValue::Set(account),
+            owner: ActiveValue::NotSet,
         })
     }
 
@@ -143,6 +168,7 @@ impl ActiveModel {
             cost,
             count,
             function,
+            account,
         } = spec;
 
         let spec = to_spec(function)?;
@@ -158,6 +184,8 @@ impl ActiveModel {
             cost: ActiveValue::Set(cost),
             count: ActiveValue::Set(count),
             spec: ActiveValue::Set(spec),
+            account_id: ActiveValue::Set(account),
+            owner: ActiveValue::NotSet,
         })
     }
 }
@@ -1,7 +1,10 @@
 use anyhow::{Error, Result};
 use chrono::NaiveDateTime;
 use kubegraph_api::{
-    market::{product::ProductSpec, BaseModel},
+    market::{
+        product::{ProductMarketSpec, ProductSpec},
+        BaseModel,
+    },
     problem::ProblemSpec,
 };
 use sea_orm::{
@@ -20,6 +23,8 @@ pub struct Model {
     #[sea_orm(column_type = "Timestamp")]
     pub created_at: NaiveDateTime,
     pub spec: Value,
+    pub market: Option<Value>,
+    pub owner: Option<String>,
 }
 
 impl TryFrom<Model> for ProductSpec {
@@ -30,11 +35,17 @@ impl TryFrom<Model> for ProductSpec {
             id: _,
             created_at: _,
             spec,
+            market,
+            owner: _,
         } = value;
 
         let problem = ::serde_json::from_value(spec)?;
+        let market = market
+            .map(::serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default();
 
-        Ok(Self { problem })
+        Ok(Self { problem, market })
     }
 }
 
@@ -44,38 +55,50 @@ impl ActiveModel {
             id: ActiveValue::Set(id),
             created_at: ActiveValue::NotSet,
             spec: ActiveValue::NotSet,
+            market: ActiveValue::NotSet,
+            owner: ActiveValue::NotSet,
         }
     }
 
     pub fn from_spec(spec: ProductSpec, id: Id) -> Result<Self> {
-        let ProductSpec { problem } = spec;
+        let (spec, market) = to_spec(spec)?;
 
-        let spec = to_problem_spec(problem)?;
-
-        Ok(Self {
-            id: ActiveValue::Set(id),
-            created_at: ActiveValue::NotSet,
-            spec: ActiveValue::Set(spec),
-        })
+        Ok(Self::from_spec_native(spec, market, id))
     }
 
-    pub const fn from_spec_native(spec: Value, id: Id) -> Self {
+    pub const fn from_spec_native(spec: Value, market: Option<Value>, id: Id) -> Self {
         Self {
             id: ActiveValue::Set(id),
             created_at: ActiveValue::NotSet,
             spec: ActiveValue::Set(spec),
+            market: ActiveValue::Set(market),
+            owner: ActiveValue::NotSet,
         }
     }
 }
 
-pub fn to_spec(spec: ProductSpec) -> Result<Value> {
-    to_problem_spec(spec.problem)
+pub fn to_spec(spec: ProductSpec) -> Result<(Value, Option<Value>)> {
+    let ProductSpec { problem, market } = spec;
+
+    let spec = to_problem_spec(problem)?;
+    let market = to_market_spec(market)?;
+    Ok((spec, market))
 }
 
 pub fn to_problem_spec(problem: ProblemSpec) -> Result<Value> {
     ::serde_json::to_value(problem).map_err(Into::into)
 }
 
+/// NOTE: the default market spec is stored as NULL, so that the products
+/// created before introducing the market spec can be found as they are.
+pub fn to_market_spec(market: ProductMarketSpec) -> Result<Option<Value>> {
+    if market.is_default() {
+        Ok(None)
+    } else {
+        ::serde_json::to_value(market).map(Some).map_err(Into::into)
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
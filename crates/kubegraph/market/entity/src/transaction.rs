@@ -33,6 +33,8 @@ pub struct Model {
     pub sub_state: TaskState,
     #[sea_orm(column_type = "Timestamp")]
     pub sub_updated_at: NaiveDateTime,
+    pub pub_account_id: Option<Id>,
+    pub sub_account_id: Option<Id>,
 }
 
 #[derive(
@@ -81,6 +83,8 @@ impl From<Model> for TransactionSpec {
             pub_updated_at,
             sub_state,
             sub_updated_at,
+            pub_account_id: _,
+            sub_account_id: _,
         } = value;
 
         Self {
@@ -118,10 +122,17 @@ impl ActiveModel {
             pub_updated_at: ActiveValue::NotSet,
             sub_state: ActiveValue::NotSet,
             sub_updated_at: ActiveValue::NotSet,
+            pub_account_id: ActiveValue::NotSet,
+            sub_account_id: ActiveValue::NotSet,
         }
     }
 
-    pub const fn from_template(id: Id, template: TransactionTemplate) -> Self {
+    pub const fn from_template(
+        id: Id,
+        template: TransactionTemplate,
+        pub_account_id: Option<Id>,
+        sub_account_id: Option<Id>,
+    ) -> Self {
         let TransactionTemplate {
             prod: prod_id,
             r#pub: pub_id,
@@ -142,6 +153,8 @@ impl ActiveModel {
             pub_updated_at: ActiveValue::NotSet,
             sub_state: ActiveValue::Set(TaskState::Running),
             sub_updated_at: ActiveValue::NotSet,
+            pub_account_id: ActiveValue::Set(pub_account_id),
+            sub_account_id: ActiveValue::Set(sub_account_id),
         }
     }
 }
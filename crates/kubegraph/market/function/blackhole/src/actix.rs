@@ -1,10 +1,13 @@
 use std::net::SocketAddr;
 
-use actix_web::{get, middleware, App, HttpResponse, HttpServer, Responder};
+use actix_web::{get, middleware, web::Data, App, HttpResponse, HttpServer, Responder};
 use actix_web_opentelemetry::{RequestMetrics, RequestTracing};
 use anyhow::{anyhow, Result};
 use ark_core::{env::infer, signal::FunctionSignal};
+use clap::Parser;
 use futures::TryFutureExt;
+use kubegraph_api::component::NetworkComponent;
+use kubegraph_market_client::{MarketClient, MarketClientArgs};
 use tracing::{error, info, instrument, Level};
 
 #[instrument(level = Level::INFO)]
@@ -14,7 +17,7 @@ async fn health() -> impl Responder {
 }
 
 pub async fn loop_forever(signal: FunctionSignal) {
-    match try_loop_forever().await {
+    match try_loop_forever(&signal).await {
         Ok(()) => signal.terminate(),
         Err(error) => {
             error!("failed to operate http server: {error}");
@@ -23,16 +26,19 @@ pub async fn loop_forever(signal: FunctionSignal) {
     }
 }
 
-async fn try_loop_forever() -> Result<()> {
+async fn try_loop_forever(signal: &FunctionSignal) -> Result<()> {
     info!("Starting http server...");
 
     // Initialize pipe
     let addr =
         infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
 
+    let client = MarketClient::try_new(MarketClientArgs::parse(), signal).await?;
+    let client = Data::new(client);
+
     // Create a http server
     let server = HttpServer::new(move || {
-        let app = App::new();
+        let app = App::new().app_data(Data::clone(&client));
         let app = app.service(health).service(crate::routes::post);
         app.wrap(middleware::NormalizePath::new(
             middleware::TrailingSlash::Trim,
@@ -1,11 +1,28 @@
-use actix_web::{post, web::Json, HttpResponse, Responder};
+use actix_web::{
+    post,
+    web::{Data, Json},
+    HttpResponse, Responder,
+};
 use ark_core::result::Result;
-use kubegraph_api::market::transaction::TransactionReceipt;
-use tracing::{info, instrument, Level};
+use kubegraph_api::market::transaction::{TaskState, TransactionReceipt};
+use kubegraph_market_client::MarketClient;
+use tokio::spawn;
+use tracing::{error, info, instrument, Level};
 
-#[instrument(level = Level::INFO, skip())]
+#[instrument(level = Level::INFO, skip(client))]
 #[post("/")]
-pub async fn post(receipt: Json<TransactionReceipt>) -> impl Responder {
+pub async fn post(client: Data<MarketClient>, receipt: Json<TransactionReceipt>) -> impl Responder {
+    let receipt = receipt.0;
     info!("{receipt:#?}");
+
+    // Deliver nothing, but complete the task so that the transaction can be settled
+    // NOTE: report the state in background, as the market waits for this webhook
+    spawn(async move {
+        let txn_id = receipt.id;
+        match client.update_pub_state(txn_id, TaskState::Completed).await {
+            Ok(_) => info!("Completed the transaction: {txn_id}"),
+            Err(error) => error!("failed to complete the transaction {txn_id}: {error}"),
+        }
+    });
     HttpResponse::Ok().json(Result::Ok(()))
 }
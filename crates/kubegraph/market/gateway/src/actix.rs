@@ -7,7 +7,7 @@ use ark_core::env::infer;
 use futures::TryFutureExt;
 use tracing::{error, info, instrument, Level};
 
-use crate::db::Database;
+use crate::{auth::MarketAuth, db::Database};
 
 #[instrument(level = Level::INFO)]
 #[get("/_health")]
@@ -15,8 +15,11 @@ async fn health() -> impl Responder {
     HttpResponse::Ok().json("healthy")
 }
 
-pub async fn loop_forever(db: Database) {
-    match try_loop_forever(&db).await {
+pub async fn loop_forever(db: Database, auth: MarketAuth) {
+    let addr =
+        infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
+
+    match serve(&db, auth, addr).await {
         Ok(()) => db.signal.terminate(),
         Err(error) => {
             error!("failed to operate http server: {error}");
@@ -25,20 +28,25 @@ pub async fn loop_forever(db: Database) {
     }
 }
 
-async fn try_loop_forever(db: &Database) -> Result<()> {
+pub async fn serve(db: &Database, auth: MarketAuth, addr: SocketAddr) -> Result<()> {
     info!("Starting http server...");
 
     // Initialize pipe
-    let addr =
-        infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
-
+    let auth = Data::new(auth);
     let db = Data::new(db.clone());
 
     // Create a http server
     let server = HttpServer::new(move || {
-        let app = App::new().app_data(Data::clone(&db));
+        let app = App::new()
+            .app_data(Data::clone(&auth))
+            .app_data(Data::clone(&db));
         let app = app
             .service(health)
+            .service(crate::routes::account::get)
+            .service(crate::routes::account::list_ledger)
+            .service(crate::routes::account::put)
+            .service(crate::routes::account::post_deposit)
+            .service(crate::routes::event::subscribe)
             .service(crate::routes::product::list)
             .service(crate::routes::product::list_price)
             .service(crate::routes::product::get)
@@ -53,7 +61,11 @@ async fn try_loop_forever(db: &Database) -> Result<()> {
             .service(crate::routes::sub::list)
             .service(crate::routes::sub::get)
             .service(crate::routes::sub::put)
-            .service(crate::routes::sub::delete);
+            .service(crate::routes::sub::delete)
+            .service(crate::routes::transaction::get)
+            .service(crate::routes::transaction::list_ledger)
+            .service(crate::routes::transaction::put_pub_state)
+            .service(crate::routes::transaction::put_sub_state);
         app.wrap(middleware::NormalizePath::new(
             middleware::TrailingSlash::Trim,
         ))
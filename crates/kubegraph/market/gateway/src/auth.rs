@@ -0,0 +1,270 @@
+use std::{collections::BTreeMap, future::Future, pin::Pin};
+
+use actix_web::{dev::Payload, error::ErrorUnauthorized, web::Data, FromRequest, HttpRequest};
+use anyhow::{anyhow, bail, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use k8s_openapi::api::authentication::v1::{TokenReview, TokenReviewSpec, TokenReviewStatus};
+use kube::{api::PostParams, Api, Client};
+use kubegraph_api::component::NetworkComponent;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn, Level};
+
+#[derive(Clone)]
+pub struct MarketAuth {
+    admins: Vec<String>,
+    backend: MarketAuthBackend,
+}
+
+#[async_trait]
+impl NetworkComponent for MarketAuth {
+    type Args = MarketAuthArgs;
+
+    #[instrument(level = Level::INFO, skip_all)]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        _signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let MarketAuthArgs {
+            auth_admins: admins,
+            auth_mode,
+            auth_tokens,
+        } = args;
+
+        let backend = match auth_mode {
+            MarketAuthMode::Disabled => {
+                warn!("The market gateway is not authenticated; every request is an admin");
+                MarketAuthBackend::Disabled
+            }
+            MarketAuthMode::Token => MarketAuthBackend::Token {
+                tokens: auth_tokens
+                    .iter()
+                    .map(|entry| match entry.split_once(':') {
+                        Some((user, token)) if !user.is_empty() && !token.is_empty() => {
+                            Ok((token.to_string(), user.to_string()))
+                        }
+                        Some(_) | None => bail!("malformed auth token: expected USER:TOKEN"),
+                    })
+                    .collect::<Result<_>>()?,
+            },
+            MarketAuthMode::Kubernetes => MarketAuthBackend::Kubernetes {
+                kube: Client::try_default()
+                    .await
+                    .map_err(|error| anyhow!("failed to init kubernetes client: {error}"))?,
+            },
+        };
+
+        Ok(Self { admins, backend })
+    }
+}
+
+impl MarketAuth {
+    #[instrument(level = Level::INFO, skip(self, request))]
+    async fn authenticate(&self, request: &HttpRequest) -> Result<MarketPrincipal> {
+        let name = match &self.backend {
+            MarketAuthBackend::Disabled => return Ok(MarketPrincipal::admin()),
+            MarketAuthBackend::Token { tokens } => {
+                let token = get_bearer_token(request)?;
+                tokens
+                    .get(token)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("unknown token"))?
+            }
+            MarketAuthBackend::Kubernetes { kube } => {
+                let token = get_bearer_token(request)?;
+                review_token(kube, token).await?
+            }
+        };
+
+        Ok(MarketPrincipal {
+            is_admin: self.admins.contains(&name),
+            name: Some(name),
+        })
+    }
+}
+
+#[derive(Clone)]
+enum MarketAuthBackend {
+    Disabled,
+    Token { tokens: BTreeMap<String, String> },
+    Kubernetes { kube: Client },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Parser)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct MarketAuthArgs {
+    /// The users allowed to manage all orders and accounts
+    ///
+    /// NOTE: the market solver should be one of them to trade the products
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_AUTH_ADMINS",
+        value_name = "USER",
+        value_delimiter = ','
+    )]
+    #[serde(default)]
+    pub auth_admins: Vec<String>,
+
+    /// The way to authenticate the requests
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_AUTH_MODE",
+        value_enum,
+        value_name = "MODE",
+        default_value_t = MarketAuthMode::default(),
+    )]
+    #[serde(default)]
+    pub auth_mode: MarketAuthMode,
+
+    /// The static bearer tokens, used by the token mode
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_AUTH_TOKENS",
+        value_name = "USER:TOKEN",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    #[serde(default)]
+    pub auth_tokens: Vec<String>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub enum MarketAuthMode {
+    /// Do not authenticate; every request is treated as an admin
+    Disabled,
+    /// Authenticate with the static bearer tokens
+    Token,
+    /// Authenticate the kubernetes service account tokens with TokenReview
+    ///
+    /// NOTE: the gateway's service account should be allowed to create `tokenreviews`
+    #[default]
+    Kubernetes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketPrincipal {
+    pub is_admin: bool,
+    pub name: Option<String>,
+}
+
+impl FromRequest for MarketPrincipal {
+    type Error = ::actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let request = request.clone();
+        Box::pin(async move {
+            let auth = request
+                .app_data::<Data<MarketAuth>>()
+                .ok_or_else(|| ErrorUnauthorized("market auth is not configured"))?;
+
+            auth.authenticate(&request).await.map_err(|error| {
+                warn!("failed to authenticate: {error}");
+                ErrorUnauthorized(error.to_string())
+            })
+        })
+    }
+}
+
+impl MarketPrincipal {
+    /// Returns a principal with full permissions.
+    ///
+    /// It is used when the authentication is disabled, and for the internal calls.
+    pub(crate) const fn admin() -> Self {
+        Self {
+            is_admin: true,
+            name: None,
+        }
+    }
+
+    pub fn owner(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    /// Returns `true` if the principal can modify the resources of the owner.
+    ///
+    /// NOTE: the resources without owners can be modified only by the admins.
+    pub fn is_owner(&self, owner: Option<&str>) -> bool {
+        self.is_admin || (owner.is_some() && owner == self.name.as_deref())
+    }
+
+    pub fn ensure_admin(&self) -> Result<()> {
+        if self.is_admin {
+            Ok(())
+        } else {
+            bail!("permission denied: admin only")
+        }
+    }
+
+    pub fn ensure_owner(&self, owner: Option<&str>) -> Result<()> {
+        if self.is_owner(owner) {
+            Ok(())
+        } else {
+            bail!("permission denied: owner only")
+        }
+    }
+}
+
+fn get_bearer_token(request: &HttpRequest) -> Result<&str> {
+    const HEADER_AUTHORIZATION: &str = "Authorization";
+
+    request
+        .headers()
+        .get(HEADER_AUTHORIZATION)
+        .ok_or_else(|| anyhow!("the Authorization token is not found"))?
+        .to_str()
+        .map_err(|_| anyhow!("the Authorization token is malformed"))?
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| anyhow!("the Authorization token is not a Bearer token"))
+}
+
+async fn review_token(kube: &Client, token: &str) -> Result<String> {
+    let api = Api::<TokenReview>::all(kube.clone());
+    let pp = PostParams::default();
+    let review = TokenReview {
+        spec: TokenReviewSpec {
+            token: Some(token.into()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    match api.create(&pp, &review).await {
+        Ok(TokenReview {
+            status:
+                Some(TokenReviewStatus {
+                    authenticated: Some(true),
+                    user: Some(user),
+                    ..
+                }),
+            ..
+        }) => user
+            .username
+            .ok_or_else(|| anyhow!("the token has no username")),
+        Ok(TokenReview { status, .. }) => {
+            let error = status.and_then(|status| status.error);
+            bail!(
+                "the token is not authenticated: {error}",
+                error = error.as_deref().unwrap_or("unknown"),
+            )
+        }
+        Err(error) => bail!("failed to review the token: {error}"),
+    }
+}
@@ -1,35 +1,42 @@
 use std::convert::identity;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use clap::Parser;
 use futures::TryFutureExt;
 use kubegraph_api::{
     component::NetworkComponent,
     market::{
-        price::{PriceHistogram, PriceItem},
+        account::{AccountSpec, LedgerItem},
+        event::MarketEventKind,
+        price::{Direction, PriceHistogram, PriceItem},
         product::ProductSpec,
         r#pub::PubSpec,
         sub::SubSpec,
-        transaction::{TransactionError, TransactionReceipt, TransactionSpec, TransactionTemplate},
+        transaction::{
+            TaskState, TransactionError, TransactionReceipt, TransactionSpec, TransactionTemplate,
+        },
         BaseModel, Page,
     },
 };
 use kubegraph_market_function::{MarketFunction, MarketFunctionClient, MarketFunctionClientArgs};
-use kubegraph_market_migration::MigratorTrait;
+use kubegraph_market_migration::{MigratorTrait, SimpleExpr};
 use sea_orm::{
     ActiveValue, ColumnTrait, DbErr, DeleteResult, EntityTrait, QueryFilter, QueryOrder,
     QuerySelect, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
-use tokio::try_join;
+use tokio::join;
 use tracing::{error, instrument, Level};
 
+use crate::{auth::MarketPrincipal, event::MarketEvents};
+
 #[derive(Clone)]
 pub struct Database {
     connection: ::sea_orm::DatabaseConnection,
+    pub(crate) events: MarketEvents,
     function: MarketFunctionClient,
     pub(crate) signal: FunctionSignal,
 }
@@ -60,6 +67,7 @@ impl NetworkComponent for Database {
 
         Ok(Self {
             connection,
+            events: MarketEvents::default(),
             function: MarketFunctionClient::try_new(function, signal).await?,
             signal: signal.clone(),
         })
@@ -87,6 +95,40 @@ impl Database {
             .and_then(|model| model.map(TryInto::try_into).transpose())
     }
 
+    /// Returns `true` if the principal takes part in the product,
+    /// i.e. it owns the product or any of its orders.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn is_product_participant(
+        &self,
+        principal: &MarketPrincipal,
+        prod_id: <ProductSpec as BaseModel>::Id,
+    ) -> Result<bool> {
+        if principal.is_admin {
+            return Ok(true);
+        }
+        let Some(owner) = principal.owner() else {
+            return Ok(false);
+        };
+
+        let is_product_owner = entity::product::Entity::find_by_id(prod_id)
+            .filter(entity::product::Column::Owner.eq(owner.clone()))
+            .one(&self.connection)
+            .await?
+            .is_some();
+        if is_product_owner {
+            return Ok(true);
+        }
+
+        let col_product_id = entity::price::Column::ProductId;
+        let col_owner = entity::price::Column::Owner;
+        entity::price::Entity::find()
+            .filter(col_product_id.eq(prod_id).and(col_owner.eq(owner)))
+            .one(&self.connection)
+            .await
+            .map(|model| model.is_some())
+            .map_err(Into::into)
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     pub async fn list_product_ids(
         &self,
@@ -111,31 +153,62 @@ impl Database {
             .map_err(Into::into)
     }
 
+    /// Finds the product of the given problem, creating it if not exists.
+    ///
+    /// Only the owner of the product can change its market spec.
     #[instrument(level = Level::INFO, skip(self, spec))]
-    pub async fn find_product(&self, spec: ProductSpec) -> Result<<ProductSpec as BaseModel>::Id> {
+    pub async fn find_product(
+        &self,
+        principal: &MarketPrincipal,
+        spec: ProductSpec,
+    ) -> Result<<ProductSpec as BaseModel>::Id> {
         self.connection
             .transaction::<_, _, DbErr>(|txn| {
                 Box::pin(async move {
                     let col_id = entity::product::Column::Id;
                     let col_spec = entity::product::Column::Spec;
+                    let col_market = entity::product::Column::Market;
+                    let col_owner = entity::product::Column::Owner;
 
-                    let spec = match entity::product::to_spec(spec) {
+                    let (spec, market) = match entity::product::to_spec(spec) {
                         Ok(spec) => spec,
                         Err(error) => return Ok(Err(error.into())),
                     };
-                    match entity::product::Entity::find()
-                        .select_only()
-                        .column(col_id)
-                        .filter(col_spec.eq(spec.clone()))
-                        .into_tuple()
-                        .one(txn)
-                        .await?
-                    {
-                        Some(item) => Ok(Ok(item)),
+
+                    // NOTE: the market spec is not a part of the product identity,
+                    // so that all the orders of the same problem share an order book
+                    let item: Option<(_, Option<::serde_json::Value>, Option<String>)> =
+                        entity::product::Entity::find()
+                            .select_only()
+                            .column(col_id)
+                            .column(col_market)
+                            .column(col_owner)
+                            .filter(col_spec.eq(spec.clone()))
+                            .order_by_asc(entity::product::Column::CreatedAt)
+                            .into_tuple()
+                            .one(txn)
+                            .await?;
+                    match item {
+                        Some((prod_id, last_market, owner)) => {
+                            // Apply the explicit market spec, keeping the former one otherwise
+                            if market.is_some() && market != last_market {
+                                if let Err(error) = principal.ensure_owner(owner.as_deref()) {
+                                    return Ok(Err(error));
+                                }
+                                let model = entity::product::ActiveModel {
+                                    market: ActiveValue::Set(market),
+                                    ..entity::product::ActiveModel::from_id(prod_id)
+                                };
+                                entity::product::Entity::update(model).exec(txn).await?;
+                            }
+                            Ok(Ok(prod_id))
+                        }
                         None => {
                             let prod_id = <ProductSpec as BaseModel>::Id::new_v4();
-                            let model =
-                                entity::product::ActiveModel::from_spec_native(spec, prod_id);
+                            let mut model = entity::product::ActiveModel::from_spec_native(
+                                spec, market, prod_id,
+                            );
+                            model.owner = ActiveValue::Set(principal.owner());
                             let dsl = entity::product::Entity::insert(model);
 
                             dsl.exec_without_returning(txn).await?;
@@ -156,13 +229,20 @@ impl Database {
             .and_then(identity)
     }
 
+    /// Inserts a new product, even if the same problem exists.
+    ///
+    /// Only the admins can insert products, as it splits the order book of the problem.
     #[instrument(level = Level::INFO, skip(self, spec))]
     pub async fn insert_product(
         &self,
+        principal: &MarketPrincipal,
         spec: ProductSpec,
     ) -> Result<<ProductSpec as BaseModel>::Id> {
+        principal.ensure_admin()?;
+
         let prod_id = <ProductSpec as BaseModel>::Id::new_v4();
-        let model = entity::product::ActiveModel::from_spec(spec, prod_id)?;
+        let mut model = entity::product::ActiveModel::from_spec(spec, prod_id)?;
+        model.owner = ActiveValue::Set(principal.owner());
         let dsl = entity::product::Entity::insert(model);
 
         dsl.exec_without_returning(&self.connection).await?;
@@ -186,12 +266,40 @@ impl Database {
     // }
 
     #[instrument(level = Level::INFO, skip(self))]
-    pub async fn remove_product(&self, prod_id: <ProductSpec as BaseModel>::Id) -> Result<()> {
-        let model = entity::product::ActiveModel::from_id(prod_id);
-        let dsl = entity::product::Entity::delete(model);
+    pub async fn remove_product(
+        &self,
+        principal: &MarketPrincipal,
+        prod_id: <ProductSpec as BaseModel>::Id,
+    ) -> Result<()> {
+        principal.ensure_admin()?;
 
-        let DeleteResult { rows_affected: _ } = dsl.exec(&self.connection).await?;
-        Ok(())
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    // NOTE: the prices and transactions are removed in cascade
+                    let col_product_id = entity::price::Column::ProductId;
+                    if let Err(error) =
+                        self::ledger::release_prices(txn, col_product_id.eq(prod_id)).await?
+                    {
+                        return Ok(Err(error));
+                    }
+
+                    let model = entity::product::ActiveModel::from_id(prod_id);
+                    let dsl = entity::product::Entity::delete(model);
+
+                    let DeleteResult { rows_affected } = dsl.exec(txn).await?;
+                    Ok(Ok(rows_affected > 0))
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "removing a product"))
+            .and_then(identity)
+            .map(|removed| {
+                if removed {
+                    self.events
+                        .publish(prod_id, MarketEventKind::ProductRemoved);
+                }
+            })
     }
 }
 
@@ -278,8 +386,13 @@ impl Database {
                 cost: _,
                 count: _,
             },
+            events,
         ) = self.trade_on_db(template).await?;
 
+        for event in events {
+            self.events.publish(template.prod, event);
+        }
+
         let receipt = TransactionReceipt {
             id: txn_id,
             template,
@@ -294,7 +407,133 @@ impl Database {
             .spawn(receipt, sub)
             .map_err(TransactionError::FunctionFailedSub);
 
-        try_join!(task_pub, task_sub).map(|((), ())| receipt)
+        let (result_pub, result_sub) = join!(task_pub, task_sub);
+
+        // Refund the transaction if any of the functions has failed
+        for (direction, result) in [(Direction::Pub, &result_pub), (Direction::Sub, &result_sub)] {
+            if result.is_err() {
+                if let Err(error) = self
+                    .update_task_state(
+                        &MarketPrincipal::admin(),
+                        txn_id,
+                        direction,
+                        TaskState::Failed,
+                    )
+                    .await
+                {
+                    error!("failed to mark the transaction {txn_id} as failed: {error}");
+                }
+            }
+        }
+        result_pub.and(result_sub).map(|()| receipt)
+    }
+
+    /// Updates the state of the pub or sub task of the transaction.
+    ///
+    /// The transaction is settled when both tasks are completed,
+    /// and refunded as soon as any task is failed.
+    ///
+    /// Only the owner of the pub or sub can update the state of its own task,
+    /// and only the admins can fail the sub task once the pub task is completed.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn update_task_state(
+        &self,
+        principal: &MarketPrincipal,
+        txn_id: <TransactionSpec as BaseModel>::Id,
+        direction: Direction,
+        state: TaskState,
+    ) -> Result<TransactionSpec> {
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    let model = match entity::transaction::Entity::find_by_id(txn_id)
+                        .one(txn)
+                        .await?
+                    {
+                        Some(model) => model,
+                        None => return Ok(Err(anyhow!("no such transaction: {txn_id}"))),
+                    };
+
+                    if !principal.is_admin {
+                        let price_id = match direction {
+                            Direction::Pub => model.pub_id,
+                            Direction::Sub => model.sub_id,
+                        };
+                        let owner = entity::price::Entity::find_by_id(price_id)
+                            .one(txn)
+                            .await?
+                            .and_then(|price| price.owner);
+                        if let Err(error) = principal.ensure_owner(owner.as_deref()) {
+                            return Ok(Err(error));
+                        }
+                    }
+
+                    let state = entity::transaction::TaskState::from(state);
+                    let (last_state, other_state) = match direction {
+                        Direction::Pub => (model.pub_state, model.sub_state),
+                        Direction::Sub => (model.sub_state, model.pub_state),
+                    };
+                    if last_state == state {
+                        return Ok(Ok(model.into()));
+                    }
+                    if last_state != entity::transaction::TaskState::Running {
+                        return Ok(Err(anyhow!(
+                            "the {direction:?} task of the transaction {txn_id} is already finished"
+                        )));
+                    }
+                    // NOTE: disputing a delivered task would refund the whole escrow
+                    if !principal.is_admin
+                        && direction == Direction::Sub
+                        && state == entity::transaction::TaskState::Failed
+                        && other_state == entity::transaction::TaskState::Completed
+                    {
+                        return Ok(Err(anyhow!(
+                            "the Pub task of the transaction {txn_id} is already completed; only the admins can dispute it"
+                        )));
+                    }
+
+                    let now = Utc::now().naive_utc();
+                    let mut next = entity::transaction::ActiveModel::from_id(txn_id);
+                    match direction {
+                        Direction::Pub => {
+                            next.pub_state = ActiveValue::Set(state);
+                            next.pub_updated_at = ActiveValue::Set(now);
+                        }
+                        Direction::Sub => {
+                            next.sub_state = ActiveValue::Set(state);
+                            next.sub_updated_at = ActiveValue::Set(now);
+                        }
+                    }
+                    let model = entity::transaction::Entity::update(next).exec(txn).await?;
+
+                    use entity::transaction::TaskState as State;
+                    let result = match (state, other_state) {
+                        // Settle the transaction
+                        (State::Completed, State::Completed) => {
+                            self::ledger::settle_transaction(txn, &model).await?
+                        }
+                        // Refund the transaction, only once
+                        (State::Failed, State::Running | State::Completed) => {
+                            self::ledger::refund_transaction(txn, &model).await?
+                        }
+                        (State::Running, _) | (State::Completed, State::Running) => Ok(()),
+                        (State::Completed | State::Failed, State::Failed) => Ok(()),
+                    };
+                    Ok(result.map(|()| model.into()))
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "updating a transaction"))
+            .and_then(identity)
+            .inspect(|spec: &TransactionSpec| {
+                self.events.publish(
+                    spec.template.prod,
+                    MarketEventKind::Transaction {
+                        id: txn_id,
+                        spec: *spec,
+                    },
+                )
+            })
     }
 
     #[instrument(level = Level::INFO, skip(self))]
@@ -305,6 +544,7 @@ impl Database {
         (
             <TransactionSpec as BaseModel>::Id,
             TransactionTemplate<<ProductSpec as BaseModel>::Id, PubSpec, SubSpec>,
+            Vec<MarketEventKind>,
         ),
         TransactionError,
     > {
@@ -324,30 +564,32 @@ impl Database {
                     }
 
                     let r#pub = match entity::price::Entity::find_by_id(pub_id).one(txn).await? {
-                        Some(item) => {
-                            if item.direction != entity::price::Direction::Pub
-                                || item.cost > cost
-                                || item.count >= count
-                            {
-                                item
-                            } else {
-                                return Ok(Err(TransactionError::OutOfPub));
-                            }
+                        Some(item)
+                            if self::trade::is_tradable(
+                                &item,
+                                prod,
+                                entity::price::Direction::Pub,
+                                cost,
+                                count,
+                            ) =>
+                        {
+                            item
                         }
-                        None => return Ok(Err(TransactionError::OutOfPub)),
+                        Some(_) | None => return Ok(Err(TransactionError::OutOfPub)),
                     };
                     let sub = match entity::price::Entity::find_by_id(sub_id).one(txn).await? {
-                        Some(item) => {
-                            if item.direction != entity::price::Direction::Sub
-                                || item.cost < cost
-                                || item.count >= count
-                            {
-                                item
-                            } else {
-                                return Ok(Err(TransactionError::OutOfSub));
-                            }
+                        Some(item)
+                            if self::trade::is_tradable(
+                                &item,
+                                prod,
+                                entity::price::Direction::Sub,
+                                cost,
+                                count,
+                            ) =>
+                        {
+                            item
                         }
-                        None => return Ok(Err(TransactionError::OutOfSub)),
+                        Some(_) | None => return Ok(Err(TransactionError::OutOfSub)),
                     };
 
                     let pub_spec = match r#pub.clone().try_into() {
@@ -365,7 +607,15 @@ impl Database {
                         }
                     };
 
+                    let txn_id = <TransactionSpec as BaseModel>::Id::new_v4();
+                    let pub_account_id = r#pub.account_id;
+                    let sub_account_id = sub.account_id;
+                    let sub_cost = sub.cost;
+
                     let withdraw = |price: entity::price::Model| async move {
+                        let mut item = PriceItem::from(&price);
+                        item.count -= count;
+
                         let col_id = entity::price::Column::Id;
                         let model = entity::price::ActiveModel {
                             id: ActiveValue::Unchanged(price.id),
@@ -375,24 +625,56 @@ impl Database {
                             cost: ActiveValue::Unchanged(price.cost),
                             count: ActiveValue::Set(price.count - count),
                             spec: ActiveValue::Unchanged(price.spec),
+                            account_id: ActiveValue::Unchanged(price.account_id),
+                            owner: ActiveValue::Unchanged(price.owner),
                         };
                         let dsl = entity::price::Entity::update(model).filter(col_id.eq(price.id));
 
-                        dsl.exec(txn).await.map(|_| ())
+                        dsl.exec(txn)
+                            .await
+                            .map(|_| MarketEventKind::OrderUpdated(item))
                     };
 
-                    withdraw(r#pub).await?;
-                    withdraw(sub).await?;
+                    let mut events = vec![withdraw(r#pub).await?, withdraw(sub).await?];
 
-                    let txn_id = {
-                        let txn_id = <TransactionSpec as BaseModel>::Id::new_v4();
-                        let model =
-                            entity::transaction::ActiveModel::from_template(txn_id, template);
+                    {
+                        let model = entity::transaction::ActiveModel::from_template(
+                            txn_id,
+                            template,
+                            pub_account_id,
+                            sub_account_id,
+                        );
                         let dsl = entity::transaction::Entity::insert(model);
 
                         dsl.exec_without_returning(txn).await?;
-                        txn_id
-                    };
+                    }
+                    if let Some(model) = entity::transaction::Entity::find_by_id(txn_id)
+                        .one(txn)
+                        .await?
+                    {
+                        events.push(MarketEventKind::Transaction {
+                            id: txn_id,
+                            spec: model.into(),
+                        });
+                    }
+
+                    // Refund the difference between the bid and the traded cost
+                    let amount = self::trade::refund_amount(sub_cost, cost, count);
+                    if let Some(account_id) = sub_account_id.filter(|_| amount > 0) {
+                        if !self::ledger::apply(
+                            txn,
+                            account_id,
+                            entity::ledger::LedgerKind::Refund,
+                            amount,
+                            Some(sub_id),
+                            Some(txn_id),
+                        )
+                        .await?
+                        {
+                            error!("failed to refund the sub {sub_id}: out of escrow");
+                            return Ok(Err(TransactionError::TransactionFailed));
+                        }
+                    }
 
                     let template = TransactionTemplate {
                         prod,
@@ -401,7 +683,7 @@ impl Database {
                         cost,
                         count,
                     };
-                    Ok(Ok((txn_id, template)))
+                    Ok(Ok((txn_id, template, events)))
                 })
             })
             .await
@@ -460,25 +742,78 @@ impl Database {
     #[instrument(level = Level::INFO, skip(self, spec))]
     pub async fn insert_pub(
         &self,
+        principal: &MarketPrincipal,
         prod_id: <ProductSpec as BaseModel>::Id,
         spec: PubSpec,
     ) -> Result<<PubSpec as BaseModel>::Id> {
+        if let Some(account_id) = spec.account {
+            self.ensure_account_owner(principal, account_id).await?;
+        }
+
         let pub_id = <PubSpec as BaseModel>::Id::new_v4();
-        let model = entity::price::ActiveModel::from_pub_spec(spec, Some(prod_id), pub_id)?;
+        let item = PriceItem {
+            id: pub_id,
+            timestamp: Utc::now(),
+            direction: Direction::Pub,
+            cost: spec.cost,
+            count: spec.count,
+        };
+        let mut model = entity::price::ActiveModel::from_pub_spec(spec, Some(prod_id), pub_id)?;
+        model.owner = ActiveValue::Set(principal.owner());
         let dsl = entity::price::Entity::insert(model);
 
         dsl.exec_without_returning(&self.connection).await?;
+        self.events
+            .publish(prod_id, MarketEventKind::OrderCreated(item));
         Ok(pub_id)
     }
 
     #[instrument(level = Level::INFO, skip(self))]
-    pub async fn remove_pub(&self, pub_id: <PubSpec as BaseModel>::Id) -> Result<()> {
-        let model = entity::price::ActiveModel::from_id(pub_id);
-        let filter = self::filter::default_price(Some(entity::price::Direction::Pub));
-        let dsl = entity::price::Entity::delete(model).filter(filter);
+    pub async fn remove_pub(
+        &self,
+        principal: &MarketPrincipal,
+        pub_id: <PubSpec as BaseModel>::Id,
+    ) -> Result<()> {
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    let prod_id = match entity::price::Entity::find_by_id(pub_id).one(txn).await? {
+                        Some(model) => match principal.ensure_owner(model.owner.as_deref()) {
+                            Ok(()) => model.product_id,
+                            Err(error) => return Ok(Err(error)),
+                        },
+                        None => return Ok(Ok(None)),
+                    };
 
-        let DeleteResult { rows_affected: _ } = dsl.exec(&self.connection).await?;
-        Ok(())
+                    let col_id = entity::price::Column::Id;
+                    let col_direction = entity::price::Column::Direction;
+                    let filter = col_id
+                        .eq(pub_id)
+                        .and(col_direction.eq(entity::price::Direction::Pub));
+                    if let Err(error) = self::ledger::release_prices(txn, filter).await? {
+                        return Ok(Err(error));
+                    }
+
+                    let model = entity::price::ActiveModel::from_id(pub_id);
+                    let filter = self::filter::default_price(Some(entity::price::Direction::Pub));
+                    let dsl = entity::price::Entity::delete(model).filter(filter);
+
+                    let DeleteResult { rows_affected } = dsl.exec(txn).await?;
+                    Ok(Ok((rows_affected > 0).then_some(prod_id)))
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "removing a pub"))
+            .and_then(identity)
+            .map(|prod_id| {
+                if let Some(prod_id) = prod_id {
+                    let kind = MarketEventKind::OrderRemoved {
+                        id: pub_id,
+                        direction: Direction::Pub,
+                    };
+                    self.events.publish(prod_id, kind);
+                }
+            })
     }
 }
 
@@ -523,25 +858,114 @@ impl Database {
     #[instrument(level = Level::INFO, skip(self, spec))]
     pub async fn insert_sub(
         &self,
+        principal: &MarketPrincipal,
         prod_id: <ProductSpec as BaseModel>::Id,
         spec: SubSpec,
     ) -> Result<<SubSpec as BaseModel>::Id> {
         let sub_id = <SubSpec as BaseModel>::Id::new_v4();
-        let model = entity::price::ActiveModel::from_sub_spec(spec, Some(prod_id), sub_id)?;
-        let dsl = entity::price::Entity::insert(model);
+        let account_id = spec.account;
+        match account_id {
+            Some(account_id) => self.ensure_account_owner(principal, account_id).await?,
+            // NOTE: only the admins can bid without escrow
+            None if !principal.is_admin => bail!("an account is required to bid: {sub_id}"),
+            None => (),
+        }
 
-        dsl.exec_without_returning(&self.connection).await?;
-        Ok(sub_id)
+        let amount = spec
+            .cost
+            .checked_mul(spec.count)
+            .ok_or_else(|| anyhow!("too expensive sub: {sub_id}"))?;
+        let item = PriceItem {
+            id: sub_id,
+            timestamp: Utc::now(),
+            direction: Direction::Sub,
+            cost: spec.cost,
+            count: spec.count,
+        };
+        let mut model = entity::price::ActiveModel::from_sub_spec(spec, Some(prod_id), sub_id)?;
+        model.owner = ActiveValue::Set(principal.owner());
+
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    let dsl = entity::price::Entity::insert(model);
+                    dsl.exec_without_returning(txn).await?;
+
+                    // Hold the bid until it is traded or removed
+                    if let Some(account_id) = account_id {
+                        if !self::ledger::apply(
+                            txn,
+                            account_id,
+                            entity::ledger::LedgerKind::Escrow,
+                            amount,
+                            Some(sub_id),
+                            None,
+                        )
+                        .await?
+                        {
+                            return Ok(Err(anyhow!(
+                                "insufficient balance of account {account_id}: {amount}"
+                            )));
+                        }
+                    }
+                    Ok(Ok(sub_id))
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "inserting a sub"))
+            .and_then(identity)
+            .inspect(|_| {
+                self.events
+                    .publish(prod_id, MarketEventKind::OrderCreated(item))
+            })
     }
 
     #[instrument(level = Level::INFO, skip(self))]
-    pub async fn remove_sub(&self, sub_id: <SubSpec as BaseModel>::Id) -> Result<()> {
-        let model = entity::price::ActiveModel::from_id(sub_id);
-        let filter = self::filter::default_price(Some(entity::price::Direction::Sub));
-        let dsl = entity::price::Entity::delete(model).filter(filter);
+    pub async fn remove_sub(
+        &self,
+        principal: &MarketPrincipal,
+        sub_id: <SubSpec as BaseModel>::Id,
+    ) -> Result<()> {
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    let prod_id = match entity::price::Entity::find_by_id(sub_id).one(txn).await? {
+                        Some(model) => match principal.ensure_owner(model.owner.as_deref()) {
+                            Ok(()) => model.product_id,
+                            Err(error) => return Ok(Err(error)),
+                        },
+                        None => return Ok(Ok(None)),
+                    };
 
-        let DeleteResult { rows_affected: _ } = dsl.exec(&self.connection).await?;
-        Ok(())
+                    let col_id = entity::price::Column::Id;
+                    let col_direction = entity::price::Column::Direction;
+                    let filter = col_id
+                        .eq(sub_id)
+                        .and(col_direction.eq(entity::price::Direction::Sub));
+                    if let Err(error) = self::ledger::release_prices(txn, filter).await? {
+                        return Ok(Err(error));
+                    }
+
+                    let model = entity::price::ActiveModel::from_id(sub_id);
+                    let filter = self::filter::default_price(Some(entity::price::Direction::Sub));
+                    let dsl = entity::price::Entity::delete(model).filter(filter);
+
+                    let DeleteResult { rows_affected } = dsl.exec(txn).await?;
+                    Ok(Ok((rows_affected > 0).then_some(prod_id)))
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "removing a sub"))
+            .and_then(identity)
+            .map(|prod_id| {
+                if let Some(prod_id) = prod_id {
+                    let kind = MarketEventKind::OrderRemoved {
+                        id: sub_id,
+                        direction: Direction::Sub,
+                    };
+                    self.events.publish(prod_id, kind);
+                }
+            })
     }
 }
 
@@ -558,6 +982,149 @@ impl Database {
             .map_err(Into::into)
             .map(|model| model.map(Into::into))
     }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn list_transaction_ledger(
+        &self,
+        principal: &MarketPrincipal,
+        txn_id: <TransactionSpec as BaseModel>::Id,
+        page: Page,
+    ) -> Result<Vec<LedgerItem>> {
+        principal.ensure_admin()?;
+
+        let col_txn_id = entity::ledger::Column::TxnId;
+        self.list_ledger(col_txn_id.eq(txn_id), page).await
+    }
+}
+
+impl Database {
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn get_account(
+        &self,
+        principal: &MarketPrincipal,
+        account_id: <AccountSpec as BaseModel>::Id,
+    ) -> Result<Option<AccountSpec>> {
+        let dsl = entity::account::Entity::find_by_id(account_id);
+
+        match dsl.one(&self.connection).await? {
+            Some(model) => {
+                principal.ensure_owner(model.owner.as_deref())?;
+                Ok(Some(model.into()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn insert_account(
+        &self,
+        principal: &MarketPrincipal,
+    ) -> Result<<AccountSpec as BaseModel>::Id> {
+        let account_id = <AccountSpec as BaseModel>::Id::new_v4();
+        let mut model = entity::account::ActiveModel::from_id(account_id);
+        model.owner = ActiveValue::Set(principal.owner());
+        let dsl = entity::account::Entity::insert(model);
+
+        dsl.exec_without_returning(&self.connection).await?;
+        Ok(account_id)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn deposit(
+        &self,
+        principal: &MarketPrincipal,
+        account_id: <AccountSpec as BaseModel>::Id,
+        amount: <AccountSpec as BaseModel>::Cost,
+    ) -> Result<AccountSpec> {
+        principal.ensure_admin()?;
+        if amount <= 0 {
+            bail!("deposit amount should be positive: {amount}");
+        }
+
+        self.connection
+            .transaction::<_, _, DbErr>(|txn| {
+                Box::pin(async move {
+                    if !self::ledger::apply(
+                        txn,
+                        account_id,
+                        entity::ledger::LedgerKind::Deposit,
+                        amount,
+                        None,
+                        None,
+                    )
+                    .await?
+                    {
+                        return Ok(Err(anyhow!("no such account: {account_id}")));
+                    }
+
+                    match entity::account::Entity::find_by_id(account_id)
+                        .one(txn)
+                        .await?
+                    {
+                        Some(model) => Ok(Ok(model.into())),
+                        None => Ok(Err(anyhow!("no such account: {account_id}"))),
+                    }
+                })
+            })
+            .await
+            .map_err(|error| self::ledger::map_txn_error(error, "depositing"))
+            .and_then(identity)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn list_account_ledger(
+        &self,
+        principal: &MarketPrincipal,
+        account_id: <AccountSpec as BaseModel>::Id,
+        page: Page,
+    ) -> Result<Vec<LedgerItem>> {
+        self.ensure_account_owner(principal, account_id).await?;
+
+        let col_account_id = entity::ledger::Column::AccountId;
+        self.list_ledger(col_account_id.eq(account_id), page).await
+    }
+
+    async fn ensure_account_owner(
+        &self,
+        principal: &MarketPrincipal,
+        account_id: <AccountSpec as BaseModel>::Id,
+    ) -> Result<()> {
+        if principal.is_admin {
+            return Ok(());
+        }
+
+        let col_owner = entity::account::Column::Owner;
+        let dsl = entity::account::Entity::find_by_id(account_id)
+            .select_only()
+            .column(col_owner);
+
+        match dsl
+            .into_tuple::<Option<String>>()
+            .one(&self.connection)
+            .await?
+        {
+            Some(owner) => principal.ensure_owner(owner.as_deref()),
+            None => bail!("no such account: {account_id}"),
+        }
+    }
+
+    async fn list_ledger(&self, filter: SimpleExpr, page: Page) -> Result<Vec<LedgerItem>> {
+        let Page { start, limit } = page;
+
+        let col_id = entity::ledger::Column::Id;
+        let dsl = entity::ledger::Entity::find()
+            .order_by_asc(col_id)
+            .limit(limit);
+        let dsl = match start {
+            Some(start) => dsl.filter(col_id.gt(start).and(filter)),
+            None => dsl.filter(filter),
+        };
+
+        dsl.all(&self.connection)
+            .await
+            .map(|models| models.into_iter().map(Into::into).collect())
+            .map_err(Into::into)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
@@ -600,3 +1167,368 @@ mod filter {
         }
     }
 }
+
+mod trade {
+    use kubegraph_api::market::{product::ProductSpec, BaseModel};
+
+    type Id = <ProductSpec as BaseModel>::Id;
+    type Cost = <ProductSpec as BaseModel>::Cost;
+    type Count = <ProductSpec as BaseModel>::Count;
+
+    /// Returns `true` if the order can be traded with the given cost and count.
+    ///
+    /// A pub should ask no more than the cost, and a sub should bid no less than the cost.
+    pub(super) fn is_tradable(
+        order: &entity::price::Model,
+        prod: Id,
+        direction: entity::price::Direction,
+        cost: Cost,
+        count: Count,
+    ) -> bool {
+        let is_cost_matched = match direction {
+            entity::price::Direction::Pub => order.cost <= cost,
+            entity::price::Direction::Sub => order.cost >= cost,
+        };
+
+        order.product_id == prod
+            && order.direction == direction
+            && is_cost_matched
+            && count > 0
+            && order.count >= count
+    }
+
+    /// Returns the difference between the bid and the traded cost, which is never negative.
+    pub(super) fn refund_amount(bid: Cost, cost: Cost, count: Count) -> Cost {
+        bid.saturating_sub(cost).max(0).saturating_mul(count.max(0))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::Utc;
+        use serde_json::Value;
+        use uuid::Uuid;
+
+        use super::*;
+
+        use entity::price::Direction;
+
+        fn new_order(
+            prod: Id,
+            direction: Direction,
+            cost: Cost,
+            count: Count,
+        ) -> entity::price::Model {
+            entity::price::Model {
+                id: Uuid::new_v4(),
+                product_id: prod,
+                created_at: Utc::now().naive_utc(),
+                direction,
+                cost,
+                count,
+                spec: Value::Null,
+                account_id: None,
+                owner: None,
+            }
+        }
+
+        #[test]
+        fn trade_matched_orders() {
+            let prod = Uuid::new_v4();
+
+            let order = new_order(prod, Direction::Pub, 10, 3);
+            assert!(is_tradable(&order, prod, Direction::Pub, 10, 3));
+            assert!(is_tradable(&order, prod, Direction::Pub, 15, 1));
+
+            let order = new_order(prod, Direction::Sub, 10, 3);
+            assert!(is_tradable(&order, prod, Direction::Sub, 10, 3));
+            assert!(is_tradable(&order, prod, Direction::Sub, 5, 1));
+        }
+
+        #[test]
+        fn reject_direction_mismatch() {
+            let prod = Uuid::new_v4();
+
+            let order = new_order(prod, Direction::Sub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Pub, 10, 1));
+
+            let order = new_order(prod, Direction::Pub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Sub, 10, 1));
+        }
+
+        #[test]
+        fn reject_product_mismatch() {
+            let order = new_order(Uuid::new_v4(), Direction::Pub, 10, 3);
+            assert!(!is_tradable(&order, Uuid::new_v4(), Direction::Pub, 10, 1));
+        }
+
+        #[test]
+        fn reject_pub_asking_more_than_cost() {
+            let prod = Uuid::new_v4();
+            let order = new_order(prod, Direction::Pub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Pub, 9, 1));
+        }
+
+        #[test]
+        fn reject_sub_bidding_less_than_cost() {
+            let prod = Uuid::new_v4();
+            let order = new_order(prod, Direction::Sub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Sub, 11, 1));
+        }
+
+        #[test]
+        fn reject_count_over_remaining() {
+            let prod = Uuid::new_v4();
+
+            let order = new_order(prod, Direction::Pub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Pub, 10, 4));
+
+            let order = new_order(prod, Direction::Sub, 10, 3);
+            assert!(!is_tradable(&order, prod, Direction::Sub, 10, 4));
+
+            // empty counts are never traded
+            assert!(!is_tradable(&order, prod, Direction::Sub, 10, 0));
+        }
+
+        #[test]
+        fn refund_is_never_negative() {
+            assert_eq!(refund_amount(10, 7, 3), 9);
+            assert_eq!(refund_amount(10, 10, 3), 0);
+            assert_eq!(refund_amount(7, 10, 3), 0);
+            assert_eq!(refund_amount(10, 7, -3), 0);
+            assert_eq!(refund_amount(Cost::MAX, Cost::MIN, 2), Cost::MAX);
+        }
+    }
+}
+
+mod ledger {
+    use anyhow::{anyhow, Error, Result};
+    use kubegraph_api::market::{account::AccountSpec, BaseModel};
+    use migration::{Expr, SimpleExpr};
+    use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, UpdateResult};
+
+    use entity::{ledger::LedgerKind, transaction::TaskState};
+
+    type Id = <AccountSpec as BaseModel>::Id;
+    type Cost = <AccountSpec as BaseModel>::Cost;
+
+    /// Applies the amount to the account and records it to the ledger.
+    ///
+    /// Returns `false` if there is no such account or it cannot afford the amount.
+    pub(super) async fn apply<C>(
+        conn: &C,
+        account_id: Id,
+        kind: LedgerKind,
+        amount: Cost,
+        price_id: Option<Id>,
+        txn_id: Option<Id>,
+    ) -> Result<bool, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if amount <= 0 {
+            return Ok(true);
+        }
+
+        let col_id = entity::account::Column::Id;
+        let col_balance = entity::account::Column::Balance;
+        let col_escrow = entity::account::Column::Escrow;
+
+        let dsl = entity::account::Entity::update_many().filter(col_id.eq(account_id));
+        let dsl = match kind {
+            LedgerKind::Deposit | LedgerKind::Income => {
+                dsl.col_expr(col_balance, Expr::col(col_balance).add(amount))
+            }
+            LedgerKind::Escrow => dsl
+                .col_expr(col_balance, Expr::col(col_balance).sub(amount))
+                .col_expr(col_escrow, Expr::col(col_escrow).add(amount))
+                .filter(col_balance.gte(amount)),
+            LedgerKind::Refund => dsl
+                .col_expr(col_balance, Expr::col(col_balance).add(amount))
+                .col_expr(col_escrow, Expr::col(col_escrow).sub(amount))
+                .filter(col_escrow.gte(amount)),
+            LedgerKind::Payment => dsl
+                .col_expr(col_escrow, Expr::col(col_escrow).sub(amount))
+                .filter(col_escrow.gte(amount)),
+        };
+
+        let UpdateResult { rows_affected } = dsl.exec(conn).await?;
+        if rows_affected == 0 {
+            return Ok(false);
+        }
+
+        let model = entity::ledger::ActiveModel::from_item(
+            Id::new_v4(),
+            account_id,
+            kind,
+            amount,
+            price_id,
+            txn_id,
+        );
+        let dsl = entity::ledger::Entity::insert(model);
+
+        dsl.exec_without_returning(conn).await?;
+        Ok(true)
+    }
+
+    /// Pays the cost of the transaction from the sub to the pub.
+    ///
+    /// NOTE: the pub is paid only if the sub has paid through its escrow.
+    pub(super) async fn settle_transaction<C>(
+        conn: &C,
+        model: &entity::transaction::Model,
+    ) -> Result<Result<()>, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let amount = model.cost.saturating_mul(model.count);
+        let sub_account_id = match model.sub_account_id {
+            Some(account_id) => account_id,
+            None => return Ok(Ok(())),
+        };
+
+        let txn_id = Some(model.id);
+        if !apply(
+            conn,
+            sub_account_id,
+            LedgerKind::Payment,
+            amount,
+            Some(model.sub_id),
+            txn_id,
+        )
+        .await?
+        {
+            return Ok(Err(anyhow!(
+                "failed to pay the transaction {id}: out of escrow",
+                id = model.id,
+            )));
+        }
+
+        if let Some(pub_account_id) = model.pub_account_id {
+            if !apply(
+                conn,
+                pub_account_id,
+                LedgerKind::Income,
+                amount,
+                Some(model.pub_id),
+                txn_id,
+            )
+            .await?
+            {
+                return Ok(Err(anyhow!("no such account: {pub_account_id}")));
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Returns the held cost of the transaction to the sub.
+    pub(super) async fn refund_transaction<C>(
+        conn: &C,
+        model: &entity::transaction::Model,
+    ) -> Result<Result<()>, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let sub_account_id = match model.sub_account_id {
+            Some(account_id) => account_id,
+            None => return Ok(Ok(())),
+        };
+
+        let amount = model.cost.saturating_mul(model.count);
+        if apply(
+            conn,
+            sub_account_id,
+            LedgerKind::Refund,
+            amount,
+            Some(model.sub_id),
+            Some(model.id),
+        )
+        .await?
+        {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(anyhow!(
+                "failed to refund the transaction {id}: out of escrow",
+                id = model.id,
+            )))
+        }
+    }
+
+    /// Refunds the open subs and the unsettled transactions of the prices,
+    /// which are going to be removed.
+    pub(super) async fn release_prices<C>(conn: &C, filter: SimpleExpr) -> Result<Result<()>, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let prices = entity::price::Entity::find()
+            .filter(filter)
+            .all(conn)
+            .await?;
+
+        for price in prices {
+            // Step 1. Refund the remaining bid
+            if let (entity::price::Direction::Sub, Some(account_id)) =
+                (price.direction, price.account_id)
+            {
+                let amount = price.cost.saturating_mul(price.count);
+                if !apply(
+                    conn,
+                    account_id,
+                    LedgerKind::Refund,
+                    amount,
+                    Some(price.id),
+                    None,
+                )
+                .await?
+                {
+                    return Ok(Err(anyhow!(
+                        "failed to refund the sub {id}: out of escrow",
+                        id = price.id,
+                    )));
+                }
+            }
+
+            // Step 2. Refund the unsettled transactions, which are removed in cascade
+            let col_pub_id = entity::transaction::Column::PubId;
+            let col_sub_id = entity::transaction::Column::SubId;
+            let filter = match price.direction {
+                entity::price::Direction::Pub => col_pub_id.eq(price.id),
+                entity::price::Direction::Sub => col_sub_id.eq(price.id),
+            };
+            let models = entity::transaction::Entity::find()
+                .filter(filter.and(unsettled()))
+                .all(conn)
+                .await?;
+            for model in models {
+                if let Err(error) = refund_transaction(conn, &model).await? {
+                    return Ok(Err(error));
+                }
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    fn unsettled() -> SimpleExpr {
+        let col_pub_state = entity::transaction::Column::PubState;
+        let col_sub_state = entity::transaction::Column::SubState;
+
+        col_pub_state
+            .ne(TaskState::Failed)
+            .and(col_sub_state.ne(TaskState::Failed))
+            .and(
+                col_pub_state
+                    .eq(TaskState::Running)
+                    .or(col_sub_state.eq(TaskState::Running)),
+            )
+    }
+
+    pub(super) fn map_txn_error(error: ::sea_orm::TransactionError<DbErr>, action: &str) -> Error {
+        match error {
+            ::sea_orm::TransactionError::Connection(error) => {
+                anyhow!("failed to connect to DB while {action}: {error}")
+            }
+            ::sea_orm::TransactionError::Transaction(error) => {
+                anyhow!("failed to execute transaction on DB while {action}: {error}")
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::Utc;
+use kubegraph_api::market::{
+    event::{MarketEvent, MarketEventKind},
+    product::ProductSpec,
+    BaseModel,
+};
+use tokio::sync::broadcast;
+
+#[derive(Clone, Default)]
+pub struct MarketEvents {
+    channels: Arc<RwLock<HashMap<<ProductSpec as BaseModel>::Id, broadcast::Sender<MarketEvent>>>>,
+}
+
+impl MarketEvents {
+    const MAX_EVENT_CHANNEL: usize = 1024;
+
+    /// Publishes an event to the subscribers of the product.
+    ///
+    /// NOTE: the events should be published only after the DB transaction is committed.
+    pub fn publish(&self, prod: <ProductSpec as BaseModel>::Id, kind: MarketEventKind) {
+        let is_last = matches!(kind, MarketEventKind::ProductRemoved);
+        let event = MarketEvent {
+            prod,
+            timestamp: Utc::now(),
+            kind,
+        };
+
+        let is_orphan = {
+            let channels = self
+                .channels
+                .read()
+                .unwrap_or_else(|error| error.into_inner());
+            match channels.get(&prod) {
+                // NOTE: it fails only if there are no subscribers
+                Some(sender) => sender.send(event).is_err(),
+                None => return,
+            }
+        };
+
+        // Drop the channel if the product is removed or no one listens to it anymore
+        if is_last || is_orphan {
+            let mut channels = self
+                .channels
+                .write()
+                .unwrap_or_else(|error| error.into_inner());
+            if is_last
+                || channels
+                    .get(&prod)
+                    .is_some_and(|sender| sender.receiver_count() == 0)
+            {
+                channels.remove(&prod);
+            }
+        }
+    }
+
+    pub fn subscribe(
+        &self,
+        prod: <ProductSpec as BaseModel>::Id,
+    ) -> broadcast::Receiver<MarketEvent> {
+        self.channels
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .entry(prod)
+            .or_insert_with(|| broadcast::channel(Self::MAX_EVENT_CHANNEL).0)
+            .subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kubegraph_api::market::price::Direction;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn removed(id: <ProductSpec as BaseModel>::Id) -> MarketEventKind {
+        MarketEventKind::OrderRemoved {
+            id,
+            direction: Direction::Pub,
+        }
+    }
+
+    #[test]
+    fn publish_to_product_subscribers() {
+        let events = MarketEvents::default();
+        let prod_a = Uuid::new_v4();
+        let prod_b = Uuid::new_v4();
+
+        let mut sub_a = events.subscribe(prod_a);
+        let mut sub_b = events.subscribe(prod_b);
+
+        let order = Uuid::new_v4();
+        events.publish(prod_a, removed(order));
+
+        let event = sub_a.try_recv().expect("failed to receive an event");
+        assert_eq!(event.prod, prod_a);
+        assert!(sub_b.try_recv().is_err());
+    }
+
+    #[test]
+    fn publish_without_subscribers() {
+        let events = MarketEvents::default();
+        let prod = Uuid::new_v4();
+
+        events.publish(prod, removed(Uuid::new_v4()));
+        assert!(events.channels.read().unwrap().is_empty());
+
+        // the channel is dropped after the last subscriber leaves
+        drop(events.subscribe(prod));
+        events.publish(prod, removed(Uuid::new_v4()));
+        assert!(events.channels.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn publish_product_removed() {
+        let events = MarketEvents::default();
+        let prod = Uuid::new_v4();
+
+        let mut sub = events.subscribe(prod);
+        events.publish(prod, MarketEventKind::ProductRemoved);
+        assert!(events.channels.read().unwrap().is_empty());
+
+        let event = sub.try_recv().expect("failed to receive an event");
+        assert!(matches!(event.kind, MarketEventKind::ProductRemoved));
+        assert!(matches!(
+            sub.try_recv(),
+            Err(broadcast::error::TryRecvError::Closed),
+        ));
+    }
+}
@@ -0,0 +1,8 @@
+extern crate kubegraph_market_entity as entity;
+extern crate kubegraph_market_migration as migration;
+
+pub mod actix;
+pub mod auth;
+pub mod db;
+pub mod event;
+mod routes;
@@ -1,13 +1,7 @@
-extern crate kubegraph_market_entity as entity;
-extern crate kubegraph_market_migration as migration;
-
-mod actix;
-mod db;
-mod routes;
-
 use anyhow::anyhow;
 use ark_core::signal::FunctionSignal;
 use kubegraph_api::component::NetworkComponentExt;
+use kubegraph_market_gateway::{actix, auth::MarketAuth, db::Database};
 use tokio::{spawn, task::JoinHandle};
 use tracing::{error, info};
 
@@ -23,7 +17,7 @@ async fn main() {
     }
 
     info!("Booting...");
-    let db = match <self::db::Database as NetworkComponentExt>::try_default(&signal).await {
+    let db = match <Database as NetworkComponentExt>::try_default(&signal).await {
         Ok(db) => db,
         Err(error) => {
             signal
@@ -32,8 +26,17 @@ async fn main() {
         }
     };
 
+    let auth = match <MarketAuth as NetworkComponentExt>::try_default(&signal).await {
+        Ok(auth) => auth,
+        Err(error) => {
+            signal
+                .panic(anyhow!("failed to init kubegraph market auth: {error}"))
+                .await
+        }
+    };
+
     info!("Registering market db workers...");
-    let handlers = spawn_workers(&db);
+    let handlers = spawn_workers(&db, auth);
 
     info!("Ready");
     signal.wait_to_terminate().await;
@@ -50,6 +53,6 @@ async fn main() {
     signal.exit().await
 }
 
-fn spawn_workers(db: &self::db::Database) -> Vec<JoinHandle<()>> {
-    vec![spawn(crate::actix::loop_forever(db.clone()))]
+fn spawn_workers(db: &Database, auth: MarketAuth) -> Vec<JoinHandle<()>> {
+    vec![spawn(actix::loop_forever(db.clone(), auth))]
 }
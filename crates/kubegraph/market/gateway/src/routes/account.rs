@@ -0,0 +1,55 @@
+use actix_web::{
+    get, post, put,
+    web::{Data, Json, Path, Query},
+    HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kubegraph_api::market::{account::AccountSpec, BaseModel, Page};
+use tracing::{instrument, Level};
+
+use crate::{auth::MarketPrincipal, db::Database};
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[get("/account/{account_id}")]
+pub async fn get(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<AccountSpec as BaseModel>::Id>,
+) -> impl Responder {
+    let account_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(db.get_account(&principal, account_id).await))
+}
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[get("/account/{account_id}/ledger")]
+pub async fn list_ledger(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<AccountSpec as BaseModel>::Id>,
+    page: Query<Page>,
+) -> impl Responder {
+    let account_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(
+        db.list_account_ledger(&principal, account_id, page.0).await,
+    ))
+}
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[put("/account")]
+pub async fn put(db: Data<Database>, principal: MarketPrincipal) -> impl Responder {
+    HttpResponse::Ok().json(Result::from(db.insert_account(&principal).await))
+}
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[post("/account/{account_id}/deposit")]
+pub async fn post_deposit(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<AccountSpec as BaseModel>::Id>,
+    amount: Json<<AccountSpec as BaseModel>::Cost>,
+) -> impl Responder {
+    let account_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(
+        db.deposit(&principal, account_id, amount.0).await,
+    ))
+}
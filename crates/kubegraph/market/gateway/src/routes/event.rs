@@ -0,0 +1,106 @@
+use std::{convert::Infallible, time::Duration};
+
+use actix_web::{
+    get,
+    http::header::{CacheControl, CacheDirective},
+    web::{Bytes, Data, Path},
+    HttpResponse, Responder,
+};
+use ark_core::result::Result as HttpResult;
+use async_stream::stream;
+use kubegraph_api::market::{
+    event::{MarketEvent, MarketEventKind},
+    product::ProductSpec,
+    BaseModel,
+};
+use tokio::{select, sync::broadcast, time::interval};
+use tracing::{error, instrument, warn, Level};
+
+use crate::{auth::MarketPrincipal, db::Database};
+
+/// Streams the events of the product as server-sent events.
+///
+/// Only the admins and the participants of the product can subscribe to it.
+///
+/// NOTE: the stream is closed when the subscriber lags behind;
+/// the subscriber should resubscribe and resync the orders with the `list_*` calls.
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[get("/prod/{prod_id}/event")]
+pub async fn subscribe(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<ProductSpec as BaseModel>::Id>,
+) -> impl Responder {
+    const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+    let prod_id = path.into_inner();
+
+    // Check the product before subscribing, so that no channels are left for unknown products
+    match db.get_product(prod_id).await {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(HttpResult::<()>::Err(format!("no such product: {prod_id}")))
+        }
+        Err(error) => {
+            error!("failed to get the product {prod_id}: {error}");
+            return HttpResponse::InternalServerError()
+                .json(HttpResult::<()>::Err(error.to_string()));
+        }
+    }
+    match db.is_product_participant(&principal, prod_id).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return HttpResponse::Forbidden().json(HttpResult::<()>::Err(format!(
+                "permission denied: not a participant of the product {prod_id}"
+            )))
+        }
+        Err(error) => {
+            error!("failed to authorize the subscriber of {prod_id}: {error}");
+            return HttpResponse::InternalServerError()
+                .json(HttpResult::<()>::Err(error.to_string()));
+        }
+    }
+
+    let mut events = db.events.subscribe(prod_id);
+
+    let stream = stream! {
+        let mut keep_alive = interval(KEEP_ALIVE_INTERVAL);
+        loop {
+            let (data, is_last) = select! {
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        let is_last = matches!(event.kind, MarketEventKind::ProductRemoved);
+                        match encode(&event) {
+                            Ok(data) => (data, is_last),
+                            Err(error) => {
+                                error!("failed to encode a market event: {error}");
+                                continue;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("market event subscriber lagged: skipped {skipped} events");
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = keep_alive.tick() => (Bytes::from_static(b": keep-alive\n\n"), false),
+            };
+
+            yield Ok::<_, Infallible>(data);
+            if is_last {
+                break;
+            }
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(stream)
+}
+
+fn encode(event: &MarketEvent) -> Result<Bytes, ::serde_json::Error> {
+    ::serde_json::to_string(event).map(|data| Bytes::from(format!("data: {data}\n\n")))
+}
@@ -9,18 +9,23 @@ use kubegraph_api::market::{
 };
 use tracing::{instrument, Level};
 
-use crate::db::Database;
+use crate::{auth::MarketPrincipal, db::Database};
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/prod")]
-pub async fn list(db: Data<Database>, page: Query<Page>) -> impl Responder {
+pub async fn list(
+    db: Data<Database>,
+    _principal: MarketPrincipal,
+    page: Query<Page>,
+) -> impl Responder {
     HttpResponse::Ok().json(Result::from(db.list_product_ids(page.0).await))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/prod/{prod_id}/price")]
 pub async fn list_price(
     db: Data<Database>,
+    _principal: MarketPrincipal,
     path: Path<<ProductSpec as BaseModel>::Id>,
     page: Query<Page>,
 ) -> impl Responder {
@@ -28,42 +33,60 @@ pub async fn list_price(
     HttpResponse::Ok().json(Result::from(db.list_price_histogram(prod_id, page.0).await))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/prod/{prod_id}")]
-pub async fn get(db: Data<Database>, path: Path<<ProductSpec as BaseModel>::Id>) -> impl Responder {
+pub async fn get(
+    db: Data<Database>,
+    _principal: MarketPrincipal,
+    path: Path<<ProductSpec as BaseModel>::Id>,
+) -> impl Responder {
     let prod_id = path.into_inner();
     HttpResponse::Ok().json(Result::from(db.get_product(prod_id).await))
 }
 
-#[instrument(level = Level::INFO, skip(db, spec))]
+#[instrument(level = Level::INFO, skip(db, principal, spec))]
 #[post("/prod")]
-pub async fn post(db: Data<Database>, spec: Json<ProductSpec>) -> impl Responder {
-    HttpResponse::Ok().json(Result::from(db.find_product(spec.0).await))
+pub async fn post(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    spec: Json<ProductSpec>,
+) -> impl Responder {
+    HttpResponse::Ok().json(Result::from(db.find_product(&principal, spec.0).await))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, principal))]
 #[post("/prod/{prod_id}/trade")]
 pub async fn post_trade(
     db: Data<Database>,
+    principal: MarketPrincipal,
     path: Path<<ProductSpec as BaseModel>::Id>,
     template: Json<TransactionTemplate>,
 ) -> impl Responder {
     let _prod_id = path.into_inner();
-    HttpResponse::Ok().json(Result::from(db.trade(template.0).await))
+    let result = match principal.ensure_admin() {
+        Ok(()) => db.trade(template.0).await.map_err(Into::into),
+        Err(error) => Err(error),
+    };
+    HttpResponse::Ok().json(Result::from(result))
 }
 
-#[instrument(level = Level::INFO, skip(db, spec))]
+#[instrument(level = Level::INFO, skip(db, principal, spec))]
 #[put("/prod")]
-pub async fn put(db: Data<Database>, spec: Json<ProductSpec>) -> impl Responder {
-    HttpResponse::Ok().json(Result::from(db.insert_product(spec.0).await))
+pub async fn put(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    spec: Json<ProductSpec>,
+) -> impl Responder {
+    HttpResponse::Ok().json(Result::from(db.insert_product(&principal, spec.0).await))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, principal))]
 #[delete("/prod/{prod_id}")]
 pub async fn delete(
     db: Data<Database>,
+    principal: MarketPrincipal,
     path: Path<<ProductSpec as BaseModel>::Id>,
 ) -> impl Responder {
     let prod_id = path.into_inner();
-    HttpResponse::Ok().json(Result::from(db.remove_product(prod_id).await))
+    HttpResponse::Ok().json(Result::from(db.remove_product(&principal, prod_id).await))
 }
@@ -7,12 +7,13 @@ use ark_core::result::Result;
 use kubegraph_api::market::{product::ProductSpec, r#pub::PubSpec, BaseModel, Page};
 use tracing::{instrument, Level};
 
-use crate::db::Database;
+use crate::{auth::MarketPrincipal, db::Database};
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/prod/{prod_id}/pub")]
 pub async fn list(
     db: Data<Database>,
+    _principal: MarketPrincipal,
     path: Path<<ProductSpec as BaseModel>::Id>,
     page: Query<Page>,
 ) -> impl Responder {
@@ -20,33 +21,38 @@ pub async fn list(
     HttpResponse::Ok().json(Result::from(db.list_pub_ids(prod_id, page.0).await))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/prod/{prod_id}/pub/{pub_id}")]
 pub async fn get(
     db: Data<Database>,
+    _principal: MarketPrincipal,
     path: Path<(<ProductSpec as BaseModel>::Id, <PubSpec as BaseModel>::Id)>,
 ) -> impl Responder {
     let (_prod_id, pub_id) = path.into_inner();
     HttpResponse::Ok().json(Result::from(db.get_pub(pub_id).await))
 }
 
-#[instrument(level = Level::INFO, skip(db, spec))]
+#[instrument(level = Level::INFO, skip(db, principal, spec))]
 #[put("/prod/{prod_id}/pub")]
 pub async fn put(
     db: Data<Database>,
+    principal: MarketPrincipal,
     path: Path<<ProductSpec as BaseModel>::Id>,
     spec: Json<PubSpec>,
 ) -> impl Responder {
     let prod_id = path.into_inner();
-    HttpResponse::Ok().json(Result::from(db.insert_pub(prod_id, spec.0).await))
+    HttpResponse::Ok().json(Result::from(
+        db.insert_pub(&principal, prod_id, spec.0).await,
+    ))
 }
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, principal))]
 #[delete("/prod/{prod_id}/pub/{pub_id}")]
 pub async fn delete(
     db: Data<Database>,
+    principal: MarketPrincipal,
     path: Path<(<ProductSpec as BaseModel>::Id, <PubSpec as BaseModel>::Id)>,
 ) -> impl Responder {
     let (_prod_id, pub_id) = path.into_inner();
-    HttpResponse::Ok().json(Result::from(db.remove_pub(pub_id).await))
+    HttpResponse::Ok().json(Result::from(db.remove_pub(&principal, pub_id).await))
 }
@@ -1,20 +1,69 @@
 use actix_web::{
-    get,
-    web::{Data, Path},
+    get, put,
+    web::{Data, Json, Path, Query},
     HttpResponse, Responder,
 };
 use ark_core::result::Result;
-use kubegraph_api::market::{transaction::TransactionSpec, BaseModel};
+use kubegraph_api::market::{
+    price::Direction,
+    transaction::{TaskState, TransactionSpec},
+    BaseModel, Page,
+};
 use tracing::{instrument, Level};
 
-use crate::db::Database;
+use crate::{auth::MarketPrincipal, db::Database};
 
-#[instrument(level = Level::INFO, skip(db))]
+#[instrument(level = Level::INFO, skip(db, _principal))]
 #[get("/txn/{txn_id}")]
 pub async fn get(
     db: Data<Database>,
+    _principal: MarketPrincipal,
     path: Path<<TransactionSpec as BaseModel>::Id>,
 ) -> impl Responder {
     let txn_id = path.into_inner();
     HttpResponse::Ok().json(Result::from(db.get_transaction(txn_id).await))
 }
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[get("/txn/{txn_id}/ledger")]
+pub async fn list_ledger(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<TransactionSpec as BaseModel>::Id>,
+    page: Query<Page>,
+) -> impl Responder {
+    let txn_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(
+        db.list_transaction_ledger(&principal, txn_id, page.0).await,
+    ))
+}
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[put("/txn/{txn_id}/pub")]
+pub async fn put_pub_state(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<TransactionSpec as BaseModel>::Id>,
+    state: Json<TaskState>,
+) -> impl Responder {
+    let txn_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(
+        db.update_task_state(&principal, txn_id, Direction::Pub, state.0)
+            .await,
+    ))
+}
+
+#[instrument(level = Level::INFO, skip(db, principal))]
+#[put("/txn/{txn_id}/sub")]
+pub async fn put_sub_state(
+    db: Data<Database>,
+    principal: MarketPrincipal,
+    path: Path<<TransactionSpec as BaseModel>::Id>,
+    state: Json<TaskState>,
+) -> impl Responder {
+    let txn_id = path.into_inner();
+    HttpResponse::Ok().json(Result::from(
+        db.update_task_state(&principal, txn_id, Direction::Sub, state.0)
+            .await,
+    ))
+}
@@ -1,6 +1,12 @@
 mod m20240701_000001_create_table_products;
 mod m20240701_000002_create_table_prices;
 mod m20240702_000001_create_table_transactions;
+mod m20261019_000001_alter_table_products_add_market;
+mod m20261019_000002_create_table_accounts;
+mod m20261019_000003_create_table_ledger;
+mod m20261019_000004_alter_tables_add_account;
+mod m20261019_000005_alter_tables_add_owner;
+mod m20261019_000006_alter_table_products_add_owner;
 
 use async_trait::async_trait;
 
@@ -15,6 +21,12 @@ impl MigratorTrait for Migrator {
             Box::new(self::m20240701_000001_create_table_products::Migration),
             Box::new(self::m20240701_000002_create_table_prices::Migration),
             Box::new(self::m20240702_000001_create_table_transactions::Migration),
+            Box::new(self::m20261019_000001_alter_table_products_add_market::Migration),
+            Box::new(self::m20261019_000002_create_table_accounts::Migration),
+            Box::new(self::m20261019_000003_create_table_ledger::Migration),
+            Box::new(self::m20261019_000004_alter_tables_add_account::Migration),
+            Box::new(self::m20261019_000005_alter_tables_add_owner::Migration),
+            Box::new(self::m20261019_000006_alter_table_products_add_owner::Migration),
         ]
     }
 }
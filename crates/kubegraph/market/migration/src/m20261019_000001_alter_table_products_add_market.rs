@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240701_000001_create_table_products::Products;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::ProductsMarket::Market)
+                            .json() // JSON Value
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .drop_column(self::ProductsMarket::Market)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub(super) enum ProductsMarket {
+    Market,
+}
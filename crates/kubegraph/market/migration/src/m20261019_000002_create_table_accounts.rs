@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(self::Accounts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(self::Accounts::Id)
+                            .uuid() // Uuid
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Accounts::CreatedAt)
+                            .timestamp() // NaiveDateTime
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Accounts::Balance)
+                            .big_integer() // i64
+                            .default(0)
+                            .check(Expr::col(self::Accounts::Balance).gte(0)) // unsigned
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Accounts::Escrow)
+                            .big_integer() // i64
+                            .default(0)
+                            .check(Expr::col(self::Accounts::Escrow).gte(0)) // unsigned
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(self::Accounts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub(super) enum Accounts {
+    Table,
+    Id,
+    CreatedAt,
+    Balance,
+    Escrow,
+}
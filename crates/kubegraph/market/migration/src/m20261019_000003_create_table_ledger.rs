@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(self::Ledger::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(self::Ledger::Id)
+                            .uuid() // Uuid
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Ledger::AccountId)
+                            .uuid() // Uuid
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-ledger-account_id")
+                            .from(self::Ledger::Table, self::Ledger::AccountId)
+                            .to(
+                                super::m20261019_000002_create_table_accounts::Accounts::Table,
+                                super::m20261019_000002_create_table_accounts::Accounts::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(self::Ledger::CreatedAt)
+                            .timestamp() // NaiveDateTime
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Ledger::Kind)
+                            .small_integer() // enum LedgerKind -> i16
+                            .check(
+                                Expr::col(self::Ledger::Kind)
+                                    .gte(0) // unsigned
+                                    .and(Expr::col(self::Ledger::Kind).lt(5)),
+                            ) // len(LedgerKind) -> 5
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Ledger::Amount)
+                            .big_integer() // i64
+                            .check(Expr::col(self::Ledger::Amount).gte(0)) // unsigned
+                            .not_null(),
+                    )
+                    // NOTE: the audit log should outlive the prices and transactions
+                    .col(
+                        ColumnDef::new(self::Ledger::PriceId)
+                            .uuid() // Uuid
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(self::Ledger::TxnId)
+                            .uuid() // Uuid
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(self::Ledger::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub(super) enum Ledger {
+    Table,
+    Id,
+    AccountId,
+    CreatedAt,
+    Kind,
+    Amount,
+    PriceId,
+    TxnId,
+}
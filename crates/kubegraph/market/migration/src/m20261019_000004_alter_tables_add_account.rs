@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20240701_000002_create_table_prices::Prices,
+    m20240702_000001_create_table_transactions::Transactions,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// NOTE: SQLite cannot alter multiple columns in a statement
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Prices::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::PricesAccount::AccountId)
+                            .uuid() // Uuid
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::TransactionsAccount::PubAccountId)
+                            .uuid() // Uuid
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::TransactionsAccount::SubAccountId)
+                            .uuid() // Uuid
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(self::TransactionsAccount::SubAccountId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(self::TransactionsAccount::PubAccountId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Prices::Table)
+                    .drop_column(self::PricesAccount::AccountId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub(super) enum PricesAccount {
+    AccountId,
+}
+
+#[derive(DeriveIden)]
+pub(super) enum TransactionsAccount {
+    PubAccountId,
+    SubAccountId,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20240701_000002_create_table_prices::Prices, m20261019_000002_create_table_accounts::Accounts,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// NOTE: SQLite cannot alter multiple columns in a statement
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Prices::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::Owner::Owner)
+                            .string() // String
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accounts::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(self::Owner::Owner)
+                            .string() // String
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accounts::Table)
+                    .drop_column(self::Owner::Owner)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Prices::Table)
+                    .drop_column(self::Owner::Owner)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub(super) enum Owner {
+    Owner,
+}
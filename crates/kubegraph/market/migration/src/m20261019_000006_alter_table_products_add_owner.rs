@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20240701_000001_create_table_products::Products,
+    m20261019_000005_alter_tables_add_owner::Owner,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Owner::Owner)
+                            .string() // String
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .drop_column(Owner::Owner)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,26 @@
+use std::net::SocketAddr;
+
+use actix_web::{post, web::Json, App, HttpResponse, HttpServer, Responder};
+use anyhow::{anyhow, Result};
+use ark_core::result::Result as FunctionResult;
+use futures::TryFutureExt;
+use kubegraph_api::market::transaction::TransactionReceipt;
+use tracing::{debug, info, instrument, Level};
+
+/// Serves a market function which accepts all transactions, like the blackhole function.
+pub(crate) async fn serve(addr: SocketAddr) -> Result<()> {
+    info!("Starting market function server...");
+
+    let server = HttpServer::new(|| App::new().service(post))
+        .bind(addr)
+        .map_err(|error| anyhow!("failed to bind to {addr}: {error}"))?;
+
+    server.run().map_err(Into::into).await
+}
+
+#[instrument(level = Level::DEBUG, skip(receipt))]
+#[post("/")]
+async fn post(receipt: Json<TransactionReceipt>) -> impl Responder {
+    debug!("Transaction ID: {}", receipt.id);
+    HttpResponse::Ok().json(FunctionResult::Ok(()))
+}
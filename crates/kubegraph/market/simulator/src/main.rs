@@ -0,0 +1,68 @@
+mod function;
+mod report;
+mod simulator;
+mod trader;
+
+use anyhow::anyhow;
+use ark_core::signal::FunctionSignal;
+use kubegraph_api::component::NetworkComponentExt;
+use tracing::{error, info};
+
+#[::tokio::main]
+async fn main() {
+    ::ark_core::tracer::init_once();
+    info!("Welcome to kubegraph market simulator!");
+
+    let signal = FunctionSignal::default().trap_on_panic();
+    if let Err(error) = signal.trap_on_sigint() {
+        error!("{error}");
+        return;
+    }
+
+    info!("Booting...");
+    let simulator =
+        match <self::simulator::MarketSimulator as NetworkComponentExt>::try_default(&signal).await
+        {
+            Ok(simulator) => simulator,
+            Err(error) => {
+                signal
+                    .panic(anyhow!(
+                        "failed to init kubegraph market simulator: {error}"
+                    ))
+                    .await
+            }
+        };
+
+    info!("Simulating...");
+    match simulator.run().await {
+        Ok(report) => {
+            let display = |value: Option<f64>| match value {
+                Some(value) => format!("{value:.3}"),
+                None => "-".into(),
+            };
+            info!("Rounds: {}", report.rounds.len());
+            info!("Mean welfare: {}", display(report.mean_welfare()));
+            info!("Mean efficiency: {}", display(report.mean_efficiency()));
+            info!(
+                "Mean clearing price: {}",
+                display(report.mean_clearing_price())
+            );
+            info!(
+                "Mean pub fill rate: {}",
+                display(report.mean_pub_fill_rate())
+            );
+            info!(
+                "Mean sub fill rate: {}",
+                display(report.mean_sub_fill_rate())
+            );
+        }
+        Err(error) => {
+            signal
+                .panic(anyhow!("failed to simulate kubegraph market: {error}"))
+                .await
+        }
+    }
+
+    info!("Terminating...");
+    signal.exit().await
+}
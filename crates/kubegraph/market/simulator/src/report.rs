@@ -0,0 +1,336 @@
+use kubegraph_api::market::{
+    price::Direction, product::ProductSpec, transaction::TransactionReceipt, BaseModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::trader::Trader;
+
+type Cost = <ProductSpec as BaseModel>::Cost;
+type Count = <ProductSpec as BaseModel>::Count;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundReport {
+    pub round: usize,
+    pub num_transactions: usize,
+    /// The total count of the pubs
+    pub supply: Count,
+    /// The total count of the subs
+    pub demand: Count,
+    /// The total traded count
+    pub volume: Count,
+    /// The volume-weighted average of the traded costs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clearing_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<Cost>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<Cost>,
+    /// The gains from trade, with respect to the private values of the traders
+    pub welfare: Cost,
+    /// The ratio of the welfare to the maximum possible welfare
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<f64>,
+}
+
+impl RoundReport {
+    /// Returns the ratio of the traded count to the total count of the pubs.
+    pub fn pub_fill_rate(&self) -> Option<f64> {
+        fill_rate(self.volume, self.supply)
+    }
+
+    /// Returns the ratio of the traded count to the total count of the subs.
+    pub fn sub_fill_rate(&self) -> Option<f64> {
+        fill_rate(self.volume, self.demand)
+    }
+}
+
+pub(crate) struct RoundReportBuilder<'a> {
+    traders: &'a [Trader],
+    report: RoundReport,
+    turnover: Cost,
+}
+
+impl<'a> RoundReportBuilder<'a> {
+    pub(crate) fn new(round: usize, traders: &'a [Trader]) -> Self {
+        let total_count = |direction| {
+            traders
+                .iter()
+                .filter(|trader| trader.direction == direction)
+                .map(|trader| trader.count)
+                .sum()
+        };
+
+        Self {
+            traders,
+            report: RoundReport {
+                round,
+                supply: total_count(Direction::Pub),
+                demand: total_count(Direction::Sub),
+                ..Default::default()
+            },
+            turnover: 0,
+        }
+    }
+
+    /// Records a transaction between the pub trader and the sub trader.
+    pub(crate) fn push(&mut self, receipt: &TransactionReceipt, r#pub: usize, sub: usize) {
+        let cost = receipt.template.cost;
+        let count = receipt.template.count;
+
+        let report = &mut self.report;
+        report.num_transactions += 1;
+        report.volume += count;
+        report.min_price = Some(report.min_price.map_or(cost, |price| price.min(cost)));
+        report.max_price = Some(report.max_price.map_or(cost, |price| price.max(cost)));
+        report.welfare += (self.traders[sub].value - self.traders[r#pub].value) * count;
+        self.turnover += cost * count;
+    }
+
+    pub(crate) fn build(self) -> RoundReport {
+        let Self {
+            traders,
+            mut report,
+            turnover,
+        } = self;
+
+        if report.volume > 0 {
+            report.clearing_price = Some(turnover as f64 / report.volume as f64);
+        }
+
+        let max_welfare = max_welfare(traders);
+        if max_welfare > 0 {
+            report.efficiency = Some(report.welfare as f64 / max_welfare as f64);
+        }
+        report
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub rounds: Vec<RoundReport>,
+}
+
+impl SimulationReport {
+    pub fn mean_welfare(&self) -> Option<f64> {
+        mean(self.rounds.iter().map(|round| Some(round.welfare as f64)))
+    }
+
+    pub fn mean_efficiency(&self) -> Option<f64> {
+        mean(self.rounds.iter().map(|round| round.efficiency))
+    }
+
+    pub fn mean_clearing_price(&self) -> Option<f64> {
+        mean(self.rounds.iter().map(|round| round.clearing_price))
+    }
+
+    pub fn mean_pub_fill_rate(&self) -> Option<f64> {
+        mean(self.rounds.iter().map(RoundReport::pub_fill_rate))
+    }
+
+    pub fn mean_sub_fill_rate(&self) -> Option<f64> {
+        mean(self.rounds.iter().map(RoundReport::sub_fill_rate))
+    }
+}
+
+/// Returns the welfare of the competitive equilibrium,
+/// matching the units of the highest subs with the cheapest pubs.
+fn max_welfare(traders: &[Trader]) -> Cost {
+    let collect_units = |direction| {
+        let mut units: Vec<_> = traders
+            .iter()
+            .filter(|trader| trader.direction == direction)
+            .map(|trader| (trader.value, trader.count))
+            .collect();
+        units.sort();
+        units
+    };
+
+    let pubs = collect_units(Direction::Pub);
+    let mut subs = collect_units(Direction::Sub);
+    subs.reverse();
+
+    let mut pubs = pubs.into_iter();
+    let mut subs = subs.into_iter();
+    let mut r#pub = pubs.next();
+    let mut sub = subs.next();
+    let mut welfare = 0;
+    while let Some(((pub_value, pub_count), (sub_value, sub_count))) =
+        r#pub.as_mut().zip(sub.as_mut())
+    {
+        if *pub_value > *sub_value {
+            break;
+        }
+
+        let count = (*pub_count).min(*sub_count);
+        *pub_count -= count;
+        *sub_count -= count;
+        welfare += (*sub_value - *pub_value) * count;
+
+        if *pub_count == 0 {
+            r#pub = pubs.next();
+        }
+        if *sub_count == 0 {
+            sub = subs.next();
+        }
+    }
+    welfare
+}
+
+fn fill_rate(volume: Count, total: Count) -> Option<f64> {
+    if total > 0 {
+        Some(volume as f64 / total as f64)
+    } else {
+        None
+    }
+}
+
+fn mean(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    let (sum, len) = values
+        .flatten()
+        .fold((0.0, 0usize), |(sum, len), value| (sum + value, len + 1));
+    if len > 0 {
+        Some(sum / len as f64)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kubegraph_api::market::transaction::TransactionTemplate;
+    use rand::{rngs::StdRng, SeedableRng};
+    use uuid::Uuid;
+
+    use crate::trader::{TraderBounds, TraderStrategy};
+
+    use super::*;
+
+    fn new_trader(direction: Direction, value: Cost, count: Count) -> Trader {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bounds = TraderBounds {
+            min_value: 1,
+            max_value: 100,
+            max_count: 1,
+            margin: 0.0,
+        };
+
+        let mut trader = Trader::new(&mut rng, direction, TraderStrategy::CostPlus, &bounds);
+        trader.value = value;
+        trader.count = count;
+        trader
+    }
+
+    /// Two pubs of the values 10 (x2) and 30, and two subs of the values 50 and 20 (x2).
+    fn new_traders() -> Vec<Trader> {
+        vec![
+            new_trader(Direction::Pub, 10, 2),
+            new_trader(Direction::Pub, 30, 1),
+            new_trader(Direction::Sub, 50, 1),
+            new_trader(Direction::Sub, 20, 2),
+        ]
+    }
+
+    fn new_receipt(cost: Cost, count: Count) -> TransactionReceipt {
+        TransactionReceipt {
+            id: Uuid::new_v4(),
+            template: TransactionTemplate {
+                prod: Uuid::new_v4(),
+                r#pub: Uuid::new_v4(),
+                sub: Uuid::new_v4(),
+                cost,
+                count,
+            },
+        }
+    }
+
+    #[test]
+    fn max_welfare_of_competitive_equilibrium() {
+        // (50 - 10) x1 + (20 - 10) x1; the pub of 30 is too expensive for the sub of 20
+        assert_eq!(max_welfare(&new_traders()), 50);
+
+        // no trades are possible
+        let traders = vec![
+            new_trader(Direction::Pub, 30, 1),
+            new_trader(Direction::Sub, 20, 1),
+        ];
+        assert_eq!(max_welfare(&traders), 0);
+
+        // the units are split over the traders
+        let traders = vec![
+            new_trader(Direction::Pub, 10, 3),
+            new_trader(Direction::Sub, 40, 1),
+            new_trader(Direction::Sub, 30, 1),
+            new_trader(Direction::Sub, 5, 1),
+        ];
+        assert_eq!(max_welfare(&traders), 30 + 20);
+
+        assert_eq!(max_welfare(&[]), 0);
+    }
+
+    #[test]
+    fn build_round_report() {
+        let traders = new_traders();
+        let mut builder = RoundReportBuilder::new(3, &traders);
+        builder.push(&new_receipt(25, 1), 0, 2);
+        builder.push(&new_receipt(15, 1), 0, 3);
+        let report = builder.build();
+
+        assert_eq!(report.round, 3);
+        assert_eq!(report.num_transactions, 2);
+        assert_eq!(report.supply, 3);
+        assert_eq!(report.demand, 3);
+        assert_eq!(report.volume, 2);
+        assert_eq!(report.clearing_price, Some(20.0));
+        assert_eq!(report.min_price, Some(15));
+        assert_eq!(report.max_price, Some(25));
+        assert_eq!(report.welfare, 50);
+        assert_eq!(report.efficiency, Some(1.0));
+        assert_eq!(report.pub_fill_rate(), Some(2.0 / 3.0));
+        assert_eq!(report.sub_fill_rate(), Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn build_round_report_without_trades() {
+        let traders = new_traders();
+        let report = RoundReportBuilder::new(0, &traders).build();
+
+        assert_eq!(report.volume, 0);
+        assert_eq!(report.clearing_price, None);
+        assert_eq!(report.min_price, None);
+        assert_eq!(report.efficiency, Some(0.0));
+        assert_eq!(report.pub_fill_rate(), Some(0.0));
+
+        // nothing can be traded without traders
+        let report = RoundReportBuilder::new(0, &[]).build();
+        assert_eq!(report.efficiency, None);
+        assert_eq!(report.pub_fill_rate(), None);
+        assert_eq!(report.sub_fill_rate(), None);
+    }
+
+    #[test]
+    fn summarize_simulation_report() {
+        let traders = new_traders();
+        let mut builder = RoundReportBuilder::new(0, &traders);
+        builder.push(&new_receipt(25, 1), 0, 2);
+        builder.push(&new_receipt(15, 1), 0, 3);
+
+        let report = SimulationReport {
+            rounds: vec![
+                builder.build(),
+                RoundReportBuilder::new(1, &traders).build(),
+            ],
+        };
+        assert_eq!(report.mean_welfare(), Some(25.0));
+        assert_eq!(report.mean_efficiency(), Some(0.5));
+        // the rounds without trades have no prices
+        assert_eq!(report.mean_clearing_price(), Some(20.0));
+        assert_eq!(report.mean_pub_fill_rate(), Some(1.0 / 3.0));
+        assert_eq!(report.mean_sub_fill_rate(), Some(1.0 / 3.0));
+
+        let report = SimulationReport::default();
+        assert_eq!(report.mean_welfare(), None);
+        assert_eq!(report.mean_efficiency(), None);
+    }
+}
@@ -0,0 +1,457 @@
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::Parser;
+use futures::TryStreamExt;
+use kubegraph_api::{
+    component::NetworkComponent,
+    function::webhook::NetworkFunctionWebhookSpec,
+    market::{
+        price::Direction,
+        product::{ProductMarketSpec, ProductSpec},
+        r#pub::PubSpec,
+        sub::SubSpec,
+        BaseModel,
+    },
+};
+use kubegraph_market_client::MarketClient;
+use kubegraph_market_gateway::{
+    auth::{MarketAuth, MarketAuthArgs, MarketAuthMode},
+    db::{Database, DatabaseArgs},
+};
+use kubegraph_market_solver::agent::{MarketAgent, MarketAgentArgs};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, spawn, time::sleep};
+use tracing::{info, instrument, Level};
+
+use crate::{
+    report::{RoundReport, RoundReportBuilder, SimulationReport},
+    trader::{Trader, TraderBounds, TraderStrategy},
+};
+
+pub struct MarketSimulator {
+    agent: MarketAgent,
+    bounds: TraderBounds,
+    client: MarketClient,
+    db: Database,
+    function: NetworkFunctionWebhookSpec,
+    function_addr: SocketAddr,
+    gateway_addr: SocketAddr,
+    output: Option<PathBuf>,
+    reserve_price: Option<<ProductSpec as BaseModel>::Cost>,
+    rng: StdRng,
+    rounds: usize,
+    signal: FunctionSignal,
+    traders: Vec<Trader>,
+}
+
+#[async_trait]
+impl NetworkComponent for MarketSimulator {
+    type Args = MarketSimulatorArgs;
+
+    #[instrument(level = Level::INFO, skip(signal))]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let MarketSimulatorArgs {
+            mut agent,
+            db,
+            function_addr,
+            gateway_addr,
+            margin,
+            max_count,
+            max_value,
+            min_value,
+            num_adaptive_traders,
+            num_cost_plus_traders,
+            num_random_traders,
+            output,
+            reserve_price,
+            rounds,
+            seed,
+        } = args;
+
+        if min_value <= 0 || min_value > max_value {
+            bail!("invalid trader values: {min_value}..={max_value}");
+        }
+        if max_count <= 0 {
+            bail!("invalid trader count: {max_count}");
+        }
+        if !(0.0..1.0).contains(&margin) {
+            bail!("invalid trader margin: {margin}");
+        }
+
+        let bounds = TraderBounds {
+            min_value,
+            max_value,
+            max_count,
+            margin,
+        };
+
+        // Alternate the directions of each population, to balance the supply and demand
+        let mut rng = StdRng::seed_from_u64(seed);
+        let traders = [
+            (TraderStrategy::Random, num_random_traders),
+            (TraderStrategy::CostPlus, num_cost_plus_traders),
+            (TraderStrategy::Adaptive, num_adaptive_traders),
+        ]
+        .into_iter()
+        .flat_map(|(strategy, num_traders)| {
+            (0..num_traders).map(move |index| {
+                let direction = if index % 2 == 0 {
+                    Direction::Pub
+                } else {
+                    Direction::Sub
+                };
+                (direction, strategy)
+            })
+        })
+        .map(|(direction, strategy)| Trader::new(&mut rng, direction, strategy, &bounds))
+        .collect();
+
+        agent.client.endpoint = format!("http://{gateway_addr}/").parse()?;
+        let client = MarketClient::try_new(agent.client.clone(), signal).await?;
+
+        Ok(Self {
+            agent: MarketAgent::try_new(agent, signal).await?,
+            bounds,
+            client,
+            db: Database::try_new(db, signal).await?,
+            function: NetworkFunctionWebhookSpec {
+                endpoint: format!("http://{function_addr}/").parse()?,
+            },
+            function_addr,
+            gateway_addr,
+            output,
+            reserve_price,
+            rng,
+            rounds,
+            signal: signal.clone(),
+            traders,
+        })
+    }
+}
+
+impl MarketSimulator {
+    pub async fn run(mut self) -> Result<SimulationReport> {
+        // NOTE: the simulated traders and solver share the local gateway without any credentials
+        let auth = MarketAuthArgs {
+            auth_mode: MarketAuthMode::Disabled,
+            ..Default::default()
+        };
+        let auth = MarketAuth::try_new(auth, &self.signal).await?;
+        let handlers = vec![
+            {
+                let db = self.db.clone();
+                let addr = self.gateway_addr;
+                spawn(
+                    async move { ::kubegraph_market_gateway::actix::serve(&db, auth, addr).await },
+                )
+            },
+            spawn(crate::function::serve(self.function_addr)),
+        ];
+
+        let result = self.try_run().await;
+        for handler in handlers {
+            handler.abort();
+        }
+
+        let report = result?;
+        if let Some(path) = &self.output {
+            let data = ::serde_json::to_vec_pretty(&report)?;
+            fs::write(path, data)
+                .await
+                .map_err(|error| anyhow!("failed to write the report to {path:?}: {error}"))?;
+        }
+        Ok(report)
+    }
+
+    async fn try_run(&mut self) -> Result<SimulationReport> {
+        self.wait_until_ready().await?;
+
+        let spec = ProductSpec {
+            problem: Default::default(),
+            market: ProductMarketSpec {
+                reserve_price: self.reserve_price,
+                solver: None,
+            },
+        };
+        let prod_id = self.client.insert_product(&spec).await?;
+
+        let mut report = SimulationReport::default();
+        for round in 0..self.rounds {
+            if self.signal.is_terminating() {
+                break;
+            }
+
+            let round = self.step(prod_id, round).await?;
+            info!(
+                "Round {round}: volume={volume}/{supply}/{demand} price={price} welfare={welfare} efficiency={efficiency}",
+                round = round.round,
+                volume = round.volume,
+                supply = round.supply,
+                demand = round.demand,
+                price = display(round.clearing_price),
+                welfare = round.welfare,
+                efficiency = display(round.efficiency),
+            );
+            report.rounds.push(round);
+        }
+        Ok(report)
+    }
+
+    async fn wait_until_ready(&self) -> Result<()> {
+        const MAX_RETRIES: usize = 50;
+        const INTERVAL: Duration = Duration::from_millis(100);
+
+        for _ in 0..MAX_RETRIES {
+            let product_ids = self.client.list_product_ids().try_collect::<Vec<_>>();
+            if product_ids.await.is_ok() {
+                return Ok(());
+            }
+            sleep(INTERVAL).await;
+        }
+        bail!("the market gateway is not ready: {}", self.gateway_addr)
+    }
+
+    /// Quotes all traders, solves the market once, and withdraws the remaining orders.
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn step(
+        &mut self,
+        prod_id: <ProductSpec as BaseModel>::Id,
+        round: usize,
+    ) -> Result<RoundReport> {
+        // Step 1. Quote
+        let mut orders = BTreeMap::default();
+        for (index, trader) in self.traders.iter().enumerate() {
+            let cost = trader.quote(&mut self.rng, &self.bounds);
+            let count = trader.count;
+            let function = self.function.clone();
+            let order_id = match trader.direction {
+                Direction::Pub => {
+                    let spec = PubSpec {
+                        cost,
+                        count,
+                        function,
+                        account: None,
+                    };
+                    self.client.insert_pub(prod_id, &spec).await?
+                }
+                Direction::Sub => {
+                    let spec = SubSpec {
+                        cost,
+                        count,
+                        function,
+                        account: None,
+                    };
+                    self.client.insert_sub(prod_id, &spec).await?
+                }
+            };
+            orders.insert(order_id, index);
+        }
+
+        // Step 2. Trade
+        let receipts = self.agent.solve_once().await?;
+
+        let mut builder = RoundReportBuilder::new(round, &self.traders);
+        let mut filled = vec![0; self.traders.len()];
+        for receipt in &receipts {
+            let r#pub = orders.get(&receipt.template.r#pub).copied();
+            let sub = orders.get(&receipt.template.sub).copied();
+            if let Some((r#pub, sub)) = r#pub.zip(sub) {
+                builder.push(receipt, r#pub, sub);
+                filled[r#pub] += receipt.template.count;
+                filled[sub] += receipt.template.count;
+            }
+        }
+        let report = builder.build();
+
+        // Step 3. Learn
+        for (trader, filled) in self.traders.iter_mut().zip(filled) {
+            trader.update(filled);
+        }
+
+        // Step 4. Withdraw
+        for (order_id, index) in orders {
+            match self.traders[index].direction {
+                Direction::Pub => self.client.remove_pub(prod_id, order_id).await?,
+                Direction::Sub => self.client.remove_sub(prod_id, order_id).await?,
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSimulatorArgs {
+    #[command(flatten)]
+    pub agent: MarketAgentArgs,
+
+    #[command(flatten)]
+    pub db: DatabaseArgs,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_FUNCTION_ADDR",
+        value_name = "ADDR",
+        default_value_t = MarketSimulatorArgs::default_function_addr(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_function_addr")]
+    pub function_addr: SocketAddr,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_GATEWAY_ADDR",
+        value_name = "ADDR",
+        default_value_t = MarketSimulatorArgs::default_gateway_addr(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_gateway_addr")]
+    pub gateway_addr: SocketAddr,
+
+    /// The initial margin of the cost-plus and adaptive traders
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_MARGIN",
+        value_name = "RATIO",
+        default_value_t = MarketSimulatorArgs::default_margin(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_margin")]
+    pub margin: f64,
+
+    /// The maximum count of the orders of a trader
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_MAX_COUNT",
+        value_name = "COUNT",
+        default_value_t = MarketSimulatorArgs::default_max_count(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_max_count")]
+    pub max_count: <ProductSpec as BaseModel>::Count,
+
+    /// The maximum private value of a trader
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_MAX_VALUE",
+        value_name = "COST",
+        default_value_t = MarketSimulatorArgs::default_max_value(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_max_value")]
+    pub max_value: <ProductSpec as BaseModel>::Cost,
+
+    /// The minimum private value of a trader
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_MIN_VALUE",
+        value_name = "COST",
+        default_value_t = MarketSimulatorArgs::default_min_value(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_min_value")]
+    pub min_value: <ProductSpec as BaseModel>::Cost,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_NUM_ADAPTIVE_TRADERS",
+        value_name = "NUM",
+        default_value_t = MarketSimulatorArgs::default_num_traders(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_num_traders")]
+    pub num_adaptive_traders: usize,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_NUM_COST_PLUS_TRADERS",
+        value_name = "NUM",
+        default_value_t = MarketSimulatorArgs::default_num_traders(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_num_traders")]
+    pub num_cost_plus_traders: usize,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_NUM_RANDOM_TRADERS",
+        value_name = "NUM",
+        default_value_t = MarketSimulatorArgs::default_num_traders(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_num_traders")]
+    pub num_random_traders: usize,
+
+    /// The file to write the report as JSON, if given
+    #[arg(long, env = "KUBEGRAPH_MARKET_SIMULATOR_OUTPUT", value_name = "PATH")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+
+    /// The reserve price of the simulated product, if any
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_RESERVE_PRICE",
+        value_name = "COST"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserve_price: Option<<ProductSpec as BaseModel>::Cost>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_ROUNDS",
+        value_name = "NUM",
+        default_value_t = MarketSimulatorArgs::default_rounds(),
+    )]
+    #[serde(default = "MarketSimulatorArgs::default_rounds")]
+    pub rounds: usize,
+
+    /// The seed of the random number generator, for reproducible simulations
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_SIMULATOR_SEED",
+        value_name = "SEED",
+        default_value_t = u64::default(),
+    )]
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl MarketSimulatorArgs {
+    fn default_function_addr() -> SocketAddr {
+        "127.0.0.1:9091".parse().unwrap()
+    }
+
+    fn default_gateway_addr() -> SocketAddr {
+        "127.0.0.1:9090".parse().unwrap()
+    }
+
+    const fn default_margin() -> f64 {
+        0.1
+    }
+
+    const fn default_max_count() -> <ProductSpec as BaseModel>::Count {
+        10
+    }
+
+    const fn default_max_value() -> <ProductSpec as BaseModel>::Cost {
+        150
+    }
+
+    const fn default_min_value() -> <ProductSpec as BaseModel>::Cost {
+        50
+    }
+
+    const fn default_num_traders() -> usize {
+        10
+    }
+
+    const fn default_rounds() -> usize {
+        100
+    }
+}
+
+fn display(value: Option<f64>) -> String {
+    match value {
+        Some(value) => format!("{value:.3}"),
+        None => "-".into(),
+    }
+}
@@ -0,0 +1,181 @@
+use kubegraph_api::market::{price::Direction, product::ProductSpec, BaseModel};
+use rand::{rngs::StdRng, Rng};
+
+type Cost = <ProductSpec as BaseModel>::Cost;
+type Count = <ProductSpec as BaseModel>::Count;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TraderStrategy {
+    /// Quote a random price, but never beyond its own value
+    Random,
+    /// Quote its own value with a fixed margin
+    CostPlus,
+    /// Quote its own value with a margin, which is raised when filled and lowered otherwise
+    Adaptive,
+}
+
+#[derive(Clone, Debug)]
+pub struct Trader {
+    pub direction: Direction,
+    pub strategy: TraderStrategy,
+    /// The private value of a unit; the cost of a pub, or the budget of a sub
+    pub value: Cost,
+    pub count: Count,
+    margin: f64,
+}
+
+impl Trader {
+    /// The ratio to update the margin of the adaptive traders.
+    const LEARNING_RATE: f64 = 0.1;
+
+    pub fn new(
+        rng: &mut StdRng,
+        direction: Direction,
+        strategy: TraderStrategy,
+        bounds: &TraderBounds,
+    ) -> Self {
+        Self {
+            direction,
+            strategy,
+            value: rng.gen_range(bounds.min_value..=bounds.max_value),
+            count: rng.gen_range(1..=bounds.max_count),
+            margin: bounds.margin,
+        }
+    }
+
+    /// Returns the cost of a unit to be quoted in this round.
+    pub fn quote(&self, rng: &mut StdRng, bounds: &TraderBounds) -> Cost {
+        match self.strategy {
+            TraderStrategy::Random => match self.direction {
+                Direction::Pub => rng.gen_range(self.value..=bounds.max_value),
+                Direction::Sub => rng.gen_range(bounds.min_value..=self.value),
+            },
+            TraderStrategy::CostPlus | TraderStrategy::Adaptive => {
+                let value = self.value as f64;
+                let cost = match self.direction {
+                    Direction::Pub => value * (1.0 + self.margin),
+                    Direction::Sub => value * (1.0 - self.margin),
+                };
+                cost.round() as Cost
+            }
+        }
+    }
+
+    /// Learns from the filled count of this round.
+    pub fn update(&mut self, filled: Count) {
+        if self.strategy != TraderStrategy::Adaptive {
+            return;
+        }
+
+        if filled >= self.count {
+            // Demand more, as the quote was competitive enough
+            self.margin += Self::LEARNING_RATE * (1.0 - self.margin);
+        } else {
+            // Concede, as the quote was not (fully) accepted
+            self.margin *= 1.0 - Self::LEARNING_RATE;
+        }
+        self.margin = self.margin.clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TraderBounds {
+    pub min_value: Cost,
+    pub max_value: Cost,
+    pub max_count: Count,
+    pub margin: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn new_bounds() -> TraderBounds {
+        TraderBounds {
+            min_value: 10,
+            max_value: 100,
+            max_count: 5,
+            margin: 0.2,
+        }
+    }
+
+    fn new_trader(direction: Direction, strategy: TraderStrategy, value: Cost) -> Trader {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut trader = Trader::new(&mut rng, direction, strategy, &new_bounds());
+        trader.value = value;
+        trader.count = 2;
+        trader
+    }
+
+    #[test]
+    fn new_traders_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let bounds = new_bounds();
+
+        for _ in 0..100 {
+            let trader = Trader::new(&mut rng, Direction::Pub, TraderStrategy::Random, &bounds);
+            assert!((bounds.min_value..=bounds.max_value).contains(&trader.value));
+            assert!((1..=bounds.max_count).contains(&trader.count));
+            assert_eq!(trader.margin, bounds.margin);
+        }
+    }
+
+    #[test]
+    fn quote_random_never_beyond_value() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let bounds = new_bounds();
+
+        let r#pub = new_trader(Direction::Pub, TraderStrategy::Random, 50);
+        let sub = new_trader(Direction::Sub, TraderStrategy::Random, 50);
+        for _ in 0..100 {
+            assert!((50..=bounds.max_value).contains(&r#pub.quote(&mut rng, &bounds)));
+            assert!((bounds.min_value..=50).contains(&sub.quote(&mut rng, &bounds)));
+        }
+    }
+
+    #[test]
+    fn quote_cost_plus_with_margin() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let bounds = new_bounds();
+
+        let r#pub = new_trader(Direction::Pub, TraderStrategy::CostPlus, 50);
+        assert_eq!(r#pub.quote(&mut rng, &bounds), 60);
+
+        let sub = new_trader(Direction::Sub, TraderStrategy::CostPlus, 50);
+        assert_eq!(sub.quote(&mut rng, &bounds), 40);
+    }
+
+    #[test]
+    fn update_adaptive_margin() {
+        let mut trader = new_trader(Direction::Pub, TraderStrategy::Adaptive, 50);
+
+        // fully filled
+        trader.update(2);
+        assert!((trader.margin - 0.28).abs() < 1e-9);
+
+        // partially filled
+        trader.update(1);
+        assert!((trader.margin - 0.252).abs() < 1e-9);
+
+        // the margin never goes beyond the bounds
+        for _ in 0..1_000 {
+            trader.update(2);
+        }
+        assert!(trader.margin <= 1.0);
+        for _ in 0..1_000 {
+            trader.update(0);
+        }
+        assert!(trader.margin >= 0.0);
+    }
+
+    #[test]
+    fn update_fixed_margin() {
+        let mut trader = new_trader(Direction::Sub, TraderStrategy::CostPlus, 50);
+        trader.update(2);
+        assert_eq!(trader.margin, 0.2);
+        trader.update(0);
+        assert_eq!(trader.margin, 0.2);
+    }
+}
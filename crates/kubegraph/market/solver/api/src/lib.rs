@@ -1,7 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use kubegraph_api::market::{
-    price::PriceHistogram, product::ProductSpec, transaction::TransactionTemplate, BaseModel,
+    price::{Direction, PriceHistogram, PriceItem},
+    product::ProductSpec,
+    transaction::TransactionTemplate,
+    BaseModel,
 };
 
 #[async_trait]
@@ -13,3 +16,31 @@ pub trait MarketSolver {
         histogram: PriceHistogram,
     ) -> Result<Vec<TransactionTemplate>>;
 }
+
+/// Collects the tradable pubs, the cheapest (and then the oldest) first.
+pub fn collect_pubs(histogram: &PriceHistogram) -> Vec<PriceItem> {
+    let mut pubs = collect_items(histogram, Direction::Pub);
+    pubs.sort_by_key(|item| (item.cost, item.timestamp, item.id));
+    pubs
+}
+
+/// Collects the tradable subs, the highest (and then the oldest) first.
+///
+/// The subs below the reserve price of the product are not tradable.
+pub fn collect_subs(product: &ProductSpec, histogram: &PriceHistogram) -> Vec<PriceItem> {
+    let reserve_price = product.market.reserve_price.unwrap_or_default();
+
+    let mut subs = collect_items(histogram, Direction::Sub);
+    subs.retain(|item| item.cost >= reserve_price);
+    subs.sort_by_key(|item| (-item.cost, item.timestamp, item.id));
+    subs
+}
+
+fn collect_items(histogram: &PriceHistogram, direction: Direction) -> Vec<PriceItem> {
+    histogram
+        .iter()
+        .filter(|item| item.direction == direction)
+        .filter(|item| item.cost >= 0 && item.count > 0)
+        .copied()
+        .collect()
+}
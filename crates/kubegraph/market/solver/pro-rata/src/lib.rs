@@ -0,0 +1,154 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use kubegraph_api::market::{
+    price::{PriceHistogram, PriceItem},
+    product::ProductSpec,
+    transaction::TransactionTemplate,
+    BaseModel,
+};
+use kubegraph_market_solver_api::{collect_pubs, collect_subs};
+use tracing::{instrument, Level};
+
+type Cost = <ProductSpec as BaseModel>::Cost;
+type Id = <ProductSpec as BaseModel>::Id;
+type Count = <ProductSpec as BaseModel>::Count;
+
+/// A pro-rata matcher.
+///
+/// The price levels are matched from the cheapest pub and the highest sub,
+/// charging the sub's price. Within a price level, the matched count is
+/// split among the orders proportionally to their counts, rather than
+/// filling the oldest order first.
+#[derive(Clone, Debug, Default)]
+pub struct MarketSolver {}
+
+#[async_trait]
+impl ::kubegraph_market_solver_api::MarketSolver for MarketSolver {
+    #[instrument(level = Level::INFO, skip(self, product, histogram))]
+    async fn solve(
+        &self,
+        prod_id: <ProductSpec as BaseModel>::Id,
+        product: &ProductSpec,
+        histogram: PriceHistogram,
+    ) -> Result<Vec<TransactionTemplate>> {
+        let mut pubs = collect_levels(collect_pubs(&histogram)).into_iter();
+        let mut subs = collect_levels(collect_subs(product, &histogram)).into_iter();
+
+        let mut r#pub = pubs.next();
+        let mut sub = subs.next();
+        let mut transactions = Vec::default();
+        while let Some((pub_level, sub_level)) = r#pub.as_mut().zip(sub.as_mut()) {
+            if pub_level.cost > sub_level.cost {
+                break;
+            }
+
+            let count = pub_level.count().min(sub_level.count());
+            let pub_counts = pub_level.withdraw(count);
+            let sub_counts = sub_level.withdraw(count);
+
+            // Pair the allocated counts of both sides in the priority order
+            let cost = sub_level.cost;
+            let mut pub_allocs = pub_counts.into_iter().filter(|(_, count)| *count > 0);
+            let mut sub_allocs = sub_counts.into_iter().filter(|(_, count)| *count > 0);
+            let mut pub_alloc = pub_allocs.next();
+            let mut sub_alloc = sub_allocs.next();
+            while let Some(((pub_id, pub_count), (sub_id, sub_count))) =
+                pub_alloc.as_mut().zip(sub_alloc.as_mut())
+            {
+                let count = (*pub_count).min(*sub_count);
+                *pub_count -= count;
+                *sub_count -= count;
+
+                transactions.push(TransactionTemplate {
+                    prod: prod_id,
+                    r#pub: *pub_id,
+                    sub: *sub_id,
+                    cost,
+                    count,
+                });
+
+                if *pub_count == 0 {
+                    pub_alloc = pub_allocs.next();
+                }
+                if *sub_count == 0 {
+                    sub_alloc = sub_allocs.next();
+                }
+            }
+
+            if pub_level.count() == 0 {
+                r#pub = pubs.next();
+            }
+            if sub_level.count() == 0 {
+                sub = subs.next();
+            }
+        }
+        Ok(transactions)
+    }
+}
+
+struct PriceLevel {
+    cost: Cost,
+    items: Vec<PriceItem>,
+}
+
+impl PriceLevel {
+    fn count(&self) -> Count {
+        self.items.iter().map(|item| item.count).sum()
+    }
+
+    /// Withdraws the given count proportionally to the counts of the items.
+    ///
+    /// The remainders are given to the items with the largest fractions,
+    /// and then to the items with the higher priority.
+    fn withdraw(&mut self, count: Count) -> Vec<(Id, Count)> {
+        let total = self.count();
+        if total == 0 || count <= 0 {
+            return Vec::default();
+        }
+
+        let mut allocs: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| {
+                let share = i128::from(count) * i128::from(item.count);
+                let base = (share / i128::from(total)) as Count;
+                let fraction = share % i128::from(total);
+                (base, fraction)
+            })
+            .collect();
+
+        let mut remaining = count - allocs.iter().map(|(base, _)| base).sum::<Count>();
+        let mut indices: Vec<_> = (0..allocs.len()).collect();
+        indices.sort_by_key(|&index| (-allocs[index].1, index));
+        for index in indices {
+            if remaining == 0 {
+                break;
+            }
+            allocs[index].0 += 1;
+            remaining -= 1;
+        }
+
+        self.items
+            .iter_mut()
+            .zip(allocs)
+            .map(|(item, (count, _))| {
+                item.count -= count;
+                (item.id, count)
+            })
+            .collect()
+    }
+}
+
+fn collect_levels(items: Vec<PriceItem>) -> Vec<PriceLevel> {
+    let mut levels: Vec<PriceLevel> = Vec::default();
+    for item in items {
+        match levels.last_mut() {
+            Some(level) if level.cost == item.cost => level.items.push(item),
+            Some(_) | None => levels.push(PriceLevel {
+                cost: item.cost,
+                items: vec![item],
+            }),
+        }
+    }
+    levels
+}
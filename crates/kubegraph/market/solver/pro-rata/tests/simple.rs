@@ -0,0 +1,109 @@
+use chrono::DateTime;
+use kubegraph_api::market::{
+    price::{Direction, PriceItem},
+    product::{ProductMarketSpec, ProductSpec},
+    transaction::TransactionTemplate,
+    BaseModel,
+};
+use kubegraph_market_solver_api::MarketSolver as _;
+use kubegraph_market_solver_pro_rata::MarketSolver;
+use uuid::Uuid;
+
+type Cost = <ProductSpec as BaseModel>::Cost;
+type Count = <ProductSpec as BaseModel>::Count;
+
+fn item(id: u128, direction: Direction, cost: Cost, count: Count) -> PriceItem {
+    PriceItem {
+        id: Uuid::from_u128(id),
+        timestamp: DateTime::from_timestamp(id as i64, 0).unwrap(),
+        direction,
+        cost,
+        count,
+    }
+}
+
+fn product(reserve_price: Option<Cost>) -> ProductSpec {
+    ProductSpec {
+        problem: Default::default(),
+        market: ProductMarketSpec {
+            reserve_price,
+            solver: None,
+        },
+    }
+}
+
+fn template(r#pub: u128, sub: u128, cost: Cost, count: Count) -> TransactionTemplate {
+    TransactionTemplate {
+        prod: Uuid::nil(),
+        r#pub: Uuid::from_u128(r#pub),
+        sub: Uuid::from_u128(sub),
+        cost,
+        count,
+    }
+}
+
+#[tokio::test]
+async fn split_pubs() {
+    let solver = MarketSolver::default();
+    let histogram = vec![
+        item(1, Direction::Pub, 10, 6),
+        item(2, Direction::Pub, 10, 3),
+        item(3, Direction::Pub, 10, 1),
+        item(4, Direction::Sub, 12, 5),
+    ];
+    let transactions = solver
+        .solve(Uuid::nil(), &product(None), histogram)
+        .await
+        .expect("failed to solve");
+
+    // 6:3:1 of 5 is 3:1.5:0.5, the tie is given to the older pub
+    assert_eq!(
+        transactions,
+        vec![template(1, 4, 12, 3), template(2, 4, 12, 2)],
+    );
+}
+
+#[tokio::test]
+async fn split_subs() {
+    let solver = MarketSolver::default();
+    let histogram = vec![
+        item(1, Direction::Pub, 0, 6),
+        item(2, Direction::Sub, 20, 2),
+        item(3, Direction::Sub, 20, 2),
+        item(4, Direction::Sub, 5, 10),
+    ];
+    let transactions = solver
+        .solve(Uuid::nil(), &product(None), histogram)
+        .await
+        .expect("failed to solve");
+
+    assert_eq!(
+        transactions,
+        vec![
+            template(1, 2, 20, 2),
+            template(1, 3, 20, 2),
+            template(1, 4, 5, 2),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn reserve_price() {
+    let solver = MarketSolver::default();
+    let histogram = vec![
+        item(1, Direction::Pub, 0, 3),
+        item(2, Direction::Sub, 20, 2),
+        item(3, Direction::Sub, 20, 2),
+        item(4, Direction::Sub, 5, 10),
+    ];
+    let transactions = solver
+        .solve(Uuid::nil(), &product(Some(10)), histogram)
+        .await
+        .expect("failed to solve");
+
+    // 3 of 2:2 is 1.5:1.5, the tie is given to the older sub
+    assert_eq!(
+        transactions,
+        vec![template(1, 2, 20, 2), template(1, 3, 20, 1)],
+    );
+}
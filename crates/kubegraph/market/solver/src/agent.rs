@@ -76,31 +76,10 @@ impl MarketAgent {
     async fn try_loop_forever(&self) -> Result<()> {
         while !self.signal.is_terminating() {
             let instant = Instant::now();
-            let product_ids: Vec<_> = self.client.list_product_ids().try_collect().await?;
-
-            let mut num_templates = 0;
-            for prod_id in product_ids {
-                let product = match self.client.get_product(prod_id).await? {
-                    Some(product) => product,
-                    None => continue,
-                };
-
-                let histogram: PriceHistogram = self
-                    .client
-                    .list_price_histogram(prod_id)
-                    .try_collect()
-                    .await?;
-
-                let templates = self.solver.solve(prod_id, &product, histogram).await?;
-
-                for template in templates {
-                    self.trade(prod_id, template).await?;
-                    num_templates += 1;
-                }
-            }
 
-            if num_templates > 0 {
-                info!("Created {num_templates} transactions");
+            let receipts = self.solve_once().await?;
+            if !receipts.is_empty() {
+                info!("Created {} transactions", receipts.len());
             }
 
             let elapsed = instant.elapsed();
@@ -113,18 +92,43 @@ impl MarketAgent {
         Ok(())
     }
 
+    /// Solves all products once, and trades the matched orders.
+    pub async fn solve_once(&self) -> Result<Vec<TransactionReceipt>> {
+        let product_ids: Vec<_> = self.client.list_product_ids().try_collect().await?;
+
+        let mut receipts = Vec::default();
+        for prod_id in product_ids {
+            let product = match self.client.get_product(prod_id).await? {
+                Some(product) => product,
+                None => continue,
+            };
+
+            let histogram: PriceHistogram = self
+                .client
+                .list_price_histogram(prod_id)
+                .try_collect()
+                .await?;
+
+            let templates = self.solver.solve(prod_id, &product, histogram).await?;
+
+            for template in templates {
+                receipts.push(self.trade(prod_id, template).await?);
+            }
+        }
+        Ok(receipts)
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn trade(
         &self,
         prod_id: <ProductSpec as BaseModel>::Id,
         template: TransactionTemplate,
-    ) -> Result<()> {
-        self.client
-            .trade(prod_id, &template)
-            .await
-            .map(|TransactionReceipt { id, template: _ }| {
+    ) -> Result<TransactionReceipt> {
+        self.client.trade(prod_id, &template).await.inspect(
+            |TransactionReceipt { id, template: _ }| {
                 info!("Transaction ID: {id}");
-            })
+            },
+        )
     }
 }
 
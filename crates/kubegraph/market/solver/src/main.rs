@@ -1,9 +1,7 @@
-mod agent;
-mod solver;
-
 use anyhow::anyhow;
 use ark_core::signal::FunctionSignal;
 use kubegraph_api::component::NetworkComponentExt;
+use kubegraph_market_solver::agent::MarketAgent;
 use tokio::spawn;
 use tracing::{error, info};
 
@@ -19,8 +17,7 @@ async fn main() {
     }
 
     info!("Booting...");
-    let solver = match <self::agent::MarketAgent as NetworkComponentExt>::try_default(&signal).await
-    {
+    let solver = match <MarketAgent as NetworkComponentExt>::try_default(&signal).await {
         Ok(solver) => solver,
         Err(error) => {
             signal
@@ -1,20 +1,28 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
 use kubegraph_api::{
     component::NetworkComponent,
     market::{
-        price::PriceHistogram, product::ProductSpec, transaction::TransactionTemplate, BaseModel,
+        price::PriceHistogram,
+        product::{ProductMarketSolver, ProductSpec},
+        transaction::TransactionTemplate,
+        BaseModel,
     },
 };
 use serde::{Deserialize, Serialize};
 use tracing::{instrument, Level};
 
 #[derive(Clone)]
-pub enum MarketSolver {
+pub struct MarketSolver {
+    default: MarketSolverType,
+    #[cfg(feature = "market-solver-pro-rata")]
+    pro_rata: ::kubegraph_market_solver_pro_rata::MarketSolver,
     #[cfg(feature = "market-solver-trivial")]
-    Trivial(::kubegraph_market_solver_trivial::MarketSolver),
+    trivial: ::kubegraph_market_solver_trivial::MarketSolver,
+    #[cfg(feature = "market-solver-uniform")]
+    uniform: ::kubegraph_market_solver_uniform::MarketSolver,
 }
 
 #[async_trait]
@@ -25,15 +33,28 @@ impl NetworkComponent for MarketSolver {
         args: <Self as NetworkComponent>::Args,
         signal: &FunctionSignal,
     ) -> Result<Self> {
-        let MarketSolverArgs { solver, trivial } = args;
+        let MarketSolverArgs {
+            solver,
+            #[cfg(feature = "market-solver-pro-rata")]
+            pro_rata,
+            #[cfg(feature = "market-solver-trivial")]
+            trivial,
+            #[cfg(feature = "market-solver-uniform")]
+            uniform,
+        } = args;
 
-        match solver {
-            MarketSolverType::Trivial => {
-                ::kubegraph_market_solver_trivial::MarketSolver::try_new(trivial, signal)
-                    .await
-                    .map(Self::Trivial)
-            }
-        }
+        Ok(Self {
+            default: solver,
+            #[cfg(feature = "market-solver-pro-rata")]
+            pro_rata: ::kubegraph_market_solver_pro_rata::MarketSolver::try_new(pro_rata, signal)
+                .await?,
+            #[cfg(feature = "market-solver-trivial")]
+            trivial: ::kubegraph_market_solver_trivial::MarketSolver::try_new(trivial, signal)
+                .await?,
+            #[cfg(feature = "market-solver-uniform")]
+            uniform: ::kubegraph_market_solver_uniform::MarketSolver::try_new(uniform, signal)
+                .await?,
+        })
     }
 }
 
@@ -46,9 +67,30 @@ impl ::kubegraph_market_solver_api::MarketSolver for MarketSolver {
         product: &ProductSpec,
         histogram: PriceHistogram,
     ) -> Result<Vec<TransactionTemplate>> {
-        match self {
+        match self.select(product)? {
+            #[cfg(feature = "market-solver-pro-rata")]
+            MarketSolverType::ProRata => self.pro_rata.solve(prod_id, product, histogram).await,
+            #[cfg(feature = "market-solver-trivial")]
+            MarketSolverType::Trivial => self.trivial.solve(prod_id, product, histogram).await,
+            #[cfg(feature = "market-solver-uniform")]
+            MarketSolverType::Uniform => self.uniform.solve(prod_id, product, histogram).await,
+        }
+    }
+}
+
+impl MarketSolver {
+    /// Selects the solver of the product, falling back to the default one.
+    fn select(&self, product: &ProductSpec) -> Result<MarketSolverType> {
+        match product.market.solver {
+            None => Ok(self.default),
+            #[cfg(feature = "market-solver-pro-rata")]
+            Some(ProductMarketSolver::ProRata) => Ok(MarketSolverType::ProRata),
             #[cfg(feature = "market-solver-trivial")]
-            Self::Trivial(solver) => solver.solve(prod_id, product, histogram).await,
+            Some(ProductMarketSolver::Greedy) => Ok(MarketSolverType::Trivial),
+            #[cfg(feature = "market-solver-uniform")]
+            Some(ProductMarketSolver::UniformPrice) => Ok(MarketSolverType::Uniform),
+            #[allow(unreachable_patterns)]
+            Some(solver) => bail!("unsupported market solver: {solver:?}"),
         }
     }
 }
@@ -67,10 +109,20 @@ pub struct MarketSolverArgs {
     #[serde(default)]
     pub solver: MarketSolverType,
 
+    #[cfg(feature = "market-solver-pro-rata")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub pro_rata: <::kubegraph_market_solver_pro_rata::MarketSolver as NetworkComponent>::Args,
+
     #[cfg(feature = "market-solver-trivial")]
     #[command(flatten)]
     #[serde(default)]
     pub trivial: <::kubegraph_market_solver_trivial::MarketSolver as NetworkComponent>::Args,
+
+    #[cfg(feature = "market-solver-uniform")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub uniform: <::kubegraph_market_solver_uniform::MarketSolver as NetworkComponent>::Args,
 }
 
 #[derive(
@@ -90,7 +142,11 @@ pub struct MarketSolverArgs {
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum MarketSolverType {
+    #[cfg(feature = "market-solver-pro-rata")]
+    ProRata,
     #[cfg(feature = "market-solver-trivial")]
     #[default]
     Trivial,
+    #[cfg(feature = "market-solver-uniform")]
+    Uniform,
 }
@@ -1,12 +1,12 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use itertools::Itertools;
 use kubegraph_api::market::{
-    price::{Direction, PriceHistogram, PriceItem},
+    price::{PriceHistogram, PriceItem},
     product::ProductSpec,
     transaction::TransactionTemplate,
     BaseModel,
 };
+use kubegraph_market_solver_api::{collect_pubs, collect_subs};
 use tracing::{instrument, Level};
 
 #[derive(Clone, Debug, Default)]
@@ -14,25 +14,15 @@ pub struct MarketSolver {}
 
 #[async_trait]
 impl ::kubegraph_market_solver_api::MarketSolver for MarketSolver {
-    #[instrument(level = Level::INFO, skip(self, _product, histogram))]
+    #[instrument(level = Level::INFO, skip(self, product, histogram))]
     async fn solve(
         &self,
         prod_id: <ProductSpec as BaseModel>::Id,
-        _product: &ProductSpec,
+        product: &ProductSpec,
         histogram: PriceHistogram,
     ) -> Result<Vec<TransactionTemplate>> {
-        let mut pubs = histogram
-            .iter()
-            .filter(|item| matches!(item.direction, Direction::Pub))
-            .filter(|item| item.cost >= 0 && item.count > 0)
-            .copied()
-            .sorted_by_key(|item| (item.cost, item.timestamp));
-        let mut subs = histogram
-            .iter()
-            .filter(|item| matches!(item.direction, Direction::Sub))
-            .filter(|item| item.cost >= 0 && item.count > 0)
-            .copied()
-            .sorted_by_key(|item| (-item.cost, item.timestamp));
+        let mut pubs = collect_pubs(&histogram).into_iter();
+        let mut subs = collect_subs(product, &histogram).into_iter();
 
         let mut r#pub = pubs.next();
         let mut sub = subs.next();
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use kubegraph_api::market::{
+    price::PriceHistogram, product::ProductSpec, transaction::TransactionTemplate, BaseModel,
+};
+use kubegraph_market_solver_api::{collect_pubs, collect_subs};
+use tracing::{instrument, Level};
+
+/// A uniform-price call auction.
+///
+/// All crossing orders of a round are matched in the price-time priority,
+/// and then charged by a single clearing price: the midpoint of the last
+/// matched pub and sub, but no lower than the reserve price of the product.
+#[derive(Clone, Debug, Default)]
+pub struct MarketSolver {}
+
+#[async_trait]
+impl ::kubegraph_market_solver_api::MarketSolver for MarketSolver {
+    #[instrument(level = Level::INFO, skip(self, product, histogram))]
+    async fn solve(
+        &self,
+        prod_id: <ProductSpec as BaseModel>::Id,
+        product: &ProductSpec,
+        histogram: PriceHistogram,
+    ) -> Result<Vec<TransactionTemplate>> {
+        let mut pubs = collect_pubs(&histogram);
+        let mut subs = collect_subs(product, &histogram);
+
+        // Step 1. Match the crossing orders
+        let mut pub_index = 0;
+        let mut sub_index = 0;
+        let mut last_costs = None;
+        let mut matches = Vec::default();
+        while let Some((r#pub, sub)) = pubs.get_mut(pub_index).zip(subs.get_mut(sub_index)) {
+            if r#pub.cost > sub.cost {
+                break;
+            }
+
+            let count = r#pub.count.min(sub.count);
+            r#pub.count -= count;
+            sub.count -= count;
+            last_costs = Some((r#pub.cost, sub.cost));
+            matches.push((r#pub.id, sub.id, count));
+
+            if r#pub.count == 0 {
+                pub_index += 1;
+            }
+            if sub.count == 0 {
+                sub_index += 1;
+            }
+        }
+
+        // Step 2. Determine the clearing price
+        let (pub_cost, sub_cost) = match last_costs {
+            Some(costs) => costs,
+            None => return Ok(Vec::default()),
+        };
+        let reserve_price = product.market.reserve_price.unwrap_or_default();
+        // NOTE: the subs below the reserve price are already excluded
+        let cost = (pub_cost + (sub_cost - pub_cost) / 2).max(reserve_price);
+
+        // Step 3. Charge all matches by the clearing price
+        Ok(matches
+            .into_iter()
+            .map(|(pub_id, sub_id, count)| TransactionTemplate {
+                prod: prod_id,
+                r#pub: pub_id,
+                sub: sub_id,
+                cost,
+                count,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::DateTime;
+use kubegraph_api::market::{
+    price::{Direction, PriceHistogram, PriceItem},
+    product::{ProductMarketSpec, ProductSpec},
+    transaction::TransactionTemplate,
+    BaseModel,
+};
+use kubegraph_market_solver_api::MarketSolver as _;
+use kubegraph_market_solver_uniform::MarketSolver;
+use uuid::Uuid;
+
+type Cost = <ProductSpec as BaseModel>::Cost;
+type Count = <ProductSpec as BaseModel>::Count;
+
+fn item(id: u128, direction: Direction, cost: Cost, count: Count) -> PriceItem {
+    PriceItem {
+        id: Uuid::from_u128(id),
+        timestamp: DateTime::from_timestamp(id as i64, 0).unwrap(),
+        direction,
+        cost,
+        count,
+    }
+}
+
+fn product(reserve_price: Option<Cost>) -> ProductSpec {
+    ProductSpec {
+        problem: Default::default(),
+        market: ProductMarketSpec {
+            reserve_price,
+            solver: None,
+        },
+    }
+}
+
+fn histogram() -> PriceHistogram {
+    vec![
+        item(1, Direction::Pub, 10, 5),
+        item(2, Direction::Pub, 20, 5),
+        item(3, Direction::Pub, 40, 5),
+        item(4, Direction::Sub, 50, 4),
+        item(5, Direction::Sub, 30, 4),
+        item(6, Direction::Sub, 15, 4),
+    ]
+}
+
+fn template(r#pub: u128, sub: u128, cost: Cost, count: Count) -> TransactionTemplate {
+    TransactionTemplate {
+        prod: Uuid::nil(),
+        r#pub: Uuid::from_u128(r#pub),
+        sub: Uuid::from_u128(sub),
+        cost,
+        count,
+    }
+}
+
+#[tokio::test]
+async fn clearing_price() {
+    let solver = MarketSolver::default();
+    let transactions = solver
+        .solve(Uuid::nil(), &product(None), histogram())
+        .await
+        .expect("failed to solve");
+
+    assert_eq!(
+        transactions,
+        vec![
+            template(1, 4, 25, 4),
+            template(1, 5, 25, 1),
+            template(2, 5, 25, 3),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn reserve_price() {
+    let solver = MarketSolver::default();
+    let transactions = solver
+        .solve(Uuid::nil(), &product(Some(28)), histogram())
+        .await
+        .expect("failed to solve");
+
+    assert_eq!(
+        transactions,
+        vec![
+            template(1, 4, 28, 4),
+            template(1, 5, 28, 1),
+            template(2, 5, 28, 3),
+        ],
+    );
+
+    let transactions = solver
+        .solve(Uuid::nil(), &product(Some(60)), histogram())
+        .await
+        .expect("failed to solve");
+    assert_eq!(transactions, vec![]);
+}
+
+#[tokio::test]
+async fn no_crossing() {
+    let solver = MarketSolver::default();
+    let histogram = vec![
+        item(1, Direction::Pub, 30, 5),
+        item(2, Direction::Sub, 20, 5),
+    ];
+    let transactions = solver
+        .solve(Uuid::nil(), &product(None), histogram)
+        .await
+        .expect("failed to solve");
+
+    assert_eq!(transactions, vec![]);
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use k8s_openapi::api::apps::v1::Deployment;
+use kube::{
+    api::{ApiResource, DynamicObject, GroupVersionKind, Patch, PatchParams},
+    Api, Client,
+};
+use serde_json::{json, Value};
+use tracing::{instrument, Level};
+
+/// A concrete change on the kubernetes objects, which can be reverted.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum NetworkRunnerAction {
+    /// Scales a deployment to the given replicas
+    ScaleDeployment {
+        namespace: String,
+        name: String,
+        replicas: i32,
+        last_replicas: i32,
+    },
+    /// Replaces the rules of an `HTTPRoute` with the weighted backends
+    PatchRouteWeights {
+        namespace: String,
+        name: String,
+        rules: Value,
+        last_rules: Value,
+    },
+    /// Replaces the preferred node affinity of a deployment
+    PatchNodeAffinity {
+        namespace: String,
+        name: String,
+        terms: Value,
+        last_terms: Value,
+    },
+}
+
+impl fmt::Display for NetworkRunnerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScaleDeployment {
+                namespace,
+                name,
+                replicas,
+                last_replicas,
+            } => write!(
+                f,
+                "scale deployment {namespace}/{name}: {last_replicas} -> {replicas}"
+            ),
+            Self::PatchRouteWeights {
+                namespace, name, ..
+            } => write!(f, "patch backend weights of httproute {namespace}/{name}"),
+            Self::PatchNodeAffinity {
+                namespace, name, ..
+            } => write!(f, "patch node affinity of deployment {namespace}/{name}"),
+        }
+    }
+}
+
+impl NetworkRunnerAction {
+    #[instrument(level = Level::INFO, skip(self, kube))]
+    pub(super) async fn apply(&self, kube: &Client, dry_run: bool) -> Result<()> {
+        self.patch(kube, dry_run, false).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self, kube))]
+    pub(super) async fn rollback(&self, kube: &Client) -> Result<()> {
+        self.patch(kube, false, true).await
+    }
+
+    async fn patch(&self, kube: &Client, dry_run: bool, is_rollback: bool) -> Result<()> {
+        let pp = PatchParams {
+            dry_run,
+            ..Default::default()
+        };
+        let patch = Patch::Merge(self.to_patch(is_rollback));
+
+        match self {
+            Self::ScaleDeployment {
+                namespace, name, ..
+            } => {
+                let api = Api::<Deployment>::namespaced(kube.clone(), namespace);
+                api.patch_scale(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to scale deployment: {error}"))
+            }
+            Self::PatchRouteWeights {
+                namespace, name, ..
+            } => {
+                let api = Api::<DynamicObject>::namespaced_with(
+                    kube.clone(),
+                    namespace,
+                    &http_route_resource(),
+                );
+                api.patch(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to patch httproute: {error}"))
+            }
+            Self::PatchNodeAffinity {
+                namespace, name, ..
+            } => {
+                let api = Api::<Deployment>::namespaced(kube.clone(), namespace);
+                api.patch(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to patch deployment: {error}"))
+            }
+        }
+    }
+
+    /// Returns the merge patch to apply, or to revert the action.
+    fn to_patch(&self, is_rollback: bool) -> Value {
+        match self {
+            Self::ScaleDeployment {
+                replicas,
+                last_replicas,
+                ..
+            } => {
+                let replicas = if is_rollback { last_replicas } else { replicas };
+                json!({
+                    "spec": {
+                        "replicas": replicas,
+                    },
+                })
+            }
+            Self::PatchRouteWeights {
+                rules, last_rules, ..
+            } => {
+                let rules = if is_rollback { last_rules } else { rules };
+                json!({
+                    "spec": {
+                        "rules": rules,
+                    },
+                })
+            }
+            Self::PatchNodeAffinity {
+                terms, last_terms, ..
+            } => {
+                // NOTE: a null value removes the terms on rollback
+                let terms = if is_rollback { last_terms } else { terms };
+                json!({
+                    "spec": {
+                        "template": {
+                            "spec": {
+                                "affinity": {
+                                    "nodeAffinity": {
+                                        "preferredDuringSchedulingIgnoredDuringExecution": terms,
+                                    },
+                                },
+                            },
+                        },
+                    },
+                })
+            }
+        }
+    }
+}
+
+pub(super) fn http_route_resource() -> ApiResource {
+    let gvk = GroupVersionKind::gvk("gateway.networking.k8s.io", "v1", "HTTPRoute");
+    ApiResource::from_gvk(&gvk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_scale_deployment() {
+        let action = NetworkRunnerAction::ScaleDeployment {
+            namespace: "default".into(),
+            name: "app".into(),
+            replicas: 3,
+            last_replicas: 1,
+        };
+
+        assert_eq!(action.to_patch(false), json!({ "spec": { "replicas": 3 } }));
+        assert_eq!(action.to_patch(true), json!({ "spec": { "replicas": 1 } }));
+    }
+
+    #[test]
+    fn rollback_route_weights() {
+        let rules = json!([{ "backendRefs": [{ "name": "app", "weight": 7 }] }]);
+        let last_rules = json!([{ "backendRefs": [{ "name": "app" }] }]);
+        let action = NetworkRunnerAction::PatchRouteWeights {
+            namespace: "default".into(),
+            name: "route".into(),
+            rules: rules.clone(),
+            last_rules: last_rules.clone(),
+        };
+
+        assert_eq!(
+            action.to_patch(false),
+            json!({ "spec": { "rules": rules } })
+        );
+        assert_eq!(
+            action.to_patch(true),
+            json!({ "spec": { "rules": last_rules } })
+        );
+    }
+
+    #[test]
+    fn rollback_node_affinity() {
+        let terms = json!([{ "weight": 100, "preference": {} }]);
+        let action = NetworkRunnerAction::PatchNodeAffinity {
+            namespace: "default".into(),
+            name: "app".into(),
+            terms: terms.clone(),
+            last_terms: Value::Null,
+        };
+
+        let get_terms = |patch: Value| {
+            patch["spec"]["template"]["spec"]["affinity"]["nodeAffinity"]
+                ["preferredDuringSchedulingIgnoredDuringExecution"]
+                .clone()
+        };
+        assert_eq!(get_terms(action.to_patch(false)), terms);
+
+        // the terms are removed if they did not exist
+        let patch = action.to_patch(true);
+        let node_affinity = &patch["spec"]["template"]["spec"]["affinity"]["nodeAffinity"];
+        assert!(node_affinity.as_object().is_some_and(
+            |object| object.contains_key("preferredDuringSchedulingIgnoredDuringExecution")
+        ));
+        assert_eq!(get_terms(patch), Value::Null);
+    }
+}
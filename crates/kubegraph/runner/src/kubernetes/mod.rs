@@ -0,0 +1,652 @@
+mod action;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::{ArgAction, Parser};
+use k8s_openapi::api::{
+    apps::v1::{Deployment, ReplicaSet},
+    core::v1::Pod,
+};
+use kube::{
+    api::{DynamicObject, ObjectMeta},
+    Api, Client, Resource, ResourceExt,
+};
+use kubegraph_api::{component::NetworkComponent, plan::NetworkPlanFlow};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, instrument, warn, Level};
+
+use self::action::NetworkRunnerAction;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRunnerKubernetesArgs {
+    /// Validate the actions on the API server without persisting them
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER_KUBERNETES_DRY_RUN",
+        action = ArgAction::SetTrue,
+    )]
+    #[serde(default)]
+    pub runner_kubernetes_dry_run: bool,
+}
+
+/// Translates the edge flows into the kubernetes objects.
+///
+/// The nodes are matched by the names published by the kubernetes connector,
+/// such as `pod/{namespace}/{name}` and `node/{name}`:
+///
+/// - The inflows of `deployment/{namespace}/{name}` scale the deployment,
+///   divided by the [`ANNOTATION_REPLICA_CAPACITY`] of the deployment.
+///   The deployments without any positive inflow are left as they are.
+/// - The flows from `httproute/{namespace}/{name}` to `service/{namespace}/{name}`
+///   become the weights of the matching backends of the route.
+/// - The flows from `pod/{namespace}/{name}` to `node/{name}` move the pods,
+///   by preferring the target nodes on the owner deployment.
+///
+/// If any action fails, the applied actions are rolled back in the reverse order.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NetworkRunner {
+    dry_run: bool,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkRunner {
+    type Args = NetworkRunnerKubernetesArgs;
+
+    async fn try_new(args: <Self as NetworkComponent>::Args, _: &FunctionSignal) -> Result<Self> {
+        let NetworkRunnerKubernetesArgs {
+            runner_kubernetes_dry_run: dry_run,
+        } = args;
+
+        Ok(Self { dry_run })
+    }
+}
+
+/// The capacity of a single replica, in the unit of the flows.
+pub const ANNOTATION_REPLICA_CAPACITY: &str = "kubegraph.ulagbulag.io/replica-capacity";
+
+impl NetworkRunner {
+    #[instrument(level = Level::INFO, skip(self, kube, flows))]
+    pub(crate) async fn execute(&self, kube: &Client, flows: &[NetworkPlanFlow]) -> Result<()> {
+        // Step 1. Plan the actions
+        let actions = plan(kube, flows).await?;
+
+        // Step 2. Apply the actions
+        let mut applied = Vec::with_capacity(actions.len());
+        for action in &actions {
+            if self.dry_run {
+                info!("Applying (dry-run): {action}");
+            } else {
+                info!("Applying: {action}");
+            }
+
+            match action.apply(kube, self.dry_run).await {
+                Ok(()) => applied.push(action),
+                Err(error) => {
+                    // Step 3. Rollback the applied actions
+                    if !self.dry_run {
+                        rollback(kube, applied).await;
+                    }
+                    bail!("failed to apply {action}: {error}")
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn rollback(kube: &Client, applied: Vec<&NetworkRunnerAction>) {
+    for action in applied.into_iter().rev() {
+        info!("Rolling back: {action}");
+        if let Err(error) = action.rollback(kube).await {
+            warn!("failed to rollback {action}: {error}");
+        }
+    }
+}
+
+async fn plan(kube: &Client, flows: &[NetworkPlanFlow]) -> Result<Vec<NetworkRunnerAction>> {
+    let PlannedFlows {
+        inflows,
+        weights,
+        placements,
+    } = PlannedFlows::collect(flows);
+
+    let mut actions = Vec::default();
+    for ((namespace, name), inflow) in inflows.into_iter().filter(|&(_, inflow)| inflow > 0) {
+        actions.extend(plan_scale(kube, namespace, name, inflow).await?);
+    }
+    for ((namespace, name), weights) in weights {
+        actions.extend(plan_route_weights(kube, namespace, name, &weights).await?);
+    }
+    actions.extend(plan_node_affinity(kube, placements).await?);
+    Ok(actions)
+}
+
+/// The flows grouped by the kubernetes objects to be changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PlannedFlows<'a> {
+    /// The total inflows of each deployment
+    inflows: BTreeMap<(&'a str, &'a str), i64>,
+    /// The flows to each backend service of each HTTP route
+    weights: BTreeMap<(&'a str, &'a str), BTreeMap<(&'a str, &'a str), i64>>,
+    /// The target nodes of each pod
+    placements: BTreeMap<(&'a str, &'a str), BTreeSet<&'a str>>,
+}
+
+impl<'a> PlannedFlows<'a> {
+    fn collect(flows: &'a [NetworkPlanFlow]) -> Self {
+        let mut inflows: BTreeMap<_, i64> = BTreeMap::default();
+        let mut weights: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::default();
+        let mut placements: BTreeMap<_, BTreeSet<_>> = BTreeMap::default();
+
+        for NetworkPlanFlow { flow, sink, src } in flows {
+            let flow = (*flow).max(0);
+            match (NodeName::parse(src), NodeName::parse(sink)) {
+                (_, Some(NodeName::Deployment(namespace, name))) => {
+                    *inflows.entry((namespace, name)).or_default() += flow;
+                }
+                (
+                    Some(NodeName::HttpRoute(namespace, name)),
+                    Some(NodeName::Service(service_namespace, service_name)),
+                ) => {
+                    weights
+                        .entry((namespace, name))
+                        .or_default()
+                        .insert((service_namespace, service_name), flow);
+                }
+                (Some(NodeName::Pod(namespace, name)), Some(NodeName::Node(node))) if flow > 0 => {
+                    placements
+                        .entry((namespace, name))
+                        .or_default()
+                        .insert(node);
+                }
+                _ => continue,
+            }
+        }
+
+        Self {
+            inflows,
+            weights,
+            placements,
+        }
+    }
+}
+
+async fn plan_scale(
+    kube: &Client,
+    namespace: &str,
+    name: &str,
+    inflow: i64,
+) -> Result<Option<NetworkRunnerAction>> {
+    let api = Api::<Deployment>::namespaced(kube.clone(), namespace);
+    let Some(deployment) = get_opt(&api, namespace, name).await? else {
+        return Ok(None);
+    };
+
+    let Some(replicas) = desired_replicas(&deployment, inflow) else {
+        return Ok(None);
+    };
+    let last_replicas = deployment
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.replicas)
+        .unwrap_or(1);
+
+    if replicas == last_replicas {
+        Ok(None)
+    } else {
+        Ok(Some(NetworkRunnerAction::ScaleDeployment {
+            namespace: namespace.into(),
+            name: name.into(),
+            replicas,
+            last_replicas,
+        }))
+    }
+}
+
+/// Returns the replicas to serve the inflow, or `None` if no flow is planned.
+///
+/// NOTE: the deployments without any inflow are left as they are,
+/// as they may be outside of the current problem or be scaled by others.
+fn desired_replicas(deployment: &Deployment, inflow: i64) -> Option<i32> {
+    if inflow <= 0 {
+        return None;
+    }
+
+    let capacity = deployment
+        .annotations()
+        .get(ANNOTATION_REPLICA_CAPACITY)
+        .and_then(|capacity| capacity.parse::<i64>().ok())
+        .filter(|&capacity| capacity > 0)
+        .unwrap_or(1);
+
+    Some(i32::try_from(inflow.saturating_add(capacity - 1) / capacity).unwrap_or(i32::MAX))
+}
+
+async fn plan_route_weights(
+    kube: &Client,
+    namespace: &str,
+    name: &str,
+    weights: &BTreeMap<(&str, &str), i64>,
+) -> Result<Option<NetworkRunnerAction>> {
+    let api = Api::<DynamicObject>::namespaced_with(
+        kube.clone(),
+        namespace,
+        &self::action::http_route_resource(),
+    );
+    let Some(route) = get_opt(&api, namespace, name).await? else {
+        return Ok(None);
+    };
+
+    let last_rules = route
+        .data
+        .get("spec")
+        .and_then(|spec| spec.get("rules"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    let rules = weighted_rules(namespace, &last_rules, weights);
+
+    if rules == last_rules {
+        Ok(None)
+    } else {
+        Ok(Some(NetworkRunnerAction::PatchRouteWeights {
+            namespace: namespace.into(),
+            name: name.into(),
+            rules,
+            last_rules,
+        }))
+    }
+}
+
+/// Replaces the weights of the backend services of the route rules.
+fn weighted_rules(
+    namespace: &str,
+    last_rules: &Value,
+    weights: &BTreeMap<(&str, &str), i64>,
+) -> Value {
+    // See: https://gateway-api.sigs.k8s.io/reference/spec/#gateway.networking.k8s.io/v1.BackendRef
+    const MAX_WEIGHT: i64 = 1_000_000;
+
+    let mut rules = last_rules.clone();
+    let backends = rules
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter_map(|rule| rule.get_mut("backendRefs"))
+        .filter_map(Value::as_array_mut)
+        .flatten();
+    for backend in backends {
+        let get = |key: &str| backend.get(key).and_then(Value::as_str);
+        let is_service = get("group").unwrap_or_default().is_empty()
+            && get("kind").unwrap_or("Service") == "Service";
+        let weight = match get("name") {
+            Some(backend_name) if is_service => {
+                let backend_namespace = get("namespace").unwrap_or(namespace);
+                weights.get(&(backend_namespace, backend_name)).copied()
+            }
+            Some(_) | None => None,
+        };
+
+        if let Some(weight) = weight {
+            backend["weight"] = json!(weight.min(MAX_WEIGHT));
+        }
+    }
+    rules
+}
+
+async fn plan_node_affinity(
+    kube: &Client,
+    placements: BTreeMap<(&str, &str), BTreeSet<&str>>,
+) -> Result<Vec<NetworkRunnerAction>> {
+    // Collect the target nodes of each deployment
+    let mut deployments: BTreeMap<_, (BTreeSet<&str>, bool)> = BTreeMap::default();
+    for ((namespace, name), nodes) in placements {
+        let api = Api::<Pod>::namespaced(kube.clone(), namespace);
+        let Some(pod) = get_opt(&api, namespace, name).await? else {
+            continue;
+        };
+        let Some(deployment) = get_owner_deployment(kube, namespace, &pod).await? else {
+            warn!("skipping moving pod without deployment: {namespace}/{name}");
+            continue;
+        };
+
+        let current_node = pod.spec.as_ref().and_then(|spec| spec.node_name.as_deref());
+        let is_moved = current_node.map_or(true, |node| !nodes.contains(node));
+
+        let (targets, has_moved) = deployments.entry((namespace, deployment)).or_default();
+        targets.extend(nodes);
+        *has_moved |= is_moved;
+    }
+
+    let mut actions = Vec::default();
+    for ((namespace, name), (nodes, has_moved)) in deployments {
+        // Do not restart the pods already placed on the target nodes
+        if !has_moved {
+            continue;
+        }
+
+        let api = Api::<Deployment>::namespaced(kube.clone(), namespace);
+        let Some(deployment) = get_opt(&api, namespace, &name).await? else {
+            continue;
+        };
+
+        let last_terms = deployment
+            .spec
+            .and_then(|spec| spec.template.spec)
+            .and_then(|spec| spec.affinity)
+            .and_then(|affinity| affinity.node_affinity)
+            .and_then(|affinity| affinity.preferred_during_scheduling_ignored_during_execution)
+            .map(::serde_json::to_value)
+            .transpose()?
+            .unwrap_or(Value::Null);
+        let terms = merge_node_affinity(&last_terms, &nodes);
+
+        if terms != last_terms {
+            actions.push(NetworkRunnerAction::PatchNodeAffinity {
+                namespace: namespace.into(),
+                name,
+                terms,
+                last_terms,
+            });
+        }
+    }
+    Ok(actions)
+}
+
+/// Replaces the preferred term of the target nodes, keeping the other terms.
+fn merge_node_affinity(last_terms: &Value, nodes: &BTreeSet<&str>) -> Value {
+    let mut terms: Vec<_> = last_terms
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|term| !is_placement_term(term))
+        .cloned()
+        .collect();
+
+    terms.push(json!({
+        "weight": 100,
+        "preference": {
+            "matchFields": [
+                {
+                    "key": "metadata.name",
+                    "operator": "In",
+                    "values": nodes,
+                },
+            ],
+        },
+    }));
+    Value::Array(terms)
+}
+
+/// Returns `true` if the term only prefers the nodes by their names,
+/// as the runner does.
+fn is_placement_term(term: &Value) -> bool {
+    let Some(preference) = term.get("preference") else {
+        return false;
+    };
+    let has_expressions = preference
+        .get("matchExpressions")
+        .and_then(Value::as_array)
+        .is_some_and(|expressions| !expressions.is_empty());
+    let fields = preference
+        .get("matchFields")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    !has_expressions
+        && matches!(
+            fields,
+            [field] if field.get("key").and_then(Value::as_str) == Some("metadata.name")
+                && field.get("operator").and_then(Value::as_str) == Some("In"),
+        )
+}
+
+async fn get_opt<K>(api: &Api<K>, namespace: &str, name: &str) -> Result<Option<K>>
+where
+    K: Clone + ::std::fmt::Debug + ::serde::de::DeserializeOwned + Resource,
+{
+    let object = api
+        .get_opt(name)
+        .await
+        .map_err(|error| anyhow!("failed to get object {namespace}/{name}: {error}"))?;
+    if object.is_none() {
+        warn!("skipping missing object: {namespace}/{name}");
+    }
+    Ok(object)
+}
+
+async fn get_owner_deployment(kube: &Client, namespace: &str, pod: &Pod) -> Result<Option<String>> {
+    let Some(name) = get_controller(&pod.metadata, "ReplicaSet") else {
+        return Ok(None);
+    };
+
+    let api = Api::<ReplicaSet>::namespaced(kube.clone(), namespace);
+    Ok(get_opt(&api, namespace, &name)
+        .await?
+        .and_then(|replica_set| get_controller(&replica_set.metadata, "Deployment")))
+}
+
+fn get_controller(metadata: &ObjectMeta, kind: &str) -> Option<String> {
+    metadata
+        .owner_references
+        .iter()
+        .flatten()
+        .find(|owner| owner.controller == Some(true) && owner.kind == kind)
+        .map(|owner| owner.name.clone())
+}
+
+/// The kubernetes objects, named as the nodes of the kubernetes connector.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NodeName<'a> {
+    Deployment(&'a str, &'a str),
+    HttpRoute(&'a str, &'a str),
+    Node(&'a str),
+    Pod(&'a str, &'a str),
+    Service(&'a str, &'a str),
+}
+
+impl<'a> NodeName<'a> {
+    fn parse(name: &'a str) -> Option<Self> {
+        let mut parts = name.splitn(3, '/');
+        match (parts.next()?, parts.next()?, parts.next()) {
+            ("deployment", namespace, Some(name)) => Some(Self::Deployment(namespace, name)),
+            ("httproute", namespace, Some(name)) => Some(Self::HttpRoute(namespace, name)),
+            ("node", name, None) => Some(Self::Node(name)),
+            ("pod", namespace, Some(name)) => Some(Self::Pod(namespace, name)),
+            ("service", namespace, Some(name)) => Some(Self::Service(namespace, name)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(src: &str, sink: &str, flow: i64) -> NetworkPlanFlow {
+        NetworkPlanFlow {
+            flow,
+            sink: sink.into(),
+            src: src.into(),
+        }
+    }
+
+    #[test]
+    fn parse_node_name() {
+        assert_eq!(
+            NodeName::parse("deployment/default/app"),
+            Some(NodeName::Deployment("default", "app")),
+        );
+        assert_eq!(
+            NodeName::parse("httproute/default/route"),
+            Some(NodeName::HttpRoute("default", "route")),
+        );
+        assert_eq!(
+            NodeName::parse("node/worker"),
+            Some(NodeName::Node("worker"))
+        );
+        assert_eq!(
+            NodeName::parse("pod/default/app-0"),
+            Some(NodeName::Pod("default", "app-0")),
+        );
+        assert_eq!(
+            NodeName::parse("service/default/app"),
+            Some(NodeName::Service("default", "app")),
+        );
+
+        assert_eq!(NodeName::parse("node/worker/extra"), None);
+        assert_eq!(NodeName::parse("pod/default"), None);
+        assert_eq!(NodeName::parse("unknown/default/app"), None);
+        assert_eq!(NodeName::parse("worker"), None);
+    }
+
+    #[test]
+    fn plan_flows() {
+        let flows = [
+            flow("service/default/app", "deployment/default/app", 30),
+            flow("service/default/web", "deployment/default/app", 12),
+            flow("service/default/app", "deployment/default/idle", -5),
+            flow("httproute/default/route", "service/default/app", 30),
+            flow("httproute/default/route", "service/other/web", 12),
+            flow("pod/default/app-0", "node/worker-1", 1),
+            flow("pod/default/app-0", "node/worker-2", 0),
+            flow("pod/default/app-1", "node/worker-2", 0),
+            flow("node/worker-1", "pod/default/app-0", 1),
+        ];
+
+        let planned = PlannedFlows::collect(&flows);
+        assert_eq!(
+            planned.inflows,
+            BTreeMap::from([(("default", "app"), 42), (("default", "idle"), 0)]),
+        );
+        assert_eq!(
+            planned.weights,
+            BTreeMap::from([(
+                ("default", "route"),
+                BTreeMap::from([(("default", "app"), 30), (("other", "web"), 12)]),
+            )]),
+        );
+        assert_eq!(
+            planned.placements,
+            BTreeMap::from([(("default", "app-0"), BTreeSet::from(["worker-1"]))]),
+        );
+    }
+
+    #[test]
+    fn plan_replicas() {
+        let mut deployment = Deployment::default();
+        assert_eq!(desired_replicas(&deployment, -1), None);
+        assert_eq!(desired_replicas(&deployment, 0), None);
+        assert_eq!(desired_replicas(&deployment, 3), Some(3));
+
+        deployment
+            .annotations_mut()
+            .insert(ANNOTATION_REPLICA_CAPACITY.into(), "10".into());
+        assert_eq!(desired_replicas(&deployment, 10), Some(1));
+        assert_eq!(desired_replicas(&deployment, 11), Some(2));
+        assert_eq!(desired_replicas(&deployment, i64::MAX), Some(i32::MAX));
+
+        deployment
+            .annotations_mut()
+            .insert(ANNOTATION_REPLICA_CAPACITY.into(), "0".into());
+        assert_eq!(desired_replicas(&deployment, 3), Some(3));
+    }
+
+    #[test]
+    fn plan_route_rules() {
+        let last_rules = json!([
+            {
+                "backendRefs": [
+                    { "name": "app", "weight": 1 },
+                    { "name": "web", "namespace": "other" },
+                    { "name": "bucket", "group": "storage.example.com", "kind": "Bucket" },
+                ],
+            },
+        ]);
+        let weights = BTreeMap::from([(("default", "app"), 30), (("other", "web"), 2_000_000)]);
+
+        assert_eq!(
+            weighted_rules("default", &last_rules, &weights),
+            json!([
+                {
+                    "backendRefs": [
+                        { "name": "app", "weight": 30 },
+                        { "name": "web", "namespace": "other", "weight": 1_000_000 },
+                        { "name": "bucket", "group": "storage.example.com", "kind": "Bucket" },
+                    ],
+                },
+            ]),
+        );
+        assert_eq!(
+            weighted_rules("default", &Value::Null, &weights),
+            Value::Null,
+        );
+    }
+
+    #[test]
+    fn plan_node_affinity_terms() {
+        let user_term = json!({
+            "weight": 10,
+            "preference": {
+                "matchExpressions": [
+                    {
+                        "key": "node-role.kubernetes.io/kiss",
+                        "operator": "In",
+                        "values": ["Compute"],
+                    },
+                ],
+            },
+        });
+        let placement_term = |nodes: &[&str]| {
+            json!({
+                "weight": 100,
+                "preference": {
+                    "matchFields": [
+                        {
+                            "key": "metadata.name",
+                            "operator": "In",
+                            "values": nodes,
+                        },
+                    ],
+                },
+            })
+        };
+
+        // the terms are created
+        let nodes = BTreeSet::from(["worker-1"]);
+        assert_eq!(
+            merge_node_affinity(&Value::Null, &nodes),
+            json!([placement_term(&["worker-1"])]),
+        );
+
+        // the other terms are kept, and the former placement is replaced
+        let last_terms = json!([user_term, placement_term(&["worker-2"])]);
+        let nodes = BTreeSet::from(["worker-1", "worker-3"]);
+        assert_eq!(
+            merge_node_affinity(&last_terms, &nodes),
+            json!([user_term, placement_term(&["worker-1", "worker-3"])]),
+        );
+        assert!(!is_placement_term(&user_term));
+    }
+}
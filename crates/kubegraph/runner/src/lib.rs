@@ -1,20 +1,124 @@
 #[cfg(feature = "df-polars")]
 extern crate polars as pl;
 
+#[cfg(feature = "runner-kubernetes")]
+pub mod kubernetes;
 #[cfg(feature = "df-polars")]
 mod polars;
 
 use anyhow::{bail, Result};
+use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
 use kubegraph_api::{
+    component::NetworkComponent,
     frame::LazyFrame,
     graph::{GraphData, GraphEdges, NetworkGraphDB},
     runner::NetworkRunnerContext,
 };
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tracing::{instrument, Level};
 
-#[derive(Clone, Debug, Default)]
-pub struct NetworkRunner {}
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Parser)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRunnerArgs {
+    /// The backends to apply the solved flows, in order
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER_BACKENDS",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "IMPL",
+        default_values_t = NetworkRunnerArgs::default_runner_backends(),
+    )]
+    #[serde(default = "NetworkRunnerArgs::default_runner_backends")]
+    pub runner_backends: Vec<NetworkRunnerBackendType>,
+
+    #[cfg(feature = "runner-kubernetes")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub kubernetes: <self::kubernetes::NetworkRunner as NetworkComponent>::Args,
+}
+
+impl Default for NetworkRunnerArgs {
+    fn default() -> Self {
+        Self {
+            runner_backends: Self::default_runner_backends(),
+            #[cfg(feature = "runner-kubernetes")]
+            kubernetes: Default::default(),
+        }
+    }
+}
+
+impl NetworkRunnerArgs {
+    fn default_runner_backends() -> Vec<NetworkRunnerBackendType> {
+        vec![NetworkRunnerBackendType::Function]
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkRunnerBackendType {
+    /// Spawn the network functions, which simulate or apply the flows by themselves
+    Function,
+    /// Apply the flows to the kubernetes objects
+    #[cfg(feature = "runner-kubernetes")]
+    Kubernetes,
+}
+
+#[derive(Clone, Debug)]
+pub struct NetworkRunner {
+    backends: Vec<NetworkRunnerBackendType>,
+    #[cfg(feature = "runner-kubernetes")]
+    kubernetes: self::kubernetes::NetworkRunner,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkRunner {
+    type Args = NetworkRunnerArgs;
+
+    #[instrument(level = Level::INFO, skip(signal))]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let NetworkRunnerArgs {
+            runner_backends: backends,
+            #[cfg(feature = "runner-kubernetes")]
+            kubernetes,
+        } = args;
+        #[cfg(not(feature = "runner-kubernetes"))]
+        let _ = signal;
+
+        Ok(Self {
+            backends,
+            #[cfg(feature = "runner-kubernetes")]
+            kubernetes: self::kubernetes::NetworkRunner::try_new(kubernetes, signal).await?,
+        })
+    }
+}
+
+impl NetworkRunner {
+    fn is_enabled(&self, backend: NetworkRunnerBackendType) -> bool {
+        self.backends.contains(&backend)
+    }
+}
 
 #[async_trait]
 impl<DB> ::kubegraph_api::runner::NetworkRunner<DB, LazyFrame> for NetworkRunner
@@ -23,6 +127,38 @@ where
 {
     #[instrument(level = Level::INFO, skip(self, ctx))]
     async fn execute<'a>(&self, ctx: NetworkRunnerContext<'a, DB, LazyFrame>) -> Result<()> {
+        // Step 1. Collect the flows before the functions take the graph
+        #[cfg(feature = "runner-kubernetes")]
+        let kubernetes = if self.is_enabled(NetworkRunnerBackendType::Kubernetes) {
+            let flows = ::kubegraph_api::plan::NetworkPlanFlow::collect_edges(
+                ctx.graph.edges.clone(),
+                &ctx.problem.spec.metadata,
+            )
+            .await?;
+            Some((ctx.kube, flows))
+        } else {
+            None
+        };
+
+        // Step 2. Spawn the functions
+        if self.is_enabled(NetworkRunnerBackendType::Function) {
+            self.spawn_functions(ctx).await?;
+        }
+
+        // Step 3. Apply the flows to the kubernetes objects
+        #[cfg(feature = "runner-kubernetes")]
+        if let Some((kube, flows)) = kubernetes {
+            self.kubernetes.execute(kube, &flows).await?;
+        }
+        Ok(())
+    }
+}
+
+impl NetworkRunner {
+    async fn spawn_functions<DB>(&self, ctx: NetworkRunnerContext<'_, DB, LazyFrame>) -> Result<()>
+    where
+        DB: NetworkGraphDB,
+    {
         let NetworkRunnerContext {
             connectors,
             functions,
@@ -68,7 +204,9 @@ where
                     problem,
                     static_edges: None,
                 };
-                self.execute(ctx).await.map(Into::into)
+                ::kubegraph_api::runner::NetworkRunner::execute(self, ctx)
+                    .await
+                    .map(Into::into)
             }
             #[cfg(feature = "df-polars")]
             (
@@ -87,7 +225,9 @@ where
                     problem,
                     static_edges: Some(GraphEdges::new(static_edges)),
                 };
-                self.execute(ctx).await.map(Into::into)
+                ::kubegraph_api::runner::NetworkRunner::execute(self, ctx)
+                    .await
+                    .map(Into::into)
             }
         }
     }
@@ -41,6 +41,7 @@ where
                     spec:
                         ProblemSpec {
                             metadata,
+                            plan: _,
                             verbose: _,
                         },
                 },
@@ -138,6 +139,11 @@ where
                     use kubegraph_function_fake::NetworkFunctionFake;
                     Some(spec.spawn(ctx))
                 }
+                #[cfg(feature = "function-grpc")]
+                NetworkFunctionKind::Grpc(spec) => {
+                    use kubegraph_function_grpc::NetworkFunctionGrpc;
+                    Some(spec.spawn(ctx))
+                }
                 #[cfg(feature = "function-webhook")]
                 NetworkFunctionKind::Webhook(spec) => {
                     use kubegraph_function_webhook::NetworkFunctionWebhook;
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use kubegraph_api::frame::{DataFrame, LazyFrame};
+use polars::{
+    datatypes::{AnyValue, DataType},
+    prelude::IntoColumn,
+    series::Series,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A column-oriented table, which is written by hand in the scenario files.
+///
+/// The column types are inferred from the values; a column may contain nulls,
+/// but should not mix booleans, numbers and strings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NetworkScenarioFrame {
+    pub columns: BTreeMap<String, Vec<Value>>,
+}
+
+impl NetworkScenarioFrame {
+    pub async fn from_lazy(frame: LazyFrame) -> Result<Self> {
+        match frame.collect().await? {
+            DataFrame::Empty => Ok(Self::default()),
+            DataFrame::Polars(df) => Ok(Self::from_polars(&df)),
+        }
+    }
+
+    pub fn from_polars(df: &::polars::frame::DataFrame) -> Self {
+        Self {
+            columns: df
+                .get_columns()
+                .iter()
+                .map(|column| {
+                    let values = (0..column.len())
+                        .map(|index| column.get(index).map_or(Value::Null, value_from_polars))
+                        .collect();
+                    (column.name().to_string(), values)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_lazy(&self) -> Result<LazyFrame> {
+        if self.columns.is_empty() {
+            Ok(LazyFrame::Empty)
+        } else {
+            self.to_polars().map(Into::into)
+        }
+    }
+
+    pub fn to_polars(&self) -> Result<::polars::frame::DataFrame> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|(key, values)| {
+                series_from_values(key, values)
+                    .map(IntoColumn::into_column)
+                    .map_err(|error| anyhow!("on {key}: {error}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ::polars::frame::DataFrame::new(columns).map_err(Into::into)
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns
+            .values()
+            .map(Vec::len)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of the given column of the first row matching all the given keys.
+    pub fn get(&self, keys: &[(&str, &str)], column: &str) -> Option<&Value> {
+        let index = self.find_rows(keys).into_iter().next()?;
+        self.columns.get(column)?.get(index)
+    }
+
+    /// Removes the rows matching all the given keys, and returns the number of the removed rows.
+    pub fn remove_rows(&mut self, keys: &[(&str, &str)]) -> usize {
+        let rows = self.find_rows(keys);
+        for values in self.columns.values_mut() {
+            let mut index = 0;
+            values.retain(|_| {
+                let is_removed = rows.contains(&index);
+                index += 1;
+                !is_removed
+            });
+        }
+        rows.len()
+    }
+
+    /// Updates the given column of the rows matching all the given keys,
+    /// and returns the number of the updated rows.
+    pub fn update_rows(&mut self, keys: &[(&str, &str)], column: &str, value: Value) -> usize {
+        let rows = self.find_rows(keys);
+        if rows.is_empty() {
+            return 0;
+        }
+
+        let len = self.len();
+        let values = self
+            .columns
+            .entry(column.into())
+            .or_insert_with(|| vec![Value::Null; len]);
+        for &index in &rows {
+            values[index] = value.clone();
+        }
+        rows.len()
+    }
+
+    fn find_rows(&self, keys: &[(&str, &str)]) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&index| {
+                keys.iter().all(|&(key, expected)| {
+                    self.columns
+                        .get(key)
+                        .and_then(|values| values.get(index))
+                        .and_then(Value::as_str)
+                        == Some(expected)
+                })
+            })
+            .collect()
+    }
+}
+
+fn series_from_values(key: &str, values: &[Value]) -> Result<Series> {
+    let name = key.into();
+    match values.iter().find(|value| !value.is_null()) {
+        Some(Value::Null) | None => Ok(Series::full_null(name, values.len(), &DataType::Null)),
+        Some(Value::Bool(_)) => {
+            collect_values(values, Value::as_bool).map(|values| Series::new(name, values))
+        }
+        Some(Value::Number(_)) if values.iter().all(|value| value.is_null() || value.is_i64()) => {
+            collect_values(values, Value::as_i64).map(|values| Series::new(name, values))
+        }
+        Some(Value::Number(_)) => {
+            collect_values(values, Value::as_f64).map(|values| Series::new(name, values))
+        }
+        Some(Value::String(_)) => {
+            collect_values(values, Value::as_str).map(|values| Series::new(name, values))
+        }
+        Some(Value::Array(_) | Value::Object(_)) => {
+            bail!("nested values are not supported")
+        }
+    }
+}
+
+fn collect_values<'a, T>(
+    values: &'a [Value],
+    f: impl Fn(&'a Value) -> Option<T>,
+) -> Result<Vec<Option<T>>> {
+    values
+        .iter()
+        .map(|value| match value {
+            Value::Null => Ok(None),
+            value => f(value)
+                .map(Some)
+                .ok_or_else(|| anyhow!("mixed value types: {value}")),
+        })
+        .collect()
+}
+
+fn value_from_polars(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(value) => value.into(),
+        AnyValue::UInt8(value) => value.into(),
+        AnyValue::UInt16(value) => value.into(),
+        AnyValue::UInt32(value) => value.into(),
+        AnyValue::UInt64(value) => value.into(),
+        AnyValue::Int8(value) => value.into(),
+        AnyValue::Int16(value) => value.into(),
+        AnyValue::Int32(value) => value.into(),
+        AnyValue::Int64(value) => value.into(),
+        AnyValue::Float32(value) => value.into(),
+        AnyValue::Float64(value) => value.into(),
+        AnyValue::String(value) => value.into(),
+        AnyValue::StringOwned(value) => value.to_string().into(),
+        value => value.to_string().into(),
+    }
+}
@@ -1,6 +1,85 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use clap::Parser;
 use kubegraph_api::vm::NetworkVirtualMachineExt;
+use kubegraph_simulator::{
+    replay::{NetworkScenarioReplay, NetworkScenarioReplayArgs, NetworkScenarioSnapshot},
+    scenario::NetworkScenario,
+};
+use kubegraph_vm_local::NetworkArgs;
+use tracing::{error, info};
+
+#[derive(Clone, Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+struct Args {
+    #[command(flatten)]
+    replay: NetworkScenarioReplayArgs,
+
+    #[command(flatten)]
+    vm: NetworkArgs,
+}
 
-#[tokio::main]
+#[::tokio::main]
 async fn main() {
-    ::kubegraph_vm_local::NetworkVirtualMachine::main(|_, _| vec![]).await
+    let Args { replay, vm } = Args::parse();
+    let NetworkScenarioReplayArgs { scenario, output } = replay;
+
+    let scenario = match scenario {
+        Some(scenario) => scenario,
+        None => return ::kubegraph_vm_local::NetworkVirtualMachine::main(|_, _| vec![]).await,
+    };
+
+    ::ark_core::tracer::init_once();
+    info!("Welcome to kubegraph simulator!");
+
+    let signal = FunctionSignal::default().trap_on_panic();
+    if let Err(error) = signal.trap_on_sigint() {
+        error!("{error}");
+        return;
+    }
+
+    info!("Replaying {scenario:?}...");
+    let replay = async {
+        let scenario = NetworkScenario::load(&scenario).await?;
+        let snapshots = NetworkScenarioReplay::try_new(vm, &signal, scenario)
+            .await?
+            .run()
+            .await?;
+        write_snapshots(output.as_deref(), &snapshots).await
+    };
+    if let Err(error) = replay.await {
+        signal
+            .panic(anyhow!("failed to replay the scenario: {error}"))
+            .await
+    }
+
+    info!("Terminating...");
+    signal.exit().await
+}
+
+async fn write_snapshots(
+    output: Option<&Path>,
+    snapshots: &[NetworkScenarioSnapshot],
+) -> Result<()> {
+    match output {
+        Some(output) => {
+            ::tokio::fs::create_dir_all(output).await?;
+            for snapshot in snapshots {
+                let path = output.join(format!("{:06}.json", snapshot.tick));
+                let data = ::serde_json::to_vec_pretty(snapshot)?;
+                ::tokio::fs::write(&path, data)
+                    .await
+                    .map_err(|error| anyhow!("failed to write snapshot {path:?}: {error}"))?;
+            }
+            info!("Written {} snapshots to {output:?}", snapshots.len());
+        }
+        None => {
+            for snapshot in snapshots {
+                println!("{}", ::serde_json::to_string(snapshot)?);
+            }
+        }
+    }
+    Ok(())
 }
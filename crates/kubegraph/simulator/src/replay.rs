@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core::signal::FunctionSignal;
+use clap::Parser;
+use kube::api::ObjectMeta;
+use kubegraph_api::{
+    component::NetworkComponent,
+    connector::{NetworkConnectorCrd, NetworkConnectorKind, NetworkConnectorSpec},
+    function::NetworkFunctionCrd,
+    graph::{Graph, GraphFilter, GraphMetadata, GraphScope, NetworkGraphDB},
+    problem::VirtualProblem,
+    resource::NetworkResourceDB,
+    solver::NetworkSolver,
+    vm::{NetworkVirtualMachine, NetworkVirtualMachineExt},
+};
+use kubegraph_vm_local::{NetworkArgs, NetworkVisualizerArgs, NetworkVisualizerType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, instrument, Level};
+
+use crate::scenario::{
+    NetworkScenario, NetworkScenarioEvent, NetworkScenarioEventKind, NetworkScenarioFunction,
+    NetworkScenarioGraph, NetworkScenarioGraphData, NetworkScenarioProblem,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Parser)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioReplayArgs {
+    /// The scenario file to be replayed; the simulator runs forever if not given
+    #[arg(long, env = "KUBEGRAPH_SIMULATOR_SCENARIO", value_name = "PATH")]
+    #[serde(default)]
+    pub scenario: Option<PathBuf>,
+
+    /// The directory to write the snapshots of each step; printed to stdout if not given
+    #[arg(long, env = "KUBEGRAPH_SIMULATOR_OUTPUT", value_name = "PATH")]
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+}
+
+/// Replays a scenario on a private virtual machine.
+///
+/// The background workers of the virtual machine are closed, so that only the
+/// scenario can drive the pipeline; the same scenario always yields the same snapshots.
+pub struct NetworkScenarioReplay {
+    problems: Vec<VirtualProblem>,
+    scenario: NetworkScenario,
+    vm: ::kubegraph_vm_local::NetworkVirtualMachine,
+}
+
+impl NetworkScenarioReplay {
+    #[instrument(level = Level::INFO, skip_all)]
+    pub async fn try_new(
+        args: NetworkArgs,
+        signal: &FunctionSignal,
+        scenario: NetworkScenario,
+    ) -> Result<Self> {
+        let args = NetworkArgs {
+            visualizer: NetworkVisualizerArgs {
+                visualizer: NetworkVisualizerType::Disabled,
+                ..args.visualizer
+            },
+            ..args
+        };
+        let vm = ::kubegraph_vm_local::NetworkVirtualMachine::try_new(args, signal).await?;
+        vm.close_workers().await?;
+
+        let mut replay = Self {
+            problems: Vec::default(),
+            scenario,
+            vm,
+        };
+
+        let NetworkScenario {
+            graphs,
+            functions,
+            problems,
+            ..
+        } = replay.scenario.clone();
+        for graph in graphs {
+            replay.insert_graph(graph).await?;
+        }
+        for function in functions {
+            replay.insert_function(function).await;
+        }
+        for problem in problems {
+            replay.insert_problem(problem);
+        }
+        Ok(replay)
+    }
+
+    /// Runs all steps of the scenario, and returns the snapshots of each step.
+    #[instrument(level = Level::INFO, skip_all)]
+    pub async fn run(mut self) -> Result<Vec<NetworkScenarioSnapshot>> {
+        let mut events = self.scenario.events.clone();
+        events.sort_by_key(|event| event.at);
+        let mut events = events.into_iter().peekable();
+
+        let mut snapshots = Vec::default();
+        for tick in 0..self.scenario.steps {
+            let mut applied = Vec::default();
+            while let Some(event) = events.next_if(|event| event.at <= tick) {
+                self.apply(&event).await?;
+                applied.push(event);
+            }
+
+            info!("Replaying tick {tick}");
+            snapshots.push(self.step(tick, applied).await?);
+        }
+        Ok(snapshots)
+    }
+
+    async fn step(
+        &self,
+        tick: u64,
+        events: Vec<NetworkScenarioEvent>,
+    ) -> Result<NetworkScenarioSnapshot> {
+        let mut solutions = BTreeMap::default();
+        for problem in &self.problems {
+            // Record the solution before the runner applies it to the graphs
+            if let Some(pipeline) = self.vm.pull_graph(problem).await? {
+                let data = self
+                    .vm
+                    .solver()
+                    .solve(pipeline.template.graph.data, &problem.spec)
+                    .await?;
+                solutions.insert(
+                    problem.scope.name.clone(),
+                    NetworkScenarioGraphData::from_lazy(data).await?,
+                );
+            }
+
+            let state = Default::default();
+            self.vm
+                .step_with_custom_problem(state, problem.clone())
+                .await
+                .map_err(|error| {
+                    anyhow!(
+                        "failed to solve {name} on tick {tick}: {error}",
+                        name = &problem.scope.name,
+                    )
+                })?;
+        }
+
+        let mut graphs = BTreeMap::default();
+        let filter = GraphFilter::all(self.scenario.namespace.clone());
+        for Graph { data, scope, .. } in self.vm.graph_db().list(&filter).await? {
+            graphs.insert(scope.name, NetworkScenarioGraphData::from_lazy(data).await?);
+        }
+
+        Ok(NetworkScenarioSnapshot {
+            tick,
+            events,
+            graphs,
+            solutions,
+        })
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn apply(&mut self, event: &NetworkScenarioEvent) -> Result<()> {
+        match event.kind.clone() {
+            NetworkScenarioEventKind::NodeFailure { graph, node } => {
+                self.update_graph(&graph, |data| {
+                    if data.nodes.remove_rows(&[("name", &node)]) == 0 {
+                        bail!("no such node: {node}");
+                    }
+                    data.edges.remove_rows(&[("src", &node)]);
+                    data.edges.remove_rows(&[("sink", &node)]);
+                    Ok(())
+                })
+                .await
+            }
+            NetworkScenarioEventKind::NodeCapacityChange {
+                graph,
+                node,
+                capacity,
+            } => {
+                self.update_graph(&graph, |data| {
+                    let keys = [("name", node.as_str())];
+                    match data
+                        .nodes
+                        .update_rows(&keys, "capacity", Value::Number(capacity))
+                    {
+                        0 => bail!("no such node: {node}"),
+                        _ => Ok(()),
+                    }
+                })
+                .await
+            }
+            NetworkScenarioEventKind::EdgeCapacityChange {
+                graph,
+                src,
+                sink,
+                capacity,
+            } => {
+                self.update_graph(&graph, |data| {
+                    let keys = [("src", src.as_str()), ("sink", sink.as_str())];
+                    match data
+                        .edges
+                        .update_rows(&keys, "capacity", Value::Number(capacity))
+                    {
+                        0 => bail!("no such edge: {src} -> {sink}"),
+                        _ => Ok(()),
+                    }
+                })
+                .await
+            }
+            NetworkScenarioEventKind::FunctionAdded(function) => {
+                self.insert_function(function).await;
+                Ok(())
+            }
+            NetworkScenarioEventKind::ProblemAdded(problem) => {
+                self.insert_problem(problem);
+                Ok(())
+            }
+        }
+    }
+
+    async fn insert_graph(&self, graph: NetworkScenarioGraph) -> Result<()> {
+        let NetworkScenarioGraph { name, data } = graph;
+
+        let connector = NetworkConnectorCrd {
+            metadata: self.metadata(name),
+            spec: NetworkConnectorSpec {
+                kind: NetworkConnectorKind::Unknown {},
+            },
+        };
+        let scope = GraphScope::from_resource(&connector);
+        let graph = Graph {
+            connector: Some(connector.into()),
+            data: data.to_lazy()?,
+            metadata: GraphMetadata::default(),
+            scope,
+        };
+        self.vm.graph_db().insert(graph).await
+    }
+
+    async fn insert_function(&self, function: NetworkScenarioFunction) {
+        let NetworkScenarioFunction { name, spec } = function;
+
+        let function = NetworkFunctionCrd {
+            metadata: self.metadata(name),
+            spec,
+        };
+        self.vm.resource_db().insert(function).await
+    }
+
+    fn insert_problem(&mut self, problem: NetworkScenarioProblem) {
+        let NetworkScenarioProblem { name, spec } = problem;
+
+        let namespace = self.scenario.namespace.clone();
+        self.problems.push(VirtualProblem {
+            filter: GraphFilter::all(namespace.clone()),
+            scope: GraphScope { namespace, name },
+            spec,
+        })
+    }
+
+    async fn update_graph<F>(&self, name: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut NetworkScenarioGraphData) -> Result<()>,
+    {
+        let scope = GraphScope {
+            namespace: self.scenario.namespace.clone(),
+            name: name.into(),
+        };
+        let graph = self
+            .vm
+            .graph_db()
+            .get(&scope)
+            .await?
+            .ok_or_else(|| anyhow!("no such graph: {name}"))?;
+
+        let mut data = NetworkScenarioGraphData::from_lazy(graph.data).await?;
+        f(&mut data)?;
+
+        let graph = Graph {
+            data: data.to_lazy()?,
+            ..graph
+        };
+        self.vm.graph_db().insert(graph).await
+    }
+
+    fn metadata(&self, name: String) -> ObjectMeta {
+        ObjectMeta {
+            namespace: Some(self.scenario.namespace.clone()),
+            name: Some(name),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioSnapshot {
+    pub tick: u64,
+
+    /// The events applied just before this step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<NetworkScenarioEvent>,
+
+    /// The graphs after this step
+    pub graphs: BTreeMap<String, NetworkScenarioGraphData>,
+
+    /// The solved graphs of each problem, including the flows on the edges
+    #[serde(default)]
+    pub solutions: BTreeMap<String, NetworkScenarioGraphData>,
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use kubegraph_api::{
+    frame::LazyFrame, function::NetworkFunctionSpec, graph::GraphData, problem::ProblemSpec,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Number;
+
+use crate::frame::NetworkScenarioFrame;
+
+/// A reproducible story of a network, replayed on a virtual clock.
+///
+/// The virtual clock starts from tick `0` and advances by one tick per step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenario {
+    #[serde(default = "NetworkScenario::default_namespace")]
+    pub namespace: String,
+
+    /// The number of the steps to be replayed
+    pub steps: u64,
+
+    /// The initial graphs
+    #[serde(default)]
+    pub graphs: Vec<NetworkScenarioGraph>,
+
+    /// The initial functions
+    #[serde(default)]
+    pub functions: Vec<NetworkScenarioFunction>,
+
+    /// The initial problems
+    #[serde(default)]
+    pub problems: Vec<NetworkScenarioProblem>,
+
+    #[serde(default)]
+    pub events: Vec<NetworkScenarioEvent>,
+}
+
+impl NetworkScenario {
+    fn default_namespace() -> String {
+        "default".into()
+    }
+
+    /// Loads a scenario file, written in either YAML or JSON.
+    pub async fn load(path: &Path) -> Result<Self> {
+        let file = ::tokio::fs::read_to_string(path)
+            .await
+            .map_err(|error| anyhow!("failed to read scenario {path:?}: {error}"))?;
+        ::serde_yaml::from_str(&file)
+            .map_err(|error| anyhow!("failed to parse scenario {path:?}: {error}"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioGraph {
+    pub name: String,
+    #[serde(flatten)]
+    pub data: NetworkScenarioGraphData,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioGraphData {
+    #[serde(default)]
+    pub edges: NetworkScenarioFrame,
+    #[serde(default)]
+    pub nodes: NetworkScenarioFrame,
+}
+
+impl NetworkScenarioGraphData {
+    pub async fn from_lazy(data: GraphData<LazyFrame>) -> Result<Self> {
+        let GraphData { edges, nodes } = data;
+        Ok(Self {
+            edges: NetworkScenarioFrame::from_lazy(edges).await?,
+            nodes: NetworkScenarioFrame::from_lazy(nodes).await?,
+        })
+    }
+
+    pub fn to_lazy(&self) -> Result<GraphData<LazyFrame>> {
+        Ok(GraphData {
+            edges: self.edges.to_lazy()?,
+            nodes: self.nodes.to_lazy()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioFunction {
+    pub name: String,
+    pub spec: NetworkFunctionSpec,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioProblem {
+    pub name: String,
+    #[serde(default)]
+    pub spec: ProblemSpec,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScenarioEvent {
+    /// The tick to apply the event, just before the step of the same tick
+    pub at: u64,
+    #[serde(flatten)]
+    pub kind: NetworkScenarioEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum NetworkScenarioEventKind {
+    /// Removes the node and its edges from the graph
+    #[serde(rename_all = "camelCase")]
+    NodeFailure {
+        graph: String,
+        node: String,
+    },
+    /// Replaces the capacity of the node
+    #[serde(rename_all = "camelCase")]
+    NodeCapacityChange {
+        graph: String,
+        node: String,
+        capacity: Number,
+    },
+    /// Replaces the capacity of the edge
+    #[serde(rename_all = "camelCase")]
+    EdgeCapacityChange {
+        graph: String,
+        src: String,
+        sink: String,
+        capacity: Number,
+    },
+    FunctionAdded(NetworkScenarioFunction),
+    ProblemAdded(NetworkScenarioProblem),
+}
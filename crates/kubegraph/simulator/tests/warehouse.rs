@@ -0,0 +1,56 @@
+use ark_core::signal::FunctionSignal;
+use kubegraph_simulator::{
+    replay::{NetworkScenarioReplay, NetworkScenarioSnapshot},
+    scenario::NetworkScenario,
+};
+use serde_json::{json, Value};
+
+async fn replay() -> Vec<NetworkScenarioSnapshot> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios/warehouse.yaml");
+    let scenario = NetworkScenario::load(path.as_ref())
+        .await
+        .expect("failed to load scenario");
+
+    let signal = FunctionSignal::default();
+    NetworkScenarioReplay::try_new(Default::default(), &signal, scenario)
+        .await
+        .expect("failed to init replay")
+        .run()
+        .await
+        .expect("failed to replay")
+}
+
+#[::tokio::test]
+async fn replay_warehouse() {
+    let snapshots = replay().await;
+    assert_eq!(snapshots.len(), 13);
+
+    let get = |tick: usize, node: &str, column: &str| -> Option<Value> {
+        snapshots[tick].graphs["warehouse"]
+            .nodes
+            .get(&[("name", node)], column)
+            .cloned()
+    };
+
+    // Step 1. Nothing is moved without functions
+    assert_eq!(get(0, "a", "supply"), Some(json!(300)));
+    assert_eq!(get(0, "c", "supply"), Some(json!(0)));
+
+    // Step 2. The failed node is removed
+    assert_eq!(get(1, "a", "supply"), Some(json!(300)));
+    assert_eq!(get(1, "c", "supply"), None);
+
+    // Step 3. The goods are balanced by the added function
+    assert_eq!(get(11, "a", "supply"), Some(json!(150)));
+    assert_eq!(get(11, "b", "supply"), Some(json!(150)));
+
+    // Step 4. The capacity is changed, but nothing is moved anymore
+    assert_eq!(get(12, "a", "capacity"), Some(json!(500)));
+    assert_eq!(get(12, "a", "supply"), Some(json!(150)));
+    assert_eq!(get(12, "b", "supply"), Some(json!(150)));
+}
+
+#[::tokio::test]
+async fn replay_warehouse_deterministic() {
+    assert_eq!(replay().await, replay().await);
+}
@@ -46,7 +46,11 @@ impl ::kubegraph_api::solver::NetworkSolver<GraphData<LazyFrame>> for super::Net
         graph: GraphData<LazyFrame>,
         problem: &ProblemSpec<GraphMetadataPinned>,
     ) -> Result<Self::Output> {
-        let ProblemSpec { metadata, verbose } = problem;
+        let ProblemSpec {
+            metadata,
+            plan: _,
+            verbose,
+        } = problem;
         let key_capacity = metadata.capacity();
         let key_flow = metadata.flow();
         let key_name = metadata.name();
@@ -8,7 +8,7 @@ use anyhow::{anyhow, Result};
 use ark_core::result::Result as HttpResult;
 use futures::TryFutureExt;
 use kubegraph_api::market::transaction::TransactionReceipt;
-use tracing::{error, info, instrument, Level};
+use tracing::{error, info, instrument, warn, Level};
 
 use crate::db::NetworkTraderDB;
 
@@ -27,8 +27,24 @@ async fn health() -> impl Responder {
 #[instrument(level = Level::INFO, skip(db))]
 #[post("/")]
 async fn handle(db: Data<NetworkTraderDB>, receipt: Json<TransactionReceipt>) -> impl Responder {
-    // TODO: to be implemented
-    HttpResponse::Ok().json(HttpResult::Ok(receipt.0))
+    let receipt = receipt.0;
+    let txn_id = receipt.id;
+
+    // Keep the trade running until the VM confirms that the problem is satisfied
+    match db.receive(&receipt).await {
+        Ok(true) => {
+            info!("Received the transaction: {txn_id}");
+            HttpResponse::Ok().json(HttpResult::Ok(receipt))
+        }
+        Ok(false) => {
+            warn!("Received the transaction before its session: {txn_id}");
+            HttpResponse::Ok().json(HttpResult::Ok(receipt))
+        }
+        Err(error) => {
+            error!("failed to receive the transaction {txn_id}: {error}");
+            HttpResponse::Ok().json(HttpResult::<TransactionReceipt>::Err(error.to_string()))
+        }
+    }
 }
 
 pub async fn loop_forever(db: NetworkTraderDB) {
@@ -0,0 +1,469 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use kubegraph_api::{
+    frame::{DataFrame, LazyFrame},
+    graph::GraphMetadataPinnedExt,
+    market::{sub::SubSpec, BaseModel},
+    trader::NetworkTraderContext,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn, Level};
+
+type Cost = <SubSpec as BaseModel>::Cost;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkTraderCostArgs {
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_TRADER_COST_ESTIMATOR",
+        value_enum,
+        value_name = "IMPL",
+        default_value_t = NetworkTraderCostEstimatorType::default(),
+    )]
+    #[serde(default)]
+    pub cost_estimator: NetworkTraderCostEstimatorType,
+
+    /// The cost of a bid when using the fixed estimator
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_TRADER_COST_FIXED",
+        value_name = "COST",
+        default_value_t = NetworkTraderCostArgs::default_cost_fixed(),
+    )]
+    #[serde(default = "NetworkTraderCostArgs::default_cost_fixed")]
+    pub cost_fixed: Cost,
+
+    /// The unit cost to be applied if no edges describe it
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_TRADER_COST_UNIT_PRICE",
+        value_name = "COST",
+        default_value_t = NetworkTraderCostArgs::default_cost_unit_price(),
+    )]
+    #[serde(default = "NetworkTraderCostArgs::default_cost_unit_price")]
+    pub cost_unit_price: Cost,
+
+    /// Extra ratio to be added to the estimated cost, in percent
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_TRADER_COST_MARGIN_PERCENT",
+        value_name = "PERCENT",
+        default_value_t = NetworkTraderCostArgs::default_cost_margin_percent(),
+    )]
+    #[serde(default = "NetworkTraderCostArgs::default_cost_margin_percent")]
+    pub cost_margin_percent: u32,
+
+    /// The maximum cost to bid
+    #[arg(long, env = "KUBEGRAPH_MARKET_TRADER_BUDGET", value_name = "COST")]
+    #[serde(default)]
+    pub budget: Option<Cost>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_MARKET_TRADER_BUDGET_POLICY",
+        value_enum,
+        value_name = "POLICY",
+        default_value_t = NetworkTraderBudgetPolicy::default(),
+    )]
+    #[serde(default)]
+    pub budget_policy: NetworkTraderBudgetPolicy,
+}
+
+impl Default for NetworkTraderCostArgs {
+    fn default() -> Self {
+        Self {
+            cost_estimator: NetworkTraderCostEstimatorType::default(),
+            cost_fixed: Self::default_cost_fixed(),
+            cost_unit_price: Self::default_cost_unit_price(),
+            cost_margin_percent: Self::default_cost_margin_percent(),
+            budget: None,
+            budget_policy: NetworkTraderBudgetPolicy::default(),
+        }
+    }
+}
+
+impl NetworkTraderCostArgs {
+    const fn default_cost_fixed() -> Cost {
+        1
+    }
+
+    const fn default_cost_unit_price() -> Cost {
+        1
+    }
+
+    const fn default_cost_margin_percent() -> u32 {
+        0
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkTraderCostEstimatorType {
+    /// Bid a constant cost
+    Fixed,
+    /// Bid the unsatisfied demand multiplied by the unit cost
+    #[default]
+    UnitCost,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkTraderBudgetPolicy {
+    /// Bid the budget instead if the cost exceeds it
+    #[default]
+    Cap,
+    /// Do not bid if the cost exceeds the budget
+    Reject,
+}
+
+/// Estimates the cost to bid for the given problem, before applying the margin and the budget.
+#[async_trait]
+pub trait NetworkTraderCostEstimator
+where
+    Self: Send + Sync,
+{
+    async fn estimate(&self, ctx: &NetworkTraderContext<LazyFrame>) -> Result<Cost>;
+}
+
+#[derive(Clone)]
+pub struct NetworkTraderCost {
+    budget: Option<Cost>,
+    budget_policy: NetworkTraderBudgetPolicy,
+    estimator: Arc<dyn NetworkTraderCostEstimator>,
+    margin_percent: u32,
+}
+
+impl NetworkTraderCost {
+    pub fn new(args: NetworkTraderCostArgs) -> Self {
+        let NetworkTraderCostArgs {
+            cost_estimator,
+            cost_fixed,
+            cost_unit_price,
+            cost_margin_percent,
+            budget,
+            budget_policy,
+        } = args;
+
+        let estimator: Arc<dyn NetworkTraderCostEstimator> = match cost_estimator {
+            NetworkTraderCostEstimatorType::Fixed => {
+                Arc::new(FixedCostEstimator { cost: cost_fixed })
+            }
+            NetworkTraderCostEstimatorType::UnitCost => Arc::new(UnitCostEstimator {
+                unit_price: cost_unit_price,
+            }),
+        };
+
+        Self {
+            budget,
+            budget_policy,
+            estimator,
+            margin_percent: cost_margin_percent,
+        }
+    }
+
+    /// Replaces the cost estimator, keeping the margin and the budget.
+    pub fn with_estimator<E>(mut self, estimator: E) -> Self
+    where
+        E: 'static + NetworkTraderCostEstimator,
+    {
+        self.estimator = Arc::new(estimator);
+        self
+    }
+
+    /// Estimates the cost to bid.
+    ///
+    /// Returns `None` if the cost exceeds the budget and the bid should be rejected.
+    #[instrument(level = Level::INFO, skip(self, ctx))]
+    pub async fn estimate(&self, ctx: &NetworkTraderContext<LazyFrame>) -> Result<Option<Cost>> {
+        let Self {
+            budget,
+            budget_policy,
+            ref estimator,
+            margin_percent,
+        } = *self;
+
+        let cost = estimator.estimate(ctx).await?;
+        let cost = cost.saturating_add(cost.saturating_mul(margin_percent.into()) / 100);
+
+        match budget {
+            Some(budget) if cost > budget => match budget_policy {
+                NetworkTraderBudgetPolicy::Cap => {
+                    info!("Capping the cost {cost} to the budget {budget}");
+                    Ok(Some(budget))
+                }
+                NetworkTraderBudgetPolicy::Reject => {
+                    warn!("Rejecting the bid: estimated cost {cost} exceeds the budget {budget}");
+                    Ok(None)
+                }
+            },
+            Some(_) | None => Ok(Some(cost)),
+        }
+    }
+}
+
+/// Bids a constant cost.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedCostEstimator {
+    pub cost: Cost,
+}
+
+#[async_trait]
+impl NetworkTraderCostEstimator for FixedCostEstimator {
+    async fn estimate(&self, _: &NetworkTraderContext<LazyFrame>) -> Result<Cost> {
+        Ok(self.cost)
+    }
+}
+
+/// Bids the unsatisfied demand multiplied by the unit cost.
+///
+/// The unit cost is taken from the static edges if any, otherwise the given unit price is used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnitCostEstimator {
+    pub unit_price: Cost,
+}
+
+#[async_trait]
+impl NetworkTraderCostEstimator for UnitCostEstimator {
+    async fn estimate(&self, ctx: &NetworkTraderContext<LazyFrame>) -> Result<Cost> {
+        let metadata = &ctx.problem.spec.metadata;
+
+        // Step 1. Collect the unsatisfied demand, i.e. the negative supply
+        // NOTE: no flows are assigned if the problem has no feasible functions
+        let demand = match ctx.graph.nodes.clone().collect().await? {
+            DataFrame::Empty => 0,
+            #[cfg(feature = "df-polars")]
+            DataFrame::Polars(df) => sum_polars_demand(&df, metadata.supply())?,
+        };
+
+        // Step 2. Collect the unit cost from the static edges, if any
+        let unit_price = match ctx.static_edges.clone().map(|edges| edges.into_inner()) {
+            Some(edges) => match edges.collect().await? {
+                DataFrame::Empty => None,
+                #[cfg(feature = "df-polars")]
+                DataFrame::Polars(df) => max_polars_column(&df, metadata.unit_cost())?,
+            },
+            None => None,
+        }
+        .filter(|&unit_price| unit_price > 0)
+        .unwrap_or(self.unit_price);
+
+        Ok(demand.saturating_mul(unit_price))
+    }
+}
+
+#[cfg(feature = "df-polars")]
+fn sum_polars_demand(df: &::polars::frame::DataFrame, name: &str) -> Result<Cost> {
+    use polars::datatypes::DataType;
+
+    match df.column(name) {
+        Ok(column) => Ok(column
+            .as_materialized_series()
+            .cast(&DataType::Int64)?
+            .i64()?
+            .into_iter()
+            .flatten()
+            .filter(|&supply| supply < 0)
+            .fold(0, |demand: Cost, supply| {
+                demand.saturating_add(supply.saturating_neg())
+            })),
+        Err(_) => Ok(0),
+    }
+}
+
+#[cfg(feature = "df-polars")]
+fn max_polars_column(df: &::polars::frame::DataFrame, name: &str) -> Result<Option<Cost>> {
+    use polars::{datatypes::DataType, prelude::ChunkAgg};
+
+    match df.column(name) {
+        Ok(column) => Ok(column
+            .as_materialized_series()
+            .cast(&DataType::Int64)?
+            .i64()?
+            .max()),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(all(test, feature = "df-polars"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use kubegraph_api::{
+        graph::{GraphData, GraphEdges, GraphFilter, GraphScope},
+        problem::{ProblemSpec, VirtualProblem},
+    };
+
+    use super::*;
+
+    fn new_context(supply: &[i64], unit_cost: Option<&[i64]>) -> NetworkTraderContext<LazyFrame> {
+        let nodes = ::polars::df!(
+            "name"      => (0..supply.len()).map(|index| format!("node-{index}")).collect::<Vec<_>>(),
+            "supply"    => supply,
+        )
+        .expect("failed to create nodes dataframe");
+
+        let static_edges = unit_cost.map(|unit_cost| {
+            let edges = ::polars::df!(
+                "unit_cost" => unit_cost,
+            )
+            .expect("failed to create edges dataframe");
+            GraphEdges::new(DataFrame::Polars(edges).lazy())
+        });
+
+        NetworkTraderContext {
+            functions: BTreeMap::default(),
+            graph: GraphData {
+                edges: LazyFrame::Empty,
+                nodes: DataFrame::Polars(nodes).lazy(),
+            },
+            problem: VirtualProblem {
+                filter: GraphFilter::all("default".into()),
+                scope: GraphScope {
+                    namespace: "default".into(),
+                    name: "optimize-warehouses".into(),
+                },
+                spec: ProblemSpec::default(),
+            },
+            static_edges,
+        }
+    }
+
+    fn new_cost(args: NetworkTraderCostArgs) -> NetworkTraderCost {
+        NetworkTraderCost::new(args)
+    }
+
+    #[::tokio::test]
+    async fn estimate_bid_from_demand() {
+        let cost = new_cost(NetworkTraderCostArgs {
+            cost_unit_price: 2,
+            ..Default::default()
+        });
+
+        // only the negative supply is the unsatisfied demand
+        let ctx = new_context(&[300, -100, -50], None);
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(300));
+
+        // the unit cost of the static edges has a priority
+        let ctx = new_context(&[300, -100, -50], Some(&[1, 5, 3]));
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(750));
+
+        // the non-positive unit costs are ignored
+        let ctx = new_context(&[300, -100, -50], Some(&[0]));
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(300));
+
+        // nothing to bid if there is no demand
+        let ctx = new_context(&[300, 100], None);
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(0));
+
+        // the margin is added to the estimated cost
+        let cost = new_cost(NetworkTraderCostArgs {
+            cost_unit_price: 2,
+            cost_margin_percent: 10,
+            ..Default::default()
+        });
+        let ctx = new_context(&[300, -100, -50], None);
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(330));
+    }
+
+    #[::tokio::test]
+    async fn estimate_bid_over_budget() {
+        let ctx = new_context(&[300, -100, -50], None);
+
+        // the cost is capped to the budget
+        let cost = new_cost(NetworkTraderCostArgs {
+            budget: Some(100),
+            budget_policy: NetworkTraderBudgetPolicy::Cap,
+            ..Default::default()
+        });
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(100));
+
+        // the bid is skipped
+        let cost = new_cost(NetworkTraderCostArgs {
+            budget: Some(100),
+            budget_policy: NetworkTraderBudgetPolicy::Reject,
+            ..Default::default()
+        });
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), None);
+
+        // the cost within the budget is kept
+        let cost = new_cost(NetworkTraderCostArgs {
+            budget: Some(150),
+            budget_policy: NetworkTraderBudgetPolicy::Reject,
+            ..Default::default()
+        });
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), Some(150));
+    }
+
+    #[::tokio::test]
+    async fn estimate_bid_with_custom_estimator() {
+        struct DoubleEstimator;
+
+        #[async_trait]
+        impl NetworkTraderCostEstimator for DoubleEstimator {
+            async fn estimate(&self, ctx: &NetworkTraderContext<LazyFrame>) -> Result<Cost> {
+                let estimator = UnitCostEstimator { unit_price: 1 };
+                Ok(estimator.estimate(ctx).await? * 2)
+            }
+        }
+
+        let ctx = new_context(&[300, -100, -50], None);
+        let cost = new_cost(NetworkTraderCostArgs {
+            budget: Some(200),
+            budget_policy: NetworkTraderBudgetPolicy::Reject,
+            ..Default::default()
+        })
+        .with_estimator(DoubleEstimator);
+
+        // the budget is still applied
+        assert_eq!(cost.estimate(&ctx).await.unwrap(), None);
+    }
+}
@@ -9,7 +9,14 @@ use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
 use clap::Parser;
 use kubegraph_api::{
-    component::NetworkComponent, function::webhook::NetworkFunctionWebhookSpec, graph::GraphScope,
+    component::NetworkComponent,
+    function::webhook::NetworkFunctionWebhookSpec,
+    graph::GraphScope,
+    market::{
+        sub::SubSpec,
+        transaction::{TransactionReceipt, TransactionSpec},
+        BaseModel,
+    },
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -22,6 +29,8 @@ use crate::session::NetworkTraderSession;
 pub struct NetworkTraderDB {
     pub(crate) args: NetworkTraderDBArgs,
     data: Arc<RwLock<BTreeMap<GraphScope, NetworkTraderSession>>>,
+    /// The transactions received before their sessions are registered
+    receipts: Arc<RwLock<BTreeMap<<SubSpec as BaseModel>::Id, <TransactionSpec as BaseModel>::Id>>>,
     pub(crate) signal: FunctionSignal,
 }
 
@@ -36,21 +45,80 @@ impl NetworkComponent for NetworkTraderDB {
         Ok(Self {
             args,
             data: Arc::default(),
+            receipts: Arc::default(),
             signal: signal.clone(),
         })
     }
 }
 
 impl NetworkTraderDB {
+    /// Returns `true` if the problem is waiting for a trade.
+    ///
+    /// Once traded, the problem is unlocked so that the VM can try to satisfy it again.
     #[instrument(level = Level::INFO, skip(self, scope))]
     pub(crate) async fn is_locked(&self, scope: &GraphScope) -> Result<bool> {
+        Ok(self
+            .data
+            .read()
+            .await
+            .get(scope)
+            .is_some_and(|session| session.txn_id.is_none()))
+    }
+
+    /// Returns `true` if the problem is already registered to the market.
+    #[instrument(level = Level::INFO, skip(self, scope))]
+    pub(crate) async fn is_registered(&self, scope: &GraphScope) -> Result<bool> {
         Ok(self.data.read().await.contains_key(scope))
     }
 
+    /// Binds the traded transaction to its session.
+    ///
+    /// Returns `false` if the session is not registered yet, keeping the transaction until then.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub(crate) async fn receive(&self, receipt: &TransactionReceipt) -> Result<bool> {
+        let mut data = self.data.write().await;
+        match data
+            .values_mut()
+            .find(|session| session.sub_id == receipt.template.sub)
+        {
+            Some(session) => {
+                session.txn_id.replace(receipt.id);
+                Ok(true)
+            }
+            None => {
+                self.receipts
+                    .write()
+                    .await
+                    .insert(receipt.template.sub, receipt.id);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Removes the session of the satisfied problem, returning its traded transaction if any.
+    #[instrument(level = Level::INFO, skip(self, scope))]
+    pub(crate) async fn complete(
+        &self,
+        scope: &GraphScope,
+    ) -> Result<Option<<TransactionSpec as BaseModel>::Id>> {
+        Ok(self
+            .data
+            .write()
+            .await
+            .remove(scope)
+            .and_then(|session| session.txn_id))
+    }
+
     #[instrument(level = Level::INFO, skip(self, session))]
-    pub(crate) async fn register(&self, session: NetworkTraderSession) -> Result<()> {
+    pub(crate) async fn register(&self, mut session: NetworkTraderSession) -> Result<()> {
         let scope = session.ctx.problem.scope.clone();
-        self.data.write().await.insert(scope, session);
+
+        // NOTE: the market may trade the sub as soon as it is created
+        let mut data = self.data.write().await;
+        if let Some(txn_id) = self.receipts.write().await.remove(&session.sub_id) {
+            session.txn_id.replace(txn_id);
+        }
+        data.insert(scope, session);
         Ok(())
     }
 
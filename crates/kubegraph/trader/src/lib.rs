@@ -1,4 +1,5 @@
 mod actix;
+pub mod cost;
 mod db;
 mod session;
 
@@ -9,7 +10,13 @@ use clap::Parser;
 use kubegraph_api::{
     component::NetworkComponent,
     frame::LazyFrame,
-    market::{product::ProductSpec, sub::SubSpec, BaseModel},
+    graph::GraphScope,
+    market::{
+        product::{ProductMarketSpec, ProductSpec},
+        sub::SubSpec,
+        transaction::TaskState,
+        BaseModel,
+    },
     problem::VirtualProblem,
     trader::NetworkTraderContext,
 };
@@ -21,7 +28,9 @@ use tracing::{debug, info, instrument, Level};
 
 #[derive(Clone)]
 pub struct NetworkTrader {
+    account: Option<<SubSpec as BaseModel>::Id>,
     client: MarketClient,
+    cost: crate::cost::NetworkTraderCost,
     db: crate::db::NetworkTraderDB,
 }
 
@@ -34,16 +43,24 @@ impl NetworkComponent for NetworkTrader {
         args: <Self as NetworkComponent>::Args,
         signal: &FunctionSignal,
     ) -> Result<Self> {
-        let NetworkTraderArgs { client, db } = args;
+        let NetworkTraderArgs {
+            account,
+            client,
+            cost,
+            db,
+        } = args;
 
+        let client = {
+            info!("Initializing market trader...");
+            MarketClient::try_new(client, signal).await?
+        };
         let db = crate::db::NetworkTraderDB::try_new(db, signal).await?;
         spawn(crate::actix::loop_forever(db.clone()));
 
         Ok(Self {
-            client: {
-                info!("Initializing market trader...");
-                MarketClient::try_new(client, signal).await?
-            },
+            account,
+            client,
+            cost: crate::cost::NetworkTraderCost::new(cost),
             db,
         })
     }
@@ -57,39 +74,72 @@ impl ::kubegraph_api::trader::NetworkTrader<LazyFrame> for NetworkTrader {
     }
 
     #[instrument(level = Level::INFO, skip(self, ctx))]
-    async fn register(&self, ctx: NetworkTraderContext<LazyFrame>) -> Result<()> {
+    async fn register(&self, ctx: NetworkTraderContext<LazyFrame>) -> Result<bool> {
         let mut state = NetworkTraderState::default();
         match self.try_register(&mut state, ctx).await {
-            Ok(()) => Ok(()),
+            Ok(registered) => Ok(registered),
             Err(error) => match self.rollback_register(state).await {
                 Ok(()) => Err(error),
                 Err(error_rollback) => Err(error.context(error_rollback)),
             },
         }
     }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn complete(&self, scope: &GraphScope) -> Result<()> {
+        // Complete the trade, so that the transaction can be settled
+        match self.db.complete(scope).await? {
+            Some(txn_id) => {
+                self.client
+                    .update_sub_state(txn_id, TaskState::Completed)
+                    .await?;
+                info!("Completed the transaction: {txn_id}");
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl NetworkTrader {
+    /// Replaces the cost estimator of the bids.
+    pub fn with_cost_estimator<E>(mut self, estimator: E) -> Self
+    where
+        E: 'static + crate::cost::NetworkTraderCostEstimator,
+    {
+        self.cost = self.cost.with_estimator(estimator);
+        self
+    }
+
     #[instrument(level = Level::INFO, skip(self, state, ctx))]
     async fn try_register(
         &self,
         state: &mut NetworkTraderState,
         ctx: NetworkTraderContext<LazyFrame>,
-    ) -> Result<()> {
-        // Step 1. Create a problem
+    ) -> Result<bool> {
+        // Step 0. Keep waiting if the problem is already traded
+        if self.db.is_registered(&ctx.problem.scope).await? {
+            debug!("Waiting for the traded problem to be satisfied");
+            return Ok(true);
+        }
+
+        // Step 1. Estimate the cost
+        debug!("Estimating the cost");
+        let Some(cost) = self.cost.estimate(&ctx).await? else {
+            return Ok(false);
+        };
+
+        // Step 2. Create a problem
         debug!("Creating a problem");
         let prod_id = {
             let spec = ProductSpec {
                 problem: ctx.problem.spec.clone(),
+                market: ProductMarketSpec::default(),
             };
             self.client.find_product(&spec).await?
         };
         state.prod_id.replace(prod_id);
 
-        // Step 2. Estimate the cost
-        debug!("Estimating the cost");
-        let cost = todo!();
-
         // Step 3. Create a subscriber
         debug!("Creating a subscriber");
         let sub_id = {
@@ -97,6 +147,7 @@ impl NetworkTrader {
                 cost,
                 count: 1,
                 function: self.db.webhook_endpoint()?,
+                account: self.account,
             };
             self.client.insert_sub(prod_id, &spec).await?
         };
@@ -104,8 +155,12 @@ impl NetworkTrader {
 
         // Step 4. Store it to the DB
         debug!("Storing the session to the DB");
-        let session = crate::session::NetworkTraderSession { ctx };
-        self.db.register(session).await
+        let session = crate::session::NetworkTraderSession {
+            ctx,
+            sub_id,
+            txn_id: None,
+        };
+        self.db.register(session).await.map(|()| true)
     }
 
     #[instrument(level = Level::INFO, skip(self, state))]
@@ -142,10 +197,19 @@ struct NetworkTraderState {
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkTraderArgs {
+    /// The market account to pay the bids, if any
+    #[arg(long, env = "KUBEGRAPH_MARKET_TRADER_ACCOUNT", value_name = "ID")]
+    #[serde(default)]
+    pub account: Option<<SubSpec as BaseModel>::Id>,
+
     #[command(flatten)]
     #[serde(default)]
     pub client: MarketClientArgs,
 
+    #[command(flatten)]
+    #[serde(default)]
+    pub cost: crate::cost::NetworkTraderCostArgs,
+
     #[command(flatten)]
     pub db: <self::db::NetworkTraderDB as NetworkComponent>::Args,
 }
@@ -1,6 +1,13 @@
-use kubegraph_api::{frame::LazyFrame, trader::NetworkTraderContext};
+use kubegraph_api::{
+    frame::LazyFrame,
+    market::{sub::SubSpec, transaction::TransactionSpec, BaseModel},
+    trader::NetworkTraderContext,
+};
 
 #[derive(Clone)]
 pub(crate) struct NetworkTraderSession {
     pub(crate) ctx: NetworkTraderContext<LazyFrame>,
+    pub(crate) sub_id: <SubSpec as BaseModel>::Id,
+    /// The traded transaction, waiting for the problem to be satisfied
+    pub(crate) txn_id: Option<<TransactionSpec as BaseModel>::Id>,
 }
@@ -0,0 +1,367 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+use actix_web::{
+    get, middleware, post,
+    rt::spawn as spawn_local,
+    web::{Data, Path, Payload},
+    App, HttpRequest, HttpResponse, HttpServer, Responder,
+};
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::Parser;
+use kubegraph_api::{
+    component::NetworkComponent,
+    frame::LazyFrame,
+    graph::{Graph, GraphData, GraphEntry, GraphMetadataExt, GraphScope},
+    visualizer::NetworkVisualizerEvent,
+};
+use petgraph::{
+    stable_graph::StableDiGraph,
+    visit::{EdgeRef, IntoEdgeReferences},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{broadcast, oneshot, Mutex, RwLock},
+    task::JoinHandle,
+};
+use tracing::{error, info, instrument, warn, Level};
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerArgs {
+    /// The address of the web visualizer.
+    ///
+    /// NOTE: anyone who can reach it can drive the VM, so it is bound to the loopback by default.
+    /// Its port is distinct from the gateway's, so that both can be served together.
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VISUALIZER_WEB_ADDR",
+        value_name = "ADDR",
+        default_value_t = NetworkVisualizerArgs::default_visualizer_web_addr(),
+    )]
+    #[serde(default = "NetworkVisualizerArgs::default_visualizer_web_addr")]
+    pub visualizer_web_addr: SocketAddr,
+}
+
+impl Default for NetworkVisualizerArgs {
+    fn default() -> Self {
+        Self {
+            visualizer_web_addr: Self::default_visualizer_web_addr(),
+        }
+    }
+}
+
+impl NetworkVisualizerArgs {
+    fn default_visualizer_web_addr() -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 8081))
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkVisualizer {
+    data: Arc<NetworkVisualizerData>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkVisualizer {
+    type Args = NetworkVisualizerArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let NetworkVisualizerArgs {
+            visualizer_web_addr: addr,
+        } = args;
+
+        if !addr.ip().is_loopback() {
+            warn!(
+                "The web visualizer is exposed on {addr}; anyone who can reach it can drive the VM"
+            );
+        }
+
+        let this = Self {
+            data: Arc::new(NetworkVisualizerData::new()),
+            task: Arc::default(),
+        };
+
+        let data = Data::from(this.data.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(Data::clone(&data))
+                .service(index)
+                .service(get_graph)
+                .service(post_event)
+                .service(ws)
+                .wrap(middleware::NormalizePath::new(
+                    middleware::TrailingSlash::Trim,
+                ))
+        })
+        .bind(addr)
+        .map_err(|error| anyhow!("failed to bind to {addr}: {error}"))?
+        .run();
+
+        info!("Starting web visualizer on {addr}...");
+        this.task.lock().await.replace(::tokio::spawn({
+            let signal = signal.clone();
+            async move {
+                match server.await {
+                    Ok(()) => info!("Completed web visualizer"),
+                    Err(error) => {
+                        error!("failed to operate web visualizer: {error}");
+                        signal.terminate_on_panic()
+                    }
+                }
+            }
+        }));
+
+        Ok(this)
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::visualizer::NetworkVisualizer for NetworkVisualizer {
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    async fn replace_graph<M>(&self, graph: Graph<GraphData<LazyFrame>, M>) -> Result<()>
+    where
+        M: Send + Clone + GraphMetadataExt,
+    {
+        let scope = graph.scope.clone();
+        let graph = NetworkVisualizerGraph::new(scope, graph.try_into()?);
+
+        *self.data.graph.write().await = Some(graph.clone());
+        self.data.publish(NetworkVisualizerMessage::Graph(graph));
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn call(&self, event: NetworkVisualizerEvent) -> Result<()> {
+        self.data.call(event).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn close(&self) -> Result<()> {
+        if let Some(session) = self.task.lock().await.take() {
+            session.abort();
+        }
+        Ok(())
+    }
+}
+
+struct NetworkVisualizerData {
+    events: Mutex<Vec<NetworkVisualizerEventContext>>,
+    graph: RwLock<Option<NetworkVisualizerGraph>>,
+    messages: broadcast::Sender<NetworkVisualizerMessage>,
+}
+
+impl NetworkVisualizerData {
+    const MAX_MESSAGE_CHANNEL: usize = 32;
+
+    fn new() -> Self {
+        Self {
+            events: Mutex::default(),
+            graph: RwLock::default(),
+            messages: broadcast::channel(Self::MAX_MESSAGE_CHANNEL).0,
+        }
+    }
+
+    async fn call(&self, event: NetworkVisualizerEvent) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        let ctx = NetworkVisualizerEventContext { event, sender: tx };
+
+        self.events.lock().await.push(ctx);
+        self.publish(NetworkVisualizerMessage::Waiting(event));
+        rx.await.map_err(Into::into)
+    }
+
+    async fn activate(&self, event: NetworkVisualizerEvent) -> usize {
+        let mut events = self.events.lock().await;
+
+        let mut count = 0;
+        for index in (0..events.len()).rev() {
+            if events[index].event == event {
+                let ctx = events.remove(index);
+                if ctx.sender.send(()).is_ok() {
+                    count += 1;
+                }
+            }
+        }
+
+        if count > 0 {
+            self.publish(NetworkVisualizerMessage::Activated(event));
+        }
+        count
+    }
+
+    async fn pending_events(&self) -> Vec<NetworkVisualizerEvent> {
+        let mut events: Vec<_> = self
+            .events
+            .lock()
+            .await
+            .iter()
+            .map(|ctx| ctx.event)
+            .collect();
+        events.sort();
+        events.dedup();
+        events
+    }
+
+    fn publish(&self, message: NetworkVisualizerMessage) {
+        // NOTE: it is okay that there is no subscriber
+        self.messages.send(message).ok();
+    }
+}
+
+struct NetworkVisualizerEventContext {
+    event: NetworkVisualizerEvent,
+    sender: oneshot::Sender<()>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+enum NetworkVisualizerMessage {
+    Activated(NetworkVisualizerEvent),
+    Graph(NetworkVisualizerGraph),
+    Waiting(NetworkVisualizerEvent),
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NetworkVisualizerGraph {
+    edges: Vec<NetworkVisualizerEdge>,
+    nodes: Vec<GraphEntry>,
+    scope: GraphScope,
+}
+
+impl NetworkVisualizerGraph {
+    fn new(scope: GraphScope, graph: StableDiGraph<GraphEntry, GraphEntry>) -> Self {
+        // NOTE: stable graphs may have holes in their indices
+        let indices: Vec<_> = graph.node_indices().collect();
+        let positions: HashMap<_, _> = indices
+            .iter()
+            .enumerate()
+            .map(|(position, &index)| (index, position))
+            .collect();
+        let find_index = |node| positions.get(&node).copied();
+
+        let edges = graph
+            .edge_references()
+            .filter_map(|edge| {
+                Some(NetworkVisualizerEdge {
+                    entry: edge.weight().clone(),
+                    sink: find_index(edge.target())?,
+                    src: find_index(edge.source())?,
+                })
+            })
+            .collect();
+        let nodes = indices.iter().map(|&index| graph[index].clone()).collect();
+
+        Self {
+            edges,
+            nodes,
+            scope,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NetworkVisualizerEdge {
+    #[serde(flatten)]
+    entry: GraphEntry,
+    #[serde(rename = "_sink")]
+    sink: usize,
+    #[serde(rename = "_src")]
+    src: usize,
+}
+
+#[get("/")]
+async fn index() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(include_str!("../static/index.html"))
+}
+
+#[get("/graph")]
+async fn get_graph(data: Data<NetworkVisualizerData>) -> impl Responder {
+    HttpResponse::Ok().json(&*data.graph.read().await)
+}
+
+#[instrument(level = Level::INFO, skip(data))]
+#[post("/events/{event}")]
+async fn post_event(event: Path<String>, data: Data<NetworkVisualizerData>) -> impl Responder {
+    let event = match event.as_str() {
+        "next" | "Next" => NetworkVisualizerEvent::Next,
+        event => return HttpResponse::NotFound().json(format!("unknown event: {event}")),
+    };
+    HttpResponse::Ok().json(data.activate(event).await)
+}
+
+#[get("/ws")]
+async fn ws(
+    req: HttpRequest,
+    body: Payload,
+    data: Data<NetworkVisualizerData>,
+) -> ::actix_web::Result<HttpResponse> {
+    let (response, mut session, _) = ::actix_ws::handle(&req, body)?;
+
+    // Send the current state first, then follow the updates
+    let mut messages = data.messages.subscribe();
+    let mut initial_messages = Vec::default();
+    if let Some(graph) = data.graph.read().await.clone() {
+        initial_messages.push(NetworkVisualizerMessage::Graph(graph));
+    }
+    for event in data.pending_events().await {
+        initial_messages.push(NetworkVisualizerMessage::Waiting(event));
+    }
+
+    spawn_local(async move {
+        for message in initial_messages {
+            if send_message(&mut session, &message).await.is_err() {
+                return;
+            }
+        }
+
+        loop {
+            match messages.recv().await {
+                Ok(message) => {
+                    if send_message(&mut session, &message).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("web visualizer session lagged: skipped {skipped} messages");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        session.close(None).await.ok();
+    });
+    Ok(response)
+}
+
+async fn send_message(
+    session: &mut ::actix_ws::Session,
+    message: &NetworkVisualizerMessage,
+) -> Result<()> {
+    let text = ::serde_json::to_string(message)?;
+    session.text(text).await.map_err(Into::into)
+}
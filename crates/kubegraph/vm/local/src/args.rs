@@ -1,6 +1,7 @@
 use clap::Parser;
 use kubegraph_api::{
     component::NetworkComponent,
+    plan::NetworkPlanPolicy,
     vm::{NetworkFallbackPolicy, NetworkVirtualMachine, NetworkVirtualMachineRestartPolicy},
 };
 use schemars::JsonSchema;
@@ -10,6 +11,10 @@ use serde::{Deserialize, Serialize};
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkArgs {
+    #[command(flatten)]
+    #[serde(default)]
+    pub cluster: crate::cluster::NetworkClusterArgs,
+
     #[command(flatten)]
     #[serde(default)]
     pub dependency_graph: <<crate::NetworkVirtualMachine as NetworkVirtualMachine>::DependencySolver as NetworkComponent>::Args,
@@ -56,6 +61,15 @@ pub struct NetworkVirtualMachineArgs {
     #[serde(default)]
     pub fallback_policy: NetworkFallbackPolicy,
 
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VM_PLAN_POLICY",
+        value_name = "POLICY",
+        default_value_t = NetworkPlanPolicy::default(),
+    )]
+    #[serde(default)]
+    pub plan_policy: NetworkPlanPolicy,
+
     #[arg(
         long,
         env = "KUBEGRAPH_VM_RESTART_POLICY",
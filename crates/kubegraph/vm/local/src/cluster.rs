@@ -0,0 +1,658 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use ark_core::{env::infer_string, signal::FunctionSignal};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::{Parser, ValueEnum};
+use k8s_openapi::{
+    api::coordination::v1::{Lease, LeaseSpec},
+    apimachinery::pkg::apis::meta::v1::MicroTime,
+};
+use kube::{
+    api::{ListParams, ObjectMeta, PostParams},
+    Api, ResourceExt,
+};
+use kubegraph_api::{graph::GraphScope, resource::NetworkResourceClient};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{sync::RwLock, task::JoinHandle, time::sleep};
+use tracing::{info, instrument, warn, Level};
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkClusterArgs {
+    /// Unique name of this replica; defaults to the hostname
+    #[arg(long, env = "KUBEGRAPH_VM_CLUSTER_IDENTITY", value_name = "NAME")]
+    #[serde(default)]
+    pub cluster_identity: Option<String>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VM_CLUSTER_LEASE_DURATION_SECONDS",
+        value_name = "SECONDS",
+        default_value_t = NetworkClusterArgs::default_cluster_lease_duration_seconds(),
+    )]
+    #[serde(default = "NetworkClusterArgs::default_cluster_lease_duration_seconds")]
+    pub cluster_lease_duration_seconds: u32,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VM_CLUSTER_LEASE_NAME",
+        value_name = "NAME",
+        default_value_t = NetworkClusterArgs::default_cluster_lease_name(),
+    )]
+    #[serde(default = "NetworkClusterArgs::default_cluster_lease_name")]
+    pub cluster_lease_name: String,
+
+    /// Namespace of the leases; defaults to the namespace of the kubernetes account
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VM_CLUSTER_LEASE_NAMESPACE",
+        value_name = "NAMESPACE"
+    )]
+    #[serde(default)]
+    pub cluster_lease_namespace: Option<String>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VM_CLUSTER_MODE",
+        value_enum,
+        value_name = "MODE",
+        default_value_t = NetworkClusterMode::default(),
+    )]
+    #[serde(default)]
+    pub cluster_mode: NetworkClusterMode,
+}
+
+impl Default for NetworkClusterArgs {
+    fn default() -> Self {
+        Self {
+            cluster_identity: None,
+            cluster_lease_duration_seconds: Self::default_cluster_lease_duration_seconds(),
+            cluster_lease_name: Self::default_cluster_lease_name(),
+            cluster_lease_namespace: None,
+            cluster_mode: NetworkClusterMode::default(),
+        }
+    }
+}
+
+impl NetworkClusterArgs {
+    const fn default_cluster_lease_duration_seconds() -> u32 {
+        15
+    }
+
+    fn default_cluster_lease_name() -> String {
+        "kubegraph-vm".into()
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkClusterMode {
+    /// Only the replica holding the lease handles the problems
+    Leader,
+    /// Each replica handles a consistent-hash subset of namespaces
+    Sharded,
+    /// A single replica handles all the problems
+    #[default]
+    Standalone,
+}
+
+#[derive(Clone)]
+pub(crate) struct NetworkCluster {
+    identity: String,
+    lease_duration: Duration,
+    mode: NetworkClusterMode,
+    state: Arc<RwLock<NetworkClusterState>>,
+}
+
+impl NetworkCluster {
+    pub(crate) fn new(args: &NetworkClusterArgs) -> Self {
+        let identity = args
+            .cluster_identity
+            .clone()
+            .or_else(|| infer_string("HOSTNAME").ok())
+            .unwrap_or_else(|| "kubegraph-vm".into());
+
+        Self {
+            identity,
+            lease_duration: Duration::from_secs(args.cluster_lease_duration_seconds.into()),
+            mode: args.cluster_mode,
+            state: Arc::default(),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    pub(crate) async fn is_responsible_for(&self, scope: &GraphScope) -> bool {
+        let now = Utc::now();
+        let state = self.state.read().await;
+        match self.mode {
+            NetworkClusterMode::Leader => state.is_alive(now),
+            NetworkClusterMode::Sharded => {
+                let namespace = scope.namespace.as_str();
+                state.is_alive(now)
+                    && find_owner(&state.members, namespace) == Some(self.identity.as_str())
+                    // Give the previous owner a chance to step down
+                    && (find_owner(&state.last_members, namespace)
+                        == Some(self.identity.as_str())
+                        || now - state.changed_at > self.lease_duration())
+            }
+            NetworkClusterMode::Standalone => true,
+        }
+    }
+
+    fn lease_duration(&self) -> TimeDelta {
+        TimeDelta::from_std(self.lease_duration).unwrap_or(TimeDelta::MAX)
+    }
+
+    /// Returns the renewed lease spec, or `None` if the lease is held by another live replica.
+    fn next_spec(&self, spec: &LeaseSpec, now: DateTime<Utc>) -> Option<LeaseSpec> {
+        let holder = spec.holder_identity.as_deref();
+        let is_held = holder == Some(self.identity.as_str());
+        let is_expired = match (&spec.renew_time, spec.lease_duration_seconds) {
+            (Some(MicroTime(renew_time)), Some(duration)) => {
+                *renew_time + TimeDelta::seconds(duration.into()) < now
+            }
+            _ => true,
+        };
+        if !is_held && !is_expired {
+            return None;
+        }
+
+        let transitions = spec.lease_transitions.unwrap_or_default();
+        Some(if is_held {
+            self.build_spec(now, spec.acquire_time.clone(), transitions)
+        } else {
+            self.build_spec(now, None, transitions + 1)
+        })
+    }
+
+    fn build_spec(
+        &self,
+        now: DateTime<Utc>,
+        acquire_time: Option<MicroTime>,
+        lease_transitions: i32,
+    ) -> LeaseSpec {
+        LeaseSpec {
+            acquire_time: Some(acquire_time.unwrap_or(MicroTime(now))),
+            holder_identity: Some(self.identity.clone()),
+            lease_duration_seconds: self.lease_duration.as_secs().try_into().ok(),
+            lease_transitions: Some(lease_transitions),
+            renew_time: Some(MicroTime(now)),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct NetworkClusterState {
+    changed_at: DateTime<Utc>,
+    expired_at: Option<DateTime<Utc>>,
+    last_members: Vec<String>,
+    members: Vec<String>,
+}
+
+impl NetworkClusterState {
+    fn is_alive(&self, now: DateTime<Utc>) -> bool {
+        self.expired_at
+            .map(|expired_at| now < expired_at)
+            .unwrap_or_default()
+    }
+
+    fn update_members(&mut self, members: Vec<String>, now: DateTime<Utc>) {
+        if self.members != members {
+            info!("Updated cluster members: {members:?}");
+            self.last_members = ::std::mem::replace(&mut self.members, members);
+            self.changed_at = now;
+        }
+    }
+}
+
+/// Selects the owner of the namespace using rendezvous hashing,
+/// so that only the namespaces of the joined or left members are moved.
+fn find_owner<'a>(members: &'a [String], namespace: &str) -> Option<&'a str> {
+    members
+        .iter()
+        .max_by_key(|&member| {
+            let hash = Sha256::new()
+                .chain_update(member.as_bytes())
+                .chain_update([0])
+                .chain_update(namespace.as_bytes())
+                .finalize();
+            let mut buf = [0; 8];
+            buf.copy_from_slice(&hash[..8]);
+            u64::from_be_bytes(buf)
+        })
+        .map(|member| member.as_str())
+}
+
+pub(crate) struct NetworkClusterWorker {
+    ctx: Arc<NetworkClusterContext>,
+    inner: JoinHandle<()>,
+}
+
+impl NetworkClusterWorker {
+    pub(crate) fn spawn(
+        signal: &FunctionSignal,
+        args: &NetworkClusterArgs,
+        cluster: &NetworkCluster,
+        kube: &impl NetworkResourceClient,
+    ) -> Option<Self> {
+        if matches!(cluster.mode, NetworkClusterMode::Standalone) {
+            return None;
+        }
+
+        let kube = kube.kube().clone();
+        let api = match args.cluster_lease_namespace.as_deref() {
+            Some(namespace) => Api::namespaced(kube, namespace),
+            None => Api::default_namespaced(kube),
+        };
+        let ctx = Arc::new(NetworkClusterContext {
+            api,
+            cluster: cluster.clone(),
+            lease_name: args.cluster_lease_name.clone(),
+        });
+        let signal = signal.clone();
+
+        Some(Self {
+            ctx: ctx.clone(),
+            inner: ::tokio::spawn(async move { ctx.loop_forever(signal).await }),
+        })
+    }
+
+    /// Stops the worker and releases the lease,
+    /// so that the other replicas can take over without waiting for its expiration.
+    pub(crate) async fn close(self) -> Result<()> {
+        self.inner.abort();
+        self.ctx.release().await
+    }
+}
+
+struct NetworkClusterContext {
+    api: Api<Lease>,
+    cluster: NetworkCluster,
+    lease_name: String,
+}
+
+impl NetworkClusterContext {
+    const LABEL_CLUSTER: &'static str = "kubegraph.ulagbulag.io/vm-cluster";
+
+    async fn loop_forever(self: Arc<Self>, signal: FunctionSignal) {
+        let NetworkCluster {
+            identity,
+            lease_duration,
+            mode,
+            ..
+        } = &self.cluster;
+        info!("Joining the cluster as {identity:?} ({mode:?})");
+
+        let interval = *lease_duration / 3;
+        loop {
+            if let Err(error) = self.renew().await {
+                warn!("failed to renew the cluster lease: {error}");
+            }
+            if signal.is_terminating() {
+                break;
+            }
+            sleep(interval).await;
+        }
+
+        if let Err(error) = self.release().await {
+            warn!("failed to release the cluster lease: {error}");
+        }
+        info!("Stopped the cluster worker");
+    }
+
+    fn name(&self) -> Option<String> {
+        match self.cluster.mode {
+            NetworkClusterMode::Leader => Some(self.lease_name.clone()),
+            NetworkClusterMode::Sharded => {
+                Some(format!("{}-{}", self.lease_name, self.cluster.identity))
+            }
+            NetworkClusterMode::Standalone => None,
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn renew(&self) -> Result<()> {
+        let now = Utc::now();
+        let Some(name) = self.name() else {
+            return Ok(());
+        };
+        let is_acquired = self.try_acquire(&name, now).await?;
+
+        let members = match self.cluster.mode {
+            NetworkClusterMode::Sharded if is_acquired => self.list_members(now).await?,
+            _ => Vec::default(),
+        };
+
+        let mut state = self.cluster.state.write().await;
+        let was_alive = state.is_alive(now);
+        state.expired_at = if is_acquired {
+            Some(now + self.cluster.lease_duration())
+        } else {
+            None
+        };
+        state.update_members(members, now);
+
+        match (was_alive, is_acquired) {
+            (false, true) => info!("Acquired the cluster lease: {name}"),
+            (true, false) => warn!("Lost the cluster lease: {name}"),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    async fn try_acquire(&self, name: &str, now: DateTime<Utc>) -> Result<bool> {
+        let pp = PostParams::default();
+
+        let lease = match self
+            .api
+            .get_opt(name)
+            .await
+            .map_err(|error| anyhow!("failed to get lease {name}: {error}"))?
+        {
+            Some(lease) => lease,
+            None => {
+                let lease = Lease {
+                    metadata: ObjectMeta {
+                        name: Some(name.into()),
+                        labels: Some(
+                            [(Self::LABEL_CLUSTER.into(), self.lease_name.clone())].into(),
+                        ),
+                        ..Default::default()
+                    },
+                    spec: Some(self.cluster.build_spec(now, None, 0)),
+                };
+                return match self.api.create(&pp, &lease).await {
+                    Ok(_) => Ok(true),
+                    Err(::kube::Error::Api(error)) if error.code == 409 => Ok(false),
+                    Err(error) => Err(anyhow!("failed to create lease {name}: {error}")),
+                };
+            }
+        };
+
+        let spec = lease.spec.clone().unwrap_or_default();
+        let Some(spec) = self.cluster.next_spec(&spec, now) else {
+            return Ok(false);
+        };
+        let lease = Lease {
+            metadata: lease.metadata,
+            spec: Some(spec),
+        };
+
+        // NOTE: the resource version guards against concurrent acquisitions
+        match self.api.replace(name, &pp, &lease).await {
+            Ok(_) => Ok(true),
+            Err(::kube::Error::Api(error)) if error.code == 409 => Ok(false),
+            Err(error) => Err(anyhow!("failed to renew lease {name}: {error}")),
+        }
+    }
+
+    /// Gives up the lease if held, clearing its holder.
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn release(&self) -> Result<()> {
+        let Some(name) = self.name() else {
+            return Ok(());
+        };
+
+        // Step down first
+        self.cluster.state.write().await.expired_at = None;
+
+        let Some(lease) = self
+            .api
+            .get_opt(&name)
+            .await
+            .map_err(|error| anyhow!("failed to get lease {name}: {error}"))?
+        else {
+            return Ok(());
+        };
+        let spec = lease.spec.clone().unwrap_or_default();
+        if spec.holder_identity.as_deref() != Some(self.cluster.identity.as_str()) {
+            return Ok(());
+        }
+
+        let lease = Lease {
+            metadata: lease.metadata,
+            spec: Some(LeaseSpec {
+                acquire_time: None,
+                holder_identity: None,
+                renew_time: None,
+                ..spec
+            }),
+        };
+
+        // NOTE: the resource version guards against releasing the lease of the others
+        let pp = PostParams::default();
+        match self.api.replace(&name, &pp, &lease).await {
+            Ok(_) => {
+                info!("Released the cluster lease: {name}");
+                Ok(())
+            }
+            Err(::kube::Error::Api(error)) if error.code == 409 => Ok(()),
+            Err(error) => Err(anyhow!("failed to release lease {name}: {error}")),
+        }
+    }
+
+    async fn list_members(&self, now: DateTime<Utc>) -> Result<Vec<String>> {
+        let lp = ListParams::default().labels(&format!(
+            "{key}={value}",
+            key = Self::LABEL_CLUSTER,
+            value = &self.lease_name,
+        ));
+        let leases = self
+            .api
+            .list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list cluster leases: {error}"))?;
+
+        let mut members: Vec<_> = leases
+            .items
+            .into_iter()
+            .filter(|lease| lease.name_any() != self.lease_name)
+            .filter_map(|lease| {
+                let spec = lease.spec?;
+                let MicroTime(renew_time) = spec.renew_time?;
+                let duration = TimeDelta::seconds(spec.lease_duration_seconds?.into());
+                if renew_time + duration < now {
+                    None
+                } else {
+                    spec.holder_identity
+                }
+            })
+            .collect();
+        members.sort();
+        members.dedup();
+        Ok(members)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_cluster(identity: &str, mode: NetworkClusterMode) -> NetworkCluster {
+        NetworkCluster::new(&NetworkClusterArgs {
+            cluster_identity: Some(identity.into()),
+            cluster_mode: mode,
+            ..Default::default()
+        })
+    }
+
+    fn members(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.into()).collect()
+    }
+
+    fn namespaces() -> Vec<String> {
+        (0..256).map(|index| format!("namespace-{index}")).collect()
+    }
+
+    #[test]
+    fn find_owner_empty() {
+        assert_eq!(find_owner(&[], "default"), None);
+    }
+
+    #[test]
+    fn find_owner_is_stable() {
+        let a = members(&["a", "b", "c"]);
+        let b = members(&["c", "a", "b"]);
+
+        for namespace in namespaces() {
+            // the owner should not depend on the order of the members
+            assert_eq!(find_owner(&a, &namespace), find_owner(&b, &namespace));
+        }
+    }
+
+    #[test]
+    fn find_owner_is_balanced() {
+        let members = members(&["a", "b", "c"]);
+        let namespaces = namespaces();
+
+        for member in &members {
+            let count = namespaces
+                .iter()
+                .filter(|namespace| find_owner(&members, namespace) == Some(member.as_str()))
+                .count();
+            assert!(count > 0, "{member} owns no namespaces");
+        }
+    }
+
+    #[test]
+    fn find_owner_moves_only_joined_or_left() {
+        let last = members(&["a", "b", "c"]);
+        let joined = members(&["a", "b", "c", "d"]);
+        let left = members(&["a", "c"]);
+
+        for namespace in namespaces() {
+            let owner = find_owner(&last, &namespace);
+
+            // only the namespaces taken by the new member are moved
+            let new_owner = find_owner(&joined, &namespace);
+            assert!(new_owner == owner || new_owner == Some("d"));
+
+            // only the namespaces of the left member are moved
+            let new_owner = find_owner(&left, &namespace);
+            assert!(new_owner == owner || owner == Some("b"));
+        }
+    }
+
+    #[test]
+    fn next_spec_acquire_released() {
+        let cluster = new_cluster("a", NetworkClusterMode::Leader);
+        let now = Utc::now();
+
+        let spec = cluster
+            .next_spec(&LeaseSpec::default(), now)
+            .expect("a released lease should be acquired");
+        assert_eq!(spec.holder_identity.as_deref(), Some("a"));
+        assert_eq!(spec.acquire_time, Some(MicroTime(now)));
+        assert_eq!(spec.renew_time, Some(MicroTime(now)));
+        assert_eq!(spec.lease_transitions, Some(1));
+    }
+
+    #[test]
+    fn next_spec_renew() {
+        let cluster = new_cluster("a", NetworkClusterMode::Leader);
+        let acquired_at = Utc::now();
+        let now = acquired_at + TimeDelta::seconds(5);
+
+        let spec = cluster.build_spec(acquired_at, None, 3);
+        let spec = cluster
+            .next_spec(&spec, now)
+            .expect("a held lease should be renewed");
+        assert_eq!(spec.holder_identity.as_deref(), Some("a"));
+        // renewing keeps the acquire time and the transitions
+        assert_eq!(spec.acquire_time, Some(MicroTime(acquired_at)));
+        assert_eq!(spec.renew_time, Some(MicroTime(now)));
+        assert_eq!(spec.lease_transitions, Some(3));
+
+        // a held lease is renewed even if it has been expired
+        let now = acquired_at + TimeDelta::hours(1);
+        assert!(cluster.next_spec(&spec, now).is_some());
+    }
+
+    #[test]
+    fn next_spec_steal_expired() {
+        let owner = new_cluster("a", NetworkClusterMode::Leader);
+        let cluster = new_cluster("b", NetworkClusterMode::Leader);
+        let acquired_at = Utc::now();
+        let spec = owner.build_spec(acquired_at, None, 1);
+
+        // the lease of a live replica cannot be stolen
+        let now = acquired_at + owner.lease_duration();
+        assert_eq!(cluster.next_spec(&spec, now), None);
+
+        // but an expired one can be
+        let now = acquired_at + owner.lease_duration() + TimeDelta::seconds(1);
+        let spec = cluster
+            .next_spec(&spec, now)
+            .expect("an expired lease should be stolen");
+        assert_eq!(spec.holder_identity.as_deref(), Some("b"));
+        assert_eq!(spec.acquire_time, Some(MicroTime(now)));
+        assert_eq!(spec.lease_transitions, Some(2));
+    }
+
+    #[::tokio::test]
+    async fn is_responsible_for_sharded() {
+        let cluster = new_cluster("a", NetworkClusterMode::Sharded);
+        let now = Utc::now();
+        let scopes: Vec<_> = namespaces()
+            .into_iter()
+            .map(|namespace| GraphScope {
+                namespace,
+                name: "problem".into(),
+            })
+            .collect();
+
+        // not responsible for anything before joining the cluster
+        for scope in &scopes {
+            assert!(!cluster.is_responsible_for(scope).await);
+        }
+
+        {
+            let mut state = cluster.state.write().await;
+            state.expired_at = Some(now + cluster.lease_duration());
+            state.update_members(members(&["a"]), now);
+            state.update_members(members(&["a", "b"]), now);
+        }
+
+        for scope in &scopes {
+            // wait for the previous owners to step down
+            let owner = find_owner(&members(&["a", "b"]), &scope.namespace);
+            let last_owner = find_owner(&members(&["a"]), &scope.namespace);
+            assert_eq!(
+                cluster.is_responsible_for(scope).await,
+                owner == Some("a") && last_owner == Some("a"),
+            );
+        }
+    }
+
+    #[::tokio::test]
+    async fn is_responsible_for_standalone() {
+        let cluster = new_cluster("a", NetworkClusterMode::Standalone);
+        let scope = GraphScope {
+            namespace: "default".into(),
+            name: "problem".into(),
+        };
+        assert!(cluster.is_responsible_for(&scope).await);
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use clap::{Parser, ValueEnum};
 use kubegraph_api::{
     component::NetworkComponent,
@@ -47,6 +48,11 @@ pub struct NetworkGraphDBArgs {
     #[command(flatten)]
     #[serde(default)]
     pub memory: <::kubegraph_graph_memory::NetworkGraphDB as NetworkComponent>::Args,
+
+    #[cfg(feature = "graph-object-store")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub object_store: <::kubegraph_graph_object_store::NetworkGraphDB as NetworkComponent>::Args,
 }
 
 #[derive(
@@ -72,6 +78,8 @@ pub enum NetworkGraphDBType {
     #[cfg(feature = "graph-memory")]
     #[default]
     Memory,
+    #[cfg(feature = "graph-object-store")]
+    ObjectStore,
 }
 
 #[derive(Clone)]
@@ -80,6 +88,8 @@ pub enum NetworkGraphDB {
     Local(::kubegraph_graph_local::NetworkGraphDB),
     #[cfg(feature = "graph-memory")]
     Memory(::kubegraph_graph_memory::NetworkGraphDB),
+    #[cfg(feature = "graph-object-store")]
+    ObjectStore(::kubegraph_graph_object_store::NetworkGraphDB),
 }
 
 #[async_trait]
@@ -97,6 +107,8 @@ impl NetworkComponent for NetworkGraphDB {
             local,
             #[cfg(feature = "graph-memory")]
             memory,
+            #[cfg(feature = "graph-object-store")]
+            object_store,
         } = args;
 
         match graph_db {
@@ -108,6 +120,11 @@ impl NetworkComponent for NetworkGraphDB {
             NetworkGraphDBType::Memory => Ok(Self::Memory(
                 ::kubegraph_graph_memory::NetworkGraphDB::try_new(memory, signal).await?,
             )),
+            #[cfg(feature = "graph-object-store")]
+            NetworkGraphDBType::ObjectStore => Ok(Self::ObjectStore(
+                ::kubegraph_graph_object_store::NetworkGraphDB::try_new(object_store, signal)
+                    .await?,
+            )),
         }
     }
 }
@@ -121,6 +138,24 @@ impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
             Self::Local(runtime) => runtime.get(scope).await,
             #[cfg(feature = "graph-memory")]
             Self::Memory(runtime) => runtime.get(scope).await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.get(scope).await,
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        match self {
+            #[cfg(feature = "graph-local")]
+            Self::Local(runtime) => runtime.get_at(scope, at).await,
+            #[cfg(feature = "graph-memory")]
+            Self::Memory(runtime) => runtime.get_at(scope, at).await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.get_at(scope, at).await,
         }
     }
 
@@ -131,6 +166,8 @@ impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
             Self::Local(runtime) => runtime.insert(graph).await,
             #[cfg(feature = "graph-memory")]
             Self::Memory(runtime) => runtime.insert(graph).await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.insert(graph).await,
         }
     }
 
@@ -141,6 +178,8 @@ impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
             Self::Local(runtime) => runtime.list(filter).await,
             #[cfg(feature = "graph-memory")]
             Self::Memory(runtime) => runtime.list(filter).await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.list(filter).await,
         }
     }
 
@@ -151,6 +190,8 @@ impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
             Self::Local(runtime) => runtime.remove(scope).await,
             #[cfg(feature = "graph-memory")]
             Self::Memory(runtime) => runtime.remove(scope).await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.remove(scope).await,
         }
     }
 
@@ -161,6 +202,8 @@ impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
             Self::Local(runtime) => runtime.close().await,
             #[cfg(feature = "graph-memory")]
             Self::Memory(runtime) => runtime.close().await,
+            #[cfg(feature = "graph-object-store")]
+            Self::ObjectStore(runtime) => runtime.close().await,
         }
     }
 }
@@ -1,4 +1,5 @@
 mod args;
+mod cluster;
 mod dependency;
 mod graph;
 mod reloader;
@@ -8,6 +9,11 @@ mod solver;
 mod trader;
 mod visualizer;
 
+pub use self::{
+    args::NetworkArgs,
+    visualizer::{NetworkVisualizerArgs, NetworkVisualizerType},
+};
+
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -16,6 +22,8 @@ use async_trait::async_trait;
 use clap::Parser;
 use kubegraph_api::{
     component::NetworkComponent,
+    graph::GraphScope,
+    plan::NetworkPlanPolicy,
     vm::{NetworkFallbackPolicy, NetworkVirtualMachineExt, NetworkVirtualMachineRestartPolicy},
 };
 use tokio::{sync::Mutex, task::JoinHandle};
@@ -23,6 +31,8 @@ use tracing::{instrument, Level};
 
 #[derive(Clone)]
 pub struct NetworkVirtualMachine {
+    cluster: self::cluster::NetworkCluster,
+    cluster_worker: Arc<Mutex<Option<self::cluster::NetworkClusterWorker>>>,
     dependency_graph: self::dependency::NetworkDependencyGraph,
     args: self::args::NetworkVirtualMachineArgs,
     graph_db: self::graph::NetworkGraphDB,
@@ -46,6 +56,7 @@ impl NetworkComponent for NetworkVirtualMachine {
     ) -> Result<Self> {
         // Step 1. Initialize components
         let self::args::NetworkArgs {
+            cluster,
             dependency_graph,
             graph_db,
             resource_db,
@@ -57,6 +68,8 @@ impl NetworkComponent for NetworkVirtualMachine {
         } = args;
         let vm = Self {
             args: vm,
+            cluster: self::cluster::NetworkCluster::new(&cluster),
+            cluster_worker: Arc::new(Mutex::new(None)),
             dependency_graph: self::dependency::NetworkDependencyGraph::try_new(
                 dependency_graph,
                 signal,
@@ -73,6 +86,12 @@ impl NetworkComponent for NetworkVirtualMachine {
         };
 
         // Step 2. Spawn workers
+        *vm.cluster_worker.lock().await = self::cluster::NetworkClusterWorker::spawn(
+            signal,
+            &cluster,
+            &vm.cluster,
+            &vm.resource_db,
+        );
         vm.resource_worker
             .lock()
             .await
@@ -133,8 +152,20 @@ impl ::kubegraph_api::vm::NetworkVirtualMachine for NetworkVirtualMachine {
         self.args.restart_policy
     }
 
+    fn plan_policy(&self) -> NetworkPlanPolicy {
+        self.args.plan_policy
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn is_responsible_for(&self, scope: &GraphScope) -> bool {
+        self.cluster.is_responsible_for(scope).await
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn close_workers(&self) -> Result<()> {
+        if let Some(worker) = self.cluster_worker.lock().await.take() {
+            worker.close().await?;
+        }
         if let Some(worker) = self.resource_worker.lock().await.take() {
             worker.abort();
         }
@@ -246,6 +246,9 @@ impl NetworkConnectorDBWorker {
                     #[cfg(feature = "connector-http")]
                     ::kubegraph_connector_http::NetworkConnector::default()
                         .loop_forever(vm.clone()),
+                    #[cfg(feature = "connector-kubernetes")]
+                    ::kubegraph_connector_kubernetes::NetworkConnector::default()
+                        .loop_forever(vm.clone()),
                     #[cfg(feature = "connector-local")]
                     ::kubegraph_connector_local::NetworkConnector::default()
                         .loop_forever(vm.clone()),
@@ -5,6 +5,8 @@ pub mod user_auth_binding;
 pub mod user_box_binding;
 pub mod user_box_quota;
 pub mod user_box_quota_binding;
+pub mod user_box_reservation;
+pub mod user_group;
 pub mod user_role;
 pub mod user_role_binding;
 pub mod user_session;
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use ark_core_k8s::data::EmailAddress;
+use ark_core_k8s::data::{EmailAddress, Quantity};
 use chrono::{DateTime, Utc};
 use kube::{CustomResource, ResourceExt};
 use schemars::JsonSchema;
@@ -70,11 +70,39 @@ impl UserCrd {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserStatus {
+    /// The group claims of the user, as of the last login
+    #[serde(default)]
+    pub group_claims: Vec<String>,
+    /// The last accounted time of the usage, keyed by the box
+    #[serde(default)]
+    pub accounted: BTreeMap<String, DateTime<Utc>>,
     pub last_box: Option<String>,
     pub last_updated: DateTime<Utc>,
+    /// The monthly usage, keyed by the month (e.g. `2024-01`)
+    #[serde(default)]
+    pub usage: BTreeMap<String, UserUsage>,
+}
+
+/// The resources consumed by a user in a month.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUsage {
+    #[serde(default)]
+    pub box_hours: f64,
+    #[serde(default)]
+    pub gpu_hours: f64,
+    /// The peak amount of the storage bytes
+    #[serde(default)]
+    pub storage: Quantity,
+}
+
+impl UserUsage {
+    pub fn month_of(timestamp: DateTime<Utc>) -> String {
+        timestamp.format("%Y-%m").to_string()
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
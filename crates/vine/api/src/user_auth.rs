@@ -1,5 +1,9 @@
+use std::fmt;
+
 use anyhow::{anyhow, Result};
 use ark_core_k8s::data::{EmailAddress, Url};
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::SecretKeySelector;
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -8,7 +12,12 @@ use uuid::Uuid;
 
 use crate::{user::UserSpec, user_box_quota::UserBoxQuotaSpec};
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+/// An auth provider to sign in with.
+///
+/// NOTE: SAML is not supported natively, as it requires verifying the XML signatures;
+/// please bridge the SAML IdPs with an OIDC broker, such as Keycloak or Dex,
+/// and register the broker as an `OIDC` provider.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
     group = "vine.ulagbulag.io",
     version = "v1alpha1",
@@ -35,6 +44,67 @@ pub enum UserAuthSpec {
         oauth2: UserAuthOAuth2Common,
         issuer: Url,
     },
+    /// Binds to the LDAP directory as the user
+    ///
+    /// NOTE: each user id should be bound to a user with a `UserAuthBinding`
+    Ldap {
+        url: Url,
+        /// DN to search the user with, e.g. `cn=admin,dc=example,dc=com`
+        #[serde(default)]
+        bind_dn: Option<String>,
+        #[serde(default)]
+        bind_password: Option<SecretKeySelector>,
+        base_dn: String,
+        /// Search filter, where `{username}` is replaced with the escaped user name
+        #[serde(default = "UserAuthSpec::default_ldap_user_filter")]
+        user_filter: String,
+        /// Attribute to be used as the user id
+        #[serde(default = "UserAuthSpec::default_ldap_user_id_attribute")]
+        user_id_attribute: String,
+        /// Attribute to be used as the group claims, e.g. `memberOf`
+        #[serde(default)]
+        group_attribute: Option<String>,
+    },
+    /// Verifies the argon2 password hashes, stored in a secret of the `vine` namespace by user ids
+    ///
+    /// NOTE: each user id should be bound to a user with a `UserAuthBinding`
+    Local { secret_name: String },
+}
+
+impl UserAuthSpec {
+    pub const fn is_password_based(&self) -> bool {
+        match self {
+            Self::OIDC { .. } => false,
+            Self::Ldap { .. } | Self::Local { .. } => true,
+        }
+    }
+
+    fn default_ldap_user_filter() -> String {
+        "(uid={username})".into()
+    }
+
+    fn default_ldap_user_id_attribute() -> String {
+        "uid".into()
+    }
+}
+
+/// Credentials for the password-based providers.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserAuthCredentials {
+    /// UserAuth name
+    pub auth: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl fmt::Debug for UserAuthCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserAuthCredentials")
+            .field("auth", &self.auth)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -66,9 +136,16 @@ pub struct UserAuthPayload {
     /// User roles
     #[serde(default)]
     roles: String,
+    /// User groups
+    #[serde(default)]
+    groups: Vec<String>,
 }
 
 impl UserAuthPayload {
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
     pub fn primary_key(&self) -> Result<String> {
         fn encode(s: &str) -> String {
             s.to_lowercase()
@@ -135,6 +212,10 @@ pub enum UserSessionError {
     NodeNotInCluster,
     #[error("This node is reserved to other user.")]
     NodeReserved,
+    #[error("This node is reserved to other user until {until}.")]
+    NodeReservedUntil { until: DateTime<Utc> },
+    #[error("This session would exceed the monthly {resource} budget.")]
+    QuotaExceeded { resource: String },
     #[error("This node does not meet quota requirements. Please contact the administrator.")]
     QuotaMismatched,
 }
@@ -148,14 +229,26 @@ impl From<UserAuthError> for UserSessionError {
 #[derive(Clone, Debug, Error, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase", tag = "status", content = "data")]
 pub enum UserAuthError {
+    #[error("This sign-in method is not registered. Please contact the administrator.")]
+    AuthProviderNotFound,
+    #[error("This sign-in method does not support passwords.")]
+    AuthProviderNotSupported,
+    #[error("This sign-in method is not available. Please contact the administrator.")]
+    AuthProviderUnavailable,
     #[error("Malformed authorization token. Please contact the administrator.")]
     AuthorizationTokenMalformed,
     #[error("Missing authorization token. Please contact the administrator.")]
     AuthorizationTokenNotFound,
+    #[error("Invalid user name or password.")]
+    CredentialsInvalid,
+    #[error("Too many failed sign-in attempts. Please try again later.")]
+    LoginThrottled,
     #[error("This user has no permission to sign in. Please contact the administrator.")]
     NamespaceNotAllowed,
     #[error("Missing namespace token. Please contact the administrator.")]
     NamespaceTokenMalformed,
+    #[error("This user has no permission to {0}. Please contact the administrator.")]
+    PermissionDenied(String),
     #[error("Malformed primary key. Please contact the administrator.")]
     PrimaryKeyMalformed,
     #[error("This user is not an admin. Please contact the administrator.")]
@@ -1,4 +1,7 @@
-use ark_core_k8s::data::ImagePullPolicy;
+use std::{str::FromStr, time::Duration};
+
+use ark_core_k8s::data::{ImagePullPolicy, Quantity};
+use duration_string::DurationString;
 use k8s_openapi::api::core::v1::{ContainerPort, EnvVar, ResourceRequirements, ServiceSpec};
 use kube::{api::ObjectMeta, CustomResource};
 use schemars::JsonSchema;
@@ -33,11 +36,15 @@ use strum::{Display, EnumString};
 )]
 #[serde(rename_all = "camelCase")]
 pub struct UserBoxQuotaSpec {
+    #[serde(default)]
+    pub budget: UserBoxQuotaBudgetSpec,
     #[serde(default)]
     pub compute: ResourceRequirements,
     #[serde(default)]
     pub desktop: UserBoxQuotaDesktopSpec,
     #[serde(default)]
+    pub session: UserBoxQuotaSessionSpec,
+    #[serde(default)]
     pub ssh: UserBoxQuotaSshSpec,
     #[serde(default)]
     pub storage: ResourceRequirements,
@@ -45,6 +52,18 @@ pub struct UserBoxQuotaSpec {
     pub storage_class_name: Option<String>,
 }
 
+/// The monthly budget of the resources, unlimited if not given.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoxQuotaBudgetSpec {
+    #[serde(default)]
+    pub box_hours: Option<f64>,
+    #[serde(default)]
+    pub gpu_hours: Option<f64>,
+    #[serde(default)]
+    pub storage: Option<Quantity>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserBoxQuotaDesktopSpec {
@@ -391,6 +410,33 @@ pub enum UserBoxQuotaDesktopVolumeKind {
     Temporary,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoxQuotaSessionSpec {
+    /// Logs out the session when no activity has been reported for the duration
+    #[serde(default)]
+    pub idle_timeout: Option<String>,
+    /// Logs out the session after the duration since login
+    #[serde(default)]
+    pub max_duration: Option<String>,
+}
+
+impl UserBoxQuotaSessionSpec {
+    pub fn idle_timeout(&self) -> Result<Option<Duration>, ::duration_string::Error> {
+        Self::parse_duration(self.idle_timeout.as_deref())
+    }
+
+    pub fn max_duration(&self) -> Result<Option<Duration>, ::duration_string::Error> {
+        Self::parse_duration(self.max_duration.as_deref())
+    }
+
+    fn parse_duration(value: Option<&str>) -> Result<Option<Duration>, ::duration_string::Error> {
+        value
+            .map(|value| DurationString::from_str(value).map(Into::into))
+            .transpose()
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserBoxQuotaSshSpec {
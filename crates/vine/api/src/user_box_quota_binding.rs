@@ -3,6 +3,8 @@ use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::user_group::UserBindingSubject;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
     group = "vine.ulagbulag.io",
@@ -16,6 +18,12 @@ use serde::{Deserialize, Serialize};
         "description": "User name",
         "jsonPath": ".spec.user"
     }"#,
+    printcolumn = r#"{
+        "name": "group",
+        "type": "string",
+        "description": "UserGroup name",
+        "jsonPath": ".spec.group"
+    }"#,
     printcolumn = r#"{
         "name": "quota",
         "type": "string",
@@ -43,8 +51,21 @@ use serde::{Deserialize, Serialize};
 )]
 #[serde(rename_all = "camelCase")]
 pub struct UserBoxQuotaBindingSpec<Quota = String> {
-    pub user: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
     pub quota: Quota,
     #[serde(default)]
     pub expired_timestamp: Option<DateTime<Utc>>,
 }
+
+impl<Quota> UserBindingSubject for UserBoxQuotaBindingSpec<Quota> {
+    fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+}
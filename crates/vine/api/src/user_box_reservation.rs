@@ -0,0 +1,461 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::user_group::UserBindingSubject;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "vine.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "UserBoxReservation",
+    root = "UserBoxReservationCrd",
+    shortname = "ubr",
+    printcolumn = r#"{
+        "name": "user",
+        "type": "string",
+        "description": "User name",
+        "jsonPath": ".spec.user"
+    }"#,
+    printcolumn = r#"{
+        "name": "group",
+        "type": "string",
+        "description": "UserGroup name",
+        "jsonPath": ".spec.group"
+    }"#,
+    printcolumn = r#"{
+        "name": "box",
+        "type": "string",
+        "description": "Box name",
+        "jsonPath": ".spec.box"
+    }"#,
+    printcolumn = r#"{
+        "name": "start-at",
+        "type": "date",
+        "description": "start time of the first slot",
+        "jsonPath": ".spec.startTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "end-at",
+        "type": "date",
+        "description": "end time of the first slot",
+        "jsonPath": ".spec.endTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "recurrence",
+        "type": "string",
+        "description": "recurrence frequency",
+        "jsonPath": ".spec.recurrence.frequency"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "reservation version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoxReservationSpec {
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    /// A specific box to be reserved
+    #[serde(default)]
+    pub r#box: Option<String>,
+    /// A class of boxes to be reserved, selected by the box labels
+    #[serde(default)]
+    pub box_selector: Option<LabelSelector>,
+    pub start_timestamp: DateTime<Utc>,
+    pub end_timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub recurrence: Option<UserBoxReservationRecurrence>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl UserBindingSubject for UserBoxReservationSpec {
+    fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+}
+
+impl UserBoxReservationSpec {
+    /// The maximum number of the slots to be compared on conflict detection.
+    const MAX_SLOTS: usize = 1_000;
+
+    pub fn duration(&self) -> TimeDelta {
+        self.end_timestamp - self.start_timestamp
+    }
+
+    /// Returns the slot which is active at the given time, if any.
+    pub fn slot_at(&self, timestamp: DateTime<Utc>) -> Option<UserBoxReservationSlot> {
+        self.slots_between(timestamp, timestamp + TimeDelta::nanoseconds(1))
+            .next()
+    }
+
+    /// Returns the slots which overlap with the given period, in order.
+    pub fn slots_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> impl '_ + Iterator<Item = UserBoxReservationSlot> {
+        let duration = self.duration();
+        let first = match self.recurrence.as_ref() {
+            // skip the slots which end before the period
+            Some(recurrence) => {
+                let elapsed = start - self.start_timestamp - duration;
+                match (elapsed.num_seconds(), recurrence.period().num_seconds()) {
+                    (elapsed, period) if elapsed > 0 && period > 0 => (elapsed / period) as usize,
+                    _ => 0,
+                }
+            }
+            None => 0,
+        };
+
+        self.slots()
+            .skip(first)
+            .take_while(move |slot| slot.start < end)
+            .filter(move |slot| slot.end > start)
+    }
+
+    /// Returns all the slots, in order.
+    pub fn slots(&self) -> impl '_ + Iterator<Item = UserBoxReservationSlot> {
+        let duration = self.duration();
+        let (period, count, until) = match self.recurrence.as_ref() {
+            Some(recurrence) => (
+                Some(recurrence.period()),
+                recurrence.count.map(|count| count as usize),
+                recurrence.until,
+            ),
+            None => (None, Some(1), None),
+        };
+
+        (0..)
+            .map_while(move |index: i32| {
+                let start = match period {
+                    Some(period) => self.start_timestamp + period * index,
+                    None if index == 0 => self.start_timestamp,
+                    None => return None,
+                };
+                Some(UserBoxReservationSlot {
+                    start,
+                    end: start + duration,
+                })
+            })
+            .take(count.unwrap_or(usize::MAX))
+            .take_while(move |slot| until.map_or(true, |until| slot.start < until))
+    }
+
+    /// Returns the end time of the last slot, or `None` if the reservation repeats forever.
+    pub fn end_of_slots(&self) -> Option<DateTime<Utc>> {
+        let recurrence = match self.recurrence.as_ref() {
+            Some(recurrence) => recurrence,
+            None => return Some(self.end_timestamp),
+        };
+        let period = recurrence.period();
+
+        // the slots should start before the time
+        let count_until = recurrence.until.map(|until| {
+            let elapsed = (until - self.start_timestamp).num_milliseconds().max(0) as u64;
+            elapsed.div_ceil(period.num_milliseconds() as u64)
+        });
+        let count = match (recurrence.count.map(u64::from), count_until) {
+            (Some(count), Some(count_until)) => count.min(count_until),
+            (count, count_until) => count.or(count_until)?,
+        };
+
+        let index = count.saturating_sub(1).try_into().ok()?;
+        let elapsed = period.checked_mul(index)?.checked_add(&self.duration())?;
+        self.start_timestamp.checked_add_signed(elapsed)
+    }
+
+    /// Returns `true` if any slot of the reservations overlaps in time.
+    ///
+    /// NOTE: Only the first slots of each are compared if both reservations repeat
+    /// many times but not forever.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self.period_forever(), other.period_forever()) {
+            (Some(period), Some(other_period)) => {
+                // the slots repeat their offsets by the GCD of the periods
+                let period = gcd(period.num_milliseconds(), other_period.num_milliseconds());
+                let offset = (self.start_timestamp - other.start_timestamp)
+                    .num_milliseconds()
+                    .rem_euclid(period);
+                offset < other.duration().num_milliseconds()
+                    || offset > period - self.duration().num_milliseconds()
+            }
+            _ => self.overlaps_slots(other) || other.overlaps_slots(self),
+        }
+    }
+
+    fn overlaps_slots(&self, other: &Self) -> bool {
+        self.slots()
+            .take(Self::MAX_SLOTS)
+            .any(|slot| other.slots_between(slot.start, slot.end).next().is_some())
+    }
+
+    /// Returns the period of the slots if the reservation repeats forever.
+    fn period_forever(&self) -> Option<TimeDelta> {
+        self.recurrence
+            .as_ref()
+            .filter(|recurrence| recurrence.count.is_none() && recurrence.until.is_none())
+            .map(|recurrence| recurrence.period())
+    }
+}
+
+const fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoxReservationRecurrence {
+    pub frequency: UserBoxReservationFrequency,
+    #[serde(default = "UserBoxReservationRecurrence::default_interval")]
+    pub interval: u32,
+    /// The maximum number of the slots
+    #[serde(default)]
+    pub count: Option<u32>,
+    /// No slots start after the time
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl UserBoxReservationRecurrence {
+    const fn default_interval() -> u32 {
+        1
+    }
+
+    pub fn period(&self) -> TimeDelta {
+        let interval = self.interval.clamp(1, i32::MAX as u32) as i32;
+        match self.frequency {
+            UserBoxReservationFrequency::Daily => TimeDelta::days(1) * interval,
+            UserBoxReservationFrequency::Weekly => TimeDelta::weeks(1) * interval,
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum UserBoxReservationFrequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoxReservationSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn reservation(
+        start: &str,
+        end: &str,
+        recurrence: Option<UserBoxReservationRecurrence>,
+    ) -> UserBoxReservationSpec {
+        UserBoxReservationSpec {
+            user: Some("user".into()),
+            group: None,
+            r#box: Some("box".into()),
+            box_selector: None,
+            start_timestamp: timestamp(start),
+            end_timestamp: timestamp(end),
+            recurrence,
+            description: None,
+        }
+    }
+
+    fn recurrence(
+        frequency: UserBoxReservationFrequency,
+        interval: u32,
+        count: Option<u32>,
+    ) -> Option<UserBoxReservationRecurrence> {
+        Some(UserBoxReservationRecurrence {
+            frequency,
+            interval,
+            count,
+            until: None,
+        })
+    }
+
+    fn slot(start: &str, end: &str) -> UserBoxReservationSlot {
+        UserBoxReservationSlot {
+            start: timestamp(start),
+            end: timestamp(end),
+        }
+    }
+
+    #[test]
+    fn slots_once() {
+        let spec = reservation("2024-01-01T09:00:00Z", "2024-01-01T12:00:00Z", None);
+
+        assert_eq!(
+            spec.slots().collect::<Vec<_>>(),
+            [slot("2024-01-01T09:00:00Z", "2024-01-01T12:00:00Z")],
+        );
+        assert_eq!(
+            spec.slot_at(timestamp("2024-01-01T10:00:00Z")),
+            Some(slot("2024-01-01T09:00:00Z", "2024-01-01T12:00:00Z")),
+        );
+        assert_eq!(spec.slot_at(timestamp("2024-01-01T12:00:00Z")), None);
+        assert_eq!(spec.slot_at(timestamp("2024-01-02T10:00:00Z")), None);
+    }
+
+    #[test]
+    fn slots_between_recurrence() {
+        let spec = reservation(
+            "2024-01-01T09:00:00Z",
+            "2024-01-01T12:00:00Z",
+            recurrence(UserBoxReservationFrequency::Daily, 2, None),
+        );
+
+        assert_eq!(
+            spec.slots_between(
+                timestamp("2024-03-01T10:00:00Z"),
+                timestamp("2024-03-05T10:00:00Z"),
+            )
+            .collect::<Vec<_>>(),
+            [
+                slot("2024-03-01T09:00:00Z", "2024-03-01T12:00:00Z"),
+                slot("2024-03-03T09:00:00Z", "2024-03-03T12:00:00Z"),
+                slot("2024-03-05T09:00:00Z", "2024-03-05T12:00:00Z"),
+            ],
+        );
+        assert_eq!(
+            spec.slot_at(timestamp("2030-01-01T11:59:59Z")),
+            Some(slot("2030-01-01T09:00:00Z", "2030-01-01T12:00:00Z")),
+        );
+        assert_eq!(spec.slot_at(timestamp("2030-01-02T10:00:00Z")), None);
+    }
+
+    #[test]
+    fn slots_between_count_and_until() {
+        let spec = reservation(
+            "2024-01-01T09:00:00Z",
+            "2024-01-01T12:00:00Z",
+            recurrence(UserBoxReservationFrequency::Weekly, 1, Some(2)),
+        );
+        assert_eq!(
+            spec.slots_between(
+                timestamp("2024-01-01T00:00:00Z"),
+                timestamp("2025-01-01T00:00:00Z"),
+            )
+            .collect::<Vec<_>>(),
+            [
+                slot("2024-01-01T09:00:00Z", "2024-01-01T12:00:00Z"),
+                slot("2024-01-08T09:00:00Z", "2024-01-08T12:00:00Z"),
+            ],
+        );
+        assert_eq!(spec.end_of_slots(), Some(timestamp("2024-01-08T12:00:00Z")));
+
+        let spec = UserBoxReservationSpec {
+            recurrence: Some(UserBoxReservationRecurrence {
+                frequency: UserBoxReservationFrequency::Daily,
+                interval: 1,
+                count: None,
+                until: Some(timestamp("2024-01-03T09:00:00Z")),
+            }),
+            ..spec
+        };
+        assert_eq!(spec.slots().count(), 2);
+        assert_eq!(spec.end_of_slots(), Some(timestamp("2024-01-02T12:00:00Z")));
+    }
+
+    #[test]
+    fn overlaps_once() {
+        let spec = reservation("2024-01-01T09:00:00Z", "2024-01-01T12:00:00Z", None);
+
+        let other = reservation("2024-01-01T11:00:00Z", "2024-01-01T13:00:00Z", None);
+        assert!(spec.overlaps(&other));
+        assert!(other.overlaps(&spec));
+
+        let other = reservation("2024-01-01T12:00:00Z", "2024-01-01T13:00:00Z", None);
+        assert!(!spec.overlaps(&other));
+        assert!(!other.overlaps(&spec));
+    }
+
+    #[test]
+    fn overlaps_far_future() {
+        let spec = reservation(
+            "2024-01-01T09:00:00Z",
+            "2024-01-01T12:00:00Z",
+            recurrence(UserBoxReservationFrequency::Daily, 1, None),
+        );
+        assert_eq!(spec.end_of_slots(), None);
+
+        let other = reservation("2027-06-01T10:00:00Z", "2027-06-01T11:00:00Z", None);
+        assert!(spec.overlaps(&other));
+        assert!(other.overlaps(&spec));
+
+        let other = reservation("2027-06-01T13:00:00Z", "2027-06-01T14:00:00Z", None);
+        assert!(!spec.overlaps(&other));
+        assert!(!other.overlaps(&spec));
+    }
+
+    #[test]
+    fn overlaps_forever() {
+        let spec = reservation(
+            "2024-01-01T09:00:00Z",
+            "2024-01-01T12:00:00Z",
+            recurrence(UserBoxReservationFrequency::Weekly, 1, None),
+        );
+
+        // every other monday, from the far future
+        let other = reservation(
+            "2040-01-02T11:00:00Z",
+            "2040-01-02T13:00:00Z",
+            recurrence(UserBoxReservationFrequency::Weekly, 2, None),
+        );
+        assert!(spec.overlaps(&other));
+        assert!(other.overlaps(&spec));
+
+        // every day, in the afternoon
+        let other = reservation(
+            "2040-01-02T12:00:00Z",
+            "2040-01-02T18:00:00Z",
+            recurrence(UserBoxReservationFrequency::Daily, 1, None),
+        );
+        assert!(!spec.overlaps(&other));
+        assert!(!other.overlaps(&spec));
+    }
+}
@@ -0,0 +1,59 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource,
+)]
+#[kube(
+    group = "vine.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "UserGroup",
+    root = "UserGroupCrd",
+    shortname = "ug",
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "group version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UserGroupSpec {
+    /// Static members by their user names
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// Group claims, whose users are the members
+    ///
+    /// The claims of the password-based auth providers are prefixed by the
+    /// provider name, such as `my-ldap:cn=lab,ou=groups,dc=example,dc=com`.
+    #[serde(default)]
+    pub oidc_groups: Vec<String>,
+}
+
+impl UserGroupSpec {
+    pub fn contains(&self, user_name: &str, claims: &[String]) -> bool {
+        self.users.iter().any(|user| user == user_name)
+            || self.oidc_groups.iter().any(|group| claims.contains(group))
+    }
+}
+
+/// The subject of a binding, which is either a single user or a group of users.
+pub trait UserBindingSubject {
+    fn user(&self) -> Option<&str>;
+
+    fn group(&self) -> Option<&str>;
+
+    /// Returns `true` if exactly one of the user and the group is given.
+    ///
+    /// NOTE: the invalid bindings never bind anyone, nor reserve anything.
+    fn is_valid(&self) -> bool {
+        self.user().is_some() != self.group().is_some()
+    }
+}
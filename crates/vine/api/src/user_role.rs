@@ -1,12 +1,12 @@
-use std::{iter::Sum, ops};
+use std::{fmt, iter::Sum, ops};
 
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
 
-#[derive(
-    Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource,
-)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
     group = "vine.ulagbulag.io",
     version = "v1alpha1",
@@ -34,6 +34,8 @@ pub struct UserRoleSpec {
     pub is_dev: bool,
     #[serde(default)]
     pub is_ops: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<UserRoleRule>,
 }
 
 impl ops::BitOr for UserRoleSpec {
@@ -44,6 +46,7 @@ impl ops::BitOr for UserRoleSpec {
             is_admin: self.is_admin || rhs.is_admin,
             is_dev: self.is_dev || rhs.is_dev,
             is_ops: self.is_ops || rhs.is_ops,
+            rules: self.rules.into_iter().chain(rhs.rules).collect(),
         }
     }
 }
@@ -53,3 +56,170 @@ impl Sum for UserRoleSpec {
         iter.reduce(ops::BitOr::bitor).unwrap_or_default()
     }
 }
+
+/// Grants the given verbs on the given resources.
+///
+/// The rule is limited to the boxes and the (user session) namespaces
+/// matched by the selectors, if any.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRoleRule {
+    #[serde(default)]
+    pub box_selector: Option<LabelSelector>,
+    #[serde(default)]
+    pub namespace_selector: Option<LabelSelector>,
+    pub resources: Vec<UserRoleResource>,
+    pub verbs: Vec<UserRoleVerb>,
+}
+
+impl UserRoleRule {
+    pub fn contains(&self, permission: UserRolePermission) -> bool {
+        let UserRolePermission { resource, verb } = permission;
+
+        self.resources
+            .iter()
+            .any(|item| matches!(item, UserRoleResource::All) || *item == resource)
+            && self
+                .verbs
+                .iter()
+                .any(|item| matches!(item, UserRoleVerb::All) || *item == verb)
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum UserRoleResource {
+    #[serde(rename = "*")]
+    #[strum(serialize = "*")]
+    All,
+    Box,
+    Desktop,
+    Model,
+    Session,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum UserRoleVerb {
+    #[serde(rename = "*")]
+    #[strum(serialize = "*")]
+    All,
+    Bind,
+    Exec,
+    List,
+    Read,
+    Write,
+}
+
+/// A single verb on a single resource, such as `desktop:exec`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserRolePermission {
+    pub resource: UserRoleResource,
+    pub verb: UserRoleVerb,
+}
+
+impl fmt::Display for UserRolePermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { resource, verb } = self;
+        write!(f, "{resource}:{verb}")
+    }
+}
+
+impl UserRolePermission {
+    /// Binds the boxes, even if they are bound to the others,
+    /// and reserves them
+    pub const BOX_BIND: Self = Self::new(UserRoleResource::Box, UserRoleVerb::Bind);
+    pub const DESKTOP_EXEC: Self = Self::new(UserRoleResource::Desktop, UserRoleVerb::Exec);
+    pub const MODEL_READ: Self = Self::new(UserRoleResource::Model, UserRoleVerb::Read);
+    pub const SESSION_LIST: Self = Self::new(UserRoleResource::Session, UserRoleVerb::List);
+
+    pub const fn new(resource: UserRoleResource, verb: UserRoleVerb) -> Self {
+        Self { resource, verb }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(resources: &[UserRoleResource], verbs: &[UserRoleVerb]) -> UserRoleRule {
+        UserRoleRule {
+            resources: resources.to_vec(),
+            verbs: verbs.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rule_contains() {
+        let rule_desktop = rule(
+            &[UserRoleResource::Desktop],
+            &[UserRoleVerb::Exec, UserRoleVerb::List],
+        );
+        assert!(rule_desktop.contains(UserRolePermission::DESKTOP_EXEC));
+        assert!(!rule_desktop.contains(UserRolePermission::BOX_BIND));
+        assert!(!rule_desktop.contains(UserRolePermission::SESSION_LIST));
+        assert!(!rule_desktop.contains(UserRolePermission::MODEL_READ));
+
+        let rule_model = rule(&[UserRoleResource::Model], &[UserRoleVerb::Read]);
+        assert!(rule_model.contains(UserRolePermission::MODEL_READ));
+        assert!(!rule_model.contains(UserRolePermission::DESKTOP_EXEC));
+    }
+
+    #[test]
+    fn rule_contains_all() {
+        let rule_all_resources = rule(&[UserRoleResource::All], &[UserRoleVerb::List]);
+        assert!(rule_all_resources.contains(UserRolePermission::SESSION_LIST));
+        assert!(!rule_all_resources.contains(UserRolePermission::DESKTOP_EXEC));
+
+        let rule_all_verbs = rule(&[UserRoleResource::Box], &[UserRoleVerb::All]);
+        assert!(rule_all_verbs.contains(UserRolePermission::BOX_BIND));
+        assert!(!rule_all_verbs.contains(UserRolePermission::SESSION_LIST));
+
+        let rule_empty = rule(&[], &[UserRoleVerb::All]);
+        assert!(!rule_empty.contains(UserRolePermission::BOX_BIND));
+    }
+
+    #[test]
+    fn parse_rule() {
+        let rule: UserRoleRule = ::serde_json::from_value(::serde_json::json!({
+            "resources": ["*"],
+            "verbs": ["exec"],
+        }))
+        .expect("failed to parse a rule");
+        assert_eq!(rule.resources, [UserRoleResource::All]);
+        assert_eq!(rule.verbs, [UserRoleVerb::Exec]);
+        assert_eq!(UserRolePermission::DESKTOP_EXEC.to_string(), "desktop:exec");
+        assert_eq!(UserRolePermission::MODEL_READ.to_string(), "model:read");
+    }
+}
@@ -12,6 +12,7 @@ use kube::Client;
 use opentelemetry::global;
 use tera::Tera;
 use tracing::{instrument, Level};
+use vine_rbac::throttle::LoginThrottle;
 
 #[instrument(level = Level::INFO)]
 #[get("/health")]
@@ -35,15 +36,21 @@ async fn main() {
         )?;
         let tera = Data::new(tera);
 
+        // Initialize login throttle
+        let throttle = Data::new(LoginThrottle::default());
+
         // Start web server
         HttpServer::new(move || {
             let app = App::new()
                 .app_data(Data::clone(&client))
-                .app_data(Data::clone(&tera));
+                .app_data(Data::clone(&tera))
+                .app_data(Data::clone(&throttle));
             let app = app
                 .service(health)
                 .service(crate::routes::auth::get)
+                .service(crate::routes::auth::post)
                 .service(crate::routes::r#box::login::get)
+                .service(crate::routes::r#box::login::post)
                 .service(crate::routes::install_os::get)
                 .service(crate::routes::reserved::get)
                 .service(crate::routes::welcome::get);
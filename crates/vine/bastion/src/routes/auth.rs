@@ -1,9 +1,14 @@
-use actix_web::{get, web::Data, HttpRequest, HttpResponse, Responder};
+use actix_web::{
+    get, post,
+    web::{Data, Json},
+    HttpRequest, HttpResponse, Responder,
+};
 use ark_core::result::Result;
+use chrono::Utc;
 use kube::Client;
 use tracing::{instrument, Level};
-use vine_api::user_session::UserSession;
-use vine_rbac::auth::AuthUserSession;
+use vine_api::{user_auth::UserAuthCredentials, user_session::UserSession};
+use vine_rbac::{auth::AuthUserSession, throttle::LoginThrottle};
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/auth")]
@@ -12,3 +17,34 @@ pub async fn get(request: HttpRequest, kube: Data<Client>) -> impl Responder {
         UserSession::from_request(&kube, &request).await,
     ))
 }
+
+#[instrument(level = Level::INFO, skip(request, kube, throttle))]
+#[post("/auth")]
+pub async fn post(
+    request: HttpRequest,
+    kube: Data<Client>,
+    throttle: Data<LoginThrottle>,
+    Json(credentials): Json<UserAuthCredentials>,
+) -> impl Responder {
+    const NAMESPACE: Option<String> = None;
+
+    // get current time
+    let now = Utc::now();
+
+    let addr = get_client_addr(&request);
+    if let Err(error) = throttle.check(&credentials, addr.as_deref(), now) {
+        return HttpResponse::from(Result::<UserSession>::Err(error.to_string()));
+    }
+
+    let result = ::vine_rbac::login::authenticate(&kube, &credentials, NAMESPACE).await;
+    throttle.record(&credentials, addr.as_deref(), result.as_ref().err(), now);
+    HttpResponse::from(Result::from(result))
+}
+
+/// Returns the address of the client, which is forwarded by the ingress, if any.
+pub(crate) fn get_client_addr(request: &HttpRequest) -> Option<String> {
+    request
+        .connection_info()
+        .realip_remote_addr()
+        .map(ToString::to_string)
+}
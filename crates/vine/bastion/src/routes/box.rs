@@ -1,15 +1,17 @@
 pub mod login {
     use actix_web::{
-        get,
-        web::{Data, Path, Redirect},
+        get, post,
+        web::{Data, Json, Path, Redirect},
         HttpRequest, HttpResponse, Responder,
     };
+    use chrono::Utc;
     use kube::Client;
     use serde::Serialize;
     use tera::{Context, Tera};
     use tracing::{error, instrument, warn, Level};
     use uuid::Uuid;
-    use vine_api::user_auth::UserSessionResponse;
+    use vine_api::user_auth::{UserAuthCredentials, UserSessionError, UserSessionResponse};
+    use vine_rbac::throttle::LoginThrottle;
 
     pub const TEMPLATE_NAME: &str = "box_error.html";
     pub const TEMPLATE_CONTENT: &str = include_str!("../../templates/box_error.html.j2");
@@ -25,10 +27,12 @@ pub mod login {
         match match ::vine_rbac::auth::get_user_name(&request) {
             Ok(user_name) => {
                 const LOGOUT_ON_FAILED: bool = false;
+                let groups = ::vine_rbac::auth::get_user_groups(&request).unwrap_or_default();
                 ::vine_rbac::login::execute(
                     &client,
                     &box_name.to_string(),
                     &user_name,
+                    &groups,
                     LOGOUT_ON_FAILED,
                 )
                 .await
@@ -53,6 +57,45 @@ pub mod login {
         }
     }
 
+    #[instrument(level = Level::INFO, skip(request, client, throttle))]
+    #[post("/box/{box_name}/login")]
+    pub async fn post(
+        request: HttpRequest,
+        client: Data<Client>,
+        throttle: Data<LoginThrottle>,
+        box_name: Path<Uuid>,
+        Json(credentials): Json<UserAuthCredentials>,
+    ) -> impl Responder {
+        const LOGOUT_ON_FAILED: bool = false;
+
+        // get current time
+        let now = Utc::now();
+
+        let addr = crate::routes::auth::get_client_addr(&request);
+        if let Err(error) = throttle.check(&credentials, addr.as_deref(), now) {
+            return HttpResponse::from(::ark_core::result::Result::Ok(UserSessionResponse::from(
+                error,
+            )));
+        }
+
+        let result = ::vine_rbac::login::execute_with_credentials(
+            &client,
+            &box_name.to_string(),
+            &credentials,
+            LOGOUT_ON_FAILED,
+        )
+        .await;
+        match &result {
+            Ok(UserSessionResponse::Error(UserSessionError::AuthError(error))) => {
+                throttle.record(&credentials, addr.as_deref(), Some(error), now)
+            }
+            // NOTE: the credentials are verified before the session errors
+            Ok(_) => throttle.record(&credentials, addr.as_deref(), None, now),
+            Err(_) => (),
+        }
+        HttpResponse::from(::ark_core::result::Result::from(result))
+    }
+
     fn create_error_html(tera: Data<Tera>, error: impl ToString) -> HttpResponse {
         #[derive(Serialize)]
         struct Value {
@@ -24,6 +24,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             ::vine_api::user::UserCrd::crd(),
             ::vine_api::user_auth::UserAuthCrd::crd(),
             ::vine_api::user_auth_binding::UserAuthBindingCrd::crd(),
+            ::vine_api::user_group::UserGroupCrd::crd(),
             ::vine_api::user_role::UserRoleCrd::crd(),
             ::vine_api::user_role_binding::UserRoleBindingCrd::crd(),
         ]
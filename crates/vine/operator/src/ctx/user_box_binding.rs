@@ -17,6 +17,7 @@ use tracing::{info, instrument, warn, Level};
 use vine_api::{
     user_auth::UserSessionResponse,
     user_box_binding::{UserBoxBindingCrd, UserBoxBindingSpec},
+    user_group::UserBindingSubject,
 };
 use vine_session::{is_persistent, is_persistent_by};
 
@@ -35,6 +36,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         vec![
             ::vine_api::user_box_quota::UserBoxQuotaCrd::crd(),
             ::vine_api::user_box_quota_binding::UserBoxQuotaBindingCrd::crd(),
+            ::vine_api::user_box_reservation::UserBoxReservationCrd::crd(),
         ]
     }
 
@@ -47,12 +49,24 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         Self: Sized,
     {
         let UserBoxBindingSpec {
-            user: user_name,
+            user,
+            group: _,
             r#box: node_name,
             autologin,
             expired_timestamp,
         } = &data.spec;
 
+        if !data.spec.is_valid() {
+            warn!("skipping invalid binding: either a user or a group should be given");
+            return Ok(Action::await_change());
+        }
+
+        // NOTE: the group bindings only reserve the box, as autologin requires a single user
+        let user_name = match user {
+            Some(user_name) => user_name,
+            None => return Ok(Action::await_change()),
+        };
+
         let now = Utc::now();
         let is_expired = expired_timestamp
             .map(|expired_timestamp| now < expired_timestamp)
@@ -99,7 +113,8 @@ async fn enable_autologin(kube: &Client, node_name: &str, user_name: &str) -> Re
     update_node_autologin(kube, node_name, Some(user_name)).await?;
 
     const LOGOUT_ON_FAILED: bool = false;
-    match ::vine_rbac::login::execute(kube, node_name, user_name, LOGOUT_ON_FAILED).await {
+    let claims = load_claims(kube, user_name).await;
+    match ::vine_rbac::login::execute(kube, node_name, user_name, &claims, LOGOUT_ON_FAILED).await {
         Ok(UserSessionResponse::Accept { .. }) => {
             info!("binded node: {node_name:?} => {user_name:?}");
             Ok(())
@@ -131,7 +146,8 @@ async fn disable_autologin(kube: &Client, node_name: &str, user_name: &str) -> R
 
     update_node_autologin(kube, node_name, None).await?;
 
-    match ::vine_rbac::logout::execute(kube, node_name, user_name).await {
+    let claims = load_claims(kube, user_name).await;
+    match ::vine_rbac::logout::execute(kube, node_name, user_name, &claims).await {
         Ok(UserSessionResponse::Accept { .. }) => {
             info!("unbinded node: {node_name:?} => {user_name:?}");
             Ok(())
@@ -147,6 +163,15 @@ async fn disable_autologin(kube: &Client, node_name: &str, user_name: &str) -> R
     }
 }
 
+async fn load_claims(kube: &Client, user_name: &str) -> Vec<String> {
+    ::vine_rbac::group::load_claims(kube, user_name)
+        .await
+        .unwrap_or_else(|error| {
+            warn!("failed to load the group claims of {user_name:?}: {error}");
+            Vec::default()
+        })
+}
+
 async fn get_node(kube: &Client, name: &str) -> Result<Option<Node>, Error> {
     let api = Api::<Node>::all(kube.clone());
     api.get_opt(name).await
@@ -1,13 +1,18 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use ark_api::NamespaceAny;
 use ark_core_k8s::manager::Manager;
 use async_trait::async_trait;
+use chrono::Utc;
 use k8s_openapi::api::core::v1::Node;
-use kube::{runtime::controller::Action, Error, ResourceExt};
+use kube::{runtime::controller::Action, Client, Error, ResourceExt};
 use tracing::{info, instrument, warn, Level};
 use vine_api::user::UserCrd;
-use vine_session::SessionManager;
+use vine_session::{
+    exec::SessionExecExt, get_session_deadline, SessionDeadline, SessionDeadlineReason,
+    SessionManager,
+};
 
 #[derive(Default)]
 pub struct Ctx {}
@@ -28,8 +33,9 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         Self: Sized,
     {
         let name = data.name_any();
-        let namespace = match data.labels().get(::ark_api::consts::LABEL_BIND_BY_USER) {
-            Some(user_name) => UserCrd::user_namespace_with(user_name),
+        let (user_name, namespace) = match data.labels().get(::ark_api::consts::LABEL_BIND_BY_USER)
+        {
+            Some(user_name) => (user_name.clone(), UserCrd::user_namespace_with(user_name)),
             None => {
                 info!("skipping unbinding node ({name}): user not found");
                 return Ok(Action::requeue(
@@ -38,6 +44,10 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             }
         };
 
+        if let Err(e) = ::vine_rbac::usage::account(&manager.kube, &data, Utc::now()).await {
+            warn!("failed to account the usage of node: {name:?}: {e}");
+        }
+
         if data
             .labels()
             .get(::ark_api::consts::LABEL_BIND_PERSISTENT)
@@ -52,6 +62,32 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             ));
         }
 
+        // Defer the idle logout while the user is working on the session
+        let (data, is_activity_measured) = if data
+            .labels()
+            .contains_key(::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT)
+        {
+            let kube = manager.kube.clone();
+            match ::vine_session::is_session_active(kube.clone(), &data, &namespace).await {
+                Ok(true) => match ::vine_session::report_activity(kube, &data).await {
+                    Ok(Some(node)) => (Arc::new(node), true),
+                    Ok(None) => (data, true),
+                    Err(e) => {
+                        warn!("failed to report the activity of node: {name:?}: {e}");
+                        (data, true)
+                    }
+                },
+                Ok(false) => (data, true),
+                Err(e) => {
+                    // NOTE: never log out the users whose activity cannot be measured
+                    warn!("failed to measure the activity of node: {name:?}: {e}");
+                    (data, false)
+                }
+            }
+        } else {
+            (data, true)
+        };
+
         let session_manager = match SessionManager::try_new(namespace, manager.kube.clone()).await {
             Ok(session_manager) => session_manager,
             Err(e) => {
@@ -62,7 +98,37 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             }
         };
 
-        match session_manager.try_delete(&data).await {
+        // Evict the session when the box is reserved by the others
+        let reserved_timestamp = match ::vine_rbac::reservation::find_upcoming_reserved(
+            &manager.kube,
+            &data,
+            &user_name,
+            Utc::now(),
+        )
+        .await
+        {
+            Ok(slot) => slot.map(|slot| slot.start),
+            Err(e) => {
+                warn!("failed to find the reservations of node: {name:?}: {e}");
+                None
+            }
+        };
+
+        // Warn the user before logging out the session
+        let deadline = get_session_deadline(&data, reserved_timestamp, is_activity_measured);
+        if let Some(deadline) = deadline.filter(|deadline| deadline.should_warn(Utc::now())) {
+            match warn_logout(manager.kube.clone(), &data, &deadline).await {
+                Ok(()) => {
+                    let reason = deadline.reason;
+                    info!("warned node: {name:?} => {reason}");
+                }
+                Err(e) => {
+                    warn!("failed to warn node: {name:?}: {e}");
+                }
+            }
+        }
+
+        match session_manager.try_delete(&data, deadline.as_ref()).await {
             Ok(Some(user_name)) => {
                 info!("unbinded node: {name:?} => {user_name:?}");
             }
@@ -73,8 +139,48 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         }
 
         // If no events were received, check back after a few minutes
-        Ok(Action::requeue(
-            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
-        ))
+        let fallback = <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK;
+        let requeue = deadline
+            .and_then(|deadline| {
+                // or just after the upcoming deadline
+                let now = Utc::now();
+                let warning_timestamp = deadline.warning_timestamp();
+                let timestamp = if now < warning_timestamp {
+                    warning_timestamp
+                } else {
+                    deadline.timestamp
+                };
+                (timestamp - now).to_std().ok()
+            })
+            .map_or(fallback, |duration| duration.min(fallback));
+        Ok(Action::requeue(requeue))
+    }
+}
+
+async fn warn_logout(kube: Client, node: &Node, deadline: &SessionDeadline) -> Result<()> {
+    let session = node.get_session_ref()?.into_owned();
+
+    let timestamp = deadline.timestamp.to_rfc3339();
+    let message = match deadline.reason {
+        SessionDeadlineReason::Expired => {
+            format!("Your session expires at {timestamp}. Please save your work.")
+        }
+        SessionDeadlineReason::Idle => {
+            format!("Your session is idle and will be logged out at {timestamp}.")
+        }
+        SessionDeadlineReason::Reserved => {
+            format!("This box is reserved by the others from {timestamp}. Please save your work.")
+        }
+    };
+    let command = vec![
+        "notify-send".into(),
+        "--urgency=critical".into(),
+        "Session Logout".into(),
+        message,
+    ];
+
+    for process in session.exec_without_tty(kube.clone(), command).await? {
+        process.join().await?;
     }
+    ::vine_session::report_warned(kube, &node.name_any()).await
 }
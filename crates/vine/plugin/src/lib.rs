@@ -10,7 +10,15 @@ where
     T: ServiceFactory<ServiceRequest, Error = Error, Config = (), InitError = ()>,
 {
     app.service(crate::routes::desktop::batch::post_exec_broadcast)
+        .service(crate::routes::desktop::recording::get)
+        .service(crate::routes::desktop::recording::list)
+        .service(crate::routes::desktop::single::post_activity)
         .service(crate::routes::desktop::single::post_exec)
+        .service(crate::routes::reservation::cancel)
+        .service(crate::routes::reservation::create)
+        .service(crate::routes::reservation::list)
         .service(crate::routes::session::list)
+        .service(crate::routes::usage::get)
+        .service(crate::routes::usage::report)
         .service(crate::routes::user::get)
 }
@@ -3,12 +3,19 @@ use actix_web::{
     web::{Data, Json},
     HttpRequest, HttpResponse, Responder,
 };
+use ark_api::SessionRef;
 use ark_core::result::Result;
 use kube::Client;
 use tracing::{instrument, warn, Level};
-use vine_api::user_session::{UserSession, UserSessionCommandBatch};
+use vine_api::{
+    user_role::UserRolePermission,
+    user_session::{UserSession, UserSessionCommandBatch},
+};
 use vine_rbac::auth::AuthUserSession;
-use vine_session::batch::{BatchCommandArgs, BatchCommandUsers};
+use vine_session::{
+    batch::{BatchCommandArgs, BatchCommandUsers},
+    exec::SessionExec,
+};
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[post("/batch/user/desktop/exec/broadcast")]
@@ -22,25 +29,59 @@ pub async fn post_exec_broadcast(
         wait,
     }): Json<UserSessionCommandBatch>,
 ) -> impl Responder {
+    const PERMISSION: UserRolePermission = UserRolePermission::DESKTOP_EXEC;
+
     let kube = kube.as_ref().clone();
-    if let Err(error) = UserSession::from_request(&kube, &request)
+    let session = match UserSession::from_request(&kube, &request)
         .await
-        .and_then(|metadata| metadata.assert_admin())
+        .and_then(|session| session.assert_permission(PERMISSION).map(|()| session))
     {
-        warn!("{error}");
-        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    let users = if session.role().is_admin {
+        match user_names {
+            Some(user_names) => BatchCommandUsers::List(user_names),
+            None => BatchCommandUsers::All,
+        }
+    } else {
+        // limit the targets to the permitted sessions
+        let sessions = match SessionRef::list(kube.clone()).await {
+            Ok(sessions) => sessions.into_iter().map(SessionRef::into_owned).collect(),
+            Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+        };
+        let sessions = match session
+            .filter_permitted_sessions(&kube, PERMISSION, sessions)
+            .await
+        {
+            Ok(sessions) => sessions,
+            Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+        };
+
+        BatchCommandUsers::List(
+            sessions
+                .into_iter()
+                .map(|session| session.user_name.into_owned())
+                .filter(|user_name| {
+                    user_names
+                        .as_ref()
+                        .map_or(true, |user_names| user_names.contains(user_name))
+                })
+                .collect(),
+        )
     };
 
     let args = BatchCommandArgs {
         command,
         terminal,
-        users: match user_names {
-            Some(user_names) => BatchCommandUsers::List(user_names),
-            None => BatchCommandUsers::All,
-        },
+        users,
         wait,
     };
 
-    let result = args.exec(&kube).await;
+    let result = args.exec(&kube, &session.user_name).await;
     HttpResponse::from(Result::from(result))
 }
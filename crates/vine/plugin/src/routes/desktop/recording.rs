@@ -0,0 +1,48 @@
+use actix_web::{
+    get,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kube::Client;
+use tracing::{instrument, warn, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+use vine_session::audit::{get_recording, list_recordings};
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/batch/user/desktop/recording")]
+pub async fn list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_admin())
+    {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    }
+
+    let result = list_recordings(&kube).await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/batch/user/desktop/recording/{name}")]
+pub async fn get(request: HttpRequest, kube: Data<Client>, name: Path<String>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_admin())
+    {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    }
+
+    match get_recording(&kube, &name).await {
+        Ok(Some(data)) => HttpResponse::Ok()
+            .content_type("application/x-asciicast")
+            .body(data),
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(error) => HttpResponse::from(Result::<()>::Err(error.to_string())),
+    }
+}
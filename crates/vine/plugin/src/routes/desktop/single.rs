@@ -4,11 +4,16 @@ use actix_web::{
     HttpRequest, HttpResponse, Responder,
 };
 use ark_core::result::Result;
-use kube::Client;
+use k8s_openapi::api::core::v1::Node;
+use kube::{Api, Client};
 use tracing::{instrument, warn, Level};
-use vine_api::user_session::{UserSession, UserSessionCommand};
+use vine_api::{
+    user_auth::UserAuthError,
+    user_role::UserRolePermission,
+    user_session::{UserSession, UserSessionCommand},
+};
 use vine_rbac::auth::{AuthUserSession, AuthUserSessionRef};
-use vine_session::exec::SessionExecExt;
+use vine_session::{audit::AuditExecContext, exec::SessionExecExt};
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[post("/user/desktop/exec")]
@@ -17,6 +22,56 @@ pub async fn post_exec(
     kube: Data<Client>,
     Json(command): Json<UserSessionCommand>,
 ) -> impl Responder {
+    const PERMISSION: UserRolePermission = UserRolePermission::DESKTOP_EXEC;
+
+    let kube = kube.as_ref().clone();
+    let (user, session) = match UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|user| user.assert_permission(PERMISSION).map(|()| user))
+        .and_then(|user| Ok((user.clone(), user.try_into_ark_session()?)))
+    {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    // limit the target to the permitted session
+    match user
+        .filter_permitted_sessions(&kube, PERMISSION, vec![session.clone()])
+        .await
+    {
+        Ok(sessions) if !sessions.is_empty() => (),
+        Ok(_) => {
+            let error = UserAuthError::PermissionDenied(PERMISSION.to_string());
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    }
+
+    let audit = AuditExecContext {
+        caller: session.user_name.to_string(),
+        command: command.clone(),
+        tty: false,
+    };
+    let result = match session.exec_without_tty(kube, command).await {
+        Ok(mut processes) => {
+            audit.watch(&session, &mut processes);
+            Ok(())
+        }
+        Err(error) => {
+            audit.fail(&session, &error);
+            Err(error)
+        }
+    };
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/user/desktop/activity")]
+pub async fn post_activity(request: HttpRequest, kube: Data<Client>) -> impl Responder {
     let kube = kube.as_ref().clone();
     let session = match UserSession::from_request(&kube, &request)
         .await
@@ -29,6 +84,11 @@ pub async fn post_exec(
         }
     };
 
-    let result = session.exec_without_tty(kube, command).await.map(|_| ());
+    let result = match Api::<Node>::all(kube.clone()).get(&session.node_name).await {
+        Ok(node) => ::vine_session::report_activity(kube, &node)
+            .await
+            .map(|_| ()),
+        Err(error) => Err(error.into()),
+    };
     HttpResponse::from(Result::from(result))
 }
@@ -1,3 +1,5 @@
 pub mod desktop;
+pub mod reservation;
 pub mod session;
+pub mod usage;
 pub mod user;
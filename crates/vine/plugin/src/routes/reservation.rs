@@ -0,0 +1,114 @@
+use actix_web::{
+    delete, get, post,
+    web::{Data, Json, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use chrono::{TimeDelta, Utc};
+use kube::Client;
+use tracing::{instrument, warn, Level};
+use vine_api::{
+    user_box_reservation::UserBoxReservationSpec, user_role::UserRolePermission,
+    user_session::UserSession,
+};
+use vine_rbac::auth::AuthUserSession;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/user/box/reservation")]
+pub async fn list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request).await {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    }
+
+    let reservations = ::vine_rbac::reservation::list(&kube).await;
+    HttpResponse::from(Result::from(reservations))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/user/box/reservation")]
+pub async fn create(
+    request: HttpRequest,
+    kube: Data<Client>,
+    Json(mut spec): Json<UserBoxReservationSpec>,
+) -> impl Responder {
+    const PERMISSION: UserRolePermission = UserRolePermission::BOX_BIND;
+    const MAX_TERM: TimeDelta = TimeDelta::days(90);
+
+    let kube = kube.as_ref().clone();
+    let session = match UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_permission(PERMISSION).map(|()| session))
+    {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    // only admins can reserve boxes on behalf of the others
+    if !session.role().is_admin {
+        spec.user = Some(session.user_name.clone());
+        spec.group = None;
+
+        // only admins can reserve a class of boxes or for a long time
+        let error = if spec.box_selector.is_some() {
+            Some("only admins can reserve boxes by a selector".to_string())
+        } else if spec
+            .end_of_slots()
+            .map_or(true, |end| end > Utc::now() + MAX_TERM)
+        {
+            let days = MAX_TERM.num_days();
+            Some(format!("the reservation should end within {days} days"))
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error));
+        }
+
+        // only the boxes which the user can bind to can be reserved
+        if let Some(box_name) = spec.r#box.as_deref() {
+            if let Err(error) =
+                ::vine_rbac::reservation::assert_reservable(&kube, &session, box_name, Utc::now())
+                    .await
+            {
+                warn!("{error}");
+                return HttpResponse::from(Result::<()>::Err(error.to_string()));
+            }
+        }
+    }
+
+    let reservation = ::vine_rbac::reservation::create(&kube, spec).await;
+    HttpResponse::from(Result::from(reservation))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[delete("/user/box/reservation/{name}")]
+pub async fn cancel(
+    request: HttpRequest,
+    kube: Data<Client>,
+    name: Path<String>,
+) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    let session = match UserSession::from_request(&kube, &request).await {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    // only admins can cancel the reservations of the others
+    let owner = if session.role().is_admin {
+        None
+    } else {
+        Some(session.user_name.as_str())
+    };
+
+    let result = ::vine_rbac::reservation::cancel(&kube, &name, owner).await;
+    HttpResponse::from(Result::from(result))
+}
@@ -3,28 +3,37 @@ use ark_api::SessionRef;
 use ark_core::result::Result;
 use kube::Client;
 use tracing::{instrument, warn, Level};
-use vine_api::user_session::UserSession;
+use vine_api::{user_role::UserRolePermission, user_session::UserSession};
 use vine_rbac::auth::AuthUserSession;
 use vine_session::exec::SessionExec;
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/batch/user/session")]
 pub async fn list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    const PERMISSION: UserRolePermission = UserRolePermission::SESSION_LIST;
+
     let kube = kube.as_ref().clone();
-    if let Err(error) = UserSession::from_request(&kube, &request)
+    let session = match UserSession::from_request(&kube, &request)
         .await
-        .and_then(|session| session.assert_admin())
+        .and_then(|session| session.assert_permission(PERMISSION).map(|()| session))
     {
-        warn!("{error}");
-        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
     };
 
-    HttpResponse::from(Result::from(SessionRef::list(kube.clone()).await.map(
-        |sessions| {
-            sessions
-                .into_iter()
-                .map(SessionRef::into_owned)
-                .collect::<Vec<_>>()
-        },
-    )))
+    let sessions = match SessionRef::list(kube.clone()).await {
+        Ok(sessions) => sessions
+            .into_iter()
+            .map(SessionRef::into_owned)
+            .collect::<Vec<_>>(),
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    HttpResponse::from(Result::from(
+        session
+            .filter_permitted_sessions(&kube, PERMISSION, sessions)
+            .await,
+    ))
 }
@@ -0,0 +1,45 @@
+use actix_web::{
+    get,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kube::Client;
+use tracing::{instrument, warn, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/user/usage")]
+pub async fn get(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    let session = UserSession::from_request(&kube, &request).await;
+    HttpResponse::from(Result::from(session.map(|session| {
+        session
+            .user
+            .status
+            .as_ref()
+            .map(|status| status.usage.clone())
+            .unwrap_or_default()
+    })))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/batch/user/usage/{month}")]
+pub async fn report(
+    request: HttpRequest,
+    kube: Data<Client>,
+    month: Path<String>,
+) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_admin())
+    {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    }
+
+    let report = ::vine_rbac::usage::get_report(&kube, &month).await;
+    HttpResponse::from(Result::from(report))
+}
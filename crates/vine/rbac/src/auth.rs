@@ -1,31 +1,44 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::{anyhow, Result};
 use ark_api::SessionRef;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use k8s_openapi::{
+    api::core::v1::{Namespace, Node},
+    apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta},
+};
+use kube::{
+    api::{ListParams, ObjectList, PartialObjectMeta},
+    Api, Client, ResourceExt,
+};
+use tracing::{info, instrument, warn, Level};
 #[cfg(feature = "actix")]
-use k8s_openapi::api::core::v1::Node;
-#[cfg(feature = "actix")]
-use kube::Client;
-use kube::{api::ListParams, Api, ResourceExt};
-#[cfg(feature = "actix")]
-use tracing::warn;
-use tracing::{instrument, Level};
+use vine_api::user_auth::UserAuthPayload;
 use vine_api::{
     user::UserCrd,
     user_auth::UserAuthError,
-    user_role::{UserRoleCrd, UserRoleSpec},
-    user_role_binding::UserRoleBindingCrd,
-    user_session::UserSession,
-};
-#[cfg(feature = "actix")]
-use vine_api::{
     user_box_binding::{UserBoxBindingCrd, UserBoxBindingSpec},
     user_box_quota::UserBoxQuotaCrd,
     user_box_quota_binding::{UserBoxQuotaBindingCrd, UserBoxQuotaBindingSpec},
+    user_role::{UserRoleCrd, UserRolePermission, UserRoleRule, UserRoleSpec},
+    user_role_binding::UserRoleBindingCrd,
+    user_session::UserSession,
 };
 
+use crate::group::UserGroups;
+
+type Labels = BTreeMap<String, String>;
+
+/// An object to be accessed, described by the labels of its box and its namespace.
+///
+/// Missing labels never match the selectors of a rule.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AuthPolicyTarget<'a> {
+    pub box_labels: Option<&'a Labels>,
+    pub namespace_labels: Option<&'a Labels>,
+}
+
 #[async_trait(?Send)]
 pub trait AuthUserSession {
     fn assert_admin(&self) -> Result<(), UserAuthError> {
@@ -36,6 +49,83 @@ pub trait AuthUserSession {
         }
     }
 
+    /// Asserts that the user is granted the permission on at least one target.
+    ///
+    /// Use [`AuthUserSession::is_permitted`] or
+    /// [`AuthUserSession::filter_permitted_sessions`] to check the concrete targets.
+    fn assert_permission(&self, permission: UserRolePermission) -> Result<(), UserAuthError> {
+        let role = self.role();
+        if role.is_admin || role.rules.iter().any(|rule| rule.contains(permission)) {
+            Ok(())
+        } else {
+            Err(UserAuthError::PermissionDenied(permission.to_string()))
+        }
+    }
+
+    fn is_permitted(&self, permission: UserRolePermission, target: AuthPolicyTarget<'_>) -> bool {
+        let role = self.role();
+        role.is_admin
+            || role
+                .rules
+                .iter()
+                .any(|rule| rule.contains(permission) && matches_rule(rule, target))
+    }
+
+    /// Retains the sessions which the user is granted the permission on.
+    #[instrument(level = Level::INFO, skip(self, kube, sessions), err(Display))]
+    async fn filter_permitted_sessions(
+        &self,
+        kube: &Client,
+        permission: UserRolePermission,
+        sessions: Vec<SessionRef<'static>>,
+    ) -> Result<Vec<SessionRef<'static>>> {
+        let role = self.role();
+        if role.is_admin {
+            return Ok(sessions);
+        }
+
+        let rules: Vec<_> = role
+            .rules
+            .iter()
+            .filter(|rule| rule.contains(permission))
+            .collect();
+        if rules.is_empty() {
+            return Ok(Vec::default());
+        }
+
+        // load the labels only if required
+        let lp = ListParams::default();
+        let boxes = if rules.iter().any(|rule| rule.box_selector.is_some()) {
+            let api = Api::<Node>::all(kube.clone());
+            api.list_metadata(&lp)
+                .await
+                .map(collect_labels)
+                .map_err(|error| anyhow!("failed to list boxes: {error}"))?
+        } else {
+            BTreeMap::default()
+        };
+        let namespaces = if rules.iter().any(|rule| rule.namespace_selector.is_some()) {
+            let api = Api::<Namespace>::all(kube.clone());
+            api.list_metadata(&lp)
+                .await
+                .map(collect_labels)
+                .map_err(|error| anyhow!("failed to list namespaces: {error}"))?
+        } else {
+            BTreeMap::default()
+        };
+
+        Ok(sessions
+            .into_iter()
+            .filter(|session| {
+                let target = AuthPolicyTarget {
+                    box_labels: boxes.get(session.node_name.as_ref()),
+                    namespace_labels: namespaces.get(session.namespace.as_ref()),
+                };
+                rules.iter().any(|rule| matches_rule(rule, target))
+            })
+            .collect())
+    }
+
     fn role(&self) -> &UserRoleSpec;
 
     #[cfg(feature = "actix")]
@@ -82,154 +172,20 @@ impl AuthUserSession for UserSession {
     where
         Self: Sized,
     {
-        use std::sync::Arc;
-
-        use tracing::info;
-
         let token = get_user_token(request)?;
-        let user_name = get_user_name_with_timestamp_impl(&token, now)?;
-
-        let api = Api::<UserCrd>::all(client.clone());
-        let user = api.get(&user_name).await.map_err(|e| {
-            warn!("[{now}] failed to find the user: {e}");
-            UserAuthError::UserNotRegistered
-        })?;
-        let user_name = user.preferred_name();
-
-        let role = get_user_role(client, &user, now).await?;
-        let namespace = get_user_namespace(request, &user, role, now)?;
-
-        let config = ::kube::Config {
-            auth_info: ::kube::config::AuthInfo {
-                token: Some(token.to_string().into()),
-                ..Default::default()
-            },
-            default_namespace: namespace.clone(),
-            ..::kube::Config::incluster().map_err(|_| UserAuthError::NamespaceNotAllowed)?
-        };
-        let client = Client::try_from(config).map_err(|_| UserAuthError::NamespaceNotAllowed)?;
-
-        // get available boxes
-        let boxes = {
-            let api = Api::<Node>::all(client.clone());
-            let lp = ListParams::default();
-            api.list(&lp)
-                .await
-                .map(|list| {
-                    list.items
-                        .into_iter()
-                        .filter(|item| {
-                            item.status
-                                .as_ref()
-                                .and_then(|status| status.conditions.as_ref())
-                                .and_then(|conditions| conditions.last())
-                                .map(|condition| condition.status == "True")
-                                .unwrap_or_default()
-                        })
-                        .map(|item| (item.name_any(), item.spec.unwrap()))
-                        .collect::<BTreeMap<_, _>>()
-                })
-                .unwrap_or_default()
-        };
-
-        let box_bindings = {
-            let api = Api::<UserBoxBindingCrd>::all(client.clone());
-            let lp = ListParams::default();
-            api.list(&lp)
-                .await
-                .map(|list| {
-                    list.items
-                        .into_iter()
-                        .filter(|item| item.spec.user == user_name)
-                        .filter(|item| {
-                            item.spec
-                                .expired_timestamp
-                                .as_ref()
-                                .map(|timestamp| timestamp < &now)
-                                .unwrap_or(true)
-                        })
-                        .filter_map(|item| {
-                            Some(UserBoxBindingSpec {
-                                user: item.spec.user,
-                                r#box: boxes.get(&item.spec.r#box)?.clone(),
-                                autologin: item.spec.autologin,
-                                expired_timestamp: item.spec.expired_timestamp,
-                            })
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default()
-        };
-
-        // get available quotas
-        let quotas = {
-            let api = Api::<UserBoxQuotaCrd>::all(client.clone());
-            let lp = ListParams::default();
-            api.list(&lp)
-                .await
-                .map(|list| {
-                    list.items
-                        .into_iter()
-                        .map(|item| (item.name_any(), item.spec))
-                        .collect::<BTreeMap<_, _>>()
-                })
-                .unwrap_or_default()
-        };
-
-        let box_quota_bindings = {
-            let api = Api::<UserBoxQuotaBindingCrd>::all(client.clone());
-            let lp = ListParams::default();
-            api.list(&lp)
-                .await
-                .map(|list| {
-                    list.items
-                        .into_iter()
-                        .filter(|item| item.spec.user == user_name)
-                        .filter(|item| {
-                            item.spec
-                                .expired_timestamp
-                                .as_ref()
-                                .map(|timestamp| timestamp < &now)
-                                .unwrap_or(true)
-                        })
-                        .filter_map(|item| {
-                            Some(UserBoxQuotaBindingSpec {
-                                user: item.spec.user,
-                                quota: quotas.get(&item.spec.quota)?.clone(),
-                                expired_timestamp: item.spec.expired_timestamp,
-                            })
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default()
-        };
-
-        // get current box info
-        let labels = user.labels();
-        let box_name = if labels
-            .get(::ark_api::consts::LABEL_BIND_STATUS)
-            .map(AsRef::as_ref)
-            == Some("true")
-        {
-            labels.get(::ark_api::consts::LABEL_BIND_NODE).cloned()
-        } else {
-            None
-        };
-
-        // Login Successed!
-        info!("[{now}] auth accepted: {user_name:?}");
-
-        Ok(Self {
-            box_bindings: Arc::new(box_bindings),
-            box_name,
-            box_quota_bindings: Arc::new(box_quota_bindings),
-            kube: Some(client),
+        let payload = get_user_payload_with_timestamp_impl(token, now)?;
+        let user_name = get_user_primary_key(&payload, now)?;
+        let namespace = get_user_namespace(request, now)?;
+
+        load_user_session(
+            client,
+            &user_name,
+            payload.groups(),
             namespace,
-            token: Some(token.into()),
-            role,
-            user: Arc::new(user),
-            user_name,
-        })
+            Some(token),
+            now,
+        )
+        .await
     }
 
     #[instrument(level = Level::INFO, skip(self), err(Display))]
@@ -246,7 +202,7 @@ impl AuthUserSession for UserSession {
             user_name,
         } = self;
 
-        check_user_namespace(namespace, &user, role)
+        check_user_namespace(namespace, &user, &role)
             .map(|namespace| Self {
                 box_bindings,
                 box_name,
@@ -262,6 +218,175 @@ impl AuthUserSession for UserSession {
     }
 }
 
+/// Loads the session of the authenticated user.
+///
+/// The user is impersonated by the given token, if any.
+#[instrument(level = Level::INFO, skip(client, token), err(Display))]
+pub(crate) async fn load_user_session(
+    client: &Client,
+    user_name: &str,
+    claims: &[String],
+    namespace: Option<String>,
+    token: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<UserSession, UserAuthError> {
+    let api = Api::<UserCrd>::all(client.clone());
+    let user = api.get(user_name).await.map_err(|e| {
+        warn!("[{now}] failed to find the user: {e}");
+        UserAuthError::UserNotRegistered
+    })?;
+    let user_name = user.preferred_name();
+
+    let groups = UserGroups::load(client, &user_name, claims)
+        .await
+        .unwrap_or_else(|error| {
+            warn!("[{now}] failed to get the user groups: {error}");
+            UserGroups::default()
+        });
+    let role = get_user_role(client, &groups, now).await?;
+    let namespace = check_user_namespace(namespace, &user, &role)?;
+
+    // impersonate the user if possible
+    let client = match token {
+        Some(token) => {
+            let config = ::kube::Config {
+                auth_info: ::kube::config::AuthInfo {
+                    token: Some(token.to_string().into()),
+                    ..Default::default()
+                },
+                default_namespace: namespace.clone(),
+                ..::kube::Config::incluster().map_err(|_| UserAuthError::NamespaceNotAllowed)?
+            };
+            Client::try_from(config).map_err(|_| UserAuthError::NamespaceNotAllowed)?
+        }
+        None => client.clone(),
+    };
+
+    // get available boxes
+    let boxes = {
+        let api = Api::<Node>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await
+            .map(|list| {
+                list.items
+                    .into_iter()
+                    .filter(|item| {
+                        item.status
+                            .as_ref()
+                            .and_then(|status| status.conditions.as_ref())
+                            .and_then(|conditions| conditions.last())
+                            .map(|condition| condition.status == "True")
+                            .unwrap_or_default()
+                    })
+                    .map(|item| (item.name_any(), item.spec.unwrap()))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default()
+    };
+
+    let box_bindings = {
+        let api = Api::<UserBoxBindingCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await
+            .map(|list| {
+                list.items
+                    .into_iter()
+                    .filter(|item| groups.is_bound(&item.spec))
+                    .filter(|item| {
+                        item.spec
+                            .expired_timestamp
+                            .as_ref()
+                            .map(|timestamp| timestamp < &now)
+                            .unwrap_or(true)
+                    })
+                    .filter_map(|item| {
+                        Some(UserBoxBindingSpec {
+                            user: item.spec.user,
+                            group: item.spec.group,
+                            r#box: boxes.get(&item.spec.r#box)?.clone(),
+                            autologin: item.spec.autologin,
+                            expired_timestamp: item.spec.expired_timestamp,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+
+    // get available quotas
+    let quotas = {
+        let api = Api::<UserBoxQuotaCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await
+            .map(|list| {
+                list.items
+                    .into_iter()
+                    .map(|item| (item.name_any(), item.spec))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default()
+    };
+
+    let box_quota_bindings = {
+        let api = Api::<UserBoxQuotaBindingCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await
+            .map(|list| {
+                list.items
+                    .into_iter()
+                    .filter(|item| groups.is_bound(&item.spec))
+                    .filter(|item| {
+                        item.spec
+                            .expired_timestamp
+                            .as_ref()
+                            .map(|timestamp| timestamp < &now)
+                            .unwrap_or(true)
+                    })
+                    .filter_map(|item| {
+                        Some(UserBoxQuotaBindingSpec {
+                            user: item.spec.user,
+                            group: item.spec.group,
+                            quota: quotas.get(&item.spec.quota)?.clone(),
+                            expired_timestamp: item.spec.expired_timestamp,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+
+    // get current box info
+    let labels = user.labels();
+    let box_name = if labels
+        .get(::ark_api::consts::LABEL_BIND_STATUS)
+        .map(AsRef::as_ref)
+        == Some("true")
+    {
+        labels.get(::ark_api::consts::LABEL_BIND_NODE).cloned()
+    } else {
+        None
+    };
+
+    // Login Successed!
+    info!("[{now}] auth accepted: {user_name:?}");
+
+    Ok(UserSession {
+        box_bindings: Arc::new(box_bindings),
+        box_name,
+        box_quota_bindings: Arc::new(box_quota_bindings),
+        kube: Some(client),
+        namespace,
+        token: token.map(Into::into),
+        role,
+        user: Arc::new(user),
+        user_name,
+    })
+}
+
 pub trait AuthUserSessionRef {
     fn try_into_ark_session(self) -> Result<SessionRef<'static>, UserAuthError>;
 }
@@ -300,7 +425,7 @@ fn get_user_name_with_timestamp(
     now: DateTime<Utc>,
 ) -> Result<String, UserAuthError> {
     ::std::env::var("DASH_UNSAFE_MOCK_USERNAME").or_else(|_| {
-        get_user_token(request).and_then(|token| get_user_name_with_timestamp_impl(&token, now))
+        get_user_token(request).and_then(|token| get_user_name_with_timestamp_impl(token, now))
     })
 }
 
@@ -311,7 +436,17 @@ fn get_user_name_with_timestamp(
     now: DateTime<Utc>,
 ) -> Result<String, UserAuthError> {
     let token = get_user_token(request)?;
-    get_user_name_with_timestamp_impl(&token, now)
+    get_user_name_with_timestamp_impl(token, now)
+}
+
+/// Returns the OIDC group claims of the user.
+#[cfg(feature = "actix")]
+pub fn get_user_groups(request: &::actix_web::HttpRequest) -> Result<Vec<String>, UserAuthError> {
+    // get current time
+    let now = Utc::now();
+
+    let token = get_user_token(request)?;
+    get_user_payload_with_timestamp_impl(token, now).map(|payload| payload.groups().to_vec())
 }
 
 #[cfg(feature = "actix")]
@@ -319,11 +454,19 @@ fn get_user_name_with_timestamp_impl(
     token: &str,
     now: DateTime<Utc>,
 ) -> Result<String, UserAuthError> {
+    let payload = get_user_payload_with_timestamp_impl(token, now)?;
+    get_user_primary_key(&payload, now)
+}
+
+#[cfg(feature = "actix")]
+fn get_user_payload_with_timestamp_impl(
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<UserAuthPayload, UserAuthError> {
     use base64::Engine;
-    use vine_api::user_auth::UserAuthPayload;
 
     // parse the Authorization token
-    let payload: UserAuthPayload = match match token.split('.').nth(1) {
+    match match token.split('.').nth(1) {
         Some(payload) => ::base64::engine::general_purpose::STANDARD_NO_PAD
             .decode(payload)
             .map_err(Into::into)
@@ -332,14 +475,19 @@ fn get_user_name_with_timestamp_impl(
             "[{now}] the Authorization token is not a Bearer token"
         )),
     } {
-        Ok(payload) => payload,
+        Ok(payload) => Ok(payload),
         Err(e) => {
             warn!("[{now}] failed to parse the token: {token:?}: {e}");
-            return Err(UserAuthError::AuthorizationTokenMalformed);
+            Err(UserAuthError::AuthorizationTokenMalformed)
         }
-    };
+    }
+}
 
-    // get the user primary key
+#[cfg(feature = "actix")]
+fn get_user_primary_key(
+    payload: &UserAuthPayload,
+    now: DateTime<Utc>,
+) -> Result<String, UserAuthError> {
     payload.primary_key().map_err(|e| {
         warn!("[{now}] failed to parse the user's primary key: {payload:?}: {e}");
         UserAuthError::PrimaryKeyMalformed
@@ -350,26 +498,24 @@ fn get_user_name_with_timestamp_impl(
 #[instrument(level = Level::INFO, skip(request), err(Display))]
 fn get_user_namespace(
     request: &::actix_web::HttpRequest,
-    user: &UserCrd,
-    role: UserRoleSpec,
     now: DateTime<Utc>,
-) -> Result<String, UserAuthError> {
+) -> Result<Option<String>, UserAuthError> {
     match request.headers().get(::ark_api::consts::HEADER_NAMESPACE) {
         Some(token) => match token.to_str().map_err(::anyhow::Error::from) {
-            Ok(namespace) => check_user_namespace(Some(namespace.into()), user, role),
+            Ok(namespace) => Ok(Some(namespace.into())),
             Err(e) => {
                 warn!("[{now}] failed to parse the token: {token:?}: {e}");
                 Err(UserAuthError::NamespaceTokenMalformed)
             }
         },
-        None => Ok(user.user_namespace()),
+        None => Ok(None),
     }
 }
 
 #[instrument(level = Level::INFO, skip(client), err(Display))]
 async fn get_user_role(
     client: &::kube::Client,
-    user: &UserCrd,
+    groups: &UserGroups,
     now: DateTime<Utc>,
 ) -> Result<UserRoleSpec, UserAuthError> {
     // get available roles
@@ -388,13 +534,12 @@ async fn get_user_role(
     let role = {
         let api = Api::<UserRoleBindingCrd>::all(client.clone());
         let lp = ListParams::default();
-        let user_name = user.preferred_name();
         api.list(&lp)
             .await
             .map(|list| list.items)
             .unwrap_or_else(|_| Default::default())
             .into_iter()
-            .filter(|item| item.spec.user == user_name)
+            .filter(|item| groups.is_bound(&item.spec))
             .filter(|item| {
                 item.spec
                     .expired_timestamp
@@ -403,7 +548,7 @@ async fn get_user_role(
                     .unwrap_or(true)
             })
             .filter_map(|item| roles.get(&item.spec.role))
-            .copied()
+            .cloned()
             .sum()
     };
     Ok(role)
@@ -432,7 +577,7 @@ fn get_user_token(request: &::actix_web::HttpRequest) -> Result<&str, UserAuthEr
 fn check_user_namespace(
     namespace: Option<String>,
     user: &UserCrd,
-    role: UserRoleSpec,
+    role: &UserRoleSpec,
 ) -> Result<String, UserAuthError> {
     match namespace {
         Some(namespace) => {
@@ -445,3 +590,171 @@ fn check_user_namespace(
         None => Ok(user.user_namespace()),
     }
 }
+
+fn collect_labels<K>(list: ObjectList<PartialObjectMeta<K>>) -> BTreeMap<String, Labels>
+where
+    K: Clone,
+{
+    list.items
+        .into_iter()
+        .filter_map(|item| {
+            let ObjectMeta { name, labels, .. } = item.metadata;
+            Some((name?, labels.unwrap_or_default()))
+        })
+        .collect()
+}
+
+pub(crate) fn matches_rule(rule: &UserRoleRule, target: AuthPolicyTarget<'_>) -> bool {
+    fn matches(selector: Option<&LabelSelector>, labels: Option<&Labels>) -> bool {
+        match selector {
+            Some(selector) => labels.is_some_and(|labels| matches_selector(selector, labels)),
+            None => true,
+        }
+    }
+
+    let AuthPolicyTarget {
+        box_labels,
+        namespace_labels,
+    } = target;
+
+    matches(rule.box_selector.as_ref(), box_labels)
+        && matches(rule.namespace_selector.as_ref(), namespace_labels)
+}
+
+pub(crate) fn matches_selector(selector: &LabelSelector, labels: &Labels) -> bool {
+    let LabelSelector {
+        match_expressions,
+        match_labels,
+    } = selector;
+
+    match_labels
+        .iter()
+        .flatten()
+        .all(|(key, value)| labels.get(key) == Some(value))
+        && match_expressions.iter().flatten().all(|requirement| {
+            let value = labels.get(&requirement.key);
+            let values = requirement.values.as_deref().unwrap_or_default();
+
+            match requirement.operator.as_str() {
+                "In" => value.is_some_and(|value| values.contains(value)),
+                "NotIn" => value.map_or(true, |value| !values.contains(value)),
+                "Exists" => value.is_some(),
+                "DoesNotExist" => value.is_none(),
+                _ => false,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelectorRequirement;
+    use vine_api::user_role::{UserRoleResource, UserRoleVerb};
+
+    use super::*;
+
+    fn labels(items: &[(&str, &str)]) -> Labels {
+        items
+            .iter()
+            .map(|&(key, value)| (key.into(), value.into()))
+            .collect()
+    }
+
+    fn requirement(key: &str, operator: &str, values: &[&str]) -> LabelSelectorRequirement {
+        LabelSelectorRequirement {
+            key: key.into(),
+            operator: operator.into(),
+            values: Some(values.iter().map(|&value| value.into()).collect()),
+        }
+    }
+
+    fn selector(
+        match_labels: &[(&str, &str)],
+        match_expressions: Vec<LabelSelectorRequirement>,
+    ) -> LabelSelector {
+        LabelSelector {
+            match_expressions: Some(match_expressions),
+            match_labels: Some(labels(match_labels)),
+        }
+    }
+
+    #[test]
+    fn selector_match_labels() {
+        let selector = selector(&[("lab", "ai")], Vec::default());
+        assert!(matches_selector(&selector, &labels(&[("lab", "ai")])));
+        assert!(matches_selector(
+            &selector,
+            &labels(&[("lab", "ai"), ("room", "101")]),
+        ));
+        assert!(!matches_selector(&selector, &labels(&[("lab", "os")])));
+        assert!(!matches_selector(&selector, &labels(&[])));
+
+        // an empty selector matches everything
+        assert!(matches_selector(&LabelSelector::default(), &labels(&[])));
+    }
+
+    #[test]
+    fn selector_match_expressions() {
+        let target = labels(&[("lab", "ai"), ("room", "101")]);
+        let matches = |requirement| matches_selector(&selector(&[], vec![requirement]), &target);
+
+        assert!(matches(requirement("lab", "In", &["ai", "os"])));
+        assert!(!matches(requirement("lab", "In", &["os"])));
+        assert!(!matches(requirement("floor", "In", &["1"])));
+
+        assert!(matches(requirement("lab", "NotIn", &["os"])));
+        assert!(!matches(requirement("lab", "NotIn", &["ai"])));
+        assert!(matches(requirement("floor", "NotIn", &["1"])));
+
+        assert!(matches(requirement("room", "Exists", &[])));
+        assert!(!matches(requirement("floor", "Exists", &[])));
+
+        assert!(matches(requirement("floor", "DoesNotExist", &[])));
+        assert!(!matches(requirement("room", "DoesNotExist", &[])));
+
+        assert!(!matches(requirement("lab", "Unknown", &["ai"])));
+    }
+
+    #[test]
+    fn rule_match_target() {
+        let box_labels = labels(&[("lab", "ai")]);
+        let namespace_labels = labels(&[("class", "os")]);
+
+        let mut rule = UserRoleRule {
+            resources: vec![UserRoleResource::Desktop],
+            verbs: vec![UserRoleVerb::Exec],
+            ..Default::default()
+        };
+
+        // a rule without selectors matches every target
+        assert!(matches_rule(&rule, AuthPolicyTarget::default()));
+
+        rule.box_selector = Some(selector(&[("lab", "ai")], Vec::default()));
+        assert!(matches_rule(
+            &rule,
+            AuthPolicyTarget {
+                box_labels: Some(&box_labels),
+                namespace_labels: None,
+            },
+        ));
+        // missing labels never match the selectors
+        assert!(!matches_rule(&rule, AuthPolicyTarget::default()));
+
+        rule.namespace_selector = Some(selector(&[("class", "ai")], Vec::default()));
+        assert!(!matches_rule(
+            &rule,
+            AuthPolicyTarget {
+                box_labels: Some(&box_labels),
+                namespace_labels: Some(&namespace_labels),
+            },
+        ));
+
+        rule.namespace_selector = Some(selector(&[("class", "os")], Vec::default()));
+        assert!(matches_rule(
+            &rule,
+            AuthPolicyTarget {
+                box_labels: Some(&box_labels),
+                namespace_labels: Some(&namespace_labels),
+            },
+        ));
+    }
+}
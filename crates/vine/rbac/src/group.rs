@@ -0,0 +1,141 @@
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    Api, Client, ResourceExt,
+};
+use serde_json::json;
+use tracing::{instrument, Level};
+use vine_api::{
+    user::UserCrd,
+    user_group::{UserBindingSubject, UserGroupCrd},
+};
+
+/// The groups which a user belongs to, either statically or by the OIDC group claims.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserGroups {
+    pub(crate) groups: BTreeSet<String>,
+    pub(crate) user_name: String,
+}
+
+impl UserGroups {
+    #[instrument(level = Level::INFO, skip(client), err(Display))]
+    pub async fn load(client: &Client, user_name: &str, claims: &[String]) -> Result<Self> {
+        let api = Api::<UserGroupCrd>::all(client.clone());
+        let lp = ListParams::default();
+        let groups = api
+            .list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list user groups: {error}"))?
+            .items
+            .into_iter()
+            .filter(|item| item.spec.contains(user_name, claims))
+            .map(|item| item.name_any())
+            .collect();
+
+        Ok(Self {
+            groups,
+            user_name: user_name.into(),
+        })
+    }
+
+    pub fn contains(&self, group: &str) -> bool {
+        self.groups.contains(group)
+    }
+
+    /// Returns `true` if the binding targets the user or any group of the user.
+    pub fn is_bound(&self, subject: &impl UserBindingSubject) -> bool {
+        subject.is_valid()
+            && (subject.user() == Some(self.user_name.as_str())
+                || subject.group().is_some_and(|group| self.contains(group)))
+    }
+}
+
+/// Returns the group claims of the user, as of the last login.
+///
+/// It is used to resolve the groups without the user's token, e.g. on autologin.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn load_claims(client: &Client, user_name: &str) -> Result<Vec<String>> {
+    let api = Api::<UserCrd>::all(client.clone());
+    api.get_opt(user_name)
+        .await
+        .map(|user| {
+            user.and_then(|user| user.status)
+                .map(|status| status.group_claims)
+                .unwrap_or_default()
+        })
+        .map_err(|error| anyhow!("failed to get the user {user_name:?}: {error}"))
+}
+
+/// Stores the group claims of the user, if changed.
+#[instrument(level = Level::INFO, skip(client, user), fields(user_name = %user.name_any()), err(Display))]
+pub(crate) async fn save_claims(
+    client: &Client,
+    user: &UserCrd,
+    claims: &[String],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let last_claims = user
+        .status
+        .as_ref()
+        .map(|status| status.group_claims.as_slice())
+        .unwrap_or_default();
+    if last_claims == claims {
+        return Ok(());
+    }
+
+    let api = Api::<UserCrd>::all(client.clone());
+    let pp = PatchParams::default();
+    let patch = Patch::Merge(json!({
+        "status": {
+            "groupClaims": claims,
+            "lastUpdated": now,
+        },
+    }));
+    api.patch_status(&user.name_any(), &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to update the group claims: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use vine_api::user_role_binding::UserRoleBindingSpec;
+
+    use super::*;
+
+    fn binding(user: Option<&str>, group: Option<&str>) -> UserRoleBindingSpec {
+        UserRoleBindingSpec {
+            user: user.map(Into::into),
+            group: group.map(Into::into),
+            role: "student".into(),
+            expired_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn bound_by_user_or_group() {
+        let groups = UserGroups {
+            groups: ["class-a".into()].into(),
+            user_name: "alice".into(),
+        };
+
+        assert!(groups.is_bound(&binding(Some("alice"), None)));
+        assert!(groups.is_bound(&binding(None, Some("class-a"))));
+        assert!(!groups.is_bound(&binding(Some("bob"), None)));
+        assert!(!groups.is_bound(&binding(None, Some("class-b"))));
+    }
+
+    #[test]
+    fn bound_by_invalid_binding() {
+        let groups = UserGroups {
+            groups: ["class-a".into()].into(),
+            user_name: "alice".into(),
+        };
+
+        assert!(!groups.is_bound(&binding(None, None)));
+        assert!(!groups.is_bound(&binding(Some("alice"), Some("class-a"))));
+    }
+}
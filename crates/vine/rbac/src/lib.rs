@@ -1,7 +1,12 @@
 #![recursion_limit = "256"]
 
 pub mod auth;
+pub mod group;
 pub mod login;
 pub mod logout;
 mod node_selector;
+mod provider;
+pub mod reservation;
 mod session;
+pub mod throttle;
+pub mod usage;
@@ -1,19 +1,26 @@
 use anyhow::Result;
-use kube::Client;
-use tracing::{instrument, Level};
-use vine_api::user_auth::UserSessionResponse;
+use chrono::{DateTime, Utc};
+use kube::{api::ListParams, Api, Client};
+use tracing::{info, instrument, warn, Level};
+use vine_api::{
+    user_auth::{UserAuthCrd, UserAuthCredentials, UserAuthError, UserSessionResponse},
+    user_auth_binding::{UserAuthBindingCrd, UserAuthBindingSpec},
+    user_session::UserSession,
+};
 
 #[instrument(level = Level::INFO, skip(client), err(Display))]
 pub async fn execute(
     client: &Client,
     box_name: &str,
     user_name: &str,
+    groups: &[String],
     logout_on_failed: bool,
 ) -> Result<UserSessionResponse> {
     super::session::execute_with(
         client,
         box_name,
         user_name,
+        groups,
         true,
         |session_manager, spec| async move {
             session_manager
@@ -23,3 +30,217 @@ pub async fn execute(
     )
     .await
 }
+
+/// Signs in to the box with the password-based auth providers.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn execute_with_credentials(
+    client: &Client,
+    box_name: &str,
+    credentials: &UserAuthCredentials,
+    logout_on_failed: bool,
+) -> Result<UserSessionResponse> {
+    // get current time
+    let now = Utc::now();
+
+    match verify_credentials(client, credentials, now).await {
+        Ok((user_name, claims)) => {
+            execute(client, box_name, &user_name, &claims, logout_on_failed).await
+        }
+        Err(error) => Ok(error.into()),
+    }
+}
+
+/// Signs in with the password-based auth providers.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn authenticate(
+    client: &Client,
+    credentials: &UserAuthCredentials,
+    namespace: Option<String>,
+) -> Result<UserSession, UserAuthError> {
+    // get current time
+    let now = Utc::now();
+
+    let (user_name, claims) = verify_credentials(client, credentials, now).await?;
+    super::auth::load_user_session(client, &user_name, &claims, namespace, None, now).await
+}
+
+/// Returns the user name and the group claims of the verified user.
+async fn verify_credentials(
+    client: &Client,
+    credentials: &UserAuthCredentials,
+    now: DateTime<Utc>,
+) -> Result<(String, Vec<String>), UserAuthError> {
+    // get the auth provider
+    let auth_name = &credentials.auth;
+    let spec = {
+        let api = Api::<UserAuthCrd>::all(client.clone());
+        match api.get_opt(auth_name).await {
+            Ok(Some(auth)) => auth.spec,
+            Ok(None) => return Err(UserAuthError::AuthProviderNotFound),
+            Err(error) => {
+                warn!("[{now}] failed to get the auth provider {auth_name:?}: {error}");
+                return Err(UserAuthError::AuthProviderUnavailable);
+            }
+        }
+    };
+    if !spec.is_password_based() {
+        return Err(UserAuthError::AuthProviderNotSupported);
+    }
+
+    // verify the credentials
+    let identity = match super::provider::verify(client, &spec, credentials).await {
+        Ok(Some(identity)) => identity,
+        Ok(None) => {
+            let user_id = &credentials.username;
+            info!("[{now}] auth denied: {auth_name:?} => {user_id:?}");
+            return Err(UserAuthError::CredentialsInvalid);
+        }
+        Err(error) => {
+            warn!("[{now}] failed to verify the credentials with {auth_name:?}: {error}");
+            return Err(UserAuthError::AuthProviderUnavailable);
+        }
+    };
+
+    // find the user bound to the identity
+    let bindings = {
+        let api = Api::<UserAuthBindingCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp).await.map_err(|error| {
+            warn!("[{now}] failed to list the auth bindings: {error}");
+            UserAuthError::AuthProviderUnavailable
+        })?
+    };
+    let user_name = find_bound_user(
+        bindings.items.into_iter().map(|item| item.spec),
+        auth_name,
+        &identity.user_id,
+        now,
+    )?;
+
+    let claims = scope_claims(auth_name, &identity.claims);
+    Ok((user_name, claims))
+}
+
+/// Returns the name of the user bound to the user id of the auth provider.
+///
+/// NOTE: the user id is never used as a user name, which could be spoofed by the provider
+fn find_bound_user(
+    bindings: impl IntoIterator<Item = UserAuthBindingSpec>,
+    auth_name: &str,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> Result<String, UserAuthError> {
+    bindings
+        .into_iter()
+        .filter(|binding| binding.auth == auth_name && binding.user_id == user_id)
+        .find(|binding| {
+            binding
+                .expired_timestamp
+                .as_ref()
+                .map(|timestamp| timestamp > &now)
+                .unwrap_or(true)
+        })
+        .map(|binding| binding.user)
+        .ok_or_else(|| {
+            warn!("[{now}] no user is bound to {auth_name:?} => {user_id:?}");
+            UserAuthError::UserNotRegistered
+        })
+}
+
+/// Scopes the group claims by the auth provider,
+/// so that a provider cannot claim the groups of the others.
+fn scope_claims(auth_name: &str, claims: &[String]) -> Vec<String> {
+    claims
+        .iter()
+        .map(|claim| format!("{auth_name}:{claim}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn binding(user: &str, auth: &str, user_id: &str) -> UserAuthBindingSpec {
+        UserAuthBindingSpec {
+            user: user.into(),
+            auth: auth.into(),
+            user_id: user_id.into(),
+            expired_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn find_user_by_binding() {
+        let now = Utc::now();
+        let bindings = || {
+            vec![
+                binding("alice", "ldap", "alice01"),
+                binding("bob", "local", "alice01"),
+                binding("carol", "ldap", "carol"),
+            ]
+        };
+
+        assert_eq!(
+            find_bound_user(bindings(), "ldap", "alice01", now).as_deref(),
+            Ok("alice"),
+        );
+        assert_eq!(
+            find_bound_user(bindings(), "local", "alice01", now).as_deref(),
+            Ok("bob"),
+        );
+    }
+
+    #[test]
+    fn find_no_user_without_binding() {
+        let now = Utc::now();
+        let bindings = vec![binding("alice", "ldap", "alice01")];
+
+        // the user ids are never used as the user names
+        assert_eq!(
+            find_bound_user(bindings.clone(), "ldap", "alice", now),
+            Err(UserAuthError::UserNotRegistered),
+        );
+        assert_eq!(
+            find_bound_user(bindings, "local", "alice01", now),
+            Err(UserAuthError::UserNotRegistered),
+        );
+        assert_eq!(
+            find_bound_user(Vec::default(), "ldap", "alice01", now),
+            Err(UserAuthError::UserNotRegistered),
+        );
+    }
+
+    #[test]
+    fn find_no_user_with_expired_binding() {
+        let now = Utc::now();
+        let expired = UserAuthBindingSpec {
+            expired_timestamp: Some(now - TimeDelta::minutes(1)),
+            ..binding("alice", "ldap", "alice01")
+        };
+        let valid = UserAuthBindingSpec {
+            expired_timestamp: Some(now + TimeDelta::minutes(1)),
+            ..binding("bob", "ldap", "alice01")
+        };
+
+        assert_eq!(
+            find_bound_user(vec![expired.clone()], "ldap", "alice01", now),
+            Err(UserAuthError::UserNotRegistered),
+        );
+        assert_eq!(
+            find_bound_user(vec![expired, valid], "ldap", "alice01", now).as_deref(),
+            Ok("bob"),
+        );
+    }
+
+    #[test]
+    fn scope_claims_by_provider() {
+        let claims = vec!["admins".to_string(), "cn=lab,dc=example,dc=com".to_string()];
+        assert_eq!(
+            scope_claims("ldap", &claims),
+            ["ldap:admins", "ldap:cn=lab,dc=example,dc=com"],
+        );
+        assert!(scope_claims("ldap", &[]).is_empty());
+    }
+}
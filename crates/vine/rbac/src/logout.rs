@@ -1,6 +1,7 @@
 use anyhow::Result;
+use chrono::Utc;
 use kube::Client;
-use tracing::{instrument, Level};
+use tracing::{instrument, warn, Level};
 use vine_api::user_auth::UserSessionResponse;
 
 #[instrument(level = Level::INFO, skip(client), err(Display))]
@@ -8,13 +9,21 @@ pub async fn execute(
     client: &Client,
     box_name: &str,
     user_name: &str,
+    groups: &[String],
 ) -> Result<UserSessionResponse> {
     super::session::execute_with(
         client,
         box_name,
         user_name,
+        groups,
         false,
-        |session_manager, spec| async move { session_manager.delete(&spec.as_ref()).await },
+        |session_manager, spec| async move {
+            // account the remaining usage before unbinding the box
+            if let Err(error) = crate::usage::account(client, &spec.node, Utc::now()).await {
+                warn!("failed to account the usage: {error}");
+            }
+            session_manager.delete(&spec.as_ref()).await
+        },
     )
     .await
 }
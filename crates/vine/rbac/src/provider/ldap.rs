@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+use ldap3::{ldap_escape, LdapConnAsync, Scope, SearchEntry};
+use tokio::spawn;
+use tracing::{instrument, warn, Level};
+use vine_api::user_auth::UserAuthCredentials;
+
+use super::UserIdentity;
+
+/// Result code of the invalid credentials.
+const LDAP_INVALID_CREDENTIALS: u32 = 49;
+
+pub(super) struct LdapSpec<'a> {
+    pub(super) url: &'a str,
+    pub(super) bind_dn: Option<&'a str>,
+    pub(super) bind_password: Option<&'a str>,
+    pub(super) base_dn: &'a str,
+    pub(super) user_filter: &'a str,
+    pub(super) user_id_attribute: &'a str,
+    pub(super) group_attribute: Option<&'a str>,
+}
+
+/// Searches the user, and then binds to the directory as the user.
+#[instrument(level = Level::INFO, skip(spec), err(Display))]
+pub(super) async fn verify(
+    spec: LdapSpec<'_>,
+    credentials: &UserAuthCredentials,
+) -> Result<Option<UserIdentity>> {
+    let LdapSpec {
+        url,
+        bind_dn,
+        bind_password,
+        base_dn,
+        user_filter,
+        user_id_attribute,
+        group_attribute,
+    } = spec;
+    let UserAuthCredentials {
+        username, password, ..
+    } = credentials;
+
+    let (conn, mut ldap) = LdapConnAsync::new(url)
+        .await
+        .map_err(|error| anyhow!("failed to connect to LDAP: {error}"))?;
+    spawn(async move {
+        if let Err(error) = conn.drive().await {
+            warn!("failed to drive LDAP connection: {error}");
+        }
+    });
+
+    // Step 1. Search the user
+    if let Some(bind_dn) = bind_dn {
+        ldap.simple_bind(bind_dn, bind_password.unwrap_or_default())
+            .await
+            .and_then(|result| result.success())
+            .map_err(|error| anyhow!("failed to bind to LDAP as {bind_dn:?}: {error}"))?;
+    }
+
+    let filter = user_filter.replace("{username}", &ldap_escape(username));
+    let mut attrs = vec![user_id_attribute];
+    attrs.extend(group_attribute);
+
+    let (entries, _) = ldap
+        .search(base_dn, Scope::Subtree, &filter, attrs)
+        .await
+        .and_then(|result| result.success())
+        .map_err(|error| anyhow!("failed to search LDAP users: {error}"))?;
+
+    // NOTE: the user should be unique
+    let mut entries = entries.into_iter().map(SearchEntry::construct);
+    let entry = match (entries.next(), entries.next()) {
+        (Some(entry), None) => entry,
+        _ => {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        }
+    };
+
+    // Step 2. Bind as the user
+    let result = ldap
+        .simple_bind(&entry.dn, password)
+        .await
+        .map_err(|error| anyhow!("failed to bind to LDAP: {error}"))?;
+    let _ = ldap.unbind().await;
+
+    match result.rc {
+        0 => {}
+        LDAP_INVALID_CREDENTIALS => return Ok(None),
+        rc => {
+            let text = &result.text;
+            return Err(anyhow!("failed to bind to LDAP: {text} ({rc})"));
+        }
+    }
+
+    // Step 3. Collect the user attributes
+    let SearchEntry { mut attrs, .. } = entry;
+    let user_id = attrs
+        .remove(user_id_attribute)
+        .and_then(|values| values.into_iter().next())
+        .unwrap_or_else(|| username.clone());
+    let claims = group_attribute
+        .and_then(|attr| attrs.remove(attr))
+        .unwrap_or_default();
+
+    Ok(Some(UserIdentity { user_id, claims }))
+}
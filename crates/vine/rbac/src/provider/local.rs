@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use k8s_openapi::api::core::v1::Secret;
+use kube::{Api, Client};
+use tracing::{instrument, warn, Level};
+use vine_api::user_auth::UserAuthCredentials;
+
+use super::UserIdentity;
+
+/// An argon2 hash of a random password, which is verified instead for the missing users,
+/// so that they cannot be told apart from the others by the response time.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$dmluZS1kdW1teS1zYWx0$20Sni2bemz3esdHbDjufAQQiXBFqfBjAjgf/am7C+cE";
+
+/// Verifies the password with the argon2 hash, which is stored in the secret by the user id.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub(super) async fn verify(
+    client: &Client,
+    secret_name: &str,
+    credentials: &UserAuthCredentials,
+) -> Result<Option<UserIdentity>> {
+    let api = Api::<Secret>::namespaced(client.clone(), ::vine_api::consts::NAMESPACE);
+    let secret = api
+        .get(secret_name)
+        .await
+        .map_err(|error| anyhow!("failed to get password secret {secret_name:?}: {error}"))?;
+
+    let hash = secret
+        .data
+        .and_then(|mut data| data.remove(&credentials.username));
+    Ok(verify_password(
+        credentials,
+        hash.as_ref().map(|hash| hash.0.as_slice()),
+    ))
+}
+
+/// Verifies the password with the given argon2 hash, if any.
+fn verify_password(credentials: &UserAuthCredentials, hash: Option<&[u8]>) -> Option<UserIdentity> {
+    let UserAuthCredentials {
+        username, password, ..
+    } = credentials;
+
+    let hash = match hash.map(parse_password_hash) {
+        Some(Ok(hash)) => Some(hash),
+        Some(Err(error)) => {
+            warn!("malformed password hash of {username:?}: {error}");
+            None
+        }
+        None => None,
+    };
+
+    // NOTE: always verify a hash, even if the user is missing
+    let is_known = hash.is_some();
+    let hash = match hash {
+        Some(hash) => hash,
+        None => parse_password_hash(DUMMY_PASSWORD_HASH.as_bytes()).ok()?,
+    };
+    let is_verified = Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok();
+
+    if is_known && is_verified {
+        Some(UserIdentity {
+            user_id: username.clone(),
+            claims: Vec::default(),
+        })
+    } else {
+        None
+    }
+}
+
+fn parse_password_hash(hash: &[u8]) -> Result<PasswordHash<'_>, String> {
+    ::core::str::from_utf8(hash)
+        .map_err(|error| error.to_string())
+        .and_then(|hash| PasswordHash::new(hash.trim()).map_err(|error| error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An argon2 hash of `correct horse battery staple`
+    const PASSWORD_HASH: &str =
+        "$argon2id$v=19$m=19456,t=2,p=1$dmluZS10ZXN0LXNhbHQ$fpsC0XDVyMdebFxAt2jjqarfbry46fkymLeUQ3hHkss";
+
+    fn credentials(password: &str) -> UserAuthCredentials {
+        UserAuthCredentials {
+            auth: "local".into(),
+            username: "alice".into(),
+            password: password.into(),
+        }
+    }
+
+    #[test]
+    fn parse_password_hashes() {
+        assert!(parse_password_hash(PASSWORD_HASH.as_bytes()).is_ok());
+        assert!(parse_password_hash(DUMMY_PASSWORD_HASH.as_bytes()).is_ok());
+
+        // the trailing new lines of the secrets are ignored
+        assert!(parse_password_hash(format!("{PASSWORD_HASH}\n").as_bytes()).is_ok());
+
+        assert!(parse_password_hash(b"correct horse battery staple").is_err());
+        assert!(parse_password_hash(b"\xff\xfe").is_err());
+    }
+
+    #[test]
+    fn verify_passwords() {
+        let hash = Some(PASSWORD_HASH.as_bytes());
+        assert_eq!(
+            verify_password(&credentials("correct horse battery staple"), hash),
+            Some(UserIdentity {
+                user_id: "alice".into(),
+                claims: Vec::default(),
+            }),
+        );
+        assert_eq!(verify_password(&credentials("Tr0ub4dor&3"), hash), None);
+    }
+
+    #[test]
+    fn verify_no_passwords_of_missing_users() {
+        assert_eq!(
+            verify_password(&credentials("vine-dummy-password"), None),
+            None
+        );
+        assert_eq!(
+            verify_password(
+                &credentials("correct horse battery staple"),
+                Some(b"malformed"),
+            ),
+            None,
+        );
+    }
+}
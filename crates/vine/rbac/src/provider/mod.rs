@@ -0,0 +1,102 @@
+mod ldap;
+mod local;
+
+use anyhow::{anyhow, Result};
+use k8s_openapi::api::core::v1::{Secret, SecretKeySelector};
+use kube::{Api, Client};
+use vine_api::user_auth::{UserAuthCredentials, UserAuthSpec};
+
+/// A user verified by an auth provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UserIdentity {
+    /// User id in the auth provider
+    pub(crate) user_id: String,
+    /// Group claims in the auth provider
+    pub(crate) claims: Vec<String>,
+}
+
+/// Verifies the credentials, returning `None` if they are invalid.
+pub(crate) async fn verify(
+    client: &Client,
+    spec: &UserAuthSpec,
+    credentials: &UserAuthCredentials,
+) -> Result<Option<UserIdentity>> {
+    if !is_filled(credentials) {
+        return Ok(None);
+    }
+
+    match spec {
+        UserAuthSpec::OIDC { .. } => Err(anyhow!("OIDC does not support passwords")),
+        UserAuthSpec::Ldap {
+            url,
+            bind_dn,
+            bind_password,
+            base_dn,
+            user_filter,
+            user_id_attribute,
+            group_attribute,
+        } => {
+            let bind_password = match bind_password {
+                Some(selector) => Some(get_secret_value(client, selector).await?),
+                None => None,
+            };
+            let spec = self::ldap::LdapSpec {
+                url: url.0.as_str(),
+                bind_dn: bind_dn.as_deref(),
+                bind_password: bind_password.as_deref(),
+                base_dn,
+                user_filter,
+                user_id_attribute,
+                group_attribute: group_attribute.as_deref(),
+            };
+            self::ldap::verify(spec, credentials).await
+        }
+        UserAuthSpec::Local { secret_name } => {
+            self::local::verify(client, secret_name, credentials).await
+        }
+    }
+}
+
+/// Returns `true` if both the user name and the password are given.
+///
+/// NOTE: never accept empty passwords, which could be an anonymous bind
+fn is_filled(credentials: &UserAuthCredentials) -> bool {
+    !credentials.username.is_empty() && !credentials.password.is_empty()
+}
+
+async fn get_secret_value(client: &Client, selector: &SecretKeySelector) -> Result<String> {
+    let SecretKeySelector { key, name, .. } = selector;
+
+    let api = Api::<Secret>::namespaced(client.clone(), ::vine_api::consts::NAMESPACE);
+    let secret = api
+        .get(name)
+        .await
+        .map_err(|error| anyhow!("failed to get secret {name:?}: {error}"))?;
+
+    let value = secret
+        .data
+        .and_then(|mut data| data.remove(key))
+        .ok_or_else(|| anyhow!("no such secret key {key:?} in {name:?}"))?;
+    String::from_utf8(value.0).map_err(|error| anyhow!("malformed secret {name:?}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(username: &str, password: &str) -> UserAuthCredentials {
+        UserAuthCredentials {
+            auth: "ldap".into(),
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    #[test]
+    fn reject_empty_credentials() {
+        assert!(is_filled(&credentials("alice", "secret")));
+        assert!(!is_filled(&credentials("alice", "")));
+        assert!(!is_filled(&credentials("", "secret")));
+        assert!(!is_filled(&credentials("", "")));
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use k8s_openapi::{api::core::v1::Node, apimachinery::pkg::apis::meta::v1::ObjectMeta};
+use kube::{
+    api::{DeleteParams, ListParams, PostParams},
+    Api, Client, ResourceExt,
+};
+use tracing::{instrument, Level};
+use vine_api::{
+    user_box_binding::{UserBoxBindingCrd, UserBoxBindingSpec},
+    user_box_reservation::{UserBoxReservationCrd, UserBoxReservationSlot, UserBoxReservationSpec},
+    user_group::UserBindingSubject,
+    user_role::UserRolePermission,
+    user_session::UserSession,
+};
+
+use crate::{
+    auth::{AuthPolicyTarget, AuthUserSession},
+    group::UserGroups,
+};
+
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn list(client: &Client) -> Result<Vec<UserBoxReservationCrd>> {
+    let api = Api::<UserBoxReservationCrd>::all(client.clone());
+    let lp = ListParams::default();
+    api.list(&lp)
+        .await
+        .map(|list| list.items)
+        .map_err(|error| anyhow!("failed to list box reservations: {error}"))
+}
+
+/// Creates a reservation, unless it conflicts with the existing ones.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn create(
+    client: &Client,
+    spec: UserBoxReservationSpec,
+) -> Result<UserBoxReservationCrd> {
+    if !spec.is_valid() {
+        bail!("the reservation should have either a user or a group")
+    }
+    if spec.r#box.is_none() && spec.box_selector.is_none() {
+        bail!("the reservation has no box nor box selector")
+    }
+    if spec.end_timestamp <= spec.start_timestamp {
+        bail!("the reservation should end after the start")
+    }
+
+    let conflicts = find_conflicts(client, &spec).await?;
+    if !conflicts.is_empty() {
+        bail!("the reservation conflicts with: {conflicts:?}")
+    }
+
+    let api = Api::<UserBoxReservationCrd>::all(client.clone());
+    let pp = PostParams::default();
+    let prefix = spec.user().or(spec.group()).unwrap_or_default();
+    let reservation = UserBoxReservationCrd {
+        metadata: ObjectMeta {
+            generate_name: Some(format!("{prefix}-")),
+            ..Default::default()
+        },
+        spec,
+    };
+    api.create(&pp, &reservation)
+        .await
+        .map_err(|error| anyhow!("failed to create a box reservation: {error}"))
+}
+
+/// Asserts that the user can reserve the box.
+///
+/// The user should be permitted to bind to the box, which is not bound to the others.
+#[instrument(level = Level::INFO, skip(client, session), fields(user_name = %session.user_name), err(Display))]
+pub async fn assert_reservable(
+    client: &Client,
+    session: &UserSession,
+    box_name: &str,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let node = {
+        let api = Api::<Node>::all(client.clone());
+        match api.get_opt(box_name).await? {
+            Some(node) => node,
+            None => bail!("no such box: {box_name:?}"),
+        }
+    };
+
+    let target = AuthPolicyTarget {
+        box_labels: Some(node.labels()),
+        namespace_labels: None,
+    };
+    if !session.is_permitted(UserRolePermission::BOX_BIND, target) {
+        bail!("the box is not permitted to be bound: {box_name:?}")
+    }
+
+    let bindings = {
+        let api = Api::<UserBoxBindingCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list box bindings: {error}"))?
+            .items
+            .into_iter()
+            .map(|item| item.spec)
+            .collect::<Vec<_>>()
+    };
+    let claims = crate::group::load_claims(client, &session.user_name).await?;
+    let groups = UserGroups::load(client, &session.user_name, &claims).await?;
+    if is_bound_to_others(&bindings, box_name, &groups, now) {
+        bail!("the box is bound to the others: {box_name:?}")
+    }
+    Ok(())
+}
+
+/// Cancels a reservation.
+///
+/// If the owner is given, only the reservations of the owner can be canceled.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn cancel(client: &Client, name: &str, owner: Option<&str>) -> Result<()> {
+    let api = Api::<UserBoxReservationCrd>::all(client.clone());
+    let reservation = match api.get_opt(name).await? {
+        Some(reservation) => reservation,
+        None => bail!("no such box reservation: {name:?}"),
+    };
+    if let Some(owner) = owner {
+        if reservation.spec.user() != Some(owner) {
+            bail!("the box reservation is not owned by {owner:?}: {name:?}")
+        }
+    }
+
+    let dp = DeleteParams::default();
+    api.delete(name, &dp)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to cancel a box reservation: {error}"))
+}
+
+/// Returns the active slot of the box reserved to the other users, if any.
+#[instrument(level = Level::INFO, skip(client, node, groups), fields(node_name = %node.name_any()), err(Display))]
+pub(crate) async fn find_reserved(
+    client: &Client,
+    node: &Node,
+    groups: &UserGroups,
+    now: DateTime<Utc>,
+) -> Result<Option<UserBoxReservationSlot>> {
+    let mut reserved = None;
+    for reservation in list(client).await? {
+        let spec = &reservation.spec;
+        if !spec.is_valid() || !is_targeted(spec, node) {
+            continue;
+        }
+        if let Some(slot) = spec.slot_at(now) {
+            if groups.is_bound(spec) {
+                // the user has reserved the box
+                return Ok(None);
+            }
+            reserved = reserved.max(Some(slot));
+        }
+    }
+    Ok(reserved)
+}
+
+/// Returns the active or the next slot of the box reserved to the others than the user, if any.
+///
+/// The sessions of the user should be logged out when the slot starts.
+#[instrument(level = Level::INFO, skip(client, node), fields(node_name = %node.name_any()), err(Display))]
+pub async fn find_upcoming_reserved(
+    client: &Client,
+    node: &Node,
+    user_name: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<UserBoxReservationSlot>> {
+    let claims = crate::group::load_claims(client, user_name).await?;
+    let groups = UserGroups::load(client, user_name, &claims).await?;
+
+    Ok(list(client)
+        .await?
+        .iter()
+        .map(|reservation| &reservation.spec)
+        .filter(|spec| spec.is_valid() && is_targeted(spec, node) && !groups.is_bound(*spec))
+        .filter_map(|spec| spec.slots_between(now, DateTime::<Utc>::MAX_UTC).next())
+        .min())
+}
+
+/// Returns the names of the reservations which conflict with the given one.
+async fn find_conflicts(client: &Client, spec: &UserBoxReservationSpec) -> Result<Vec<String>> {
+    let nodes = {
+        let api = Api::<Node>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp).await?.items
+    };
+
+    Ok(list(client)
+        .await?
+        .into_iter()
+        .filter(|reservation| {
+            let other = &reservation.spec;
+            let is_same_box = (spec.r#box.is_some() && spec.r#box == other.r#box)
+                || nodes
+                    .iter()
+                    .any(|node| is_targeted(spec, node) && is_targeted(other, node));
+            is_same_box && spec.overlaps(other)
+        })
+        .map(|reservation| reservation.name_any())
+        .collect())
+}
+
+/// Returns `true` if the box is bound only to the others than the user.
+fn is_bound_to_others(
+    bindings: &[UserBoxBindingSpec],
+    box_name: &str,
+    groups: &UserGroups,
+    now: DateTime<Utc>,
+) -> bool {
+    let mut bindings = bindings
+        .iter()
+        .filter(|binding| binding.r#box == box_name && binding.is_valid())
+        .filter(|binding| {
+            binding
+                .expired_timestamp
+                .map_or(true, |timestamp| timestamp > now)
+        })
+        .peekable();
+
+    bindings.peek().is_some() && !bindings.any(|binding| groups.is_bound(binding))
+}
+
+/// Returns `true` if the reservation targets the box.
+fn is_targeted(spec: &UserBoxReservationSpec, node: &Node) -> bool {
+    spec.r#box.as_deref() == Some(node.name_any().as_str())
+        || spec
+            .box_selector
+            .as_ref()
+            .is_some_and(|selector| crate::auth::matches_selector(selector, node.labels()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn binding(
+        user: Option<&str>,
+        group: Option<&str>,
+        r#box: &str,
+        expired_timestamp: Option<DateTime<Utc>>,
+    ) -> UserBoxBindingSpec {
+        UserBoxBindingSpec {
+            user: user.map(Into::into),
+            group: group.map(Into::into),
+            r#box: r#box.into(),
+            autologin: false,
+            expired_timestamp,
+        }
+    }
+
+    fn groups() -> UserGroups {
+        UserGroups {
+            groups: ["class-a".into()].into(),
+            user_name: "alice".into(),
+        }
+    }
+
+    #[test]
+    fn reserve_unbound_boxes() {
+        let now = Utc::now();
+        assert!(!is_bound_to_others(&[], "box-a", &groups(), now));
+
+        // the bindings of the other boxes
+        let bindings = [binding(Some("bob"), None, "box-b", None)];
+        assert!(!is_bound_to_others(&bindings, "box-a", &groups(), now));
+    }
+
+    #[test]
+    fn reserve_boxes_bound_to_the_user() {
+        let now = Utc::now();
+        let bindings = [
+            binding(Some("bob"), None, "box-a", None),
+            binding(Some("alice"), None, "box-a", None),
+        ];
+        assert!(!is_bound_to_others(&bindings, "box-a", &groups(), now));
+
+        // by the groups
+        let bindings = [
+            binding(Some("bob"), None, "box-a", None),
+            binding(None, Some("class-a"), "box-a", None),
+        ];
+        assert!(!is_bound_to_others(&bindings, "box-a", &groups(), now));
+    }
+
+    #[test]
+    fn reject_boxes_bound_to_the_others() {
+        let now = Utc::now();
+        let bindings = [
+            binding(Some("bob"), None, "box-a", None),
+            binding(None, Some("class-b"), "box-a", None),
+        ];
+        assert!(is_bound_to_others(&bindings, "box-a", &groups(), now));
+
+        // the expired bindings of the user
+        let bindings = [
+            binding(Some("bob"), None, "box-a", None),
+            binding(Some("alice"), None, "box-a", Some(now - TimeDelta::days(1))),
+        ];
+        assert!(is_bound_to_others(&bindings, "box-a", &groups(), now));
+    }
+
+    #[test]
+    fn ignore_expired_or_invalid_bindings() {
+        let now = Utc::now();
+        let bindings = [
+            binding(Some("bob"), None, "box-a", Some(now - TimeDelta::days(1))),
+            binding(Some("bob"), Some("class-b"), "box-a", None),
+        ];
+        assert!(!is_bound_to_others(&bindings, "box-a", &groups(), now));
+
+        let bindings = [binding(
+            Some("bob"),
+            None,
+            "box-a",
+            Some(now + TimeDelta::days(1)),
+        )];
+        assert!(is_bound_to_others(&bindings, "box-a", &groups(), now));
+    }
+}
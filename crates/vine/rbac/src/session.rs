@@ -6,21 +6,28 @@ use k8s_openapi::api::core::v1::Node;
 use kube::{api::ListParams, Api, Client, ResourceExt};
 use tracing::{instrument, warn, Level};
 use vine_api::{
-    user::UserCrd,
+    user::{UserCrd, UserUsage},
     user_auth::{UserAuthError, UserSessionError, UserSessionResponse},
     user_box_binding::UserBoxBindingCrd,
     user_box_quota::UserBoxQuotaCrd,
     user_box_quota_binding::UserBoxQuotaBindingCrd,
-    user_role::UserRoleCrd,
+    user_group::UserBindingSubject,
+    user_role::{UserRoleCrd, UserRolePermission, UserRoleSpec},
     user_role_binding::UserRoleBindingCrd,
 };
 use vine_session::{is_persistent, AllocationState, SessionContextSpecOwned, SessionManager};
 
+use crate::{
+    auth::{matches_rule, AuthPolicyTarget},
+    group::UserGroups,
+};
+
 #[instrument(level = Level::INFO, skip(client, f), err(Display))]
 pub async fn execute_with<'f, Fut>(
     client: &Client,
     box_name: &str,
     user_name: &str,
+    claims: &[String],
     check_resources: bool,
     f: impl FnOnce(SessionManager, SessionContextSpecOwned) -> Fut,
 ) -> Result<UserSessionResponse>
@@ -46,6 +53,9 @@ where
     };
     let user_name = user.preferred_name();
 
+    // get the user groups
+    let groups = UserGroups::load(client, &user_name, claims).await?;
+
     // check the box state
     {
         let api = Api::<Node>::all(client.clone());
@@ -80,6 +90,38 @@ where
         }
     });
 
+    // parse user role
+    let role: UserRoleSpec = {
+        // get available roles
+        let roles = {
+            let api = Api::<UserRoleCrd>::all(client.clone());
+            let lp = ListParams::default();
+            api.list(&lp)
+                .await?
+                .items
+                .into_iter()
+                .map(|item| (item.name_any(), item.spec))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let api = Api::<UserRoleBindingCrd>::all(client.clone());
+        let lp = ListParams::default();
+        api.list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|item| groups.is_bound(&item.spec))
+            .filter(|item| {
+                item.spec
+                    .expired_timestamp
+                    .as_ref()
+                    .map(|timestamp| timestamp < &now)
+                    .unwrap_or(true)
+            })
+            .filter_map(|item| roles.get(&item.spec.role).cloned())
+            .sum()
+    };
+
     // check the box bindings
     {
         let api = Api::<UserBoxBindingCrd>::all(client.clone());
@@ -89,7 +131,7 @@ where
             .await?
             .items
             .into_iter()
-            .filter(|item| item.spec.r#box == box_name)
+            .filter(|item| item.spec.r#box == box_name && item.spec.is_valid())
             .filter(|item| {
                 item.spec
                     .expired_timestamp
@@ -99,11 +141,34 @@ where
             })
             .collect();
 
-        if !bindings.is_empty() && bindings.iter().all(|item| item.spec.user != user_name) {
+        // NOTE: the admins should be bound explicitly, too
+        let is_permitted = || {
+            let target = AuthPolicyTarget {
+                box_labels: Some(node.labels()),
+                namespace_labels: None,
+            };
+            role.rules.iter().any(|rule| {
+                rule.contains(UserRolePermission::BOX_BIND) && matches_rule(rule, target)
+            })
+        };
+
+        if !bindings.is_empty()
+            && !bindings.iter().any(|item| groups.is_bound(&item.spec))
+            && !is_permitted()
+        {
             return Ok(UserSessionResponse::Error(UserSessionError::NodeReserved));
         }
     }
 
+    // check the box reservations
+    if let Some(slot) = crate::reservation::find_reserved(client, &node, &groups, now).await? {
+        let until = slot.end;
+        warn!("[{now}] the node is reserved until {until}: {box_name:?}");
+        return Ok(UserSessionResponse::Error(
+            UserSessionError::NodeReservedUntil { until },
+        ));
+    }
+
     let box_quota = {
         // get available quotas
         let quotas = {
@@ -123,7 +188,7 @@ where
             .await?
             .items
             .into_iter()
-            .filter(|item| item.spec.user == user_name)
+            .filter(|item| groups.is_bound(&item.spec))
             .filter(|item| {
                 item.spec
                     .expired_timestamp
@@ -135,37 +200,26 @@ where
             .find(|item| crate::node_selector::is_affordable(available_resources, &item.compute))
     };
 
-    // parse user role
-    let role = {
-        // get available quotas
-        let roles = {
-            let api = Api::<UserRoleCrd>::all(client.clone());
-            let lp = ListParams::default();
-            api.list(&lp)
-                .await?
-                .items
-                .into_iter()
-                .map(|item| (item.name_any(), item.spec))
-                .collect::<BTreeMap<_, _>>()
-        };
-
-        let api = Api::<UserRoleBindingCrd>::all(client.clone());
-        let lp = ListParams::default();
-        api.list(&lp)
-            .await?
-            .items
-            .into_iter()
-            .filter(|item| item.spec.user == user_name)
-            .filter(|item| {
-                item.spec
-                    .expired_timestamp
-                    .as_ref()
-                    .map(|timestamp| timestamp < &now)
-                    .unwrap_or(true)
-            })
-            .filter_map(|item| roles.get(&item.spec.role).cloned())
-            .sum()
-    };
+    // check the monthly budget, only on login
+    if let Some(box_quota) = box_quota.as_ref().filter(|_| check_resources) {
+        let month = UserUsage::month_of(now);
+        let usage = user
+            .status
+            .as_ref()
+            .and_then(|status| status.usage.get(&month))
+            .cloned()
+            .unwrap_or_default();
+        let session = crate::usage::estimate_session(&node, box_quota)?;
+        let exceeded = crate::usage::find_exceeded(&box_quota.budget, &usage, &session);
+        if let Some(resource) = exceeded {
+            warn!("[{now}] quota exceeded ({resource}): {user_name:?}");
+            return Ok(UserSessionResponse::Error(
+                UserSessionError::QuotaExceeded {
+                    resource: resource.into(),
+                },
+            ));
+        }
+    }
 
     match box_quota {
         // Login Successed!
@@ -183,12 +237,19 @@ where
                 user_name: user_name.into(),
             };
 
-            f(session_manager, spec)
-                .await
-                .map(|()| UserSessionResponse::Accept {
-                    box_quota,
-                    user: user.spec,
-                })
+            f(session_manager, spec).await?;
+
+            // store the group claims for the later sessions without the user token
+            if check_resources {
+                if let Err(error) = crate::group::save_claims(client, &user, claims, now).await {
+                    warn!("[{now}] failed to store the group claims: {error}");
+                }
+            }
+
+            Ok(UserSessionResponse::Accept {
+                box_quota,
+                user: user.spec,
+            })
         }
         None => {
             warn!("[{now}] quota mismatched: {user_name:?} => {box_name:?}");
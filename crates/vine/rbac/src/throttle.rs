@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, PoisonError},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::warn;
+use vine_api::user_auth::{UserAuthCredentials, UserAuthError};
+
+/// Throttles the password-based logins, which have failed too many times
+/// by the same user or from the same client address.
+#[derive(Debug)]
+pub struct LoginThrottle {
+    max_failures_per_addr: u32,
+    max_failures_per_user: u32,
+    window: TimeDelta,
+    failures: Mutex<BTreeMap<LoginThrottleKey, LoginFailures>>,
+}
+
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        Self::new(20, 5, TimeDelta::minutes(15))
+    }
+}
+
+impl LoginThrottle {
+    pub fn new(max_failures_per_addr: u32, max_failures_per_user: u32, window: TimeDelta) -> Self {
+        Self {
+            max_failures_per_addr,
+            max_failures_per_user,
+            window,
+            failures: Mutex::default(),
+        }
+    }
+
+    /// Asserts that neither the user nor the client address has failed too many times.
+    pub fn check(
+        &self,
+        credentials: &UserAuthCredentials,
+        addr: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), UserAuthError> {
+        let failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for (key, max_failures) in self.keys(credentials, addr) {
+            if failures
+                .get(&key)
+                .filter(|failures| !failures.is_expired(self.window, now))
+                .is_some_and(|failures| failures.count >= max_failures)
+            {
+                warn!("[{now}] login throttled: {key:?}");
+                return Err(UserAuthError::LoginThrottled);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the result of a login.
+    ///
+    /// Only the invalid credentials are counted as failures,
+    /// and a successful login resets the failures of the user.
+    pub fn record(
+        &self,
+        credentials: &UserAuthCredentials,
+        addr: Option<&str>,
+        error: Option<&UserAuthError>,
+        now: DateTime<Utc>,
+    ) {
+        let mut failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match error {
+            None => {
+                failures.remove(&LoginThrottleKey::user(credentials));
+            }
+            Some(UserAuthError::CredentialsInvalid | UserAuthError::UserNotRegistered) => {
+                // NOTE: forget the expired failures so that the records are bounded
+                failures.retain(|_, failures| !failures.is_expired(self.window, now));
+
+                for (key, _) in self.keys(credentials, addr) {
+                    let failures = failures.entry(key).or_insert(LoginFailures {
+                        count: 0,
+                        since: now,
+                    });
+                    failures.count = failures.count.saturating_add(1);
+                }
+            }
+            Some(_) => (),
+        }
+    }
+
+    fn keys(
+        &self,
+        credentials: &UserAuthCredentials,
+        addr: Option<&str>,
+    ) -> impl Iterator<Item = (LoginThrottleKey, u32)> {
+        let user = (
+            LoginThrottleKey::user(credentials),
+            self.max_failures_per_user,
+        );
+        let addr = addr.map(|addr| {
+            (
+                LoginThrottleKey::Addr(addr.into()),
+                self.max_failures_per_addr,
+            )
+        });
+        ::std::iter::once(user).chain(addr)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LoginThrottleKey {
+    Addr(String),
+    User { auth: String, username: String },
+}
+
+impl LoginThrottleKey {
+    fn user(credentials: &UserAuthCredentials) -> Self {
+        Self::User {
+            auth: credentials.auth.clone(),
+            username: credentials.username.clone(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct LoginFailures {
+    count: u32,
+    since: DateTime<Utc>,
+}
+
+impl LoginFailures {
+    fn is_expired(&self, window: TimeDelta, now: DateTime<Utc>) -> bool {
+        self.since + window <= now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(username: &str) -> UserAuthCredentials {
+        UserAuthCredentials {
+            auth: "local".into(),
+            username: username.into(),
+            password: "secret".into(),
+        }
+    }
+
+    fn fail(throttle: &LoginThrottle, username: &str, addr: &str, now: DateTime<Utc>) {
+        let error = UserAuthError::CredentialsInvalid;
+        throttle.record(&credentials(username), Some(addr), Some(&error), now);
+    }
+
+    #[test]
+    fn throttle_by_user() {
+        let now = Utc::now();
+        let throttle = LoginThrottle::new(100, 3, TimeDelta::minutes(15));
+
+        for index in 0..3 {
+            let addr = format!("10.0.0.{index}");
+            assert_eq!(
+                throttle.check(&credentials("alice"), Some(&addr), now),
+                Ok(())
+            );
+            fail(&throttle, "alice", &addr, now);
+        }
+
+        // from any address
+        assert_eq!(
+            throttle.check(&credentials("alice"), Some("10.0.0.100"), now),
+            Err(UserAuthError::LoginThrottled),
+        );
+        assert_eq!(
+            throttle.check(&credentials("alice"), None, now),
+            Err(UserAuthError::LoginThrottled),
+        );
+        assert_eq!(
+            throttle.check(&credentials("bob"), Some("10.0.0.0"), now),
+            Ok(()),
+        );
+
+        // the same user name of other providers
+        let other = UserAuthCredentials {
+            auth: "ldap".into(),
+            ..credentials("alice")
+        };
+        assert_eq!(throttle.check(&other, None, now), Ok(()));
+    }
+
+    #[test]
+    fn throttle_by_addr() {
+        let now = Utc::now();
+        let throttle = LoginThrottle::new(3, 100, TimeDelta::minutes(15));
+
+        for username in ["alice", "bob", "carol"] {
+            fail(&throttle, username, "10.0.0.1", now);
+        }
+
+        // for any user
+        assert_eq!(
+            throttle.check(&credentials("dave"), Some("10.0.0.1"), now),
+            Err(UserAuthError::LoginThrottled),
+        );
+        assert_eq!(
+            throttle.check(&credentials("dave"), Some("10.0.0.2"), now),
+            Ok(()),
+        );
+    }
+
+    #[test]
+    fn throttle_until_expired() {
+        let now = Utc::now();
+        let window = TimeDelta::minutes(15);
+        let throttle = LoginThrottle::new(100, 1, window);
+
+        fail(&throttle, "alice", "10.0.0.1", now);
+        assert!(throttle.check(&credentials("alice"), None, now).is_err());
+        assert!(throttle
+            .check(
+                &credentials("alice"),
+                None,
+                now + window - TimeDelta::seconds(1)
+            )
+            .is_err());
+        assert!(throttle
+            .check(&credentials("alice"), None, now + window)
+            .is_ok());
+
+        // the expired failures are forgotten
+        fail(&throttle, "bob", "10.0.0.2", now + window);
+        assert_eq!(throttle.failures.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn reset_on_success() {
+        let now = Utc::now();
+        let throttle = LoginThrottle::new(2, 2, TimeDelta::minutes(15));
+
+        fail(&throttle, "alice", "10.0.0.1", now);
+        throttle.record(&credentials("alice"), Some("10.0.0.1"), None, now);
+        fail(&throttle, "alice", "10.0.0.1", now);
+        assert_eq!(throttle.check(&credentials("alice"), None, now), Ok(()));
+
+        // the failures of the address are kept
+        assert_eq!(
+            throttle.check(&credentials("bob"), Some("10.0.0.1"), now),
+            Err(UserAuthError::LoginThrottled),
+        );
+    }
+
+    #[test]
+    fn ignore_unavailable_providers() {
+        let now = Utc::now();
+        let throttle = LoginThrottle::new(1, 1, TimeDelta::minutes(15));
+
+        let error = UserAuthError::AuthProviderUnavailable;
+        throttle.record(&credentials("alice"), Some("10.0.0.1"), Some(&error), now);
+        assert_eq!(
+            throttle.check(&credentials("alice"), Some("10.0.0.1"), now),
+            Ok(()),
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use ark_api::NamespaceAny;
+use ark_core_k8s::data::Quantity;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use k8s_openapi::api::core::v1::{Node, PersistentVolumeClaim};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    Api, Client, ResourceExt,
+};
+use serde_json::json;
+use tracing::{instrument, Level};
+use vine_api::{
+    user::{UserCrd, UserUsage},
+    user_box_quota::{UserBoxQuotaBudgetSpec, UserBoxQuotaSpec},
+};
+
+const RESOURCE_GPU: &str = "nvidia.com/gpu";
+const RESOURCE_STORAGE: &str = "storage";
+
+/// The minimum duration of a session, to be affordable on login.
+const MIN_SESSION_HOURS: f64 = 1.0;
+
+/// Accumulates the usage of the session on the box since the last accounting of the box.
+///
+/// NOTE: the elapsed time is split into the months it belongs to.
+#[instrument(level = Level::INFO, skip(client, node), fields(node_name = %node.name_any()), err(Display))]
+pub async fn account(client: &Client, node: &Node, now: DateTime<Utc>) -> Result<()> {
+    let session = match node.get_session_ref() {
+        Ok(session) => session,
+        // the box is not in use
+        Err(_) => return Ok(()),
+    };
+
+    let api = Api::<UserCrd>::all(client.clone());
+    let user = api.get(&session.user_name).await?;
+    let status = user.status.as_ref();
+
+    let box_name = node.name_any();
+    let since = match [
+        status.and_then(|status| status.accounted.get(&box_name).copied()),
+        session.timestamp,
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    {
+        Some(since) if since < now => since,
+        _ => return Ok(()),
+    };
+
+    let gpus = get_gpus(node)?;
+    let storage = get_storage_usage(client, &session.namespace).await?;
+
+    let usage = accumulate(
+        status.map(|status| &status.usage),
+        since,
+        now,
+        gpus,
+        storage,
+    );
+    let accounted = BTreeMap::from([(box_name.as_str(), now)]);
+
+    let pp = PatchParams::default();
+    let patch = Patch::Merge(json!({
+        "status": {
+            "accounted": accounted,
+            "lastBox": box_name,
+            "lastUpdated": now,
+            "usage": usage,
+        },
+    }));
+    api.patch_status(&user.name_any(), &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| {
+            anyhow!(
+                "failed to update the usage of {:?}: {error}",
+                user.name_any()
+            )
+        })
+}
+
+/// Returns the usages of the months which the period spans, adding the consumed resources.
+fn accumulate(
+    usages: Option<&BTreeMap<String, UserUsage>>,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+    gpus: f64,
+    storage: Quantity,
+) -> BTreeMap<String, UserUsage> {
+    let mut updated = BTreeMap::default();
+    let mut start = since;
+    while start < now {
+        let end = start_of_next_month(start).min(now);
+        let elapsed_hours = (end - start).num_milliseconds() as f64 / 3_600_000.0;
+
+        let month = UserUsage::month_of(start);
+        let usage: &mut UserUsage = updated.entry(month).or_insert_with_key(|month| {
+            usages
+                .and_then(|usages| usages.get(month))
+                .cloned()
+                .unwrap_or_default()
+        });
+        usage.box_hours += elapsed_hours;
+        usage.gpu_hours += elapsed_hours * gpus;
+        usage.storage = usage.storage.max(storage);
+        start = end;
+    }
+    updated
+}
+
+fn start_of_next_month(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match timestamp.month() {
+        12 => (timestamp.year() + 1, 1),
+        month => (timestamp.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map_or(DateTime::<Utc>::MAX_UTC, |timestamp| timestamp.and_utc())
+}
+
+/// Returns the least usage of a new session on the box.
+pub fn estimate_session(node: &Node, box_quota: &UserBoxQuotaSpec) -> Result<UserUsage> {
+    let max_hours = box_quota
+        .session
+        .max_duration()
+        .map_err(|error| anyhow!("failed to parse the max session duration: {error}"))?
+        .map(|max_duration| max_duration.as_secs_f64() / 3_600.0);
+    let box_hours = max_hours.map_or(MIN_SESSION_HOURS, |max_hours| {
+        max_hours.min(MIN_SESSION_HOURS)
+    });
+
+    let gpus = get_gpus(node)?;
+    let storage = box_quota
+        .storage
+        .requests
+        .as_ref()
+        .and_then(|requests| requests.get(RESOURCE_STORAGE))
+        .map(Quantity::try_from)
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(UserUsage {
+        box_hours,
+        gpu_hours: box_hours * gpus,
+        storage,
+    })
+}
+
+/// Returns the name of the resource whose monthly budget would be exceeded by the session, if any.
+pub fn find_exceeded(
+    budget: &UserBoxQuotaBudgetSpec,
+    usage: &UserUsage,
+    session: &UserUsage,
+) -> Option<&'static str> {
+    let UserBoxQuotaBudgetSpec {
+        box_hours,
+        gpu_hours,
+        storage,
+    } = budget;
+
+    if box_hours.is_some_and(|budget| usage.box_hours + session.box_hours > budget) {
+        Some("box-hours")
+    } else if gpu_hours.is_some_and(|budget| usage.gpu_hours + session.gpu_hours > budget) {
+        Some("GPU-hours")
+    } else if storage.is_some_and(|budget| usage.storage.max(session.storage) > budget) {
+        Some("storage")
+    } else {
+        None
+    }
+}
+
+/// Returns the usage of all users in the month.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn get_report(client: &Client, month: &str) -> Result<BTreeMap<String, UserUsage>> {
+    let api = Api::<UserCrd>::all(client.clone());
+    let lp = ListParams::default();
+    Ok(api
+        .list(&lp)
+        .await
+        .map_err(|error| anyhow!("failed to list users: {error}"))?
+        .items
+        .into_iter()
+        .filter_map(|user| {
+            let usage = user.status.as_ref()?.usage.get(month)?.clone();
+            Some((user.name_any(), usage))
+        })
+        .collect())
+}
+
+fn get_gpus(node: &Node) -> Result<f64> {
+    node.status
+        .as_ref()
+        .and_then(|status| status.capacity.as_ref())
+        .and_then(|capacity| capacity.get(RESOURCE_GPU))
+        .map(Quantity::try_from)
+        .transpose()
+        .map(|gpus| gpus.map(|gpus| gpus.as_f64()).unwrap_or_default())
+}
+
+async fn get_storage_usage(client: &Client, namespace: &str) -> Result<Quantity> {
+    let api = Api::<PersistentVolumeClaim>::namespaced(client.clone(), namespace);
+    let lp = ListParams::default();
+    api.list(&lp)
+        .await?
+        .items
+        .iter()
+        .filter_map(|pvc| {
+            pvc.spec
+                .as_ref()?
+                .resources
+                .as_ref()?
+                .requests
+                .as_ref()?
+                .get(RESOURCE_STORAGE)
+        })
+        .map(Quantity::try_from)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn usage(box_hours: f64, gpu_hours: f64, storage: &str) -> UserUsage {
+        UserUsage {
+            box_hours,
+            gpu_hours,
+            storage: storage.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn accumulate_in_month() {
+        let usages = BTreeMap::from([("2024-01".to_string(), usage(10.0, 5.0, "10Gi"))]);
+        let updated = accumulate(
+            Some(&usages),
+            timestamp("2024-01-10T09:00:00Z"),
+            timestamp("2024-01-10T12:00:00Z"),
+            2.0,
+            "5Gi".parse().unwrap(),
+        );
+        assert_eq!(
+            updated,
+            BTreeMap::from([("2024-01".to_string(), usage(13.0, 11.0, "10Gi"))]),
+        );
+    }
+
+    #[test]
+    fn accumulate_month_rollover() {
+        let usages = BTreeMap::from([
+            ("2023-12".to_string(), usage(1.0, 0.0, "1Gi")),
+            ("2024-01".to_string(), usage(100.0, 0.0, "1Gi")),
+        ]);
+        let updated = accumulate(
+            Some(&usages),
+            timestamp("2023-12-31T22:00:00Z"),
+            timestamp("2024-02-01T03:00:00Z"),
+            1.0,
+            "2Gi".parse().unwrap(),
+        );
+        assert_eq!(
+            updated,
+            BTreeMap::from([
+                ("2023-12".to_string(), usage(3.0, 2.0, "2Gi")),
+                (
+                    "2024-01".to_string(),
+                    usage(100.0 + 31.0 * 24.0, 31.0 * 24.0, "2Gi")
+                ),
+                ("2024-02".to_string(), usage(3.0, 3.0, "2Gi")),
+            ]),
+        );
+    }
+
+    #[test]
+    fn accumulate_nothing() {
+        let now = timestamp("2024-01-10T09:00:00Z");
+        assert!(accumulate(None, now, now, 1.0, Quantity::ZERO).is_empty());
+    }
+
+    #[test]
+    fn find_exceeded_budget() {
+        let budget = UserBoxQuotaBudgetSpec {
+            box_hours: Some(100.0),
+            gpu_hours: Some(10.0),
+            storage: Some("100Gi".parse().unwrap()),
+        };
+        let session = usage(1.0, 1.0, "50Gi");
+
+        assert_eq!(
+            find_exceeded(&budget, &usage(99.0, 9.0, "100Gi"), &session),
+            None,
+        );
+        assert_eq!(
+            find_exceeded(&budget, &usage(99.5, 0.0, "0"), &session),
+            Some("box-hours"),
+        );
+        assert_eq!(
+            find_exceeded(&budget, &usage(0.0, 9.5, "0"), &session),
+            Some("GPU-hours"),
+        );
+        assert_eq!(
+            find_exceeded(&budget, &usage(0.0, 0.0, "101Gi"), &session),
+            Some("storage"),
+        );
+        assert_eq!(
+            find_exceeded(&budget, &UserUsage::default(), &usage(0.0, 0.0, "200Gi")),
+            Some("storage"),
+        );
+    }
+
+    #[test]
+    fn find_exceeded_unlimited() {
+        let budget = UserBoxQuotaBudgetSpec::default();
+        let usage = usage(1e9, 1e9, "1Ei");
+        assert_eq!(find_exceeded(&budget, &usage, &usage), None);
+    }
+}
@@ -0,0 +1,784 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use ark_api::SessionRef;
+use ark_core::env::infer;
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::{
+    api::{authentication::v1::SelfSubjectReview, core::v1::Secret},
+    apimachinery::pkg::apis::meta::v1::{ObjectMeta, Status},
+};
+use kube::{
+    api::{DeleteParams, ListParams, PostParams},
+    Api, Client, ResourceExt,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::spawn;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::exec::Process;
+
+/// Returns the caller name of the local commands, such as the CLI,
+/// as authenticated by the kubernetes API server.
+pub async fn kube_caller(kube: &Client) -> String {
+    let api = Api::<SelfSubjectReview>::all(kube.clone());
+    let pp = PostParams::default();
+    match api.create(&pp, &SelfSubjectReview::default()).await {
+        Ok(review) => match review
+            .status
+            .and_then(|status| status.user_info)
+            .and_then(|user_info| user_info.username)
+        {
+            Some(user_name) => format!("kube:{user_name}"),
+            None => "kube:unknown".into(),
+        },
+        Err(error) => {
+            warn!("failed to review the kubernetes user: {error}");
+            "kube:unknown".into()
+        }
+    }
+}
+
+/// An audit record of a command executed in a user desktop.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditExecRecord {
+    pub timestamp: DateTime<Utc>,
+    pub caller: String,
+    pub user_name: String,
+    pub namespace: String,
+    pub node_name: String,
+    pub command: Vec<String>,
+    pub tty: bool,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// The caller and the command to be audited.
+#[derive(Clone, Debug)]
+pub struct AuditExecContext {
+    pub caller: String,
+    pub command: Vec<String>,
+    pub tty: bool,
+}
+
+impl AuditExecContext {
+    /// Records the processes on completion, in background.
+    pub fn watch(&self, session: &SessionRef, processes: &mut [Process]) {
+        let timestamp = Utc::now();
+        for process in processes {
+            let record = self.to_record(session, timestamp);
+            match process.ap.take_status() {
+                Some(status) => {
+                    spawn(async move {
+                        let status = status.await;
+                        AuditSink::global().write_exec(record.complete(status.as_ref()));
+                    });
+                }
+                None => AuditSink::global().write_exec(record),
+            }
+        }
+    }
+
+    /// Records the failure of the command.
+    pub fn fail(&self, session: &SessionRef, error: impl ToString) {
+        let record = AuditExecRecord {
+            error: Some(error.to_string()),
+            ..self.to_record(session, Utc::now())
+        };
+        AuditSink::global().write_exec(record)
+    }
+
+    fn to_record(&self, session: &SessionRef, timestamp: DateTime<Utc>) -> AuditExecRecord {
+        AuditExecRecord {
+            timestamp,
+            caller: self.caller.clone(),
+            user_name: session.user_name.to_string(),
+            namespace: session.namespace.to_string(),
+            node_name: session.node_name.to_string(),
+            command: self.command.clone(),
+            tty: self.tty,
+            exit_code: None,
+            error: None,
+            duration_ms: 0,
+        }
+    }
+}
+
+impl AuditExecRecord {
+    fn complete(self, status: Option<&Status>) -> Self {
+        let duration_ms = (Utc::now() - self.timestamp).num_milliseconds();
+        match status {
+            Some(status) if status.status.as_deref() == Some("Success") => Self {
+                exit_code: Some(0),
+                duration_ms,
+                ..self
+            },
+            Some(status) => Self {
+                exit_code: status
+                    .details
+                    .as_ref()
+                    .and_then(|details| details.causes.as_ref())
+                    .and_then(|causes| {
+                        causes
+                            .iter()
+                            .find(|cause| cause.reason.as_deref() == Some("ExitCode"))
+                    })
+                    .and_then(|cause| cause.message.as_ref()?.parse().ok()),
+                error: status.message.clone(),
+                duration_ms,
+                ..self
+            },
+            None => Self {
+                error: Some("unknown exit status".into()),
+                duration_ms,
+                ..self
+            },
+        }
+    }
+}
+
+/// An append-only audit log, which is rotated by size.
+///
+/// The records are written to the tracing logs if `VINE_AUDIT_LOG_PATH` is not given.
+pub struct AuditSink {
+    file: Option<Mutex<AuditFile>>,
+}
+
+impl AuditSink {
+    pub fn global() -> &'static Self {
+        static SINK: OnceLock<AuditSink> = OnceLock::new();
+        SINK.get_or_init(|| match Self::try_default() {
+            Ok(sink) => sink,
+            Err(error) => {
+                error!("failed to open audit log: {error}");
+                Self { file: None }
+            }
+        })
+    }
+
+    fn try_default() -> Result<Self> {
+        let path = match infer::<_, PathBuf>("VINE_AUDIT_LOG_PATH") {
+            Ok(path) => path,
+            Err(_) => return Ok(Self { file: None }),
+        };
+        let max_size = infer("VINE_AUDIT_LOG_MAX_SIZE").unwrap_or(16 * 1024 * 1024);
+        let max_files = infer("VINE_AUDIT_LOG_MAX_FILES").unwrap_or(8);
+
+        AuditFile::open(path, max_size, max_files).map(|file| Self {
+            file: Some(Mutex::new(file)),
+        })
+    }
+
+    pub fn write_exec(&self, record: AuditExecRecord) {
+        let line = match ::serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(error) => {
+                error!("failed to encode audit record: {error}");
+                return;
+            }
+        };
+
+        match &self.file {
+            Some(file) => match file.lock() {
+                Ok(mut file) => {
+                    if let Err(error) = file.write_line(&line) {
+                        error!("failed to write audit record: {error}: {line}");
+                    }
+                }
+                Err(error) => error!("failed to lock audit log: {error}: {line}"),
+            },
+            None => info!("audit: {line}"),
+        }
+    }
+}
+
+struct AuditFile {
+    file: File,
+    max_files: usize,
+    max_size: u64,
+    path: PathBuf,
+    size: u64,
+}
+
+impl AuditFile {
+    fn open(path: PathBuf, max_size: u64, max_files: usize) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|error| anyhow!("failed to create audit log directory: {error}"))?;
+        }
+        let file = open_append(&path)?;
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+
+        Ok(Self {
+            file,
+            max_files,
+            max_size,
+            path,
+            size,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        // shift the old files: audit.log.1 -> audit.log.2 -> ...
+        for index in (1..self.max_files).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                if let Err(error) = fs::rename(&from, rotated_path(&self.path, index + 1)) {
+                    warn!("failed to rotate audit log {from:?}: {error}");
+                }
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| anyhow!("failed to open audit log {path:?}: {error}"))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{index}"));
+    path.into()
+}
+
+/// Returns the names of the shell recordings, in the recorded order.
+pub async fn list_recordings(kube: &Client) -> Result<Vec<String>> {
+    let names: ::std::collections::BTreeSet<_> = list_recording_parts(kube, Utc::now())
+        .await?
+        .into_keys()
+        .collect();
+    Ok(names.into_iter().collect())
+}
+
+/// Returns the shell recording, if any.
+pub async fn get_recording(kube: &Client, name: &str) -> Result<Option<String>> {
+    if !is_recording_name(name) {
+        return Ok(None);
+    }
+    let parts = match list_recording_parts(kube, Utc::now()).await?.remove(name) {
+        Some(parts) => parts,
+        None => return Ok(None),
+    };
+
+    let api = recording_api(kube);
+    let mut recording = String::default();
+    for (_, object_name) in parts {
+        let data = api
+            .get(&object_name)
+            .await
+            .map_err(|error| anyhow!("failed to get recording {name:?}: {error}"))?
+            .data
+            .and_then(|mut data| data.remove(RECORDING_KEY))
+            .unwrap_or_default();
+        let data = String::from_utf8(data.0)
+            .map_err(|error| anyhow!("malformed recording {name:?}: {error}"))?;
+        recording.push_str(&data);
+    }
+    Ok(Some(recording))
+}
+
+/// Deletes the shell recordings, which have been expired.
+pub async fn cleanup_recordings(kube: &Client, now: DateTime<Utc>) -> Result<()> {
+    let api = recording_api(kube);
+    let dp = DeleteParams::default();
+    let lp = ListParams::default().labels(LABEL_RECORDING);
+    let list = api
+        .list_metadata(&lp)
+        .await
+        .map_err(|error| anyhow!("failed to list recordings: {error}"))?;
+
+    for item in list.items {
+        if is_recording_expired(item.annotations(), now) {
+            let name = item.name_any();
+            api.delete(&name, &dp)
+                .await
+                .map_err(|error| anyhow!("failed to delete recording {name:?}: {error}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the object names of the recording parts, keyed by the recording names.
+///
+/// The expired parts are never returned, even if they are not deleted yet.
+async fn list_recording_parts(
+    kube: &Client,
+    now: DateTime<Utc>,
+) -> Result<BTreeMap<String, BTreeMap<usize, String>>> {
+    let api = recording_api(kube);
+    let lp = ListParams::default().labels(LABEL_RECORDING);
+    let list = api
+        .list_metadata(&lp)
+        .await
+        .map_err(|error| anyhow!("failed to list recordings: {error}"))?;
+
+    let mut recordings: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::default();
+    for item in list.items {
+        let annotations = item.annotations();
+        if is_recording_expired(annotations, now) {
+            continue;
+        }
+
+        let name = annotations.get(ANNOTATION_RECORDING_NAME);
+        let part = annotations
+            .get(ANNOTATION_RECORDING_PART)
+            .and_then(|part| part.parse().ok());
+        if let (Some(name), Some(part)) = (name, part) {
+            recordings
+                .entry(name.clone())
+                .or_default()
+                .insert(part, item.name_any());
+        }
+    }
+    Ok(recordings)
+}
+
+/// NOTE: the recordings may contain sensitive outputs, so they are stored as `Secret`s
+fn recording_api(kube: &Client) -> Api<Secret> {
+    Api::namespaced(kube.clone(), ::vine_api::consts::NAMESPACE)
+}
+
+fn is_recording_name(name: &str) -> bool {
+    name.ends_with(".cast")
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn is_recording_expired(annotations: &BTreeMap<String, String>, now: DateTime<Utc>) -> bool {
+    annotations
+        .get(ANNOTATION_RECORDING_EXPIRED_TIMESTAMP)
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .is_some_and(|timestamp| timestamp <= now)
+}
+
+const ANNOTATION_RECORDING_EXPIRED_TIMESTAMP: &str =
+    "vine.ulagbulag.io/recording-expired-timestamp";
+const ANNOTATION_RECORDING_NAME: &str = "vine.ulagbulag.io/recording-name";
+const ANNOTATION_RECORDING_PART: &str = "vine.ulagbulag.io/recording-part";
+const LABEL_RECORDING: &str = "vine.ulagbulag.io/recording";
+const RECORDING_KEY: &str = "recording.cast";
+
+/// A recorder of an interactive shell in the asciicast v2 format.
+///
+/// The recording is uploaded to the `vine` namespace in parts, as `Secret`s,
+/// so that the admins can download it from the plugin until it is expired.
+pub struct AsciicastRecorder {
+    kube: Client,
+    writer: AsciicastWriter,
+}
+
+impl AsciicastRecorder {
+    /// Creates a recorder, which records the key inputs only if `record_input` is given,
+    /// as they may contain the passwords.
+    pub fn create(
+        kube: Client,
+        title: &str,
+        width: u16,
+        height: u16,
+        record_input: bool,
+    ) -> Result<Self> {
+        let ttl = infer("VINE_AUDIT_RECORDING_TTL_DAYS").unwrap_or(30);
+        let ttl = TimeDelta::try_days(ttl).ok_or_else(|| anyhow!("malformed recording TTL"))?;
+
+        let options = AsciicastOptions {
+            record_input,
+            title,
+            ttl,
+            width,
+            height,
+        };
+        AsciicastWriter::new(options, Utc::now()).map(|writer| Self { kube, writer })
+    }
+
+    pub fn input(&mut self, data: &str) -> Result<()> {
+        self.writer.input(data)
+    }
+
+    pub fn output(&mut self, data: &str) -> Result<()> {
+        self.writer.output(data)
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.writer.resize(width, height)
+    }
+
+    /// Uploads the recorded events if a part is full.
+    pub async fn sync(&mut self) -> Result<()> {
+        const FORCE: bool = false;
+        match self.writer.take_part(FORCE) {
+            Some(part) => part.upload(&self.kube).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Uploads the remaining events, and cleans up the expired recordings.
+    pub async fn close(mut self) -> Result<()> {
+        const FORCE: bool = true;
+        if let Some(part) = self.writer.take_part(FORCE) {
+            part.upload(&self.kube).await?;
+        }
+        cleanup_recordings(&self.kube, Utc::now()).await
+    }
+}
+
+struct AsciicastOptions<'a> {
+    record_input: bool,
+    title: &'a str,
+    ttl: TimeDelta,
+    width: u16,
+    height: u16,
+}
+
+/// Buffers the asciicast events, and splits them into parts.
+struct AsciicastWriter {
+    buf: String,
+    expired_timestamp: DateTime<Utc>,
+    name: String,
+    part: usize,
+    record_input: bool,
+    started: Instant,
+}
+
+impl AsciicastWriter {
+    /// The maximum size of a part, far below the `Secret` limit (1 MiB).
+    const MAX_PART_SIZE: usize = 512 * 1024;
+
+    fn new(options: AsciicastOptions<'_>, timestamp: DateTime<Utc>) -> Result<Self> {
+        let AsciicastOptions {
+            record_input,
+            title,
+            ttl,
+            width,
+            height,
+        } = options;
+
+        // NOTE: the shells of the same title may be recorded at the same time
+        let name = format!(
+            "{timestamp}-{title}-{id}.cast",
+            timestamp = timestamp.format("%Y%m%dT%H%M%SZ"),
+            id = Uuid::new_v4(),
+        );
+        if !is_recording_name(&name) {
+            return Err(anyhow!("malformed recording name: {name:?}"));
+        }
+
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp.timestamp(),
+            "title": title,
+        });
+
+        Ok(Self {
+            buf: format!("{header}\n"),
+            expired_timestamp: timestamp + ttl,
+            name,
+            part: 0,
+            record_input,
+            started: Instant::now(),
+        })
+    }
+
+    fn input(&mut self, data: &str) -> Result<()> {
+        if self.record_input {
+            self.write_event("i", data)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn output(&mut self, data: &str) -> Result<()> {
+        self.write_event("o", data)
+    }
+
+    fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.write_event("r", &format!("{width}x{height}"))
+    }
+
+    fn write_event(&mut self, code: &str, data: &str) -> Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let event = json!([elapsed, code, data]);
+        writeln!(self.buf, "{event}").map_err(Into::into)
+    }
+
+    /// Takes the recorded events as a new part if it is full, or if forced.
+    fn take_part(&mut self, force: bool) -> Option<AsciicastPart> {
+        if self.buf.len() >= Self::MAX_PART_SIZE || (force && !self.buf.is_empty()) {
+            let part = AsciicastPart {
+                data: ::std::mem::take(&mut self.buf),
+                expired_timestamp: self.expired_timestamp,
+                index: self.part,
+                name: self.name.clone(),
+            };
+            self.part += 1;
+            Some(part)
+        } else {
+            None
+        }
+    }
+}
+
+struct AsciicastPart {
+    data: String,
+    expired_timestamp: DateTime<Utc>,
+    index: usize,
+    name: String,
+}
+
+impl AsciicastPart {
+    async fn upload(self, kube: &Client) -> Result<()> {
+        let Self {
+            data,
+            expired_timestamp,
+            index,
+            name,
+        } = self;
+
+        let object = Secret {
+            metadata: ObjectMeta {
+                generate_name: Some("vine-recording-".into()),
+                annotations: Some(
+                    [
+                        (
+                            ANNOTATION_RECORDING_EXPIRED_TIMESTAMP.into(),
+                            expired_timestamp.to_rfc3339(),
+                        ),
+                        (ANNOTATION_RECORDING_NAME.into(), name.clone()),
+                        (ANNOTATION_RECORDING_PART.into(), index.to_string()),
+                    ]
+                    .into(),
+                ),
+                labels: Some([(LABEL_RECORDING.into(), "true".into())].into()),
+                ..Default::default()
+            },
+            string_data: Some([(RECORDING_KEY.into(), data)].into()),
+            ..Default::default()
+        };
+
+        let api = recording_api(kube);
+        let pp = PostParams::default();
+        api.create(&pp, &object)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to upload recording {name:?}: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_writer(record_input: bool) -> AsciicastWriter {
+        let options = AsciicastOptions {
+            record_input,
+            title: "user-alice",
+            ttl: TimeDelta::days(30),
+            width: 80,
+            height: 24,
+        };
+        AsciicastWriter::new(options, Utc::now()).expect("failed to create a writer")
+    }
+
+    fn new_temp_dir() -> PathBuf {
+        let path = ::std::env::temp_dir().join(format!("vine-audit-{}", Uuid::new_v4()));
+        fs::create_dir_all(&path).expect("failed to create a temp dir");
+        path
+    }
+
+    #[test]
+    fn validate_recording_names() {
+        assert!(is_recording_name("20261019T000000Z-user-alice.cast"));
+        assert!(is_recording_name("a_b.c.cast"));
+
+        assert!(!is_recording_name(".cast"));
+        assert!(!is_recording_name("../secret.cast"));
+        assert!(!is_recording_name("dir/name.cast"));
+        assert!(!is_recording_name("name with spaces.cast"));
+        assert!(!is_recording_name("name.txt"));
+        assert!(!is_recording_name(""));
+    }
+
+    #[test]
+    fn create_unique_recording_names() {
+        let a = new_writer(false);
+        let b = new_writer(false);
+        assert!(is_recording_name(&a.name));
+        assert!(a.name.contains("-user-alice-"));
+        assert_ne!(a.name, b.name);
+
+        // the titles are never escaped
+        let options = AsciicastOptions {
+            record_input: false,
+            title: "../etc",
+            ttl: TimeDelta::days(30),
+            width: 80,
+            height: 24,
+        };
+        assert!(AsciicastWriter::new(options, Utc::now()).is_err());
+    }
+
+    #[test]
+    fn record_inputs_only_if_requested() {
+        let mut writer = new_writer(false);
+        writer.input("secret\n").unwrap();
+        writer.output("$ ").unwrap();
+        let data = writer.take_part(true).unwrap().data;
+        assert!(!data.contains("secret"));
+        assert!(data.contains("\"o\""));
+
+        let mut writer = new_writer(true);
+        writer.input("ls\n").unwrap();
+        let data = writer.take_part(true).unwrap().data;
+        assert!(data.contains(r#""i","ls\n""#));
+    }
+
+    #[test]
+    fn split_recording_parts() {
+        let mut writer = new_writer(false);
+        let expired_timestamp = writer.expired_timestamp;
+
+        // the header is kept until the first part is full
+        assert!(writer.take_part(false).is_none());
+
+        let chunk = "x".repeat(1024);
+        while writer.buf.len() < AsciicastWriter::MAX_PART_SIZE {
+            writer.output(&chunk).unwrap();
+        }
+        let part = writer.take_part(false).unwrap();
+        assert_eq!(part.index, 0);
+        assert_eq!(part.name, writer.name);
+        assert_eq!(part.expired_timestamp, expired_timestamp);
+        assert!(part.data.starts_with(r#"{"#));
+        assert!(part.data.len() >= AsciicastWriter::MAX_PART_SIZE);
+
+        // every event is a line
+        for line in part.data.lines().skip(1) {
+            let event: ::serde_json::Value = ::serde_json::from_str(line).unwrap();
+            assert_eq!(event[1], "o");
+        }
+
+        writer.resize(120, 40).unwrap();
+        assert!(writer.take_part(false).is_none());
+        let part = writer.take_part(true).unwrap();
+        assert_eq!(part.index, 1);
+        assert!(part.data.contains(r#""r","120x40""#));
+
+        // nothing remains
+        assert!(writer.take_part(true).is_none());
+    }
+
+    #[test]
+    fn expire_recordings() {
+        let now = Utc::now();
+        let annotations = |timestamp: DateTime<Utc>| {
+            BTreeMap::from([(
+                ANNOTATION_RECORDING_EXPIRED_TIMESTAMP.to_string(),
+                timestamp.to_rfc3339(),
+            )])
+        };
+
+        assert!(is_recording_expired(&annotations(now), now));
+        assert!(is_recording_expired(
+            &annotations(now - TimeDelta::days(1)),
+            now,
+        ));
+        assert!(!is_recording_expired(
+            &annotations(now + TimeDelta::days(1)),
+            now,
+        ));
+
+        // the legacy recordings are kept
+        assert!(!is_recording_expired(&BTreeMap::default(), now));
+    }
+
+    #[test]
+    fn rotate_audit_files() {
+        let dir = new_temp_dir();
+        let path = dir.join("audit.log");
+        let read = |path: &Path| fs::read_to_string(path).unwrap_or_default();
+
+        // each line takes 11 bytes with a new line
+        let mut file = AuditFile::open(path.clone(), 20, 2).unwrap();
+        for line in ["line-00001", "line-00002", "line-00003", "line-00004"] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(read(&path), "line-00004\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "line-00003\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "line-00002\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        // the size of the existing file is kept on reopen
+        drop(file);
+        let mut file = AuditFile::open(path.clone(), 20, 2).unwrap();
+        assert_eq!(file.size, 11);
+        file.write_line("line-00005").unwrap();
+        assert_eq!(read(&path), "line-00005\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "line-00003\n");
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn rotate_audit_files_without_backups() {
+        let dir = new_temp_dir();
+        let path = dir.join("audit.log");
+
+        let mut file = AuditFile::open(path.clone(), 20, 0).unwrap();
+        file.write_line("line-00001").unwrap();
+        file.write_line("line-00002").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line-00002\n");
+        assert!(!rotated_path(&path, 1).exists());
+
+        // a line is never split, even if it is larger than the limit
+        let line = "x".repeat(64);
+        file.write_line(&line).unwrap();
+        file.write_line(&line).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{line}\n"));
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -10,7 +10,7 @@ use regex::Regex;
 use tokio::spawn;
 use tracing::{debug, instrument, warn, Level};
 
-use crate::exec::SessionExecExt;
+use crate::{audit::AuditExecContext, exec::SessionExecExt};
 
 pub struct BatchCommandArgs<C, U> {
     pub command: C,
@@ -20,7 +20,7 @@ pub struct BatchCommandArgs<C, U> {
 }
 
 impl<C, U> BatchCommandArgs<C, U> {
-    pub async fn exec(&self, kube: &Client) -> Result<usize>
+    pub async fn exec(&self, kube: &Client, caller: &str) -> Result<usize>
     where
         C: 'static + Send + Sync + Clone + fmt::Debug + IntoIterator,
         <C as IntoIterator>::Item: Sync + Into<String>,
@@ -50,10 +50,27 @@ impl<C, U> BatchCommandArgs<C, U> {
         let sessions_filtered = users.filter(sessions_all)?;
         let num_sessions = sessions_filtered.len();
 
+        let audit = AuditExecContext {
+            caller: caller.into(),
+            command: command.clone(),
+            tty: false,
+        };
         let processes = sessions_filtered.into_iter().map(|session| {
             let kube = kube.clone();
             let command = command.clone();
-            spawn(async move { session.exec_without_tty(kube, command).await })
+            let audit = audit.clone();
+            spawn(async move {
+                match session.exec_without_tty(kube, command).await {
+                    Ok(mut processes) => {
+                        audit.watch(&session, &mut processes);
+                        Ok(processes)
+                    }
+                    Err(error) => {
+                        audit.fail(&session, &error);
+                        Err(error)
+                    }
+                }
+            })
         });
 
         processes
@@ -1,3 +1,5 @@
+#[cfg(feature = "audit")]
+pub mod audit;
 #[cfg(feature = "batch")]
 pub mod batch;
 #[cfg(feature = "exec")]
@@ -7,10 +9,11 @@ pub mod shell;
 
 use std::{collections::BTreeMap, fmt, fs, path::PathBuf, time::Duration};
 
-use anyhow::{bail, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use ark_api::{NamespaceAny, SessionRef};
 use ark_core::env;
-use chrono::Utc;
+use ark_core_k8s::data::{Quantity, QuantityFormat};
+use chrono::{DateTime, TimeDelta, Utc};
 use dash_provider::client::job::TaskActorJobClient;
 use dash_provider_api::SessionContextMetadata;
 use futures::TryFutureExt;
@@ -20,7 +23,7 @@ use k8s_openapi::{
 };
 use kiss_api::r#box::BoxCrd;
 use kube::{
-    api::{DeleteParams, ListParams, Patch, PatchParams},
+    api::{ApiResource, DeleteParams, DynamicObject, ListParams, Patch, PatchParams},
     Api, Client, Resource, ResourceExt,
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -30,6 +33,9 @@ use vine_api::{user::UserCrd, user_box_quota::UserBoxQuotaSpec, user_role::UserR
 
 pub(crate) mod consts {
     pub const NAME: &str = "vine-session";
+    pub const NAME_ACTIVITY: &str = "vine-session-activity";
+    pub const NAME_LIMITS: &str = "vine-session-limits";
+    pub const NAME_WARNING: &str = "vine-session-warning";
 }
 
 pub struct SessionManager {
@@ -88,7 +94,11 @@ impl SessionManager {
     }
 
     #[instrument(level = Level::INFO, skip(self, node), fields(node_name = %node.name_any()), err(Display))]
-    pub async fn try_delete(&self, node: &Node) -> Result<Option<String>> {
+    pub async fn try_delete(
+        &self,
+        node: &Node,
+        deadline: Option<&SessionDeadline>,
+    ) -> Result<Option<String>> {
         match node
             .get_session_ref()
             .and_then(|session| session.assert_started().map(|()| session))
@@ -123,6 +133,11 @@ impl SessionManager {
                     }).unwrap_or(false))
                 .unwrap_or(false)
                 ||
+                // If the session has been expired, idle for a long time or reserved by the others
+                deadline
+                    .map(|deadline| deadline.is_passed(Utc::now()))
+                    .unwrap_or(false)
+                ||
                 // If the node's managed session has been logged out
                 !self.exists_template(&ctx).await?
                 {
@@ -145,6 +160,9 @@ impl SessionManager {
         let ctx = self.get_context(spec);
 
         self.label_node(ctx.spec.node, Some(ctx.spec.user_name))
+            .and_then(|()| {
+                self.label_node_limits(ctx.spec.node, Some(ctx.spec.user_name), ctx.spec.box_quota)
+            })
             .and_then(|()| self.label_namespace(&ctx, Some(ctx.spec.user_name)))
             .and_then(|()| self.label_user(ctx.spec.node, ctx.spec.user_name, true))
             .and_then(|()| self.try_label_box(ctx.spec.node, Some(ctx.spec.user_name)))
@@ -162,6 +180,7 @@ impl SessionManager {
             .and_then(|()| self.try_label_box(ctx.spec.node, None))
             .and_then(|()| self.label_user(ctx.spec.node, ctx.spec.user_name, false))
             .and_then(|()| self.label_namespace(&ctx, None))
+            .and_then(|()| self.label_node_limits(ctx.spec.node, None, None))
             .and_then(|()| self.label_node(ctx.spec.node, None))
             .await
     }
@@ -325,6 +344,24 @@ impl SessionManager {
         self.label::<Node>(&name, node, user_name).await
     }
 
+    #[instrument(level = Level::INFO, skip(self, node, box_quota), fields(node_name = %node.name_any()), err(Display))]
+    async fn label_node_limits(
+        &self,
+        node: &Node,
+        user_name: Option<&str>,
+        box_quota: Option<&UserBoxQuotaSpec>,
+    ) -> Result<()> {
+        let labels = get_limit_labels(node, user_name, box_quota, Utc::now())?;
+        label_node_with(
+            self.client.kube.clone(),
+            &node.name_any(),
+            self::consts::NAME_LIMITS,
+            labels,
+        )
+        .await
+        .map(|_| ())
+    }
+
     #[instrument(level = Level::INFO, skip(self, node), fields(node_name = %node.name_any()), err(Display))]
     async fn label_user(&self, node: &Node, user_name: &str, create: bool) -> Result<()> {
         self.label::<UserCrd>(user_name, node, if create { Some(user_name) } else { None })
@@ -357,7 +394,6 @@ impl SessionManager {
             ..Default::default()
         };
 
-        let node_name = node.name_any();
         let persistence = node
             .labels()
             .get(::ark_api::consts::LABEL_BIND_PERSISTENT)
@@ -368,7 +404,7 @@ impl SessionManager {
             "kind": K::kind(&()),
             "metadata": {
                 "name": name,
-                "labels": get_label(&node_name, user_name, persistence),
+                "labels": get_label(node, user_name, persistence, Utc::now()),
             },
         }));
         api.patch(name, &pp, &patch)
@@ -438,17 +474,267 @@ pub fn is_persistent_by(node: &Node, user_name: &str) -> bool {
             .unwrap_or_default()
 }
 
-fn get_label(node_name: &str, user_name: Option<&str>, persistent: bool) -> Value {
+/// Returns whether the user is working on the session's desktop, measured by its CPU usage.
+///
+/// NOTE: the metrics server should be installed on the cluster.
+#[instrument(level = Level::INFO, skip(kube, node), fields(node_name = %node.name_any()), err(Display))]
+pub async fn is_session_active(kube: Client, node: &Node, namespace: &str) -> Result<bool> {
+    // NOTE: an idle desktop environment barely uses CPU
+    const THRESHOLD_CPU: Quantity = Quantity::from_nanos(100_000_000, QuantityFormat::DecimalSI); // 100m
+
+    let api_resource = ApiResource {
+        group: "metrics.k8s.io".into(),
+        version: "v1beta1".into(),
+        api_version: "metrics.k8s.io/v1beta1".into(),
+        kind: "PodMetrics".into(),
+        plural: "pods".into(),
+    };
+    let api = Api::<DynamicObject>::namespaced_with(kube, namespace, &api_resource);
+    let lp = ListParams::default().labels(&format!("app=desktop,node={}", node.name_any()));
+
+    let usage = api
+        .list(&lp)
+        .await?
+        .items
+        .iter()
+        .filter_map(|metrics| metrics.data.get("containers")?.as_array())
+        .flatten()
+        .filter_map(|container| container.get("usage")?.get("cpu")?.as_str())
+        .map(str::parse::<Quantity>)
+        .sum::<Result<Quantity>>()?;
+    Ok(usage >= THRESHOLD_CPU)
+}
+
+/// Defers the idle logout of the session, as the user is active on it.
+///
+/// The activity is written at most once a minute, so that the frequent reports
+/// do not churn the node and its reconcilers.
+/// Returns the updated node if the activity has been written.
+#[instrument(level = Level::INFO, skip(kube, node), fields(node_name = %node.name_any()), err(Display))]
+pub async fn report_activity(kube: Client, node: &Node) -> Result<Option<Node>> {
+    const THRESHOLD_ACTIVITY: TimeDelta = TimeDelta::minutes(1);
+
+    let now = Utc::now();
+    let is_reported_recently = node
+        .labels()
+        .get(::ark_api::consts::LABEL_BIND_ACTIVITY_TIMESTAMP)
+        .and_then(|value| value.parse().ok())
+        .and_then(DateTime::<Utc>::from_timestamp_millis)
+        .map(|timestamp| now - timestamp < THRESHOLD_ACTIVITY)
+        .unwrap_or_default();
+    if is_reported_recently {
+        return Ok(None);
+    }
+
+    let labels = json!({
+        ::ark_api::consts::LABEL_BIND_ACTIVITY_TIMESTAMP: now.timestamp_millis().to_string(),
+    });
+    label_node_with(kube, &node.name_any(), self::consts::NAME_ACTIVITY, labels)
+        .await
+        .map(Some)
+}
+
+/// Marks that the user has been warned of the upcoming logout.
+#[instrument(level = Level::INFO, skip(kube), err(Display))]
+pub async fn report_warned(kube: Client, node_name: &str) -> Result<()> {
+    let labels = json!({
+        ::ark_api::consts::LABEL_BIND_WARNED_TIMESTAMP: Utc::now().timestamp_millis().to_string(),
+    });
+    label_node_with(kube, node_name, self::consts::NAME_WARNING, labels)
+        .await
+        .map(|_| ())
+}
+
+async fn label_node_with(
+    kube: Client,
+    node_name: &str,
+    field_manager: &str,
+    labels: Value,
+) -> Result<Node> {
+    let api = Api::<Node>::all(kube);
+    let pp = PatchParams {
+        field_manager: Some(field_manager.into()),
+        force: true,
+        ..Default::default()
+    };
+    let patch = Patch::Apply(json!({
+        "apiVersion": Node::api_version(&()),
+        "kind": Node::kind(&()),
+        "metadata": {
+            "name": node_name,
+            "labels": labels,
+        },
+    }));
+    api.patch(node_name, &pp, &patch).await.map_err(Into::into)
+}
+
+/// Returns the earliest time when the session should be logged out, if limited.
+///
+/// The box may be reserved by the others from the given time.
+/// The idle timeout is ignored if the activity of the session cannot be measured.
+pub fn get_session_deadline(
+    node: &Node,
+    reserved_timestamp: Option<DateTime<Utc>>,
+    is_activity_measured: bool,
+) -> Option<SessionDeadline> {
+    let labels = node.labels();
+    let parse_timestamp = |key: &str| {
+        labels
+            .get(key)
+            .and_then(|value| value.parse().ok())
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+    };
+
+    let bind_timestamp = parse_timestamp(::ark_api::consts::LABEL_BIND_TIMESTAMP)?;
+    let expired_timestamp = parse_timestamp(::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP);
+    let idle_timestamp = labels
+        .get(::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT)
+        .filter(|_| is_activity_measured)
+        .and_then(|value| value.parse().ok())
+        .and_then(::chrono::Duration::try_seconds)
+        .map(|idle_timeout| {
+            // NOTE: the session is idle since the login if no activity has been reported
+            let timestamp = parse_timestamp(::ark_api::consts::LABEL_BIND_ACTIVITY_TIMESTAMP)
+                .filter(|&timestamp| timestamp >= bind_timestamp)
+                .unwrap_or(bind_timestamp);
+            timestamp + idle_timeout
+        });
+
+    [
+        (SessionDeadlineReason::Expired, expired_timestamp),
+        (SessionDeadlineReason::Idle, idle_timestamp),
+        (SessionDeadlineReason::Reserved, reserved_timestamp),
+    ]
+    .into_iter()
+    .filter_map(|(reason, timestamp)| {
+        Some(SessionDeadline {
+            reason,
+            timestamp: timestamp?,
+            warned_timestamp: parse_timestamp(::ark_api::consts::LABEL_BIND_WARNED_TIMESTAMP),
+        })
+    })
+    .min_by_key(|deadline| deadline.timestamp)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SessionDeadline {
+    pub reason: SessionDeadlineReason,
+    pub timestamp: DateTime<Utc>,
+    pub warned_timestamp: Option<DateTime<Utc>>,
+}
+
+impl SessionDeadline {
+    const THRESHOLD_WARNING: TimeDelta = TimeDelta::minutes(5);
+
+    pub fn is_passed(&self, now: DateTime<Utc>) -> bool {
+        now >= self.timestamp
+    }
+
+    /// Returns the time when the user should be warned of the logout.
+    pub fn warning_timestamp(&self) -> DateTime<Utc> {
+        self.timestamp - Self::THRESHOLD_WARNING
+    }
+
+    /// Returns whether the user should be warned of the logout now.
+    pub fn should_warn(&self, now: DateTime<Utc>) -> bool {
+        let warning_timestamp = self.warning_timestamp();
+        now >= warning_timestamp
+            && !self.is_passed(now)
+            && self
+                .warned_timestamp
+                .map(|timestamp| timestamp < warning_timestamp)
+                .unwrap_or(true)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionDeadlineReason {
+    Expired,
+    Idle,
+    Reserved,
+}
+
+impl fmt::Display for SessionDeadlineReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired => "expired".fmt(f),
+            Self::Idle => "idle".fmt(f),
+            Self::Reserved => "reserved".fmt(f),
+        }
+    }
+}
+
+fn get_label(node: &Node, user_name: Option<&str>, persistent: bool, now: DateTime<Utc>) -> Value {
+    let node_name = node.name_any();
+    let bind_timestamp = user_name.map(|user_name| get_bind_timestamp(node, user_name, now));
+
     json!({
         ::ark_api::consts::LABEL_BIND_BY_USER: user_name,
         ::ark_api::consts::LABEL_BIND_NAMESPACE: user_name.map(UserCrd::user_namespace_with),
         ::ark_api::consts::LABEL_BIND_NODE: node_name,
         ::ark_api::consts::LABEL_BIND_PERSISTENT: persistent.to_string(),
         ::ark_api::consts::LABEL_BIND_STATUS: user_name.is_some().to_string(),
-        ::ark_api::consts::LABEL_BIND_TIMESTAMP: user_name.map(|_| Utc::now().timestamp_millis().to_string()),
+        ::ark_api::consts::LABEL_BIND_TIMESTAMP: bind_timestamp.map(|timestamp| timestamp.timestamp_millis().to_string()),
     })
 }
 
+fn get_limit_labels(
+    node: &Node,
+    user_name: Option<&str>,
+    box_quota: Option<&UserBoxQuotaSpec>,
+    now: DateTime<Utc>,
+) -> Result<Value> {
+    let (expired_timestamp, idle_timeout) = match box_quota.map(|box_quota| &box_quota.session) {
+        Some(session) => {
+            let max_duration = session
+                .max_duration()
+                .map_err(|error| anyhow!("failed to parse the max session duration: {error}"))?;
+            let idle_timeout = session
+                .idle_timeout()
+                .map_err(|error| anyhow!("failed to parse the session idle timeout: {error}"))?;
+
+            let expired_timestamp = match max_duration {
+                Some(max_duration) => {
+                    // NOTE: the session is not extended by signing in again
+                    let bind_timestamp = user_name
+                        .map(|user_name| get_bind_timestamp(node, user_name, now))
+                        .unwrap_or(now);
+                    let max_duration = ::chrono::Duration::from_std(max_duration)?;
+                    Some(
+                        (bind_timestamp + max_duration)
+                            .timestamp_millis()
+                            .to_string(),
+                    )
+                }
+                None => None,
+            };
+            let idle_timeout = idle_timeout.map(|idle_timeout| idle_timeout.as_secs().to_string());
+            (expired_timestamp, idle_timeout)
+        }
+        None => (None, None),
+    };
+
+    Ok(json!({
+        ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP: expired_timestamp,
+        ::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT: idle_timeout,
+    }))
+}
+
+/// Returns the time when the user has been bound to the node.
+///
+/// The time is kept if the node is already bound to the user.
+fn get_bind_timestamp(node: &Node, user_name: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+    let labels = node.labels();
+    match is_allocable(labels, Some(&node.name_any()), user_name) {
+        AllocationState::AllocatedByMyself => labels
+            .get(::ark_api::consts::LABEL_BIND_TIMESTAMP)
+            .and_then(|value| value.parse().ok())
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .unwrap_or(now),
+        _ => now,
+    }
+}
+
 pub fn is_allocable<'a>(
     labels: &'a BTreeMap<String, String>,
     node_name: Option<&str>,
@@ -477,3 +763,330 @@ pub enum AllocationState<'a> {
     AllocatedByOtherUser { user_name: &'a str },
     NotAllocated,
 }
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+    use vine_api::user_box_quota::UserBoxQuotaSessionSpec;
+
+    use super::*;
+
+    const NODE_NAME: &str = "box-a";
+
+    fn now() -> DateTime<Utc> {
+        // NOTE: the labels are stored in milliseconds
+        DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).unwrap()
+    }
+
+    fn node<'a>(labels: impl IntoIterator<Item = (&'a str, String)>) -> Node {
+        Node {
+            metadata: ObjectMeta {
+                name: Some(NODE_NAME.into()),
+                labels: Some(
+                    labels
+                        .into_iter()
+                        .map(|(key, value)| (key.into(), value))
+                        .collect(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn bound_node<'a>(
+        user_name: &str,
+        bind_timestamp: DateTime<Utc>,
+        labels: impl IntoIterator<Item = (&'a str, String)>,
+    ) -> Node {
+        node(
+            [
+                (::ark_api::consts::LABEL_BIND_BY_USER, user_name.into()),
+                (::ark_api::consts::LABEL_BIND_NODE, NODE_NAME.into()),
+                (::ark_api::consts::LABEL_BIND_STATUS, "true".into()),
+                (
+                    ::ark_api::consts::LABEL_BIND_TIMESTAMP,
+                    timestamp(bind_timestamp),
+                ),
+            ]
+            .into_iter()
+            .chain(labels),
+        )
+    }
+
+    fn timestamp(timestamp: DateTime<Utc>) -> String {
+        timestamp.timestamp_millis().to_string()
+    }
+
+    fn deadline(
+        reason: SessionDeadlineReason,
+        timestamp: DateTime<Utc>,
+        warned_timestamp: Option<DateTime<Utc>>,
+    ) -> SessionDeadline {
+        SessionDeadline {
+            reason,
+            timestamp,
+            warned_timestamp,
+        }
+    }
+
+    #[test]
+    fn no_deadline_without_binding() {
+        let now = now();
+        let node = node([(::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT, "60".into())]);
+        assert_eq!(get_session_deadline(&node, Some(now), true), None);
+    }
+
+    #[test]
+    fn no_deadline_without_limits() {
+        let now = now();
+        let node = bound_node("alice", now, []);
+        assert_eq!(get_session_deadline(&node, None, true), None);
+    }
+
+    #[test]
+    fn select_the_earliest_deadline() {
+        let now = now();
+        let expired_timestamp = now + TimeDelta::hours(2);
+        let reserved_timestamp = now + TimeDelta::hours(3);
+        let node = bound_node(
+            "alice",
+            now,
+            [
+                (
+                    ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP,
+                    timestamp(expired_timestamp),
+                ),
+                (::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT, "3600".into()),
+            ],
+        );
+
+        assert_eq!(
+            get_session_deadline(&node, Some(reserved_timestamp), true),
+            Some(deadline(
+                SessionDeadlineReason::Idle,
+                now + TimeDelta::hours(1),
+                None,
+            )),
+        );
+        assert_eq!(
+            get_session_deadline(&node, Some(now + TimeDelta::minutes(30)), true),
+            Some(deadline(
+                SessionDeadlineReason::Reserved,
+                now + TimeDelta::minutes(30),
+                None,
+            )),
+        );
+
+        let node = bound_node(
+            "alice",
+            now,
+            [
+                (
+                    ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP,
+                    timestamp(expired_timestamp),
+                ),
+                (::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT, "86400".into()),
+            ],
+        );
+        assert_eq!(
+            get_session_deadline(&node, Some(reserved_timestamp), true),
+            Some(deadline(
+                SessionDeadlineReason::Expired,
+                expired_timestamp,
+                None,
+            )),
+        );
+    }
+
+    #[test]
+    fn measure_idle_since_the_last_activity() {
+        let now = now();
+        let idle_timeout = TimeDelta::minutes(10);
+        let deadline_of = |activity_timestamp: DateTime<Utc>| {
+            let node = bound_node(
+                "alice",
+                now,
+                [
+                    (
+                        ::ark_api::consts::LABEL_BIND_ACTIVITY_TIMESTAMP,
+                        timestamp(activity_timestamp),
+                    ),
+                    (
+                        ::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT,
+                        idle_timeout.num_seconds().to_string(),
+                    ),
+                ],
+            );
+            get_session_deadline(&node, None, true).map(|deadline| deadline.timestamp)
+        };
+
+        // since the login, if no activity has been reported
+        let node = bound_node(
+            "alice",
+            now,
+            [(
+                ::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT,
+                idle_timeout.num_seconds().to_string(),
+            )],
+        );
+        assert_eq!(
+            get_session_deadline(&node, None, true).map(|deadline| deadline.timestamp),
+            Some(now + idle_timeout),
+        );
+
+        // since the last activity
+        let activity_timestamp = now + TimeDelta::minutes(5);
+        assert_eq!(
+            deadline_of(activity_timestamp),
+            Some(activity_timestamp + idle_timeout),
+        );
+
+        // the activities of the previous sessions are ignored
+        assert_eq!(
+            deadline_of(now - TimeDelta::hours(1)),
+            Some(now + idle_timeout),
+        );
+    }
+
+    #[test]
+    fn skip_idle_if_activity_is_not_measured() {
+        let now = now();
+        let expired_timestamp = now + TimeDelta::hours(2);
+        let node = bound_node(
+            "alice",
+            now,
+            [
+                (
+                    ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP,
+                    timestamp(expired_timestamp),
+                ),
+                (::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT, "60".into()),
+            ],
+        );
+
+        assert_eq!(
+            get_session_deadline(&node, None, false),
+            Some(deadline(
+                SessionDeadlineReason::Expired,
+                expired_timestamp,
+                None,
+            )),
+        );
+
+        let node = bound_node(
+            "alice",
+            now,
+            [(::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT, "60".into())],
+        );
+        assert_eq!(get_session_deadline(&node, None, false), None);
+    }
+
+    #[test]
+    fn warn_once_per_deadline() {
+        let now = now();
+        let timestamp = now + TimeDelta::hours(1);
+        let warning_timestamp = timestamp - SessionDeadline::THRESHOLD_WARNING;
+
+        let deadline_not_warned = deadline(SessionDeadlineReason::Expired, timestamp, None);
+        assert!(!deadline_not_warned.should_warn(now));
+        assert!(!deadline_not_warned.should_warn(warning_timestamp - TimeDelta::seconds(1)));
+        assert!(deadline_not_warned.should_warn(warning_timestamp));
+        assert!(deadline_not_warned.should_warn(timestamp - TimeDelta::seconds(1)));
+
+        // never after the deadline
+        assert!(!deadline_not_warned.should_warn(timestamp));
+
+        // the user has been warned of this deadline
+        let deadline_warned = deadline(
+            SessionDeadlineReason::Expired,
+            timestamp,
+            Some(warning_timestamp + TimeDelta::seconds(10)),
+        );
+        assert!(!deadline_warned.should_warn(warning_timestamp + TimeDelta::minutes(1)));
+
+        // the user has been warned of the previous deadline
+        let deadline_postponed = deadline(
+            SessionDeadlineReason::Idle,
+            timestamp,
+            Some(warning_timestamp - TimeDelta::minutes(30)),
+        );
+        assert!(deadline_postponed.should_warn(warning_timestamp));
+    }
+
+    #[test]
+    fn keep_bind_timestamp_on_relogin() {
+        let now = now();
+        let bind_timestamp = now - TimeDelta::hours(1);
+        let node_bound = bound_node("alice", bind_timestamp, []);
+        let get_bind_label = |user_name: &str| {
+            get_label(&node_bound, Some(user_name), false, now)
+                .get(::ark_api::consts::LABEL_BIND_TIMESTAMP)
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        };
+
+        assert_eq!(get_bind_label("alice"), Some(timestamp(bind_timestamp)));
+        assert_eq!(get_bind_label("bob"), Some(timestamp(now)));
+
+        // a new session after logout
+        let node_unbound = node([
+            (::ark_api::consts::LABEL_BIND_STATUS, "false".into()),
+            (
+                ::ark_api::consts::LABEL_BIND_TIMESTAMP,
+                timestamp(bind_timestamp),
+            ),
+        ]);
+        assert_eq!(get_bind_timestamp(&node_unbound, "alice", now), now);
+
+        // logout
+        assert_eq!(
+            get_label(&node_bound, None, false, now).get(::ark_api::consts::LABEL_BIND_TIMESTAMP),
+            Some(&Value::Null),
+        );
+    }
+
+    #[test]
+    fn keep_expired_timestamp_on_relogin() {
+        let now = now();
+        let bind_timestamp = now - TimeDelta::hours(1);
+        let node = bound_node("alice", bind_timestamp, []);
+        let box_quota = UserBoxQuotaSpec {
+            session: UserBoxQuotaSessionSpec {
+                idle_timeout: Some("30m".into()),
+                max_duration: Some("8h".into()),
+            },
+            ..Default::default()
+        };
+        let get_limit_label = |user_name: Option<&str>, key: &str| {
+            get_limit_labels(&node, user_name, Some(&box_quota), now)
+                .unwrap()
+                .get(key)
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        };
+
+        assert_eq!(
+            get_limit_label(
+                Some("alice"),
+                ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP
+            ),
+            Some(timestamp(bind_timestamp + TimeDelta::hours(8))),
+        );
+        assert_eq!(
+            get_limit_label(Some("bob"), ::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP),
+            Some(timestamp(now + TimeDelta::hours(8))),
+        );
+        assert_eq!(
+            get_limit_label(Some("alice"), ::ark_api::consts::LABEL_BIND_IDLE_TIMEOUT),
+            Some("1800".into()),
+        );
+
+        // unlimited
+        let labels = get_limit_labels(&node, Some("alice"), None, now).unwrap();
+        assert_eq!(
+            labels.get(::ark_api::consts::LABEL_BIND_EXPIRED_TIMESTAMP),
+            Some(&Value::Null),
+        );
+    }
+}
@@ -27,12 +27,17 @@ use tokio::{
 use tracing::{error, info};
 
 use crate::{
+    audit::{kube_caller, AsciicastRecorder, AuditExecContext},
     batch::{collect_user_sessions, BatchCommandUsers},
     exec::{Process, SessionExecExt},
 };
 
 pub struct BatchShellArgs<C, U> {
     pub command: C,
+    /// Records the shells in the asciicast format, to be downloaded by the admins
+    pub record: bool,
+    /// Records the key inputs too, which may contain the passwords
+    pub record_input: bool,
     pub users: BatchCommandUsers<U>,
 }
 
@@ -42,7 +47,12 @@ impl<C, U> BatchShellArgs<C, U> {
         C: 'static + Send + Sync + Clone + fmt::Debug + Into<String>,
         U: AsRef<str>,
     {
-        let Self { command, users } = self;
+        let Self {
+            command,
+            record,
+            record_input,
+            users,
+        } = self;
 
         let sessions_all = collect_user_sessions(kube).await?;
         let sessions_filtered = users.filter(sessions_all)?;
@@ -52,12 +62,29 @@ impl<C, U> BatchShellArgs<C, U> {
             return Ok(());
         }
 
+        let audit = AuditExecContext {
+            caller: kube_caller(kube).await,
+            command: vec![command.clone().into()],
+            tty: true,
+        };
         let processes = sessions_filtered
             .into_iter()
             .map(|session| {
                 let kube = kube.clone();
                 let command = [command.clone()];
-                async move { session.exec_with_tty(kube, command).await }
+                let audit = &audit;
+                async move {
+                    match session.exec_with_tty(kube, command).await {
+                        Ok(mut processes) => {
+                            audit.watch(&session, &mut processes);
+                            Ok(processes)
+                        }
+                        Err(error) => {
+                            audit.fail(&session, &error);
+                            Err(error)
+                        }
+                    }
+                }
             })
             .collect::<FuturesUnordered<_>>()
             .filter_map(|result| async move {
@@ -74,7 +101,7 @@ impl<C, U> BatchShellArgs<C, U> {
             .into_iter()
             .flatten();
 
-        let app = App::new(processes)?;
+        let app = App::new(processes, Some(kube).filter(|_| *record), *record_input)?;
         app.try_loop_forever().await
     }
 }
@@ -86,7 +113,11 @@ struct App {
 }
 
 impl App {
-    fn new(processes: impl Iterator<Item = Process>) -> Result<Self> {
+    fn new(
+        processes: impl Iterator<Item = Process>,
+        recorder_kube: Option<&Client>,
+        record_input: bool,
+    ) -> Result<Self> {
         Ok(Self {
             is_closed: false,
             session_selected: 0,
@@ -97,6 +128,14 @@ impl App {
                          name,
                          namespace,
                      }| {
+                        // NOTE: the size is updated on the first render
+                        let recorder = recorder_kube.and_then(|kube| {
+                            let name = namespace.as_deref().unwrap_or(&name);
+                            AsciicastRecorder::create(kube.clone(), name, 80, 24, record_input)
+                                .map_err(|error| error!("{error}"))
+                                .ok()
+                        });
+
                         Some(Session {
                             channel_stdin: Box::new(ap.stdin()?),
                             channel_stdout: Box::new(ap.stdout()?),
@@ -104,6 +143,7 @@ impl App {
                             events: Vec::default(),
                             name,
                             namespace,
+                            recorder,
                             state: SessionState::Running,
                             vt: None,
                         })
@@ -129,6 +169,15 @@ impl App {
         };
 
         self.exit()?;
+
+        // upload the remaining recordings
+        for session in &mut self.sessions {
+            if let Some(recorder) = session.recorder.take() {
+                if let Err(error) = recorder.close().await {
+                    error!("failed to record session: {error}");
+                }
+            }
+        }
         state.map(|AppState::Completed| ())
     }
 
@@ -309,6 +358,7 @@ struct Session {
     events: Vec<SessionEvent>,
     name: String,
     namespace: Option<String>,
+    recorder: Option<AsciicastRecorder>,
     state: SessionState,
     vt: Option<SessionTerminal>,
 }
@@ -344,6 +394,9 @@ impl Session {
                         self.channel_terminal_size
                             .send(TerminalSize { width, height })
                             .await?;
+                        record(&mut self.recorder, |recorder| {
+                            recorder.resize(width, height)
+                        });
                     }
                 }
             }
@@ -377,6 +430,7 @@ impl Session {
                 let buf = &buf[..*len];
                 if let Some(text) = ::std::str::from_utf8(buf).ok() {
                     vt.feed_str(text);
+                    record(&mut self.recorder, |recorder| recorder.output(text));
                     *len = 0;
                 }
             }
@@ -384,6 +438,7 @@ impl Session {
 
         // handle stdin
         if !inputs.is_empty() {
+            record(&mut self.recorder, |recorder| recorder.input(inputs));
             match self.channel_stdin.write_all(inputs.as_bytes()).await {
                 Ok(()) => (),
                 Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {
@@ -393,6 +448,14 @@ impl Session {
                 Err(error) => return Err(error.into()),
             }
         }
+
+        // upload the recorded parts
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(error) = recorder.sync().await {
+                error!("failed to record session: {error}");
+                self.recorder.take();
+            }
+        }
         Ok(())
     }
 
@@ -422,6 +485,17 @@ impl Session {
     }
 }
 
+/// Stops recording on error, not to interrupt the session.
+fn record(
+    recorder: &mut Option<AsciicastRecorder>,
+    f: impl FnOnce(&mut AsciicastRecorder) -> Result<()>,
+) {
+    if let Some(error) = recorder.as_mut().and_then(|recorder| f(recorder).err()) {
+        error!("failed to record session: {error}");
+        recorder.take();
+    }
+}
+
 #[derive(Debug)]
 enum SessionEvent {
     UpdateSize { width: u16, height: u16 },